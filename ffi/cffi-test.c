@@ -1,9 +1,18 @@
+#include <stdbool.h>
 #include <stdio.h>
+#include <stdlib.h>
 #include <string.h>
 #include <inttypes.h>
 
 #include "delta_kernel_ffi.h"
 
+void* allocate_string(struct KernelStringSlice value) {
+    char* copy = malloc(value.len + 1);
+    memcpy(copy, value.ptr, value.len);
+    copy[value.len] = '\0';
+    return copy;
+}
+
 void visit_file(void *engine_context, struct KernelStringSlice file_name) {
     printf("file: ");
     for (int i = 0; i < file_name.len; i++) {
@@ -12,6 +21,177 @@ void visit_file(void *engine_context, struct KernelStringSlice file_name) {
     printf("\n");
 }
 
+// A flattened representation of expressions, built by visit_expression: each list is a string of
+// comma-separated expressions, e.g. `AND(LessThan(a, 5), GreaterThan(b, 10))`.
+#define MAX_LISTS 32
+#define MAX_LIST_LEN 256
+typedef struct {
+  char lists[MAX_LISTS][MAX_LIST_LEN];
+  size_t num_lists;
+} ExprLists;
+
+uintptr_t make_expr_list(void* data, uintptr_t reserve) {
+  ExprLists* state = data;
+  state->lists[state->num_lists][0] = '\0';
+  return state->num_lists++;
+}
+
+void append_expr(void* data, uintptr_t list_id, const char* expr) {
+  char* list = ((ExprLists*) data)->lists[list_id];
+  if (list[0] != '\0') {
+    strncat(list, ", ", MAX_LIST_LEN - strlen(list) - 1);
+  }
+  strncat(list, expr, MAX_LIST_LEN - strlen(list) - 1);
+}
+
+void append_slice(void* data, uintptr_t list_id, const char* format, struct KernelStringSlice slice) {
+  char expr[MAX_LIST_LEN];
+  snprintf(expr, MAX_LIST_LEN, format, (int) slice.len, slice.ptr);
+  append_expr(data, list_id, expr);
+}
+
+void append_call(void* data, uintptr_t list_id, const char* name, uintptr_t child_list_id) {
+  char expr[2 * MAX_LIST_LEN];
+  snprintf(expr, sizeof(expr), "%s(%s)", name, ((ExprLists*) data)->lists[child_list_id]);
+  append_expr(data, list_id, expr);
+}
+
+void visit_literal_int(void* data, uintptr_t list_id, int32_t value) {
+  char expr[32];
+  snprintf(expr, sizeof(expr), "%" PRId32, value);
+  append_expr(data, list_id, expr);
+}
+
+void visit_literal_long(void* data, uintptr_t list_id, int64_t value) {
+  char expr[32];
+  snprintf(expr, sizeof(expr), "%" PRId64, value);
+  append_expr(data, list_id, expr);
+}
+
+void visit_literal_double(void* data, uintptr_t list_id, double value) {
+  char expr[32];
+  snprintf(expr, sizeof(expr), "%f", value);
+  append_expr(data, list_id, expr);
+}
+
+void visit_literal_bool(void* data, uintptr_t list_id, bool value) {
+  append_expr(data, list_id, value ? "true" : "false");
+}
+
+void visit_literal_string(void* data, uintptr_t list_id, struct KernelStringSlice value) {
+  append_slice(data, list_id, "'%.*s'", value);
+}
+
+void visit_literal_null(void* data, uintptr_t list_id) {
+  append_expr(data, list_id, "NULL");
+}
+
+void visit_column(void* data, uintptr_t list_id, struct KernelStringSlice name) {
+  append_slice(data, list_id, "%.*s", name);
+}
+
+void visit_binary(void* data, uintptr_t list_id, KernelBinaryOperator op, uintptr_t child_list_id) {
+  const char* name = op == LessThan ? "LessThan" : op == GreaterThan ? "GreaterThan" : "Binary";
+  append_call(data, list_id, name, child_list_id);
+}
+
+void visit_and(void* data, uintptr_t list_id, uintptr_t child_list_id) {
+  append_call(data, list_id, "AND", child_list_id);
+}
+
+void visit_or(void* data, uintptr_t list_id, uintptr_t child_list_id) {
+  append_call(data, list_id, "OR", child_list_id);
+}
+
+void visit_not(void* data, uintptr_t list_id, uintptr_t child_list_id) {
+  append_call(data, list_id, "NOT", child_list_id);
+}
+
+void visit_is_null(void* data, uintptr_t list_id, uintptr_t child_list_id) {
+  append_call(data, list_id, "IS_NULL", child_list_id);
+}
+
+void visit_is_not_null(void* data, uintptr_t list_id, uintptr_t child_list_id) {
+  append_call(data, list_id, "IS_NOT_NULL", child_list_id);
+}
+
+void visit_unknown(void* data, uintptr_t list_id, struct KernelStringSlice rendered) {
+  append_slice(data, list_id, "UNKNOWN %.*s", rendered);
+}
+
+// build `a < 5 AND b > 10`, and check that visiting it reconstructs the same expression
+int test_visit_expression() {
+  char* a = "a";
+  char* b = "b";
+  KernelStringSlice a_slice = {a, strlen(a)};
+  KernelStringSlice b_slice = {b, strlen(b)};
+  const SharedExpression *a_column = expression_column(a_slice, NULL).ok;
+  const SharedExpression *b_column = expression_column(b_slice, NULL).ok;
+  const SharedExpression *five = expression_literal_int(5);
+  const SharedExpression *ten = expression_literal_long(10);
+  const SharedExpression *left = expression_binary(LessThan, a_column, five);
+  const SharedExpression *right = expression_binary(GreaterThan, b_column, ten);
+  const SharedExpression *predicate = expression_and(left, right);
+
+  ExprLists state = { .num_lists = 0 };
+  EngineExpressionVisitor visitor = {
+    .data = &state,
+    .make_expr_list = make_expr_list,
+    .visit_literal_int = visit_literal_int,
+    .visit_literal_long = visit_literal_long,
+    .visit_literal_double = visit_literal_double,
+    .visit_literal_bool = visit_literal_bool,
+    .visit_literal_string = visit_literal_string,
+    .visit_literal_null = visit_literal_null,
+    .visit_column = visit_column,
+    .visit_binary = visit_binary,
+    .visit_and = visit_and,
+    .visit_or = visit_or,
+    .visit_not = visit_not,
+    .visit_is_null = visit_is_null,
+    .visit_is_not_null = visit_is_not_null,
+    .visit_unknown = visit_unknown,
+  };
+  uintptr_t list_id = visit_expression(predicate, &visitor);
+
+  const SharedExpression *exprs[] = {a_column, b_column, five, ten, left, right, predicate};
+  for (size_t i = 0; i < sizeof(exprs) / sizeof(exprs[0]); i++) {
+    drop_expression(exprs[i]);
+  }
+
+  const char* expected = "AND(LessThan(a, 5), GreaterThan(b, 10))";
+  printf("visited expression: %s\n", state.lists[list_id]);
+  if (strcmp(state.lists[list_id], expected) != 0) {
+    printf("Expected visited expression %s\n", expected);
+    return -1;
+  }
+  return 0;
+}
+
+// iterate and free the scan files of a (successfully initialized) scan file iterator
+int scan_files(ExternResult_____KernelScanFileIterator file_iter_res) {
+  if (file_iter_res.tag != Ok_____KernelScanFileIterator) {
+    printf("Failed to construct scan file iterator\n");
+    return -1;
+  }
+
+  KernelScanFileIterator *file_iter = file_iter_res.ok;
+
+  // iterate scan files
+  for (;;) {
+    ExternResult_bool ok_res = kernel_scan_files_next(file_iter, NULL, visit_file);
+    if (ok_res.tag != Ok_bool) {
+      printf("Failed to iterate scan file\n");
+      return -1;
+    } else if (!ok_res.ok) {
+      break;
+    }
+  }
+
+  kernel_scan_files_free(file_iter);
+  return 0;
+}
+
 int main(int argc, char* argv[]) {
 
   if (argc < 2) {
@@ -19,6 +199,10 @@ int main(int argc, char* argv[]) {
     return -1;
   }
 
+  if (test_visit_expression() != 0) {
+    return -1;
+  }
+
   char* table_path = argv[1];
   printf("Reading table at %s\n", table_path);
 
@@ -44,27 +228,36 @@ int main(int argc, char* argv[]) {
   uint64_t v = version(snapshot_handle);
   printf("version: %" PRIu64 "\n", v);
 
+  printf("all files:\n");
   ExternResult_____KernelScanFileIterator file_iter_res =
     kernel_scan_files_init(snapshot_handle, table_client, NULL);
-  if (file_iter_res.tag != Ok_____KernelScanFileIterator) {
-    printf("Failed to construct scan file iterator\n");
+  if (scan_files(file_iter_res) != 0) {
     return -1;
   }
 
-  KernelScanFileIterator *file_iter = file_iter_res.ok;
+  // build the predicate `number < 5` and hand it to kernel for data skipping
+  char* column_name = "number";
+  KernelStringSlice column_name_slice = {column_name, strlen(column_name)};
+  ExternResult______SharedExpression column_res = expression_column(column_name_slice, NULL);
+  if (column_res.tag != Ok______SharedExpression) {
+    printf("Failed to build column expression\n");
+    return -1;
+  }
+  const SharedExpression *column = column_res.ok;
+  const SharedExpression *literal = expression_literal_long(5);
+  const SharedExpression *predicate = expression_binary(LessThan, column, literal);
+  drop_expression(column);
+  drop_expression(literal);
 
-  // iterate scan files
-  for (;;) {
-    ExternResult_bool ok_res = kernel_scan_files_next(file_iter, NULL, visit_file);
-    if (ok_res.tag != Ok_bool) {
-      printf("Failed to iterate scan file\n");
-      return -1;
-    } else if (!ok_res.ok) {
-      break;
-    }
+  char* predicate_string = expression_to_string(predicate, allocate_string);
+  printf("files matching %s:\n", predicate_string);
+  free(predicate_string);
+  file_iter_res = kernel_scan_files_init_with_predicate(snapshot_handle, table_client, predicate);
+  drop_expression(predicate);
+  if (scan_files(file_iter_res) != 0) {
+    return -1;
   }
 
-  kernel_scan_files_free(file_iter);
   drop_snapshot(snapshot_handle);
   drop_table_client(table_client);
 
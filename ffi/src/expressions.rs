@@ -0,0 +1,380 @@
+//! Lets engines build kernel expressions directly, as handles that compose into bigger expressions.
+//!
+//! Each constructor returns a new [SharedExpression] handle, which the engine must eventually pass
+//! to [drop_expression]. Constructors that take other expressions as operands only borrow them, so
+//! the engine remains responsible to drop the operand handles as well (which it may do as soon as
+//! the constructor returns).
+use std::os::raw::c_void;
+use std::sync::Arc;
+
+use delta_kernel::expressions::{
+    BinaryOperator, Expression, Scalar, UnaryOperator, VariadicOperator,
+};
+use delta_kernel::DeltaResult;
+
+use crate::handle::{ArcHandle, SizedArcHandle, Unconstructable};
+use crate::{
+    AllocateErrorFn, ExternResult, IntoExternResult, KernelStringSlice, TryFromStringSlice,
+};
+
+/// A kernel [Expression] built by the engine.
+pub struct SharedExpression {
+    _unconstructable: Unconstructable,
+}
+
+impl SizedArcHandle for SharedExpression {
+    type Target = Expression;
+}
+
+/// The binary operators an engine can build expressions with (see [expression_binary]).
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub enum KernelBinaryOperator {
+    Plus,
+    Minus,
+    Multiply,
+    Divide,
+    Modulo,
+    LessThan,
+    LessThanOrEqual,
+    GreaterThan,
+    GreaterThanOrEqual,
+    Equal,
+    NotEqual,
+    Distinct,
+    NullSafeEqual,
+    StartsWith,
+    RLike,
+}
+
+impl From<KernelBinaryOperator> for BinaryOperator {
+    fn from(op: KernelBinaryOperator) -> Self {
+        match op {
+            KernelBinaryOperator::Plus => BinaryOperator::Plus,
+            KernelBinaryOperator::Minus => BinaryOperator::Minus,
+            KernelBinaryOperator::Multiply => BinaryOperator::Multiply,
+            KernelBinaryOperator::Divide => BinaryOperator::Divide,
+            KernelBinaryOperator::Modulo => BinaryOperator::Modulo,
+            KernelBinaryOperator::LessThan => BinaryOperator::LessThan,
+            KernelBinaryOperator::LessThanOrEqual => BinaryOperator::LessThanOrEqual,
+            KernelBinaryOperator::GreaterThan => BinaryOperator::GreaterThan,
+            KernelBinaryOperator::GreaterThanOrEqual => BinaryOperator::GreaterThanOrEqual,
+            KernelBinaryOperator::Equal => BinaryOperator::Equal,
+            KernelBinaryOperator::NotEqual => BinaryOperator::NotEqual,
+            KernelBinaryOperator::Distinct => BinaryOperator::Distinct,
+            KernelBinaryOperator::NullSafeEqual => BinaryOperator::NullSafeEqual,
+            KernelBinaryOperator::StartsWith => BinaryOperator::StartsWith,
+            KernelBinaryOperator::RLike => BinaryOperator::RLike,
+        }
+    }
+}
+
+impl From<BinaryOperator> for KernelBinaryOperator {
+    fn from(op: BinaryOperator) -> Self {
+        match op {
+            BinaryOperator::Plus => KernelBinaryOperator::Plus,
+            BinaryOperator::Minus => KernelBinaryOperator::Minus,
+            BinaryOperator::Multiply => KernelBinaryOperator::Multiply,
+            BinaryOperator::Divide => KernelBinaryOperator::Divide,
+            BinaryOperator::Modulo => KernelBinaryOperator::Modulo,
+            BinaryOperator::LessThan => KernelBinaryOperator::LessThan,
+            BinaryOperator::LessThanOrEqual => KernelBinaryOperator::LessThanOrEqual,
+            BinaryOperator::GreaterThan => KernelBinaryOperator::GreaterThan,
+            BinaryOperator::GreaterThanOrEqual => KernelBinaryOperator::GreaterThanOrEqual,
+            BinaryOperator::Equal => KernelBinaryOperator::Equal,
+            BinaryOperator::NotEqual => KernelBinaryOperator::NotEqual,
+            BinaryOperator::Distinct => KernelBinaryOperator::Distinct,
+            BinaryOperator::NullSafeEqual => KernelBinaryOperator::NullSafeEqual,
+            BinaryOperator::StartsWith => KernelBinaryOperator::StartsWith,
+            BinaryOperator::RLike => KernelBinaryOperator::RLike,
+        }
+    }
+}
+
+fn wrap_shared_expression(expr: Expression) -> *const SharedExpression {
+    ArcHandle::into_handle(Arc::new(expr))
+}
+
+/// Clones the expression a handle refers to, without dropping the handle.
+///
+/// # Safety
+///
+/// The handle must be valid (returned by an `expression_XXX` function and not yet dropped).
+pub(crate) unsafe fn unwrap_shared_expression(expr: *const SharedExpression) -> Expression {
+    unsafe { ArcHandle::clone_as_arc(expr) }.as_ref().clone()
+}
+
+/// # Safety
+/// The string slice must be valid
+#[no_mangle]
+pub unsafe extern "C" fn expression_column(
+    name: KernelStringSlice,
+    allocate_error: AllocateErrorFn,
+) -> ExternResult<*const SharedExpression> {
+    expression_column_impl(name).into_extern_result(allocate_error)
+}
+unsafe fn expression_column_impl(name: KernelStringSlice) -> DeltaResult<*const SharedExpression> {
+    let name = unsafe { String::try_from_slice(name) };
+    Ok(wrap_shared_expression(Expression::column(name)))
+}
+
+/// # Safety
+/// The string slice must be valid
+#[no_mangle]
+pub unsafe extern "C" fn expression_literal_string(
+    value: KernelStringSlice,
+    allocate_error: AllocateErrorFn,
+) -> ExternResult<*const SharedExpression> {
+    expression_literal_string_impl(value).into_extern_result(allocate_error)
+}
+unsafe fn expression_literal_string_impl(
+    value: KernelStringSlice,
+) -> DeltaResult<*const SharedExpression> {
+    let value = unsafe { String::try_from_slice(value) };
+    Ok(wrap_shared_expression(Expression::literal(value)))
+}
+
+#[no_mangle]
+pub extern "C" fn expression_literal_int(value: i32) -> *const SharedExpression {
+    wrap_shared_expression(Expression::literal(value))
+}
+
+#[no_mangle]
+pub extern "C" fn expression_literal_long(value: i64) -> *const SharedExpression {
+    wrap_shared_expression(Expression::literal(value))
+}
+
+#[no_mangle]
+pub extern "C" fn expression_literal_double(value: f64) -> *const SharedExpression {
+    wrap_shared_expression(Expression::literal(Scalar::Double(value)))
+}
+
+#[no_mangle]
+pub extern "C" fn expression_literal_bool(value: bool) -> *const SharedExpression {
+    wrap_shared_expression(Expression::literal(value))
+}
+
+/// Builds `left <op> right`. The operands are borrowed, and remain owned by the engine.
+///
+/// # Safety
+/// Both operands must be valid expression handles.
+#[no_mangle]
+pub unsafe extern "C" fn expression_binary(
+    op: KernelBinaryOperator,
+    left: *const SharedExpression,
+    right: *const SharedExpression,
+) -> *const SharedExpression {
+    let left = unsafe { unwrap_shared_expression(left) };
+    let right = unsafe { unwrap_shared_expression(right) };
+    wrap_shared_expression(Expression::binary(op.into(), left, right))
+}
+
+/// Builds `left AND right`. The operands are borrowed, and remain owned by the engine.
+///
+/// # Safety
+/// Both operands must be valid expression handles.
+#[no_mangle]
+pub unsafe extern "C" fn expression_and(
+    left: *const SharedExpression,
+    right: *const SharedExpression,
+) -> *const SharedExpression {
+    let left = unsafe { unwrap_shared_expression(left) };
+    let right = unsafe { unwrap_shared_expression(right) };
+    wrap_shared_expression(left.and(right))
+}
+
+/// Builds `left OR right`. The operands are borrowed, and remain owned by the engine.
+///
+/// # Safety
+/// Both operands must be valid expression handles.
+#[no_mangle]
+pub unsafe extern "C" fn expression_or(
+    left: *const SharedExpression,
+    right: *const SharedExpression,
+) -> *const SharedExpression {
+    let left = unsafe { unwrap_shared_expression(left) };
+    let right = unsafe { unwrap_shared_expression(right) };
+    wrap_shared_expression(left.or(right))
+}
+
+/// Allocates a copy of a string in engine memory, returning a pointer to the engine's copy. The
+/// slice is only valid until the call returns, so the engine must copy it (see [KernelStringSlice]).
+pub type AllocateStringFn = extern "C" fn(value: KernelStringSlice) -> *mut c_void;
+
+/// Renders an expression in human-readable form (e.g. `Column(a) < 5`), for logging and debugging.
+/// The string is passed to `allocate_string`, and the engine owns (and must eventually free) the
+/// copy it allocated, which this function returns.
+///
+/// # Safety
+/// The expression handle must be valid.
+#[no_mangle]
+pub unsafe extern "C" fn expression_to_string(
+    expr: *const SharedExpression,
+    allocate_string: AllocateStringFn,
+) -> *mut c_void {
+    let expr = unsafe { ArcHandle::clone_as_arc(expr) };
+    let rendered = expr.to_string();
+    allocate_string(rendered.as_str().into())
+}
+
+/// # Safety
+///
+/// Caller is responsible to (at most once) pass a valid handle returned by an `expression_XXX`
+/// function.
+#[no_mangle]
+pub unsafe extern "C" fn drop_expression(expr: *const SharedExpression) {
+    ArcHandle::drop_handle(expr);
+}
+
+// WARNING: the visitor MUST NOT retain internal references to the string slices passed to visitor
+// methods
+/// Lets the engine rebuild a kernel expression in its own representation, without knowing the
+/// layout of kernel's [Expression]. See [visit_expression] for the traversal order.
+#[repr(C)]
+pub struct EngineExpressionVisitor {
+    // opaque state pointer
+    data: *mut c_void,
+    // Creates a new expression list, optionally reserving capacity up front
+    make_expr_list: extern "C" fn(data: *mut c_void, reserve: usize) -> usize,
+    // visitor methods that should instantiate and append the appropriate expression to the list
+    visit_literal_int: extern "C" fn(data: *mut c_void, sibling_list_id: usize, value: i32),
+    visit_literal_long: extern "C" fn(data: *mut c_void, sibling_list_id: usize, value: i64),
+    visit_literal_double: extern "C" fn(data: *mut c_void, sibling_list_id: usize, value: f64),
+    visit_literal_bool: extern "C" fn(data: *mut c_void, sibling_list_id: usize, value: bool),
+    visit_literal_string:
+        extern "C" fn(data: *mut c_void, sibling_list_id: usize, value: KernelStringSlice),
+    visit_literal_null: extern "C" fn(data: *mut c_void, sibling_list_id: usize),
+    visit_column: extern "C" fn(data: *mut c_void, sibling_list_id: usize, name: KernelStringSlice),
+    // The child list of a binary operation always holds exactly two expressions (left, right)
+    visit_binary: extern "C" fn(
+        data: *mut c_void,
+        sibling_list_id: usize,
+        op: KernelBinaryOperator,
+        child_list_id: usize,
+    ),
+    visit_and: extern "C" fn(data: *mut c_void, sibling_list_id: usize, child_list_id: usize),
+    visit_or: extern "C" fn(data: *mut c_void, sibling_list_id: usize, child_list_id: usize),
+    // The child list of a unary operation always holds exactly one expression
+    visit_not: extern "C" fn(data: *mut c_void, sibling_list_id: usize, child_list_id: usize),
+    visit_is_null: extern "C" fn(data: *mut c_void, sibling_list_id: usize, child_list_id: usize),
+    visit_is_not_null:
+        extern "C" fn(data: *mut c_void, sibling_list_id: usize, child_list_id: usize),
+    // Called for (sub)expressions the visitor has no specific method for, with their rendered form
+    visit_unknown:
+        extern "C" fn(data: *mut c_void, sibling_list_id: usize, rendered: KernelStringSlice),
+}
+
+/// Walks an expression depth-first, calling the visitor's method for each (sub)expression. The
+/// traversal is post-order: the children of an expression are visited (and appended to a new child
+/// list, in order) before the expression itself is appended to its list of siblings, so the
+/// engine can build each expression from its already-built children. Returns the id of the list
+/// that holds the (single) top-level expression.
+///
+/// # Safety
+///
+/// Caller is responsible to pass a valid expression handle.
+#[no_mangle]
+pub unsafe extern "C" fn visit_expression(
+    expr: *const SharedExpression,
+    visitor: &mut EngineExpressionVisitor,
+) -> usize {
+    let expr = unsafe { ArcHandle::clone_as_arc(expr) };
+
+    // Visit the children of an expression and return the list that holds them
+    fn visit_children<'a>(
+        visitor: &EngineExpressionVisitor,
+        children: impl ExactSizeIterator<Item = &'a Expression>,
+    ) -> usize {
+        let child_list_id = (visitor.make_expr_list)(visitor.data, children.len());
+        for child in children {
+            visit_expression_impl(visitor, child_list_id, child);
+        }
+        child_list_id
+    }
+
+    // Visit an expression (recursively) and add the result to the list of siblings.
+    fn visit_expression_impl(
+        visitor: &EngineExpressionVisitor,
+        sibling_list_id: usize,
+        expr: &Expression,
+    ) {
+        let data = visitor.data;
+        match expr {
+            Expression::Literal(Scalar::Integer(value)) => {
+                (visitor.visit_literal_int)(data, sibling_list_id, *value)
+            }
+            Expression::Literal(Scalar::Long(value)) => {
+                (visitor.visit_literal_long)(data, sibling_list_id, *value)
+            }
+            Expression::Literal(Scalar::Double(value)) => {
+                (visitor.visit_literal_double)(data, sibling_list_id, *value)
+            }
+            Expression::Literal(Scalar::Boolean(value)) => {
+                (visitor.visit_literal_bool)(data, sibling_list_id, *value)
+            }
+            Expression::Literal(Scalar::String(value)) => {
+                (visitor.visit_literal_string)(data, sibling_list_id, value.as_str().into())
+            }
+            Expression::Literal(Scalar::Null(_)) => {
+                (visitor.visit_literal_null)(data, sibling_list_id)
+            }
+            Expression::Column(name) => {
+                (visitor.visit_column)(data, sibling_list_id, name.as_str().into())
+            }
+            Expression::BinaryOperation { op, left, right } => {
+                let children = [left.as_ref(), right.as_ref()];
+                let child_list_id = visit_children(visitor, children.into_iter());
+                (visitor.visit_binary)(data, sibling_list_id, op.clone().into(), child_list_id)
+            }
+            Expression::VariadicOperation { op, exprs } => {
+                let child_list_id = visit_children(visitor, exprs.iter());
+                let visit = match op {
+                    VariadicOperator::And => visitor.visit_and,
+                    VariadicOperator::Or => visitor.visit_or,
+                };
+                visit(data, sibling_list_id, child_list_id)
+            }
+            Expression::UnaryOperation { op, expr } => {
+                let child_list_id = visit_children(visitor, std::iter::once(expr.as_ref()));
+                let visit = match op {
+                    UnaryOperator::Not => visitor.visit_not,
+                    UnaryOperator::IsNull => visitor.visit_is_null,
+                    UnaryOperator::IsNotNull => visitor.visit_is_not_null,
+                };
+                visit(data, sibling_list_id, child_list_id)
+            }
+            other => {
+                let rendered = other.to_string();
+                (visitor.visit_unknown)(data, sibling_list_id, rendered.as_str().into())
+            }
+        }
+    }
+
+    let top_level_list_id = (visitor.make_expr_list)(visitor.data, 1);
+    visit_expression_impl(visitor, top_level_list_id, &expr);
+    top_level_list_id
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    extern "C" fn allocate_string(value: KernelStringSlice) -> *mut c_void {
+        let value = unsafe { String::try_from_slice(value) };
+        Box::into_raw(Box::new(value)).cast()
+    }
+
+    #[test]
+    fn test_expression_to_string() {
+        let expr = Expression::column("a")
+            .lt(Expression::literal(5))
+            .and(Expression::column("b").is_null());
+        let handle = wrap_shared_expression(expr.clone());
+
+        let rendered = unsafe { expression_to_string(handle, allocate_string) };
+        let rendered = unsafe { Box::from_raw(rendered.cast::<String>()) };
+        assert_eq!(*rendered, expr.to_string());
+
+        unsafe { drop_expression(handle) };
+    }
+}
@@ -17,7 +17,9 @@ use delta_kernel::schema::{DataType, PrimitiveType, StructField, StructType};
 use delta_kernel::snapshot::Snapshot;
 use delta_kernel::{DeltaResult, EngineInterface, Error};
 
+mod expressions;
 mod handle;
+use expressions::{unwrap_shared_expression, SharedExpression};
 use handle::{ArcHandle, BoxHandle, SizedArcHandle, Unconstructable};
 
 /// Model iterators. This allows an engine to specify iteration however it likes, and we simply wrap
@@ -142,6 +144,10 @@ pub enum KernelError {
     JoinFailureError,
     Utf8Error,
     ParseIntError,
+    InvalidExpressionError,
+    StatsParseError,
+    SkippingEvaluationError,
+    InternalError,
 }
 
 impl From<Error> for KernelError {
@@ -176,6 +182,10 @@ impl From<Error> for KernelError {
             Error::JoinFailure(_) => KernelError::JoinFailureError,
             Error::Utf8Error(_) => KernelError::Utf8Error,
             Error::ParseIntError(_) => KernelError::ParseIntError,
+            Error::InvalidExpression(_) => KernelError::InvalidExpressionError,
+            Error::StatsParse { .. } => KernelError::StatsParseError,
+            Error::SkippingEvaluation { .. } => KernelError::SkippingEvaluationError,
+            Error::InternalError(_) => KernelError::InternalError,
             Error::Backtraced {
                 source,
                 backtrace: _,
@@ -747,15 +757,29 @@ pub unsafe extern "C" fn kernel_scan_files_init(
     kernel_scan_files_init_impl(snapshot, table_client, predicate).into_extern_result(table_client)
 }
 
+/// Get a FileList for all the files that need to be read from the table, skipping files that
+/// cannot match the given predicate (which the engine built with the `expression_XXX` functions).
+/// The predicate is borrowed, and remains owned by the engine.
+///
+/// # Safety
+///
+/// Caller is responsible to pass a valid snapshot pointer and predicate handle.
+#[no_mangle]
+pub unsafe extern "C" fn kernel_scan_files_init_with_predicate(
+    snapshot: *const SnapshotHandle,
+    table_client: *const ExternEngineInterfaceHandle,
+    predicate: *const SharedExpression,
+) -> ExternResult<*mut KernelScanFileIterator> {
+    let predicate = unsafe { unwrap_shared_expression(predicate) };
+    scan_files_init_impl(snapshot, table_client, Some(predicate)).into_extern_result(table_client)
+}
+
 fn kernel_scan_files_init_impl(
     snapshot: *const SnapshotHandle,
     extern_table_client: *const ExternEngineInterfaceHandle,
     predicate: Option<&mut EnginePredicate>,
 ) -> DeltaResult<*mut KernelScanFileIterator> {
-    let snapshot = unsafe { ArcHandle::clone_as_arc(snapshot) };
-    let extern_table_client = unsafe { ArcHandle::clone_as_arc(extern_table_client) };
-    let mut scan_builder = ScanBuilder::new(snapshot.clone());
-    if let Some(predicate) = predicate {
+    let predicate = predicate.and_then(|predicate| {
         // TODO: There is a lot of redundancy between the various visit_expression_XXX methods here,
         // vs. ProvidesMetadataFilter trait and the class hierarchy that supports it. Can we justify
         // combining the two, so that native rust kernel code also uses the visitor idiom? Doing so
@@ -764,10 +788,22 @@ fn kernel_scan_files_init_impl(
         // from the kernel proper and make it one of the sensible default engine clients instead.
         let mut visitor_state = KernelExpressionVisitorState::new();
         let exprid = (predicate.visitor)(predicate.predicate, &mut visitor_state);
-        if let Some(predicate) = unwrap_kernel_expression(&mut visitor_state, exprid) {
-            println!("Got predicate: {}", predicate);
-            scan_builder = scan_builder.with_predicate(predicate);
-        }
+        unwrap_kernel_expression(&mut visitor_state, exprid)
+    });
+    scan_files_init_impl(snapshot, extern_table_client, predicate)
+}
+
+fn scan_files_init_impl(
+    snapshot: *const SnapshotHandle,
+    extern_table_client: *const ExternEngineInterfaceHandle,
+    predicate: Option<Expression>,
+) -> DeltaResult<*mut KernelScanFileIterator> {
+    let snapshot = unsafe { ArcHandle::clone_as_arc(snapshot) };
+    let extern_table_client = unsafe { ArcHandle::clone_as_arc(extern_table_client) };
+    let mut scan_builder = ScanBuilder::new(snapshot.clone());
+    if let Some(predicate) = predicate {
+        println!("Got predicate: {}", predicate);
+        scan_builder = scan_builder.with_predicate(predicate);
     }
     let scan_adds = scan_builder
         .build()
@@ -84,7 +84,7 @@ impl Metadata {
     }
 
     pub fn schema(&self) -> DeltaResult<StructType> {
-        Ok(serde_json::from_str(&self.schema_string)?)
+        StructType::from_json(&self.schema_string)
     }
 }
 
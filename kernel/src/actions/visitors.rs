@@ -5,6 +5,7 @@ use std::collections::HashMap;
 
 use crate::{
     engine_data::{GetData, TypedGetData},
+    scan::selection::SelectionBitmap,
     DataVisitor, DeltaResult,
 };
 
@@ -64,7 +65,7 @@ impl DataVisitor for MetadataVisitor {
 
 #[derive(Default)]
 pub(crate) struct SelectionVectorVisitor {
-    pub(crate) selection_vector: Vec<bool>,
+    pub(crate) selection_vector: SelectionBitmap,
 }
 
 impl DataVisitor for SelectionVectorVisitor {
@@ -48,6 +48,16 @@ impl EngineData for ArrowEngineData {
         self.data.num_rows()
     }
 
+    fn slice(&self, offset: usize, length: usize) -> DeltaResult<Box<dyn EngineData>> {
+        if offset.saturating_add(length) > self.length() {
+            return Err(Error::generic(format!(
+                "Cannot slice {length} rows at offset {offset} of {} rows",
+                self.length()
+            )));
+        }
+        Ok(Box::new(Self::new(self.data.slice(offset, length))))
+    }
+
     fn as_any(&self) -> &dyn Any {
         self
     }
@@ -1,23 +1,31 @@
 //! Expression handling based on arrow-rs compute kernels.
 use std::sync::Arc;
 
-use arrow_arith::boolean::{and, is_null, not, or};
-use arrow_arith::numeric::{add, div, mul, sub};
+use arrow_arith::boolean::{and_kleene, is_not_null, is_null, not, or, or_kleene};
+use arrow_arith::numeric::{add, div, mul, rem, sub};
 use arrow_array::cast::AsArray;
 use arrow_array::{
     Array, ArrayRef, BinaryArray, BooleanArray, Date32Array, Datum, Decimal128Array, Float32Array,
     Float64Array, Int16Array, Int32Array, Int64Array, Int8Array, RecordBatch, StringArray,
-    StructArray, TimestampMicrosecondArray,
+    StructArray, TimestampMicrosecondArray, UInt32Array,
 };
-use arrow_ord::cmp::{distinct, eq, gt, gt_eq, lt, lt_eq, neq};
+use arrow_cast::cast;
+use arrow_ord::cmp::{distinct, eq, gt, gt_eq, lt, lt_eq, neq, not_distinct};
 use arrow_schema::{
     ArrowError, DataType as ArrowDataType, Field as ArrowField, Schema as ArrowSchema,
 };
+use arrow_select::filter::prep_null_mask_filter;
+use arrow_select::take::take;
+use arrow_select::zip::zip;
+use arrow_string::like::starts_with;
 use itertools::Itertools;
+use regex::Regex;
 
 use crate::client::arrow_data::ArrowEngineData;
 use crate::error::{DeltaResult, Error};
-use crate::expressions::{BinaryOperator, Expression, Scalar, UnaryOperator, VariadicOperator};
+use crate::expressions::{
+    expand_quantified, BinaryOperator, Expression, Scalar, UnaryOperator, VariadicOperator,
+};
 use crate::schema::{DataType, PrimitiveType, SchemaRef};
 use crate::{EngineData, ExpressionEvaluator, ExpressionHandler};
 
@@ -42,7 +50,9 @@ impl Scalar {
             Double(val) => Arc::new(Float64Array::from_value(*val, num_rows)),
             String(val) => Arc::new(StringArray::from(vec![val.clone(); num_rows])),
             Boolean(val) => Arc::new(BooleanArray::from(vec![*val; num_rows])),
-            Timestamp(val) => Arc::new(TimestampMicrosecondArray::from_value(*val, num_rows)),
+            Timestamp(val) | TimestampNtz(val) => {
+                Arc::new(TimestampMicrosecondArray::from_value(*val, num_rows))
+            }
             Date(val) => Arc::new(Date32Array::from_value(*val, num_rows)),
             Binary(val) => Arc::new(BinaryArray::from(vec![val.as_slice(); num_rows])),
             Decimal(val, precision, scale) => Arc::new(
@@ -78,6 +88,106 @@ impl Scalar {
     }
 }
 
+/// Returns the type that both operands of an arithmetic operation are promoted to, or `None` if
+/// they are not both integer or floating point numbers. Integers are promoted to the wider of the
+/// two types, and to the floating point type if the other operand is one, so `int + long` is a
+/// `long` and `int + float` is a `float`.
+fn arithmetic_type(left: &ArrowDataType, right: &ArrowDataType) -> Option<ArrowDataType> {
+    use ArrowDataType::*;
+    let rank = |data_type: &ArrowDataType| match data_type {
+        Int8 => Some(0),
+        Int16 => Some(1),
+        Int32 => Some(2),
+        Int64 => Some(3),
+        Float32 => Some(4),
+        Float64 => Some(5),
+        _ => None,
+    };
+    if rank(left)? >= rank(right)? {
+        Some(left.clone())
+    } else {
+        Some(right.clone())
+    }
+}
+
+/// Returns the type that values of both types can be cast to, for expressions that combine
+/// values such as CASE. Numbers are promoted the same as for arithmetic (see [`arithmetic_type`]),
+/// while other types must be the same.
+fn common_type(left: &ArrowDataType, right: &ArrowDataType) -> Option<ArrowDataType> {
+    if left == right {
+        Some(left.clone())
+    } else {
+        arithmetic_type(left, right)
+    }
+}
+
+/// Returns the common type (see [`common_type`]) of the values of an expression, or an error that
+/// names the expression if there is none.
+fn unify_types<'a>(
+    name: &str,
+    data_type: &ArrowDataType,
+    values: impl IntoIterator<Item = &'a ArrayRef>,
+) -> DeltaResult<ArrowDataType> {
+    values
+        .into_iter()
+        .try_fold(data_type.clone(), |data_type, value| {
+            common_type(&data_type, value.data_type()).ok_or_else(|| {
+                Error::generic(format!(
+                    "{name} values have incompatible types {data_type} and {}",
+                    value.data_type()
+                ))
+            })
+        })
+}
+
+/// Casts the operands of an arithmetic operation to a common type (see [`arithmetic_type`]).
+/// Operands of other types are returned as-is.
+fn promote_arithmetic_operands(
+    left: ArrayRef,
+    right: ArrayRef,
+) -> DeltaResult<(ArrayRef, ArrayRef)> {
+    match arithmetic_type(left.data_type(), right.data_type()) {
+        Some(data_type) => Ok((cast(&left, &data_type)?, cast(&right, &data_type)?)),
+        None => Ok((left, right)),
+    }
+}
+
+/// Evaluates `left RLIKE right`, i.e. whether each string of `left` contains a match of the
+/// regular expression in the same row of `right`. Each distinct pattern of consecutive rows is
+/// compiled once, so a literal pattern is only compiled once per batch.
+fn rlike(left: &ArrayRef, right: &ArrayRef) -> DeltaResult<ArrayRef> {
+    let (Some(values), Some(patterns)) =
+        (left.as_string_opt::<i32>(), right.as_string_opt::<i32>())
+    else {
+        return Err(Error::generic(format!(
+            "RLIKE expects string operands, got {} and {}",
+            left.data_type(),
+            right.data_type()
+        )));
+    };
+    let mut regex: Option<(&str, Regex)> = None;
+    let result: BooleanArray = values
+        .iter()
+        .zip(patterns.iter())
+        .map(|(value, pattern)| {
+            let (Some(value), Some(pattern)) = (value, pattern) else {
+                return Ok(None);
+            };
+            let regex = match regex.take() {
+                Some((cached, compiled)) if cached == pattern => regex.insert((cached, compiled)),
+                _ => {
+                    let compiled = Regex::new(pattern).map_err(|err| {
+                        Error::invalid_expression(format!("Invalid RLIKE pattern {pattern}: {err}"))
+                    })?;
+                    regex.insert((pattern, compiled))
+                }
+            };
+            Ok(Some(regex.1.is_match(value)))
+        })
+        .collect::<DeltaResult<_>>()?;
+    Ok(Arc::new(result))
+}
+
 fn wrap_comparison_result(arr: BooleanArray) -> ArrayRef {
     Arc::new(arr) as Arc<dyn Array>
 }
@@ -133,7 +243,7 @@ fn column_as_struct<'a>(
         .ok_or(ArrowError::SchemaError(format!("{} is not a struct", name)))
 }
 
-fn evaluate_expression(
+pub(crate) fn evaluate_expression(
     expression: &Expression,
     batch: &RecordBatch,
     result_type: Option<&DataType>,
@@ -175,11 +285,18 @@ fn evaluate_expression(
             Ok(match op {
                 UnaryOperator::Not => Arc::new(not(downcast_to_bool(&arr)?)?),
                 UnaryOperator::IsNull => Arc::new(is_null(&arr)?),
+                UnaryOperator::IsNotNull => Arc::new(is_not_null(&arr)?),
             })
         }
         (BinaryOperation { op, left, right }, _) => {
             let left_arr = evaluate_expression(left.as_ref(), batch, None)?;
             let right_arr = evaluate_expression(right.as_ref(), batch, None)?;
+            let (left_arr, right_arr) = match op {
+                Plus | Minus | Multiply | Divide | Modulo => {
+                    promote_arithmetic_operands(left_arr, right_arr)?
+                }
+                _ => (left_arr, right_arr),
+            };
 
             type Operation = fn(&dyn Datum, &dyn Datum) -> Result<Arc<dyn Array>, ArrowError>;
             let eval: Operation = match op {
@@ -187,6 +304,7 @@ fn evaluate_expression(
                 Minus => sub,
                 Multiply => mul,
                 Divide => div,
+                Modulo => rem,
                 LessThan => |l, r| lt(l, r).map(wrap_comparison_result),
                 LessThanOrEqual => |l, r| lt_eq(l, r).map(wrap_comparison_result),
                 GreaterThan => |l, r| gt(l, r).map(wrap_comparison_result),
@@ -194,15 +312,19 @@ fn evaluate_expression(
                 Equal => |l, r| eq(l, r).map(wrap_comparison_result),
                 NotEqual => |l, r| neq(l, r).map(wrap_comparison_result),
                 Distinct => |l, r| distinct(l, r).map(wrap_comparison_result),
+                NullSafeEqual => |l, r| not_distinct(l, r).map(wrap_comparison_result),
+                StartsWith => |l, r| starts_with(l, r).map(wrap_comparison_result),
+                RLike => return rlike(&left_arr, &right_arr),
             };
 
             eval(&left_arr, &right_arr).map_err(Error::generic_err)
         }
         (VariadicOperation { op, exprs }, None | Some(&DataType::BOOLEAN)) => {
             type Operation = fn(&BooleanArray, &BooleanArray) -> Result<BooleanArray, ArrowError>;
+            // Kleene logic, like SQL: `FALSE AND NULL` is FALSE and `TRUE OR NULL` is TRUE
             let (reducer, default): (Operation, _) = match op {
-                VariadicOperator::And => (and, true),
-                VariadicOperator::Or => (or, false),
+                VariadicOperator::And => (and_kleene, true),
+                VariadicOperator::Or => (or_kleene, false),
             };
             exprs
                 .iter()
@@ -221,6 +343,190 @@ fn evaluate_expression(
                 "Variadic {expression:?} is expected to return boolean results, got {result_type:?}"
             )))
         }
+        (InList { expr, list }, None | Some(&DataType::BOOLEAN)) => {
+            // `x IN (a, b, ...)` has the same (null) semantics as `x = a OR x = b OR ...`
+            let exprs = list
+                .iter()
+                .map(|value| expr.as_ref().clone().eq(value.clone()));
+            evaluate_expression(&Expression::or_from(exprs), batch, result_type)
+        }
+        (InList { .. }, _) => Err(Error::Generic(format!(
+            "IN list {expression:?} is expected to return boolean results, got {result_type:?}"
+        ))),
+        (
+            Quantified {
+                op,
+                quantifier,
+                expr,
+                list,
+            },
+            None | Some(&DataType::BOOLEAN),
+        ) => {
+            let expanded = expand_quantified(op, quantifier, expr, list);
+            evaluate_expression(&expanded, batch, result_type)
+        }
+        (Quantified { .. }, _) => Err(Error::Generic(format!(
+            "Quantified comparison {expression:?} is expected to return boolean results, got {result_type:?}"
+        ))),
+        (Between { expr, low, high }, None | Some(&DataType::BOOLEAN)) => {
+            let exprs = [
+                expr.as_ref().clone().gt_eq(low.as_ref().clone()),
+                expr.as_ref().clone().lt_eq(high.as_ref().clone()),
+            ];
+            evaluate_expression(&Expression::and_from(exprs), batch, result_type)
+        }
+        (Between { .. }, _) => Err(Error::Generic(format!(
+            "BETWEEN {expression:?} is expected to return boolean results, got {result_type:?}"
+        ))),
+        (
+            CaseWhen {
+                branches,
+                otherwise,
+            },
+            _,
+        ) => {
+            let otherwise = evaluate_expression(otherwise, batch, result_type)?;
+            let branches: Vec<_> = branches
+                .iter()
+                .map(|(condition, value)| {
+                    let condition = evaluate_expression(condition, batch, None)?;
+                    let condition = condition.as_boolean_opt().ok_or_else(|| {
+                        Error::generic(format!(
+                            "CASE condition is expected to be boolean, got {}",
+                            condition.data_type()
+                        ))
+                    })?;
+                    // a NULL condition does not match
+                    let condition = prep_null_mask_filter(condition);
+                    let value = evaluate_expression(value, batch, result_type)?;
+                    Ok((condition, value))
+                })
+                .try_collect::<_, _, Error>()?;
+
+            // All values are cast to a common type, so that they can be combined
+            let values = branches.iter().map(|(_, value)| value);
+            let data_type = unify_types("CASE", otherwise.data_type(), values)?;
+            // Going from the last branch to the first makes the first matching branch win
+            branches.into_iter().rev().try_fold(
+                cast(&otherwise, &data_type)?,
+                |result, (condition, value)| {
+                    Ok(zip(&condition, &cast(&value, &data_type)?, &result)?)
+                },
+            )
+        }
+        (Coalesce(exprs), _) => {
+            let values: Vec<_> = exprs
+                .iter()
+                .map(|expr| evaluate_expression(expr, batch, result_type))
+                .try_collect()?;
+            let (last, rest) = values
+                .split_last()
+                .ok_or_else(|| Error::generic("COALESCE requires at least one argument"))?;
+            let data_type = unify_types("COALESCE", last.data_type(), rest)?;
+            // Going from the last value to the first makes the first non-null value win
+            rest.iter()
+                .rev()
+                .try_fold(cast(last, &data_type)?, |result, value| {
+                    let value = cast(value, &data_type)?;
+                    Ok(zip(&is_not_null(&value)?, &value, &result)?)
+                })
+        }
+        (Greatest(exprs) | Least(exprs), _) => {
+            let name = match expression {
+                Greatest(_) => "GREATEST",
+                _ => "LEAST",
+            };
+            let values: Vec<_> = exprs
+                .iter()
+                .map(|expr| evaluate_expression(expr, batch, result_type))
+                .try_collect()?;
+            let (first, rest) = values
+                .split_first()
+                .ok_or_else(|| Error::generic(format!("{name} requires at least one argument")))?;
+            let data_type = unify_types(name, first.data_type(), rest)?;
+            // NULL values are ignored: a value replaces the result so far if that is NULL, or if
+            // the value is greater (or smaller) than it
+            rest.iter()
+                .try_fold(cast(first, &data_type)?, |result, value| {
+                    let value = cast(value, &data_type)?;
+                    let better = match expression {
+                        Greatest(_) => gt(&value, &result)?,
+                        _ => lt(&value, &result)?,
+                    };
+                    let replace = or(&prep_null_mask_filter(&better), &is_null(&result)?)?;
+                    Ok(zip(&replace, &value, &result)?)
+                })
+        }
+        (Cast { expr, target_type }, _) => {
+            let value = evaluate_expression(expr, batch, None)?;
+            Ok(cast(&value, &ArrowDataType::try_from(target_type)?)?)
+        }
+        (MapAccess { map, key }, _) => {
+            let map = evaluate_expression(map, batch, None)?;
+            let map = map.as_map_opt().ok_or_else(|| {
+                Error::generic(format!(
+                    "Cannot look up a key in {}, which is not a map",
+                    map.data_type()
+                ))
+            })?;
+            let key = key.to_array(map.keys().len())?;
+            if key.data_type() != map.keys().data_type() {
+                return Err(Error::generic(format!(
+                    "Cannot look up a {} key in a map with {} keys",
+                    key.data_type(),
+                    map.keys().data_type()
+                )));
+            }
+            // Look up the index of the (first) matching entry of each map, if any
+            let matches = eq(map.keys(), &key)?;
+            let offsets = map.value_offsets();
+            let indices: UInt32Array = (0..map.len())
+                .map(|row| {
+                    let entries = offsets[row] as usize..offsets[row + 1] as usize;
+                    match map.is_valid(row) {
+                        true => entries
+                            .into_iter()
+                            .find(|entry| matches.value(*entry))
+                            .map(|entry| entry as u32),
+                        false => None,
+                    }
+                })
+                .collect();
+            Ok(take(map.values(), &indices, None)?)
+        }
+        (ArraySize(array), _) => {
+            let array = evaluate_expression(array, batch, None)?;
+            let array = array.as_list_opt::<i32>().ok_or_else(|| {
+                Error::generic(format!(
+                    "Cannot get the size of {}, which is not an array",
+                    array.data_type()
+                ))
+            })?;
+            let sizes: Int32Array = (0..array.len())
+                .map(|row| array.is_valid(row).then(|| array.value_length(row)))
+                .collect();
+            Ok(Arc::new(sizes))
+        }
+        (ArrayElement { array, index }, _) => {
+            let index = usize::try_from(*index)
+                .map_err(|_| Error::generic(format!("Array index {index} must not be negative")))?;
+            let array = evaluate_expression(array, batch, None)?;
+            let array = array.as_list_opt::<i32>().ok_or_else(|| {
+                Error::generic(format!(
+                    "Cannot get an element of {}, which is not an array",
+                    array.data_type()
+                ))
+            })?;
+            let offsets = array.value_offsets();
+            let indices: UInt32Array = (0..array.len())
+                .map(|row| {
+                    let position = offsets[row] as usize + index;
+                    (array.is_valid(row) && position < offsets[row + 1] as usize)
+                        .then_some(position as u32)
+                })
+                .collect();
+            Ok(take(array.values(), &indices, None)?)
+        }
     }
 }
 
@@ -342,7 +648,6 @@ mod tests {
         let expected = Arc::new(Int32Array::from(vec![2, 4, 6]));
         assert_eq!(results.as_ref(), expected.as_ref());
 
-        // TODO handle type casting
         let expression = Box::new(column.div(Expression::Literal(Scalar::Integer(1))));
         let results = evaluate_expression(&expression, &batch, None).unwrap();
         let expected = Arc::new(Int32Array::from(vec![1, 2, 3]));
@@ -380,6 +685,472 @@ mod tests {
         assert_eq!(results.as_ref(), expected.as_ref());
     }
 
+    #[test]
+    fn test_arithmetic_promotion() {
+        let schema = Schema::new(vec![
+            Field::new("i", DataType::Int32, false),
+            Field::new("l", DataType::Int64, false),
+            Field::new("f", DataType::Float32, false),
+            Field::new("d", DataType::Float64, false),
+        ]);
+        let batch = RecordBatch::try_new(
+            Arc::new(schema),
+            vec![
+                Arc::new(Int32Array::from(vec![1, 2, 3])),
+                Arc::new(Int64Array::from(vec![10, 20, 30])),
+                Arc::new(Float32Array::from(vec![0.5, 1.5, 2.5])),
+                Arc::new(Float64Array::from(vec![0.25, 0.5, 0.75])),
+            ],
+        )
+        .unwrap();
+        let column = Expression::column;
+
+        // int + long -> long
+        let expression = column("i").add(column("l"));
+        let results = evaluate_expression(&expression, &batch, None).unwrap();
+        assert_eq!(results.as_ref(), &Int64Array::from(vec![11, 22, 33]));
+
+        // long - int literal -> long
+        let expression = column("l").sub(Expression::literal(1));
+        let results = evaluate_expression(&expression, &batch, None).unwrap();
+        assert_eq!(results.as_ref(), &Int64Array::from(vec![9, 19, 29]));
+
+        // int * float -> float
+        let expression = column("i").mul(column("f"));
+        let results = evaluate_expression(&expression, &batch, None).unwrap();
+        assert_eq!(results.as_ref(), &Float32Array::from(vec![0.5, 3.0, 7.5]));
+
+        // float / double -> double
+        let expression = column("f").div(column("d"));
+        let results = evaluate_expression(&expression, &batch, None).unwrap();
+        assert_eq!(
+            results.as_ref(),
+            &Float64Array::from(vec![2.0, 3.0, 2.5 / 0.75])
+        );
+
+        // promoted operands can be compared with literals of the promoted type
+        let expression = column("i").add(column("l")).gt(Expression::literal(20_i64));
+        let results = evaluate_expression(&expression, &batch, None).unwrap();
+        assert_eq!(
+            results.as_ref(),
+            &BooleanArray::from(vec![false, true, true])
+        );
+
+        // non-numeric operands are not promoted
+        let expression = column("i").add(Expression::literal("a"));
+        assert!(evaluate_expression(&expression, &batch, None).is_err());
+    }
+
+    #[test]
+    fn test_divide_by_zero() {
+        let schema = Schema::new(vec![
+            Field::new("i", DataType::Int32, false),
+            Field::new("d", DataType::Float64, false),
+        ]);
+        let batch = RecordBatch::try_new(
+            Arc::new(schema),
+            vec![
+                Arc::new(Int32Array::from(vec![1, -1, 0])),
+                Arc::new(Float64Array::from(vec![1.0, -1.0, 0.0])),
+            ],
+        )
+        .unwrap();
+
+        // integer division by zero is an error
+        let expression = Expression::column("i").div(Expression::literal(0));
+        assert!(evaluate_expression(&expression, &batch, None).is_err());
+
+        // floating point division by zero follows IEEE 754, also after promotion
+        for expression in [
+            Expression::column("d").div(Expression::literal(Scalar::Double(0.0))),
+            Expression::column("i").div(Expression::literal(Scalar::Double(0.0))),
+        ] {
+            let results = evaluate_expression(&expression, &batch, None).unwrap();
+            let results = results.as_primitive::<arrow_array::types::Float64Type>();
+            assert_eq!(results.value(0), f64::INFINITY);
+            assert_eq!(results.value(1), f64::NEG_INFINITY);
+            assert!(results.value(2).is_nan());
+        }
+    }
+
+    #[test]
+    fn test_modulo() {
+        let schema = Schema::new(vec![
+            Field::new("i", DataType::Int32, true),
+            Field::new("l", DataType::Int64, false),
+        ]);
+        let batch = RecordBatch::try_new(
+            Arc::new(schema),
+            vec![
+                Arc::new(Int32Array::from(vec![Some(7), Some(-7), Some(0), None])),
+                Arc::new(Int64Array::from(vec![3, 3, -3, 2])),
+            ],
+        )
+        .unwrap();
+
+        // the remainder has the sign of the dividend
+        let expression = Expression::column("i") % Expression::literal(3);
+        let results = evaluate_expression(&expression, &batch, None).unwrap();
+        let expected = Int32Array::from(vec![Some(1), Some(-1), Some(0), None]);
+        assert_eq!(results.as_ref(), &expected);
+
+        let expression = Expression::column("i") % Expression::literal(-3);
+        let results = evaluate_expression(&expression, &batch, None).unwrap();
+        let expected = Int32Array::from(vec![Some(1), Some(-1), Some(0), None]);
+        assert_eq!(results.as_ref(), &expected);
+
+        // operands are promoted like for the other arithmetic operators
+        let expression = Expression::column("i") % Expression::column("l");
+        let results = evaluate_expression(&expression, &batch, None).unwrap();
+        let expected = Int64Array::from(vec![Some(1), Some(-1), Some(0), None]);
+        assert_eq!(results.as_ref(), &expected);
+
+        // e.g. the bucket of a row, for a table bucketed by `i`
+        let expression =
+            (Expression::column("i") % Expression::literal(4)).eq(Expression::literal(3));
+        let results = evaluate_expression(&expression, &batch, None).unwrap();
+        let expected = BooleanArray::from(vec![Some(true), Some(false), Some(false), None]);
+        assert_eq!(results.as_ref(), &expected);
+
+        // integer modulo by zero is an error
+        let expression = Expression::column("i") % Expression::literal(0);
+        assert!(evaluate_expression(&expression, &batch, None).is_err());
+    }
+
+    #[test]
+    fn test_case_when() {
+        let schema = Schema::new(vec![
+            Field::new("a", DataType::Int32, true),
+            Field::new("b", DataType::Int64, true),
+        ]);
+        let batch = RecordBatch::try_new(
+            Arc::new(schema),
+            vec![
+                Arc::new(Int32Array::from(vec![Some(-5), Some(0), Some(5), None])),
+                Arc::new(Int64Array::from(vec![Some(1), Some(2), Some(3), Some(4)])),
+            ],
+        )
+        .unwrap();
+        let column = Expression::column;
+        let lit = |value: i32| Expression::literal(value);
+
+        // the first matching branch wins, and a NULL condition falls through to `otherwise`
+        let expression = Expression::case_when(
+            [
+                (column("a").lt(lit(0)), Expression::literal("negative")),
+                (column("a").lt(lit(1)), Expression::literal("small")),
+                (
+                    column("a").gt_eq(lit(0)),
+                    Expression::literal("non-negative"),
+                ),
+            ],
+            Expression::literal("unknown"),
+        );
+        let results = evaluate_expression(&expression, &batch, None).unwrap();
+        let expected = StringArray::from(vec!["negative", "small", "non-negative", "unknown"]);
+        assert_eq!(results.as_ref(), &expected);
+
+        // no match falls through to `otherwise`, and values are promoted to a common type
+        let expression =
+            Expression::case_when([(column("a").gt(lit(100)), column("a"))], column("b"));
+        let results = evaluate_expression(&expression, &batch, None).unwrap();
+        assert_eq!(results.as_ref(), &Int64Array::from(vec![1, 2, 3, 4]));
+
+        let expression = Expression::case_when([(column("a").eq(lit(0)), column("b"))], lit(0));
+        let results = evaluate_expression(&expression, &batch, None).unwrap();
+        assert_eq!(results.as_ref(), &Int64Array::from(vec![0, 2, 0, 0]));
+
+        // without any branches, the result is `otherwise`
+        let expression = Expression::case_when([], column("a"));
+        let results = evaluate_expression(&expression, &batch, None).unwrap();
+        assert_eq!(
+            results.as_ref(),
+            &Int32Array::from(vec![Some(-5), Some(0), Some(5), None])
+        );
+
+        // conditions must be boolean, and values must have a common type
+        let expression = Expression::case_when([(column("a"), lit(1))], lit(0));
+        assert!(evaluate_expression(&expression, &batch, None).is_err());
+        let expression = Expression::case_when(
+            [(column("a").eq(lit(0)), Expression::literal("zero"))],
+            lit(0),
+        );
+        assert!(evaluate_expression(&expression, &batch, None).is_err());
+    }
+
+    #[test]
+    fn test_map_access() {
+        use arrow_array::builder::{Int32Builder, MapBuilder, StringBuilder};
+
+        let mut builder = MapBuilder::new(None, StringBuilder::new(), Int32Builder::new());
+        for row in [
+            Some(vec![("latency", 100), ("errors", 2)]),
+            Some(vec![("errors", 5)]),
+            None,
+            Some(vec![("latency", 300)]),
+        ] {
+            for (key, value) in row.iter().flatten() {
+                builder.keys().append_value(key);
+                builder.values().append_value(*value);
+            }
+            builder.append(row.is_some()).unwrap();
+        }
+        let metrics = builder.finish();
+        let schema = Schema::new(vec![Field::new(
+            "metrics",
+            metrics.data_type().clone(),
+            true,
+        )]);
+        let batch = RecordBatch::try_new(Arc::new(schema), vec![Arc::new(metrics)]).unwrap();
+        let metrics = Expression::column("metrics");
+
+        // present keys return their value, and absent keys (or null maps) null
+        let expression = metrics.clone().map_access("latency");
+        let results = evaluate_expression(&expression, &batch, None).unwrap();
+        let expected = Int32Array::from(vec![Some(100), None, None, Some(300)]);
+        assert_eq!(results.as_ref(), &expected);
+
+        let expression = metrics.clone().map_access("missing");
+        let results = evaluate_expression(&expression, &batch, None).unwrap();
+        assert_eq!(results.as_ref(), &Int32Array::new_null(4));
+
+        let expression = metrics
+            .clone()
+            .map_access("latency")
+            .gt(Expression::literal(150));
+        let results = evaluate_expression(&expression, &batch, None).unwrap();
+        let expected = BooleanArray::from(vec![Some(false), None, None, Some(true)]);
+        assert_eq!(results.as_ref(), &expected);
+
+        // the key must have the map's key type
+        let expression = metrics.map_access(1);
+        let error = evaluate_expression(&expression, &batch, None).unwrap_err();
+        assert!(
+            error.to_string().contains("Cannot look up a Int32 key"),
+            "{error}"
+        );
+    }
+
+    #[test]
+    fn test_array_size_and_element() {
+        use arrow_array::builder::{Int32Builder, ListBuilder, StructBuilder};
+        use arrow_array::types::Int32Type;
+
+        let mut numbers = ListBuilder::new(Int32Builder::new());
+        for row in [Some(vec![1, 2, 3]), Some(vec![]), None, Some(vec![4])] {
+            numbers.append_option(row.map(|values| values.into_iter().map(Some)));
+        }
+        let numbers = numbers.finish();
+
+        // an array of structs
+        let item_fields = vec![Field::new("x", DataType::Int32, true)];
+        let mut items = ListBuilder::new(StructBuilder::from_fields(item_fields, 0));
+        for row in [Some(vec![10, 20]), None, Some(vec![]), Some(vec![30])] {
+            for x in row.iter().flatten() {
+                let item = items.values();
+                item.field_builder::<Int32Builder>(0)
+                    .unwrap()
+                    .append_value(*x);
+                item.append(true);
+            }
+            items.append(row.is_some());
+        }
+        let items = items.finish();
+
+        let schema = Schema::new(vec![
+            Field::new("numbers", numbers.data_type().clone(), true),
+            Field::new("items", items.data_type().clone(), true),
+        ]);
+        let batch =
+            RecordBatch::try_new(Arc::new(schema), vec![Arc::new(numbers), Arc::new(items)])
+                .unwrap();
+        let column = Expression::column;
+        let evaluate = |expression: Expression| evaluate_expression(&expression, &batch, None);
+
+        // sizes of empty arrays are 0, and of null arrays null
+        let results = evaluate(column("numbers").array_size()).unwrap();
+        let expected = Int32Array::from(vec![Some(3), Some(0), None, Some(1)]);
+        assert_eq!(results.as_ref(), &expected);
+        let results = evaluate(column("items").array_size()).unwrap();
+        let expected = Int32Array::from(vec![Some(2), None, Some(0), Some(1)]);
+        assert_eq!(results.as_ref(), &expected);
+
+        // elements past the end of an array are null
+        let results = evaluate(column("numbers").array_element(0)).unwrap();
+        let expected = Int32Array::from(vec![Some(1), None, None, Some(4)]);
+        assert_eq!(results.as_ref(), &expected);
+        let results = evaluate(column("numbers").array_element(2)).unwrap();
+        let expected = Int32Array::from(vec![Some(3), None, None, None]);
+        assert_eq!(results.as_ref(), &expected);
+        let results = evaluate(column("numbers").array_element(100)).unwrap();
+        assert_eq!(results.as_ref(), &Int32Array::new_null(4));
+
+        let results = evaluate(column("items").array_element(1)).unwrap();
+        let results = results.as_struct();
+        assert_eq!(results.logical_nulls().unwrap().null_count(), 3);
+        assert_eq!(results.column(0).as_primitive::<Int32Type>().value(0), 20);
+
+        // negative indexes are an error
+        let error = evaluate(column("numbers").array_element(-1)).unwrap_err();
+        assert!(
+            error.to_string().contains("must not be negative"),
+            "{error}"
+        );
+    }
+
+    #[test]
+    fn test_typed_literals() {
+        use arrow_array::{Date32Array, Decimal128Array, TimestampMicrosecondArray};
+
+        let decimal = Decimal128Array::from(vec![1025, 2075])
+            .with_precision_and_scale(10, 2)
+            .unwrap();
+        let columns: Vec<ArrayRef> = vec![
+            Arc::new(decimal),
+            Arc::new(Date32Array::from(vec![19723, 19754])),
+            Arc::new(TimestampMicrosecondArray::from(vec![0, 1_000_000])),
+            Arc::new(TimestampMicrosecondArray::from(vec![0, 1_000_000])),
+        ];
+        let fields: Vec<_> = ["price", "d", "ts", "ts_ntz"]
+            .into_iter()
+            .zip(&columns)
+            .map(|(name, column)| Field::new(name, column.data_type().clone(), true))
+            .collect();
+        let batch = RecordBatch::try_new(Arc::new(Schema::new(fields)), columns).unwrap();
+
+        let cases = [
+            ("price", Scalar::decimal(1025, 10, 2).unwrap()),
+            ("d", Scalar::date(19754)),
+            ("ts", Scalar::timestamp(0)),
+            ("ts_ntz", Scalar::timestamp_ntz(1_000_000)),
+        ];
+        for (column, value) in cases {
+            let literal =
+                evaluate_expression(&Expression::literal(value.clone()), &batch, None).unwrap();
+            assert_eq!(literal.len(), 2);
+            assert_eq!(
+                literal.data_type(),
+                batch.column_by_name(column).unwrap().data_type()
+            );
+
+            let expression = Expression::column(column).eq(Expression::literal(value));
+            let results = evaluate_expression(&expression, &batch, None).unwrap();
+            let expected = match column {
+                "price" | "ts" => BooleanArray::from(vec![true, false]),
+                _ => BooleanArray::from(vec![false, true]),
+            };
+            assert_eq!(results.as_ref(), &expected, "{column}");
+        }
+    }
+
+    #[test]
+    fn test_coalesce() {
+        let schema = Schema::new(vec![
+            Field::new("a", DataType::Int32, true),
+            Field::new("b", DataType::Int64, true),
+        ]);
+        let batch = RecordBatch::try_new(
+            Arc::new(schema),
+            vec![
+                Arc::new(Int32Array::from(vec![Some(1), None, None])),
+                Arc::new(Int64Array::from(vec![Some(10), Some(20), None])),
+            ],
+        )
+        .unwrap();
+        let column = Expression::column;
+
+        // the first non-null value wins, promoted to a common type
+        let expression = Expression::coalesce([column("a"), column("b"), Expression::literal(0)]);
+        let results = evaluate_expression(&expression, &batch, None).unwrap();
+        assert_eq!(results.as_ref(), &Int64Array::from(vec![1, 20, 0]));
+
+        // all-null inputs return null
+        let expression = Expression::coalesce([
+            column("a"),
+            Expression::literal(Scalar::Null(crate::schema::DataType::INTEGER)),
+        ]);
+        let results = evaluate_expression(&expression, &batch, None).unwrap();
+        assert_eq!(
+            results.as_ref(),
+            &Int32Array::from(vec![Some(1), None, None])
+        );
+
+        // values must have a common type
+        let expression = Expression::coalesce([column("a"), Expression::literal("zero")]);
+        assert!(evaluate_expression(&expression, &batch, None).is_err());
+        assert!(evaluate_expression(&Expression::coalesce([]), &batch, None).is_err());
+    }
+
+    #[test]
+    fn test_greatest_least() {
+        let schema = Schema::new(vec![
+            Field::new("a", DataType::Int32, true),
+            Field::new("b", DataType::Int64, true),
+            Field::new("c", DataType::Float64, true),
+        ]);
+        let batch = RecordBatch::try_new(
+            Arc::new(schema),
+            vec![
+                Arc::new(Int32Array::from(vec![Some(1), Some(5), None, None])),
+                Arc::new(Int64Array::from(vec![Some(3), None, Some(-2), None])),
+                Arc::new(Float64Array::from(vec![Some(2.5), Some(0.5), None, None])),
+            ],
+        )
+        .unwrap();
+        let column = Expression::column;
+
+        // NULL values are ignored, unless all values are NULL
+        let expression = Expression::greatest([column("a"), column("b")]);
+        let results = evaluate_expression(&expression, &batch, None).unwrap();
+        let expected = Int64Array::from(vec![Some(3), Some(5), Some(-2), None]);
+        assert_eq!(results.as_ref(), &expected);
+
+        let expression = Expression::least([column("a"), column("b")]);
+        let results = evaluate_expression(&expression, &batch, None).unwrap();
+        let expected = Int64Array::from(vec![Some(1), Some(5), Some(-2), None]);
+        assert_eq!(results.as_ref(), &expected);
+
+        // the values are promoted to a common type
+        let expression = Expression::greatest([column("a"), column("c"), Expression::literal(0)]);
+        let results = evaluate_expression(&expression, &batch, None).unwrap();
+        let expected = Float64Array::from(vec![2.5, 5.0, 0.0, 0.0]);
+        assert_eq!(results.as_ref(), &expected);
+
+        let expression = Expression::least([column("c"), column("b"), column("a")]);
+        let results = evaluate_expression(&expression, &batch, None).unwrap();
+        let expected = Float64Array::from(vec![Some(1.0), Some(0.5), Some(-2.0), None]);
+        assert_eq!(results.as_ref(), &expected);
+
+        // a single value is returned as is
+        let expression = Expression::least([column("a")]);
+        let results = evaluate_expression(&expression, &batch, None).unwrap();
+        assert_eq!(results.as_ref(), batch.column(0).as_ref());
+
+        // values must have a common type
+        let expression = Expression::greatest([column("a"), Expression::literal("zero")]);
+        assert!(evaluate_expression(&expression, &batch, None).is_err());
+        assert!(evaluate_expression(&Expression::least([]), &batch, None).is_err());
+    }
+
+    #[test]
+    fn test_null_safe_equal() {
+        let schema = Schema::new(vec![Field::new("a", DataType::Int32, true)]);
+        let values = Int32Array::from(vec![Some(1), Some(2), None]);
+        let batch = RecordBatch::try_new(Arc::new(schema), vec![Arc::new(values)]).unwrap();
+        let column = Expression::column("a");
+
+        let expression = column.clone().null_safe_eq(Expression::literal(1));
+        let results = evaluate_expression(&expression, &batch, None).unwrap();
+        let expected = BooleanArray::from(vec![true, false, false]);
+        assert_eq!(results.as_ref(), &expected);
+
+        let null = Expression::literal(Scalar::Null(crate::schema::DataType::INTEGER));
+        let expression = column.null_safe_eq(null);
+        let results = evaluate_expression(&expression, &batch, None).unwrap();
+        let expected = BooleanArray::from(vec![false, false, true]);
+        assert_eq!(results.as_ref(), &expected);
+    }
+
     #[test]
     fn test_binary_cmp() {
         let schema = Schema::new(vec![Field::new("a", DataType::Int32, false)]);
@@ -419,6 +1190,171 @@ mod tests {
         assert_eq!(results.as_ref(), expected.as_ref());
     }
 
+    #[test]
+    fn test_starts_with() {
+        let schema = Schema::new(vec![Field::new("a", DataType::Utf8, true)]);
+        let values = StringArray::from(vec![Some("abc"), Some("abd"), Some("ab"), None]);
+        let batch = RecordBatch::try_new(Arc::new(schema.clone()), vec![Arc::new(values)]).unwrap();
+        let expression = Expression::column("a").starts_with(Expression::literal("abc"));
+        let results = evaluate_expression(&expression, &batch, None).unwrap();
+        let expected = Arc::new(BooleanArray::from(vec![
+            Some(true),
+            Some(false),
+            Some(false),
+            None,
+        ]));
+        assert_eq!(results.as_ref(), expected.as_ref());
+    }
+
+    #[test]
+    fn test_rlike() {
+        let schema = Schema::new(vec![
+            Field::new("a", DataType::Utf8, true),
+            Field::new("p", DataType::Utf8, true),
+        ]);
+        let values = StringArray::from(vec![Some("abc"), Some("xabc"), Some("ab1"), None]);
+        let patterns = StringArray::from(vec![Some("b"), Some("^ab"), None, Some("a")]);
+        let batch = RecordBatch::try_new(
+            Arc::new(schema.clone()),
+            vec![Arc::new(values), Arc::new(patterns)],
+        )
+        .unwrap();
+
+        let expression = Expression::column("a").rlike(Expression::literal("^ab"));
+        let results = evaluate_expression(&expression, &batch, None).unwrap();
+        let expected = BooleanArray::from(vec![Some(true), Some(false), Some(true), None]);
+        assert_eq!(results.as_ref(), &expected);
+
+        // the pattern may differ per row, and matches anywhere in the string unless anchored
+        let expression = Expression::column("a").rlike(Expression::column("p"));
+        let results = evaluate_expression(&expression, &batch, None).unwrap();
+        let expected = BooleanArray::from(vec![Some(true), Some(false), None, None]);
+        assert_eq!(results.as_ref(), &expected);
+
+        let expression = Expression::column("a").rlike(Expression::literal("("));
+        let result = evaluate_expression(&expression, &batch, None);
+        assert!(matches!(result, Err(Error::InvalidExpression(_))));
+    }
+
+    #[test]
+    fn test_cast() {
+        let schema = Schema::new(vec![Field::new("a", DataType::Int32, true)]);
+        let values = Int32Array::from(vec![Some(1), None, Some(3)]);
+        let batch = RecordBatch::try_new(Arc::new(schema), vec![Arc::new(values)]).unwrap();
+
+        let expression = Expression::column("a").cast(crate::schema::DataType::LONG);
+        let results = evaluate_expression(&expression, &batch, None).unwrap();
+        let expected = Int64Array::from(vec![Some(1), None, Some(3)]);
+        assert_eq!(results.as_ref(), &expected);
+
+        let expression = Expression::column("a")
+            .cast(crate::schema::DataType::DOUBLE)
+            .gt(Expression::literal(Scalar::Double(2.5)));
+        let results = evaluate_expression(&expression, &batch, None).unwrap();
+        let expected = BooleanArray::from(vec![Some(false), None, Some(true)]);
+        assert_eq!(results.as_ref(), &expected);
+    }
+
+    #[test]
+    fn test_quantified() {
+        use crate::expressions::Quantifier;
+
+        let schema = Schema::new(vec![Field::new("a", DataType::Int32, true)]);
+        let values = Int32Array::from(vec![Some(1), Some(4), Some(9), None]);
+        let batch = RecordBatch::try_new(Arc::new(schema), vec![Arc::new(values)]).unwrap();
+        let list = || [Expression::literal(3), Expression::literal(5)];
+
+        let expression = Expression::column("a").quantified(
+            BinaryOperator::GreaterThan,
+            Quantifier::All,
+            list(),
+        );
+        let results = evaluate_expression(&expression, &batch, None).unwrap();
+        let expected = BooleanArray::from(vec![Some(false), Some(false), Some(true), None]);
+        assert_eq!(results.as_ref(), &expected);
+
+        let expression = Expression::column("a").quantified(
+            BinaryOperator::GreaterThan,
+            Quantifier::Any,
+            list(),
+        );
+        let results = evaluate_expression(&expression, &batch, None).unwrap();
+        let expected = BooleanArray::from(vec![Some(false), Some(true), Some(true), None]);
+        assert_eq!(results.as_ref(), &expected);
+
+        // comparisons to an empty list
+        let expression =
+            Expression::column("a").quantified(BinaryOperator::Equal, Quantifier::All, []);
+        let results = evaluate_expression(&expression, &batch, None).unwrap();
+        assert_eq!(results.as_ref(), &BooleanArray::from(vec![true; 4]));
+    }
+
+    #[test]
+    fn test_between() {
+        let schema = Schema::new(vec![Field::new("a", DataType::Int32, true)]);
+        let values = Int32Array::from(vec![Some(1), Some(2), Some(3), Some(4), None]);
+        let batch = RecordBatch::try_new(Arc::new(schema.clone()), vec![Arc::new(values)]).unwrap();
+        let expression = Expression::between(
+            Expression::column("a"),
+            Expression::literal(2),
+            Expression::literal(3),
+        );
+
+        let results = evaluate_expression(&expression, &batch, None).unwrap();
+        let expected = Arc::new(BooleanArray::from(vec![
+            Some(false),
+            Some(true),
+            Some(true),
+            Some(false),
+            None,
+        ]));
+        assert_eq!(results.as_ref(), expected.as_ref());
+
+        let results = evaluate_expression(&!expression, &batch, None).unwrap();
+        let expected = Arc::new(BooleanArray::from(vec![
+            Some(true),
+            Some(false),
+            Some(false),
+            Some(true),
+            None,
+        ]));
+        assert_eq!(results.as_ref(), expected.as_ref());
+    }
+
+    #[test]
+    fn test_in_list() {
+        let schema = Schema::new(vec![Field::new("a", DataType::Int32, true)]);
+        let values = Int32Array::from(vec![Some(1), Some(2), Some(3), None]);
+        let batch = RecordBatch::try_new(Arc::new(schema.clone()), vec![Arc::new(values)]).unwrap();
+        let column = Expression::column("a");
+        let list = [Expression::literal(1), Expression::literal(3)];
+
+        let expression = column.clone().in_list(list.clone());
+        let results = evaluate_expression(&expression, &batch, None).unwrap();
+        let expected = Arc::new(BooleanArray::from(vec![
+            Some(true),
+            Some(false),
+            Some(true),
+            None,
+        ]));
+        assert_eq!(results.as_ref(), expected.as_ref());
+
+        let expression = column.clone().not_in_list(list);
+        let results = evaluate_expression(&expression, &batch, None).unwrap();
+        let expected = Arc::new(BooleanArray::from(vec![
+            Some(false),
+            Some(true),
+            Some(false),
+            None,
+        ]));
+        assert_eq!(results.as_ref(), expected.as_ref());
+
+        let expression = column.in_list([]);
+        let results = evaluate_expression(&expression, &batch, None).unwrap();
+        let expected = Arc::new(BooleanArray::from(vec![false; 4]));
+        assert_eq!(results.as_ref(), expected.as_ref());
+    }
+
     #[test]
     fn test_logical() {
         let schema = Schema::new(vec![
@@ -468,4 +1404,29 @@ mod tests {
         let expected = Arc::new(BooleanArray::from(vec![true, false]));
         assert_eq!(results.as_ref(), expected.as_ref());
     }
+
+    #[test]
+    fn test_logical_nulls() {
+        let schema = Schema::new(vec![Field::new("a", DataType::Boolean, true)]);
+        let values = BooleanArray::from(vec![Some(true), Some(false), None]);
+        let batch = RecordBatch::try_new(Arc::new(schema), vec![Arc::new(values)]).unwrap();
+        let column_a = || Expression::column("a");
+        let null = || Expression::literal(Scalar::Null(crate::schema::DataType::BOOLEAN));
+
+        // like SQL, a NULL operand only makes the result NULL if it could change the result
+        let expression = column_a().and(null());
+        let results = evaluate_expression(&expression, &batch, None).unwrap();
+        let expected = BooleanArray::from(vec![None, Some(false), None]);
+        assert_eq!(results.as_ref(), &expected);
+
+        let expression = column_a().or(null());
+        let results = evaluate_expression(&expression, &batch, None).unwrap();
+        let expected = BooleanArray::from(vec![Some(true), None, None]);
+        assert_eq!(results.as_ref(), &expected);
+
+        let expression = !column_a().and(null());
+        let results = evaluate_expression(&expression, &batch, None).unwrap();
+        let expected = BooleanArray::from(vec![None, Some(true), None]);
+        assert_eq!(results.as_ref(), &expected);
+    }
 }
@@ -76,16 +76,14 @@ impl JsonHandler for SyncJsonHandler {
                     "Expected column to be String",
                 ))?;
 
+        // a null string is parsed as an empty object, so that every string produces a row
         let data: Vec<_> = json_strings
             .into_iter()
-            .filter_map(|d| {
-                d.map(|dd| {
-                    let mut data = dd.as_bytes().to_vec();
-                    data.extend("\n".as_bytes());
-                    data
-                })
+            .flat_map(|d| {
+                let mut data = d.unwrap_or("{}").as_bytes().to_vec();
+                data.extend("\n".as_bytes());
+                data
             })
-            .flatten()
             .collect();
 
         let schema: ArrowSchemaRef = Arc::new(output_schema.as_ref().try_into()?);
@@ -178,6 +178,9 @@ pub trait DataVisitor {
 
 /// Any type that an engine wants to return as "data" needs to implement this trait. The bulk of the
 /// work is in the [`EngineData::extract`] method. See the docs for that method for more details.
+///
+/// Engine data must be `Send + Sync`, so that Delta Kernel can share it between threads, e.g. the
+/// stats that a [`StatsCache`](crate::scan::StatsCache) keeps for several scans.
 /// ```rust
 /// # use std::any::Any;
 /// # use delta_kernel::DeltaResult;
@@ -206,7 +209,7 @@ pub trait DataVisitor {
 ///   }
 /// }
 /// ```
-pub trait EngineData: Send {
+pub trait EngineData: Send + Sync {
     /// Request that the data be visited for the passed schema. The contract of this method is that
     /// it will call back into the passed [`DataVisitor`]s `visit` method. The call to `visit` must
     /// include `GetData` items for each leaf of the schema, as well as the number of rows in this
@@ -216,6 +219,13 @@ pub trait EngineData: Send {
     /// Return the number of items (rows) in blob
     fn length(&self) -> usize;
 
+    /// Return the `length` rows of this data starting at row `offset`. This is optional: engines
+    /// that don't implement it return an error, which only rules out the features that need it
+    /// (e.g. evaluating data skipping in chunks of rows).
+    fn slice(&self, _offset: usize, _length: usize) -> DeltaResult<Box<dyn EngineData>> {
+        Err(Error::generic("This EngineData does not support slicing"))
+    }
+
     // TODO(nick) implement this and below here in the trait when it doesn't cause a compiler error
     fn as_any(&self) -> &dyn Any;
 
@@ -3,6 +3,7 @@
 use std::{
     backtrace::{Backtrace, BacktraceStatus},
     num::ParseIntError,
+    ops::Range,
     string::FromUtf8Error,
 };
 
@@ -132,6 +133,40 @@ pub enum Error {
     /// Could not parse an integer
     #[error("Could not parse int: {0}")]
     ParseIntError(#[from] ParseIntError),
+
+    /// An expression is not valid, e.g. its operands have the wrong types
+    #[error("Invalid expression: {0}")]
+    InvalidExpression(String),
+
+    /// The stats of add actions could not be parsed for data skipping, e.g. because they are
+    /// malformed json or don't match the types of the table's columns
+    #[error(
+        "Failed to parse stats of actions {}..{} with schema {schema}: {source}",
+        .rows.start,
+        .rows.end
+    )]
+    StatsParse {
+        /// The schema the stats were parsed with
+        schema: String,
+        /// The (0-based) rows of the batch of actions whose stats failed to parse, which is a
+        /// single row if the malformed stats could be located
+        rows: Range<usize>,
+        /// The error the json handler returned
+        source: Box<Self>,
+    },
+
+    /// A data skipping predicate could not be evaluated on the parsed stats of add actions
+    #[error("Failed to evaluate data skipping predicate {predicate}: {source}")]
+    SkippingEvaluation {
+        /// The data skipping predicate
+        predicate: String,
+        /// The error the expression evaluator returned
+        source: Box<Self>,
+    },
+
+    /// An invariant was violated, e.g. an engine's evaluator returned the wrong number of rows
+    #[error("Internal error: {0}")]
+    InternalError(String),
 }
 
 // Convenience constructors for Error types that take a String argument
@@ -165,6 +200,25 @@ impl Error {
     pub fn join_failure(msg: impl ToString) -> Self {
         Self::JoinFailure(msg.to_string())
     }
+    pub fn invalid_expression(msg: impl ToString) -> Self {
+        Self::InvalidExpression(msg.to_string())
+    }
+    pub fn stats_parse(schema: impl ToString, rows: Range<usize>, source: Self) -> Self {
+        Self::StatsParse {
+            schema: schema.to_string(),
+            rows,
+            source: Box::new(source),
+        }
+    }
+    pub fn internal_error(msg: impl ToString) -> Self {
+        Self::InternalError(msg.to_string())
+    }
+    pub fn skipping_evaluation(predicate: impl ToString, source: Self) -> Self {
+        Self::SkippingEvaluation {
+            predicate: predicate.to_string(),
+            source: Box::new(source),
+        }
+    }
 
     // Capture a backtrace when the error is constructed.
     #[must_use]
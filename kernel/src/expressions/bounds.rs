@@ -0,0 +1,254 @@
+//! Derivation of the range of values that a predicate allows for a column
+
+use std::cmp::Ordering;
+use std::ops::Bound;
+
+use super::{push_down_not, BinaryOperator, Expression, Scalar, VariadicOperator};
+
+/// Returns the tightest range of values of the column `col` that `predicate` implies, as a lower
+/// and an upper bound, or `None` if the predicate doesn't bound the column.
+///
+/// The range is conservative: it contains every value of `col` for which the predicate may be
+/// true. Only comparisons of `col` with a non-NULL literal (and `col BETWEEN low AND high`) that
+/// are conjuncts of the predicate in negation normal form (see [`push_down_not`]) bound the range,
+/// e.g. `a > 5 AND (a < 100 OR b = 1)` only implies `a > 5`. Comparisons with literals of another
+/// type than the other bounds of the column are ignored.
+///
+/// Contradictory comparisons yield an empty range, whose lower bound is greater than its upper
+/// bound, e.g. `(Excluded(100), Excluded(5))` for `a > 100 AND a < 5`.
+pub fn derive_column_bounds(
+    predicate: &Expression,
+    col: &str,
+) -> Option<(Bound<Scalar>, Bound<Scalar>)> {
+    let mut bounds = ColumnBounds {
+        col,
+        lower: Bound::Unbounded,
+        upper: Bound::Unbounded,
+    };
+    bounds.collect(&push_down_not(predicate));
+    match (bounds.lower, bounds.upper) {
+        (Bound::Unbounded, Bound::Unbounded) => None,
+        bounds => Some(bounds),
+    }
+}
+
+/// The bounds of a column collected so far.
+struct ColumnBounds<'a> {
+    col: &'a str,
+    lower: Bound<Scalar>,
+    upper: Bound<Scalar>,
+}
+
+impl ColumnBounds<'_> {
+    /// Tightens the bounds with the comparisons of the column in `expr`.
+    fn collect(&mut self, expr: &Expression) {
+        use BinaryOperator::*;
+        match expr {
+            Expression::VariadicOperation {
+                op: VariadicOperator::And,
+                exprs,
+            } => exprs.iter().for_each(|expr| self.collect(expr)),
+            Expression::BinaryOperation { op, left, right } => {
+                let (op, value) = match (left.as_ref(), right.as_ref()) {
+                    (Expression::Column(name), Expression::Literal(value)) if name == self.col => {
+                        (op.clone(), value)
+                    }
+                    // `value < a` is equivalent to `a > value`, etc.
+                    (Expression::Literal(value), Expression::Column(name)) if name == self.col => {
+                        let op = match op {
+                            LessThan => GreaterThan,
+                            LessThanOrEqual => GreaterThanOrEqual,
+                            GreaterThan => LessThan,
+                            GreaterThanOrEqual => LessThanOrEqual,
+                            op => op.clone(),
+                        };
+                        (op, value)
+                    }
+                    _ => return,
+                };
+                if is_null_or_nan(value) {
+                    return; // NaN is not ordered like other floats
+                }
+                let value = value.clone();
+                match op {
+                    LessThan => self.tighten_upper(Bound::Excluded(value)),
+                    LessThanOrEqual => self.tighten_upper(Bound::Included(value)),
+                    GreaterThan => self.tighten_lower(Bound::Excluded(value)),
+                    GreaterThanOrEqual => self.tighten_lower(Bound::Included(value)),
+                    Equal | NullSafeEqual => {
+                        self.tighten_lower(Bound::Included(value.clone()));
+                        self.tighten_upper(Bound::Included(value));
+                    }
+                    _ => {}
+                }
+            }
+            Expression::Between { expr, low, high } => {
+                self.collect(&Expression::ge(expr.as_ref().clone(), low.as_ref().clone()));
+                self.collect(&Expression::le(
+                    expr.as_ref().clone(),
+                    high.as_ref().clone(),
+                ));
+            }
+            _ => {}
+        }
+    }
+
+    fn tighten_lower(&mut self, bound: Bound<Scalar>) {
+        tighten(&mut self.lower, bound, Ordering::Greater);
+    }
+
+    fn tighten_upper(&mut self, bound: Bound<Scalar>) {
+        tighten(&mut self.upper, bound, Ordering::Less);
+    }
+}
+
+/// Replaces `bound` with `new` if `new` is tighter, i.e. if its value is ordered `tighter` than
+/// the value of `bound`, or is the same value but excluded.
+fn tighten(bound: &mut Bound<Scalar>, new: Bound<Scalar>, tighter: Ordering) {
+    let (Bound::Included(value) | Bound::Excluded(value)) = &new else {
+        return;
+    };
+    let replace = match bound {
+        Bound::Unbounded => true,
+        Bound::Included(current) | Bound::Excluded(current) => match value.partial_cmp(current) {
+            Some(Ordering::Equal) => matches!(new, Bound::Excluded(_)),
+            Some(ordering) => ordering == tighter,
+            None => false, // incomparable values, e.g. of different types
+        },
+    };
+    if replace {
+        *bound = new;
+    }
+}
+
+fn is_null_or_nan(value: &Scalar) -> bool {
+    match value {
+        Scalar::Null(_) => true,
+        Scalar::Float(value) => value.is_nan(),
+        Scalar::Double(value) => value.is_nan(),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ops::Bound::{Excluded, Included, Unbounded};
+
+    use super::*;
+    use crate::schema::DataType;
+
+    #[test]
+    fn test_derive_column_bounds() {
+        let a = || Expression::column("a");
+        let lit = |value: i32| Expression::literal(value);
+        let cases = [
+            (a().gt(lit(5)), Some((Excluded(5), Unbounded))),
+            (a().le(lit(100)), Some((Unbounded, Included(100)))),
+            (
+                Expression::and_from([a().gt(lit(5)), a().lt(lit(100))]),
+                Some((Excluded(5), Excluded(100))),
+            ),
+            // the tightest bound wins
+            (
+                Expression::and_from([
+                    a().gt(lit(5)),
+                    a().ge(lit(10)),
+                    a().lt(lit(100)),
+                    a().le(lit(50)),
+                    a().lt(lit(70)),
+                ]),
+                Some((Included(10), Included(50))),
+            ),
+            (
+                Expression::and_from([a().ge(lit(5)), a().gt(lit(5))]),
+                Some((Excluded(5), Unbounded)),
+            ),
+            // literals on the left, nested conjunctions and BETWEEN
+            (
+                Expression::and_from([
+                    lit(5).lt(a()),
+                    Expression::and_from([lit(100).ge(a()), Expression::column("b").lt(lit(0))]),
+                ]),
+                Some((Excluded(5), Included(100))),
+            ),
+            (
+                Expression::between(a(), lit(1), lit(10)),
+                Some((Included(1), Included(10))),
+            ),
+            (a().eq(lit(7)), Some((Included(7), Included(7)))),
+            // NOT is pushed down first
+            (!a().le(lit(5)), Some((Excluded(5), Unbounded))),
+            (
+                !Expression::or_from([a().lt(lit(5)), a().gt(lit(100))]),
+                Some((Included(5), Included(100))),
+            ),
+            // contradictory bounds yield an empty range
+            (
+                Expression::and_from([a().gt(lit(100)), a().lt(lit(5))]),
+                Some((Excluded(100), Excluded(5))),
+            ),
+            (
+                Expression::and_from([a().eq(lit(3)), a().gt(lit(3))]),
+                Some((Excluded(3), Included(3))),
+            ),
+            // disjunctions, other columns and other comparisons don't bound the column
+            (
+                Expression::or_from([a().gt(lit(5)), Expression::column("b").lt(lit(0))]),
+                None,
+            ),
+            (
+                Expression::and_from([
+                    a().gt(lit(5)),
+                    Expression::or_from([a().lt(lit(100)), Expression::column("b").eq(lit(1))]),
+                ]),
+                Some((Excluded(5), Unbounded)),
+            ),
+            (Expression::column("b").gt(lit(5)), None),
+            (a().ne(lit(5)), None),
+            (!Expression::between(a(), lit(1), lit(10)), None),
+            (a().gt(Expression::column("b")), None),
+            (
+                a().lt(Expression::literal(Scalar::Null(DataType::INTEGER))),
+                None,
+            ),
+        ];
+        for (predicate, expected) in cases {
+            let expected = expected.map(|(lower, upper): (Bound<i32>, Bound<i32>)| {
+                (lower.map(Scalar::from), upper.map(Scalar::from))
+            });
+            assert_eq!(
+                derive_column_bounds(&predicate, "a"),
+                expected,
+                "{predicate}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_derive_column_bounds_incomparable() {
+        let a = || Expression::column("a");
+        // NaN is not ordered like other floats
+        let predicate = Expression::and_from([
+            a().gt(Expression::literal(Scalar::Double(1.5))),
+            a().lt(Expression::literal(Scalar::Double(f64::NAN))),
+        ]);
+        assert_eq!(
+            derive_column_bounds(&predicate, "a"),
+            Some((Excluded(Scalar::Double(1.5)), Unbounded))
+        );
+
+        // the first bound of a type wins over bounds of other types
+        let predicate = Expression::and_from([
+            a().gt(Expression::literal(5)),
+            a().gt(Expression::literal("x")),
+            a().lt(Expression::literal("z")),
+        ]);
+        assert_eq!(
+            derive_column_bounds(&predicate, "a"),
+            Some((
+                Excluded(Scalar::Integer(5)),
+                Excluded(Scalar::String("z".into()))
+            ))
+        );
+    }
+}
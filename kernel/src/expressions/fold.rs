@@ -0,0 +1,629 @@
+//! Constant folding of kernel expressions
+
+use std::cmp::Ordering;
+
+use regex::Regex;
+
+use super::{BinaryOperator, Expression, Scalar, UnaryOperator, VariadicOperator};
+use crate::schema::{DataType, PrimitiveType};
+
+/// The maximum precision of a decimal value.
+const MAX_DECIMAL_PRECISION: u8 = 38;
+
+/// Applies an integer operation, or returns `None` if it overflows or divides by zero.
+macro_rules! checked_op {
+    ($op:expr, $a:expr, $b:expr) => {
+        match $op {
+            BinaryOperator::Plus => $a.checked_add(*$b),
+            BinaryOperator::Minus => $a.checked_sub(*$b),
+            BinaryOperator::Multiply => $a.checked_mul(*$b),
+            BinaryOperator::Divide => $a.checked_div(*$b),
+            BinaryOperator::Modulo => $a.checked_rem(*$b),
+            _ => None,
+        }
+    };
+}
+
+/// Applies a floating point operation, or returns `None` if it divides by zero.
+macro_rules! float_op {
+    ($op:expr, $a:expr, $b:expr) => {
+        match $op {
+            BinaryOperator::Plus => Some($a + $b),
+            BinaryOperator::Minus => Some($a - $b),
+            BinaryOperator::Multiply => Some($a * $b),
+            BinaryOperator::Divide if *$b != 0.0 => Some($a / $b),
+            BinaryOperator::Modulo if *$b != 0.0 => Some($a % $b),
+            _ => None,
+        }
+    };
+}
+
+/// Returns the decimal with the given value, precision and scale, or `None` if the value needs
+/// more digits than the precision allows.
+fn checked_decimal(value: i128, precision: u8, scale: i8) -> Option<Scalar> {
+    let precision = precision.min(MAX_DECIMAL_PRECISION);
+    (value.unsigned_abs() < 10_u128.pow(precision as u32))
+        .then_some(Scalar::Decimal(value, precision, scale))
+}
+
+/// Evaluates an arithmetic operation on two literals. Returns `None` if the operation can't be
+/// evaluated exactly, e.g. because the operands have different types or the result overflows.
+fn fold_arithmetic(op: &BinaryOperator, left: &Scalar, right: &Scalar) -> Option<Scalar> {
+    use Scalar::*;
+    match (left, right) {
+        (Null(data_type), _) | (_, Null(data_type)) => Some(Null(data_type.clone())),
+        (Integer(a), Integer(b)) => checked_op!(op, a, b).map(Integer),
+        (Long(a), Long(b)) => checked_op!(op, a, b).map(Long),
+        (Short(a), Short(b)) => checked_op!(op, a, b).map(Short),
+        (Byte(a), Byte(b)) => checked_op!(op, a, b).map(Byte),
+        (Float(a), Float(b)) => float_op!(op, a, b).map(Float),
+        (Double(a), Double(b)) => float_op!(op, a, b).map(Double),
+        (Decimal(a, a_precision, a_scale), Decimal(b, b_precision, b_scale)) => match op {
+            // Same as Spark, the result has room for an extra digit (or all digits of both
+            // operands when multiplying), up to the maximum precision.
+            BinaryOperator::Plus | BinaryOperator::Minus if a_scale == b_scale => {
+                let value = checked_op!(op, a, b)?;
+                checked_decimal(value, a_precision.max(b_precision) + 1, *a_scale)
+            }
+            BinaryOperator::Multiply => {
+                let scale = a_scale.checked_add(*b_scale)?;
+                if scale.unsigned_abs() > MAX_DECIMAL_PRECISION {
+                    return None;
+                }
+                checked_decimal(a.checked_mul(*b)?, a_precision + b_precision + 1, scale)
+            }
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Evaluates a comparison of two literals. Returns `None` if the literals are not comparable, or
+/// `op` is not a comparison.
+fn fold_comparison(op: &BinaryOperator, left: &Scalar, right: &Scalar) -> Option<Scalar> {
+    use BinaryOperator::*;
+    use Scalar::*;
+    let result = match (op, left, right) {
+        (Distinct, Null(_), Null(_)) => false,
+        (Distinct, Null(_), _) | (Distinct, _, Null(_)) => true,
+        (NullSafeEqual, Null(_), Null(_)) => true,
+        (NullSafeEqual, Null(_), _) | (NullSafeEqual, _, Null(_)) => false,
+        (_, Null(_), _) | (_, _, Null(_)) => return Some(Null(DataType::BOOLEAN)),
+        (StartsWith, String(value), String(prefix)) => value.starts_with(prefix.as_str()),
+        (StartsWith, _, _) => return None,
+        (RLike, String(value), String(pattern)) => Regex::new(pattern).ok()?.is_match(value),
+        (RLike, _, _) => return None,
+        _ => {
+            let ordering = left.partial_cmp(right)?;
+            match op {
+                LessThan => ordering == Ordering::Less,
+                LessThanOrEqual => ordering != Ordering::Greater,
+                GreaterThan => ordering == Ordering::Greater,
+                GreaterThanOrEqual => ordering != Ordering::Less,
+                Equal | NullSafeEqual => ordering == Ordering::Equal,
+                NotEqual | Distinct => ordering != Ordering::Equal,
+                _ => return None,
+            }
+        }
+    };
+    Some(Boolean(result))
+}
+
+/// Converts a literal to `target_type`, or returns `None` if the value can't be represented
+/// exactly in that type (e.g. a `LONG` that overflows an `INTEGER`, or a `DOUBLE` with a
+/// fraction converted to an integer type). Converting a NULL literal gives a NULL of the type.
+/// Only conversions between numbers, and between dates and timestamps without a timezone, are
+/// supported.
+pub(crate) fn cast_scalar(value: &Scalar, target_type: &DataType) -> Option<Scalar> {
+    use PrimitiveType::*;
+    const MICROS_PER_DAY: i64 = 24 * 60 * 60 * 1_000_000;
+    if let Scalar::Null(_) = value {
+        return Some(Scalar::Null(target_type.clone()));
+    }
+    if &value.data_type() == target_type {
+        return Some(value.clone());
+    }
+    let DataType::Primitive(target_type) = target_type else {
+        return None;
+    };
+    let integer = match value {
+        Scalar::Byte(value) => Some(i128::from(*value)),
+        Scalar::Short(value) => Some(i128::from(*value)),
+        Scalar::Integer(value) => Some(i128::from(*value)),
+        Scalar::Long(value) => Some(i128::from(*value)),
+        // floating point numbers without a fraction are integers too (2^100 safely fits i128)
+        Scalar::Float(value) if value.fract() == 0.0 && value.abs() < 2_f32.powi(100) => {
+            Some(*value as i128)
+        }
+        Scalar::Double(value) if value.fract() == 0.0 && value.abs() < 2_f64.powi(100) => {
+            Some(*value as i128)
+        }
+        // decimals are integers if scaling them down doesn't drop any digits
+        Scalar::Decimal(value, _, scale) if *scale >= 0 => {
+            let divisor = 10_i128.checked_pow(*scale as u32)?;
+            (value % divisor == 0).then(|| value / divisor)
+        }
+        _ => None,
+    };
+    match (value, target_type) {
+        (_, Byte) => Some(Scalar::Byte(integer?.try_into().ok()?)),
+        (_, Short) => Some(Scalar::Short(integer?.try_into().ok()?)),
+        (_, Integer) => Some(Scalar::Integer(integer?.try_into().ok()?)),
+        (_, Long) => Some(Scalar::Long(integer?.try_into().ok()?)),
+        // integers up to the size of the mantissa are exact
+        (Scalar::Double(value), Float) => {
+            let float = *value as f32;
+            (f64::from(float) == *value).then_some(Scalar::Float(float))
+        }
+        (Scalar::Float(value), Double) => Some(Scalar::Double((*value).into())),
+        (_, Float) => {
+            let integer = integer.filter(|value| value.unsigned_abs() <= 1 << 24)?;
+            Some(Scalar::Float(integer as f32))
+        }
+        (_, Double) => {
+            let integer = integer.filter(|value| value.unsigned_abs() <= 1 << 53)?;
+            Some(Scalar::Double(integer as f64))
+        }
+        (Scalar::Decimal(value, _, scale), Decimal(precision, target_scale)) => checked_decimal(
+            rescale(*value, *scale, *target_scale)?,
+            *precision,
+            *target_scale,
+        ),
+        (Scalar::Float(_) | Scalar::Double(_), Decimal(..)) => None,
+        (_, Decimal(precision, scale)) => {
+            checked_decimal(rescale(integer?, 0, *scale)?, *precision, *scale)
+        }
+        (Scalar::Date(days), TimestampNtz) => {
+            Some(Scalar::TimestampNtz(i64::from(*days) * MICROS_PER_DAY))
+        }
+        (Scalar::TimestampNtz(micros), Date) if micros % MICROS_PER_DAY == 0 => {
+            Some(Scalar::Date((micros / MICROS_PER_DAY).try_into().ok()?))
+        }
+        _ => None,
+    }
+}
+
+/// Rescales the unscaled value of a decimal from `scale` to `target_scale`, or returns `None` if
+/// that overflows or drops digits.
+fn rescale(value: i128, scale: i8, target_scale: i8) -> Option<i128> {
+    match i32::from(target_scale) - i32::from(scale) {
+        diff if diff >= 0 => value.checked_mul(10_i128.checked_pow(diff as u32)?),
+        diff => {
+            let divisor = 10_i128.checked_pow(diff.unsigned_abs())?;
+            (value % divisor == 0).then(|| value / divisor)
+        }
+    }
+}
+
+/// Folds the literal operands of a conjunction (`dominant` is false) or disjunction (`dominant`
+/// is true). Any operand equal to `dominant` decides the result, while operands equal to
+/// `!dominant` can be dropped.
+fn fold_variadic(op: &VariadicOperator, exprs: Vec<Expression>) -> Expression {
+    let dominant = matches!(op, VariadicOperator::Or);
+    let mut remaining = Vec::with_capacity(exprs.len());
+    for expr in exprs {
+        match expr {
+            Expression::Literal(Scalar::Boolean(value)) if value == dominant => {
+                return Expression::literal(dominant);
+            }
+            Expression::Literal(Scalar::Boolean(_)) => {}
+            expr => remaining.push(expr),
+        }
+    }
+    match remaining.len() {
+        0 => Expression::literal(!dominant),
+        1 => remaining.remove(0),
+        _ => Expression::variadic(op.clone(), remaining),
+    }
+}
+
+/// Removes the boolean literals from the boolean structure (AND, OR and NOT) of `expr`: `true` is
+/// dropped from ANDs and `false` from ORs, an AND containing `false` becomes `false` and an OR
+/// containing `true` becomes `true`, and ANDs and ORs left with a single operand are replaced by
+/// it. Unlike [`fold_constants`], nothing else is evaluated, so e.g. `a AND 1 < 2` is unchanged.
+pub fn simplify(expr: &Expression) -> Expression {
+    use Expression::*;
+    match expr {
+        VariadicOperation { op, exprs } => fold_variadic(op, exprs.iter().map(simplify).collect()),
+        UnaryOperation {
+            op: UnaryOperator::Not,
+            expr,
+        } => match simplify(expr) {
+            Literal(Scalar::Boolean(value)) => Expression::literal(!value),
+            expr => !expr,
+        },
+        _ => expr.clone(),
+    }
+}
+
+/// Folds the constant sub-expressions of `expr`, by evaluating the arithmetic, comparisons and
+/// boolean logic that only involves literals. For example, `1 + 2 < a` becomes `3 < a`, and
+/// `false AND <anything>` becomes `false`. Column references are left untouched, as are
+/// operations that can't be evaluated exactly (e.g. an integer overflow or a division by zero).
+pub fn fold_constants(expr: &Expression) -> Expression {
+    use Expression::*;
+    match expr {
+        Literal(_) | Column(_) => expr.clone(),
+        Struct(exprs) => Struct(exprs.iter().map(fold_constants).collect()),
+        BinaryOperation { op, left, right } => {
+            let left = fold_constants(left);
+            let right = fold_constants(right);
+            let folded = match (&left, &right) {
+                (Literal(left), Literal(right)) => match op {
+                    BinaryOperator::Plus
+                    | BinaryOperator::Minus
+                    | BinaryOperator::Multiply
+                    | BinaryOperator::Divide
+                    | BinaryOperator::Modulo => fold_arithmetic(op, left, right),
+                    _ => fold_comparison(op, left, right),
+                },
+                _ => None,
+            };
+            match folded {
+                Some(value) => Literal(value),
+                None => Expression::binary(op.clone(), left, right),
+            }
+        }
+        UnaryOperation { op, expr } => match (op, fold_constants(expr)) {
+            (UnaryOperator::Not, Literal(Scalar::Boolean(value))) => Expression::literal(!value),
+            (UnaryOperator::Not, Literal(Scalar::Null(_))) => {
+                Literal(Scalar::Null(DataType::BOOLEAN))
+            }
+            (UnaryOperator::IsNull, Literal(value)) => {
+                Expression::literal(matches!(value, Scalar::Null(_)))
+            }
+            (UnaryOperator::IsNotNull, Literal(value)) => {
+                Expression::literal(!matches!(value, Scalar::Null(_)))
+            }
+            (op, expr) => Expression::unary(op.clone(), expr),
+        },
+        VariadicOperation { op, exprs } => {
+            fold_variadic(op, exprs.iter().map(fold_constants).collect())
+        }
+        InList { expr, list } => InList {
+            expr: Box::new(fold_constants(expr)),
+            list: list.iter().map(fold_constants).collect(),
+        },
+        Quantified {
+            op,
+            quantifier,
+            expr,
+            list,
+        } => Quantified {
+            op: op.clone(),
+            quantifier: *quantifier,
+            expr: Box::new(fold_constants(expr)),
+            list: list.iter().map(fold_constants).collect(),
+        },
+        Between { expr, low, high } => Between {
+            expr: Box::new(fold_constants(expr)),
+            low: Box::new(fold_constants(low)),
+            high: Box::new(fold_constants(high)),
+        },
+        CaseWhen {
+            branches,
+            otherwise,
+        } => CaseWhen {
+            branches: branches
+                .iter()
+                .map(|(condition, value)| (fold_constants(condition), fold_constants(value)))
+                .collect(),
+            otherwise: Box::new(fold_constants(otherwise)),
+        },
+        Coalesce(exprs) => Coalesce(exprs.iter().map(fold_constants).collect()),
+        Greatest(exprs) => Greatest(exprs.iter().map(fold_constants).collect()),
+        Least(exprs) => Least(exprs.iter().map(fold_constants).collect()),
+        MapAccess { map, key } => MapAccess {
+            map: Box::new(fold_constants(map)),
+            key: key.clone(),
+        },
+        ArraySize(array) => ArraySize(Box::new(fold_constants(array))),
+        ArrayElement { array, index } => ArrayElement {
+            array: Box::new(fold_constants(array)),
+            index: *index,
+        },
+        // only exact conversions are folded, so that the result doesn't depend on how the
+        // engine rounds or handles overflows
+        Cast { expr, target_type } => match fold_constants(expr) {
+            Literal(value) => match cast_scalar(&value, target_type) {
+                Some(value) => Literal(value),
+                None => Literal(value).cast(target_type.clone()),
+            },
+            expr => expr.cast(target_type.clone()),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fold_arithmetic() {
+        let col = Expression::column;
+        let lit = |value: i32| Expression::literal(value);
+
+        let cases = [
+            (lit(1) + lit(2), lit(3)),
+            ((lit(1) + lit(2)).lt(col("a")), lit(3).lt(col("a"))),
+            (lit(7) / lit(2) * lit(3) - lit(1), lit(8)),
+            (lit(-7) % lit(3), lit(-1)),
+            (lit(7) % lit(-3), lit(1)),
+            (
+                Expression::literal(Scalar::Double(7.5)) % Expression::literal(Scalar::Double(2.0)),
+                Expression::literal(Scalar::Double(1.5)),
+            ),
+            (
+                (col("a") + lit(2) * lit(3)).gt(lit(10) - lit(4)),
+                (col("a") + lit(6)).gt(lit(6)),
+            ),
+            (
+                Expression::literal(Scalar::Double(1.5)) * Expression::literal(Scalar::Double(2.0)),
+                Expression::literal(Scalar::Double(3.0)),
+            ),
+            (
+                Expression::literal(Scalar::Decimal(125, 3, 2))
+                    + Expression::literal(Scalar::Decimal(100, 3, 2)),
+                Expression::literal(Scalar::Decimal(225, 4, 2)),
+            ),
+            (
+                Expression::literal(Scalar::Decimal(15, 2, 1))
+                    * Expression::literal(Scalar::Decimal(25, 2, 1)),
+                Expression::literal(Scalar::Decimal(375, 5, 2)),
+            ),
+            (
+                lit(1) + Expression::literal(Scalar::Null(DataType::INTEGER)),
+                Expression::literal(Scalar::Null(DataType::INTEGER)),
+            ),
+            // operations that can't be evaluated exactly are left alone
+            (lit(1) / lit(0), lit(1) / lit(0)),
+            (lit(1) % lit(0), lit(1) % lit(0)),
+            (lit(i32::MIN) % lit(-1), lit(i32::MIN) % lit(-1)),
+            (lit(i32::MAX) + lit(1), lit(i32::MAX) + lit(1)),
+            (
+                lit(1) + Expression::literal(1_i64),
+                lit(1) + Expression::literal(1_i64),
+            ),
+            (
+                Expression::literal(Scalar::Decimal(1, 3, 2))
+                    + Expression::literal(Scalar::Decimal(1, 3, 1)),
+                Expression::literal(Scalar::Decimal(1, 3, 2))
+                    + Expression::literal(Scalar::Decimal(1, 3, 1)),
+            ),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(fold_constants(&input), expected, "{input}");
+        }
+    }
+
+    #[test]
+    fn test_fold_boolean() {
+        let col = Expression::column;
+        let lit = |value: i32| Expression::literal(value);
+        let pred = col("a").lt(lit(5));
+
+        let cases = [
+            (lit(1).lt(lit(2)), Expression::literal(true)),
+            (!lit(1).eq(lit(2)), Expression::literal(true)),
+            (
+                Expression::literal("abc").starts_with(Expression::literal("ab")),
+                Expression::literal(true),
+            ),
+            (
+                Expression::literal("abc").rlike(Expression::literal("^a.c$")),
+                Expression::literal(true),
+            ),
+            (
+                Expression::literal("abc").rlike(Expression::literal("(")),
+                Expression::literal("abc").rlike(Expression::literal("(")),
+            ),
+            (
+                lit(1).eq(Expression::literal(Scalar::Null(DataType::INTEGER))),
+                Expression::literal(Scalar::Null(DataType::BOOLEAN)),
+            ),
+            (
+                Expression::literal(Scalar::Null(DataType::INTEGER)).distinct(lit(1)),
+                Expression::literal(true),
+            ),
+            (
+                Expression::literal(Scalar::Null(DataType::INTEGER))
+                    .null_safe_eq(Expression::literal(Scalar::Null(DataType::INTEGER))),
+                Expression::literal(true),
+            ),
+            (
+                lit(1).null_safe_eq(Expression::literal(Scalar::Null(DataType::INTEGER))),
+                Expression::literal(false),
+            ),
+            (
+                Expression::literal(Scalar::Null(DataType::INTEGER)).is_null(),
+                Expression::literal(true),
+            ),
+            (
+                Expression::literal(Scalar::Null(DataType::INTEGER)).is_not_null(),
+                Expression::literal(false),
+            ),
+            (lit(1).is_not_null(), Expression::literal(true)),
+            // short circuits
+            (
+                Expression::literal(false).and(pred.clone()),
+                Expression::literal(false),
+            ),
+            (
+                pred.clone().or(lit(1).lt(lit(2))),
+                Expression::literal(true),
+            ),
+            (
+                Expression::and_from([pred.clone(), lit(1).lt(lit(2)), col("b")]),
+                Expression::and_from([pred.clone(), col("b")]),
+            ),
+            (Expression::literal(true).and(pred.clone()), pred.clone()),
+            (
+                Expression::literal(false).or(Expression::literal(false)),
+                Expression::literal(false),
+            ),
+            // nested folding
+            (
+                Expression::or_from([
+                    Expression::and_from([lit(2).gt(lit(1) + lit(1)), pred.clone()]),
+                    col("c").eq(lit(2) * lit(2)),
+                ]),
+                col("c").eq(lit(4)),
+            ),
+            (
+                col("a").in_list([lit(1) + lit(1), lit(3)]),
+                col("a").in_list([lit(2), lit(3)]),
+            ),
+            (
+                Expression::between(col("a"), lit(1) - lit(1), lit(5) * lit(2)),
+                Expression::between(col("a"), lit(0), lit(10)),
+            ),
+            // NULL operands of AND/OR can't be folded away
+            (
+                Expression::literal(Scalar::Null(DataType::BOOLEAN)).and(pred.clone()),
+                Expression::literal(Scalar::Null(DataType::BOOLEAN)).and(pred.clone()),
+            ),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(fold_constants(&input), expected, "{input}");
+        }
+    }
+
+    #[test]
+    fn test_cast_scalar() {
+        let cases = [
+            (Scalar::Integer(5), DataType::LONG, Some(Scalar::Long(5))),
+            (Scalar::Long(5), DataType::BYTE, Some(Scalar::Byte(5))),
+            (Scalar::Long(1 << 40), DataType::INTEGER, None),
+            (
+                Scalar::Integer(3),
+                DataType::DOUBLE,
+                Some(Scalar::Double(3.0)),
+            ),
+            (Scalar::Long(1 << 60), DataType::DOUBLE, None),
+            (
+                Scalar::Double(3.0),
+                DataType::INTEGER,
+                Some(Scalar::Integer(3)),
+            ),
+            (Scalar::Double(3.5), DataType::INTEGER, None),
+            (
+                Scalar::Double(1.5),
+                DataType::FLOAT,
+                Some(Scalar::Float(1.5)),
+            ),
+            (Scalar::Double(0.1), DataType::FLOAT, None),
+            (
+                Scalar::Float(1.5),
+                DataType::DOUBLE,
+                Some(Scalar::Double(1.5)),
+            ),
+            (
+                Scalar::Integer(5),
+                DataType::decimal(5, 2),
+                Some(Scalar::Decimal(500, 5, 2)),
+            ),
+            (Scalar::Integer(5000), DataType::decimal(5, 2), None),
+            (
+                Scalar::Decimal(1250, 5, 2),
+                DataType::decimal(6, 1),
+                Some(Scalar::Decimal(125, 6, 1)),
+            ),
+            (Scalar::Decimal(1255, 5, 2), DataType::decimal(6, 1), None),
+            (
+                Scalar::Decimal(1200, 5, 2),
+                DataType::SHORT,
+                Some(Scalar::Short(12)),
+            ),
+            (
+                Scalar::Date(1),
+                DataType::TIMESTAMP_NTZ,
+                Some(Scalar::TimestampNtz(86_400_000_000)),
+            ),
+            (
+                Scalar::TimestampNtz(86_400_000_000),
+                DataType::DATE,
+                Some(Scalar::Date(1)),
+            ),
+            (Scalar::TimestampNtz(1), DataType::DATE, None),
+            (Scalar::Date(1), DataType::TIMESTAMP, None),
+            (Scalar::String("5".into()), DataType::INTEGER, None),
+            (
+                Scalar::Null(DataType::INTEGER),
+                DataType::LONG,
+                Some(Scalar::Null(DataType::LONG)),
+            ),
+        ];
+        for (value, target_type, expected) in cases {
+            assert_eq!(
+                cast_scalar(&value, &target_type),
+                expected,
+                "{value} to {target_type}"
+            );
+        }
+
+        // only exact casts of literals are folded
+        let cast = |value: i64| Expression::literal(value).cast(DataType::INTEGER);
+        let col = Expression::column;
+        assert_eq!(
+            fold_constants(&col("a").lt(cast(5))),
+            col("a").lt(Expression::literal(5))
+        );
+        assert_eq!(
+            fold_constants(&col("a").lt(cast(1 << 40))),
+            col("a").lt(cast(1 << 40))
+        );
+    }
+
+    #[test]
+    fn test_simplify() {
+        let col = Expression::column;
+        let t = || Expression::literal(true);
+        let f = || Expression::literal(false);
+
+        let cases = [
+            // AND
+            (
+                Expression::and_from([col("a"), t(), col("b")]),
+                col("a").and(col("b")),
+            ),
+            (Expression::and_from([col("a"), f(), col("b")]), f()),
+            (Expression::and_from([t(), t()]), t()),
+            (col("a").and(t()), col("a")),
+            // OR
+            (
+                Expression::or_from([col("a"), f(), col("b")]),
+                col("a").or(col("b")),
+            ),
+            (Expression::or_from([col("a"), t(), col("b")]), t()),
+            (Expression::or_from([f(), f()]), f()),
+            (col("a").or(f()), col("a")),
+            // single operands are unwrapped, even without literals
+            (Expression::and_from([col("a")]), col("a")),
+            // NOT
+            (!t(), f()),
+            (!(col("a").or(t())), f()),
+            (!(col("a").and(f())), t()),
+            ((!col("a")).and(t()), !col("a")),
+            // nested
+            (col("a").and(col("b").or(t())), col("a")),
+            (col("a").or(col("b").and(f())), col("a")),
+            (
+                Expression::and_from([
+                    col("a").or(f()),
+                    Expression::or_from([col("b"), !t(), col("c").and(t())]),
+                ]),
+                col("a").and(col("b").or(col("c"))),
+            ),
+            (col("a").and(!(col("b").and(f()))), col("a")),
+            // other expressions are left alone
+            (
+                col("a").and(Expression::literal(1).lt(Expression::literal(2))),
+                col("a").and(Expression::literal(1).lt(Expression::literal(2))),
+            ),
+            (col("a").is_null(), col("a").is_null()),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(simplify(&input), expected, "{input}");
+        }
+    }
+}
@@ -5,9 +5,24 @@ use std::fmt::{Display, Formatter};
 
 use itertools::Itertools;
 
+use crate::schema::DataType;
+
+pub use self::bounds::derive_column_bounds;
+pub(crate) use self::fold::cast_scalar;
+pub use self::fold::{fold_constants, simplify};
+pub(crate) use self::normalize::expand_quantified;
+pub use self::normalize::{
+    canonicalize, expand_tuple_comparisons, push_down_not, rewrite_quantified_comparisons,
+};
 pub use self::scalars::Scalar;
+pub(crate) use self::typecheck::is_widening_cast;
+pub use self::typecheck::typecheck;
 
+mod bounds;
+mod fold;
+mod normalize;
 mod scalars;
+mod typecheck;
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 /// A binary operator.
@@ -20,6 +35,8 @@ pub enum BinaryOperator {
     Multiply,
     /// Arithmetic Divide
     Divide,
+    /// Arithmetic Modulo: the remainder of a division, which has the sign of the dividend
+    Modulo,
     /// Comparison Less Than
     LessThan,
     /// Comparison Less Than Or Equal
@@ -34,6 +51,46 @@ pub enum BinaryOperator {
     NotEqual,
     /// Distinct
     Distinct,
+    /// Null-safe equal, which is true if both operands are NULL
+    NullSafeEqual,
+    /// String starts with prefix
+    StartsWith,
+    /// String contains a match of a regular expression
+    RLike,
+}
+
+impl BinaryOperator {
+    /// Returns the operator (if any) whose result is the negation of this operator's result,
+    /// i.e. such that `a <op2> b` is equivalent to `NOT (a <op> b)`. Comparisons with NULL
+    /// operands are NULL either way.
+    pub fn invert(&self) -> Option<BinaryOperator> {
+        use BinaryOperator::*;
+        match self {
+            LessThan => Some(GreaterThanOrEqual),
+            LessThanOrEqual => Some(GreaterThan),
+            GreaterThan => Some(LessThanOrEqual),
+            GreaterThanOrEqual => Some(LessThan),
+            Equal => Some(NotEqual),
+            NotEqual => Some(Equal),
+            Distinct => Some(NullSafeEqual),
+            NullSafeEqual => Some(Distinct),
+            Plus | Minus | Multiply | Divide | Modulo | StartsWith | RLike => None,
+        }
+    }
+
+    /// Returns the operator (if any) that gives the same result with the operands swapped, i.e.
+    /// such that `b <op2> a` is equivalent to `a <op> b`.
+    pub fn commute(&self) -> Option<BinaryOperator> {
+        use BinaryOperator::*;
+        match self {
+            GreaterThan => Some(LessThan),
+            GreaterThanOrEqual => Some(LessThanOrEqual),
+            LessThan => Some(GreaterThan),
+            LessThanOrEqual => Some(GreaterThanOrEqual),
+            Equal | NotEqual | NullSafeEqual | Distinct | Plus | Multiply => Some(self.clone()),
+            Minus | Divide | Modulo | StartsWith | RLike => None,
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -42,6 +99,17 @@ pub enum VariadicOperator {
     Or,
 }
 
+impl VariadicOperator {
+    /// Returns the operator that combines negated operands into the negation of this operator's
+    /// result (De Morgan's laws): `NOT (a AND b)` is `NOT a OR NOT b`, and vice versa.
+    pub fn invert(&self) -> VariadicOperator {
+        match self {
+            Self::And => Self::Or,
+            Self::Or => Self::And,
+        }
+    }
+}
+
 impl Display for BinaryOperator {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -49,6 +117,7 @@ impl Display for BinaryOperator {
             Self::Minus => write!(f, "-"),
             Self::Multiply => write!(f, "*"),
             Self::Divide => write!(f, "/"),
+            Self::Modulo => write!(f, "%"),
             Self::LessThan => write!(f, "<"),
             Self::LessThanOrEqual => write!(f, "<="),
             Self::GreaterThan => write!(f, ">"),
@@ -59,6 +128,27 @@ impl Display for BinaryOperator {
             // so ideally this would not be used as we use Display for rendering expressions
             // in our code we take care of this, bot thers might now ...
             Self::Distinct => write!(f, "DISTINCT"),
+            Self::NullSafeEqual => write!(f, "<=>"),
+            Self::StartsWith => write!(f, "STARTS WITH"),
+            Self::RLike => write!(f, "RLIKE"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// The quantifier of a quantified comparison `expr op ANY (list...)` or `expr op ALL (list...)`.
+pub enum Quantifier {
+    /// The comparison is true for any value of the list
+    Any,
+    /// The comparison is true for all values of the list
+    All,
+}
+
+impl Display for Quantifier {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Any => write!(f, "ANY"),
+            Self::All => write!(f, "ALL"),
         }
     }
 }
@@ -70,6 +160,21 @@ pub enum UnaryOperator {
     Not,
     /// Unary Is Null
     IsNull,
+    /// Unary Is Not Null
+    IsNotNull,
+}
+
+impl UnaryOperator {
+    /// Returns the operator (if any) whose result is the negation of this operator's result,
+    /// i.e. such that `<op2> a` is equivalent to `NOT <op> a`. NOT has no inverse operator, since
+    /// `NOT NOT a` is `a` itself.
+    pub fn invert(&self) -> Option<UnaryOperator> {
+        match self {
+            Self::IsNull => Some(Self::IsNotNull),
+            Self::IsNotNull => Some(Self::IsNull),
+            Self::Not => None,
+        }
+    }
 }
 
 /// A SQL expression.
@@ -107,7 +212,77 @@ pub enum Expression {
         /// The expressions.
         exprs: Vec<Expression>,
     },
-    // TODO: support more expressions, such as IS IN, LIKE, etc.
+    /// A set membership test `expr IN (list...)`.
+    InList {
+        /// The expression to look up.
+        expr: Box<Expression>,
+        /// The values to look for.
+        list: Vec<Expression>,
+    },
+    /// A quantified comparison `expr op ANY (list...)` or `expr op ALL (list...)`, which compares
+    /// the expression to each value of the list. It has the same (null) semantics as the OR (for
+    /// `ANY`) or AND (for `ALL`) of the comparisons, so it is false for `ANY` and true for `ALL`
+    /// of an empty list.
+    Quantified {
+        /// The comparison operator.
+        op: BinaryOperator,
+        /// Whether any or all of the comparisons must be true.
+        quantifier: Quantifier,
+        /// The expression to compare.
+        expr: Box<Expression>,
+        /// The values to compare to.
+        list: Vec<Expression>,
+    },
+    /// A range test `expr BETWEEN low AND high`, which includes both bounds.
+    Between {
+        /// The expression to test.
+        expr: Box<Expression>,
+        /// The lower bound.
+        low: Box<Expression>,
+        /// The upper bound.
+        high: Box<Expression>,
+    },
+    /// A conditional expression `CASE WHEN condition THEN value ... ELSE otherwise END`, which
+    /// returns the value of the first branch whose condition is true, or `otherwise` if there is
+    /// no such branch. A NULL condition does not match.
+    CaseWhen {
+        /// The (condition, value) pairs, in order.
+        branches: Vec<(Expression, Expression)>,
+        /// The value if no condition matches.
+        otherwise: Box<Expression>,
+    },
+    /// The first of the expressions that is not NULL, or NULL if all of them are.
+    Coalesce(Vec<Expression>),
+    /// The largest of the expressions, ignoring NULL values, or NULL if all of them are NULL.
+    Greatest(Vec<Expression>),
+    /// The smallest of the expressions, ignoring NULL values, or NULL if all of them are NULL.
+    Least(Vec<Expression>),
+    /// The value of a map `map[key]`, or NULL if the map is NULL or doesn't contain the key.
+    MapAccess {
+        /// The map to look up the key in.
+        map: Box<Expression>,
+        /// The key to look up, which must have the map's key type.
+        key: Scalar,
+    },
+    /// The number of elements of an array `SIZE(array)`, or NULL if the array is NULL.
+    ArraySize(Box<Expression>),
+    /// The element of an array at a (0-based) position `array[index]`, or NULL if the array is
+    /// NULL or doesn't have that many elements. A negative index is an error.
+    ArrayElement {
+        /// The array to get the element of.
+        array: Box<Expression>,
+        /// The position of the element.
+        index: i32,
+    },
+    /// A conversion of a value to another type `CAST(expr AS target_type)`, or NULL if the value
+    /// is NULL.
+    Cast {
+        /// The value to convert.
+        expr: Box<Expression>,
+        /// The type to convert the value to.
+        target_type: DataType,
+    },
+    // TODO: support more expressions, such as LIKE, etc.
 }
 
 impl<T: Into<Scalar>> From<T> for Expression {
@@ -117,47 +292,114 @@ impl<T: Into<Scalar>> From<T> for Expression {
 }
 
 impl Display for Expression {
+    /// Formats the expression on a single line. The alternate form (`{:#}`) additionally wraps
+    /// operands in parentheses where needed to reflect the precedence of the operators, e.g.
+    /// `(Column(x) + 4) * 2` instead of `Column(x) + 4 * 2`.
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let alternate = f.alternate();
+        let show = |expr: &Expression| match alternate {
+            true => format!("{expr:#}"),
+            false => format!("{expr}"),
+        };
+        // Operands bind weaker than their operator need parentheses (in the alternate form)
+        let operand = |expr: &Expression, parenthesize: bool| match alternate && parenthesize {
+            true => format!("({expr:#})"),
+            false => show(expr),
+        };
+        let list = |exprs: &[Expression]| exprs.iter().map(show).join(", ");
         match self {
             Self::Literal(l) => write!(f, "{}", l),
             Self::Column(name) => write!(f, "Column({})", name),
-            Self::Struct(exprs) => write!(
-                f,
-                "Struct({})",
-                &exprs.iter().map(|e| format!("{e}")).join(", ")
-            ),
+            Self::Struct(exprs) => write!(f, "Struct({})", list(exprs)),
             Self::BinaryOperation {
                 op: BinaryOperator::Distinct,
                 left,
                 right,
-            } => write!(f, "DISTINCT({}, {})", left, right),
-            Self::BinaryOperation { op, left, right } => write!(f, "{} {} {}", left, op, right),
+            } => write!(f, "DISTINCT({}, {})", show(left), show(right)),
+            Self::BinaryOperation { op, left, right } => {
+                let precedence = self.precedence();
+                let left = operand(left, left.precedence() < precedence);
+                let right = operand(right, right.precedence() <= precedence);
+                write!(f, "{} {} {}", left, op, right)
+            }
             Self::UnaryOperation { op, expr } => match op {
-                UnaryOperator::Not => write!(f, "NOT {}", expr),
-                UnaryOperator::IsNull => write!(f, "{} IS NULL", expr),
-            },
-            Self::VariadicOperation { op, exprs } => match op {
-                VariadicOperator::And => {
+                UnaryOperator::Not => {
+                    write!(f, "NOT {}", operand(expr, expr.precedence() < u8::MAX))
+                }
+                UnaryOperator::IsNull => {
                     write!(
                         f,
-                        "AND({})",
-                        &exprs.iter().map(|e| format!("{e}")).join(", ")
+                        "{} IS NULL",
+                        operand(expr, expr.precedence() <= self.precedence())
                     )
                 }
-                VariadicOperator::Or => {
+                UnaryOperator::IsNotNull => {
                     write!(
                         f,
-                        "OR({})",
-                        &exprs.iter().map(|e| format!("{e}")).join(", ")
+                        "{} IS NOT NULL",
+                        operand(expr, expr.precedence() <= self.precedence())
                     )
                 }
             },
+            Self::VariadicOperation { op, exprs } => match op {
+                VariadicOperator::And => write!(f, "AND({})", list(exprs)),
+                VariadicOperator::Or => write!(f, "OR({})", list(exprs)),
+            },
+            Self::Between { expr, low, high } => {
+                let precedence = self.precedence();
+                write!(
+                    f,
+                    "{} BETWEEN {} AND {}",
+                    operand(expr, expr.precedence() <= precedence),
+                    operand(low, low.precedence() <= precedence),
+                    operand(high, high.precedence() <= precedence),
+                )
+            }
+            Self::InList { expr, list: values } => write!(
+                f,
+                "{} IN ({})",
+                operand(expr, expr.precedence() <= self.precedence()),
+                list(values)
+            ),
+            Self::Quantified {
+                op,
+                quantifier,
+                expr,
+                list: values,
+            } => write!(
+                f,
+                "{} {} {} ({})",
+                operand(expr, expr.precedence() <= self.precedence()),
+                op,
+                quantifier,
+                list(values)
+            ),
+            Self::CaseWhen {
+                branches,
+                otherwise,
+            } => {
+                write!(f, "CASE")?;
+                for (condition, value) in branches {
+                    write!(f, " WHEN {} THEN {}", show(condition), show(value))?;
+                }
+                write!(f, " ELSE {} END", show(otherwise))
+            }
+            Self::Coalesce(exprs) => write!(f, "COALESCE({})", list(exprs)),
+            Self::Greatest(exprs) => write!(f, "GREATEST({})", list(exprs)),
+            Self::Least(exprs) => write!(f, "LEAST({})", list(exprs)),
+            Self::MapAccess { map, key } => write!(f, "{}[{}]", show(map), key),
+            Self::ArraySize(array) => write!(f, "SIZE({})", show(array)),
+            Self::ArrayElement { array, index } => write!(f, "{}[{}]", show(array), index),
+            Self::Cast { expr, target_type } => {
+                write!(f, "CAST({} AS {})", show(expr), target_type)
+            }
         }
     }
 }
 
 impl Expression {
-    /// Returns a set of columns referenced by this expression.
+    /// Returns a set of columns referenced by this expression, by their full (possibly nested)
+    /// path, e.g. `a.b` for a reference to the field `b` of the struct column `a`.
     pub fn references(&self) -> HashSet<&str> {
         let mut set = HashSet::new();
 
@@ -170,11 +412,34 @@ impl Expression {
         set
     }
 
+    /// Returns a set of the top-level columns referenced by this expression, i.e. the first
+    /// segment of the path of each column in [`Self::references`], e.g. `a` for `a.b`.
+    pub fn top_level_references(&self) -> HashSet<&str> {
+        self.references()
+            .into_iter()
+            // Safety: split always returns at least one item
+            .map(|name| name.split('.').next().unwrap())
+            .collect()
+    }
+
     /// Create an new expression for a column reference
     pub fn column(name: impl ToString) -> Self {
         Self::Column(name.to_string())
     }
 
+    /// Create a new expression for a reference to the column `name` nested in the struct column
+    /// `prefix`, e.g. the min stats of a column, which are nested under `minValues`:
+    ///
+    /// ```rust
+    /// # use delta_kernel::expressions::Expression;
+    /// let min = Expression::joined_column("minValues", "a.b");
+    /// assert_eq!(min, Expression::column("minValues.a.b"));
+    /// assert_eq!(min.to_string(), "Column(minValues.a.b)");
+    /// ```
+    pub fn joined_column(prefix: impl AsRef<str>, name: impl AsRef<str>) -> Self {
+        Self::Column(format!("{}.{}", prefix.as_ref(), name.as_ref()))
+    }
+
     /// Create a new expression for a literal value
     pub fn literal(value: impl Into<Scalar>) -> Self {
         Self::Literal(value.into())
@@ -227,6 +492,11 @@ impl Expression {
         Self::unary(UnaryOperator::IsNull, self)
     }
 
+    /// Create a new expression `self IS NOT NULL`
+    pub fn is_not_null(self) -> Self {
+        Self::unary(UnaryOperator::IsNotNull, self)
+    }
+
     /// Create a new expression `self == other`
     pub fn eq(self, other: Self) -> Self {
         Self::binary(BinaryOperator::Equal, self, other)
@@ -282,26 +552,256 @@ impl Expression {
         Self::binary(BinaryOperator::Distinct, self, other)
     }
 
-    fn walk(&self) -> impl Iterator<Item = &Self> + '_ {
+    /// Create a new expression `self <=> other`, which unlike `self = other` is true if both
+    /// operands are NULL, and false if only one of them is
+    pub fn null_safe_eq(self, other: Self) -> Self {
+        Self::binary(BinaryOperator::NullSafeEqual, self, other)
+    }
+
+    /// Create a new expression `self STARTS WITH prefix`
+    pub fn starts_with(self, prefix: Self) -> Self {
+        Self::binary(BinaryOperator::StartsWith, self, prefix)
+    }
+
+    /// Create a new expression `self RLIKE pattern`, which is true if `self` contains a match of
+    /// the regular expression `pattern` (anchor it with `^` and `$` to match the whole string)
+    pub fn rlike(self, pattern: Self) -> Self {
+        Self::binary(BinaryOperator::RLike, self, pattern)
+    }
+
+    /// Create a new expression `self IN (list...)`
+    pub fn in_list(self, list: impl IntoIterator<Item = Self>) -> Self {
+        Self::InList {
+            expr: Box::new(self),
+            list: list.into_iter().collect(),
+        }
+    }
+
+    /// Create a new expression `NOT (self IN (list...))`
+    pub fn not_in_list(self, list: impl IntoIterator<Item = Self>) -> Self {
+        !self.in_list(list)
+    }
+
+    /// Create a new expression `self op ANY (list...)` or `self op ALL (list...)`, which compares
+    /// `self` to each value of the list with the comparison operator `op`
+    pub fn quantified(
+        self,
+        op: BinaryOperator,
+        quantifier: Quantifier,
+        list: impl IntoIterator<Item = Self>,
+    ) -> Self {
+        Self::Quantified {
+            op,
+            quantifier,
+            expr: Box::new(self),
+            list: list.into_iter().collect(),
+        }
+    }
+
+    /// Create a new expression `expr BETWEEN low AND high`
+    pub fn between(expr: Self, low: Self, high: Self) -> Self {
+        Self::Between {
+            expr: Box::new(expr),
+            low: Box::new(low),
+            high: Box::new(high),
+        }
+    }
+
+    /// Create a new expression `CASE WHEN condition THEN value ... ELSE otherwise END`
+    pub fn case_when(
+        branches: impl IntoIterator<Item = (Self, Self)>,
+        otherwise: impl Into<Self>,
+    ) -> Self {
+        Self::CaseWhen {
+            branches: branches.into_iter().collect(),
+            otherwise: Box::new(otherwise.into()),
+        }
+    }
+
+    /// Create a new expression `COALESCE(exprs...)`
+    pub fn coalesce(exprs: impl IntoIterator<Item = Self>) -> Self {
+        Self::Coalesce(exprs.into_iter().collect())
+    }
+
+    /// Create a new expression `GREATEST(exprs...)`
+    pub fn greatest(exprs: impl IntoIterator<Item = Self>) -> Self {
+        Self::Greatest(exprs.into_iter().collect())
+    }
+
+    /// Create a new expression `LEAST(exprs...)`
+    pub fn least(exprs: impl IntoIterator<Item = Self>) -> Self {
+        Self::Least(exprs.into_iter().collect())
+    }
+
+    /// Create a new expression `SIZE(self)` for the number of elements of an array
+    pub fn array_size(self) -> Self {
+        Self::ArraySize(Box::new(self))
+    }
+
+    /// Create a new expression `self[index]` for the element of an array at a (0-based) position
+    pub fn array_element(self, index: i32) -> Self {
+        Self::ArrayElement {
+            array: Box::new(self),
+            index,
+        }
+    }
+
+    /// Create a new expression `CAST(self AS target_type)`
+    pub fn cast(self, target_type: DataType) -> Self {
+        Self::Cast {
+            expr: Box::new(self),
+            target_type,
+        }
+    }
+
+    /// Create a new expression `self[key]` that looks up a key in a map
+    pub fn map_access(self, key: impl Into<Scalar>) -> Self {
+        Self::MapAccess {
+            map: Box::new(self),
+            key: key.into(),
+        }
+    }
+
+    /// Returns how strongly the (infix or postfix) operator of this expression binds its operands,
+    /// where expressions that are never ambiguous (e.g. columns, or `AND(...)`) bind strongest.
+    fn precedence(&self) -> u8 {
+        use BinaryOperator::*;
+        match self {
+            Self::BinaryOperation { op, .. } => match op {
+                Multiply | Divide | Modulo => 5,
+                Plus | Minus => 4,
+                Distinct => u8::MAX,
+                _ => 3,
+            },
+            Self::UnaryOperation {
+                op: UnaryOperator::IsNull | UnaryOperator::IsNotNull,
+                ..
+            }
+            | Self::Between { .. }
+            | Self::InList { .. }
+            | Self::Quantified { .. } => 2,
+            Self::UnaryOperation {
+                op: UnaryOperator::Not,
+                ..
+            } => 1,
+            _ => u8::MAX,
+        }
+    }
+
+    /// Renders the expression as an indented tree with one node per line, which is easier to
+    /// read than [`Display`] for large, deeply nested expressions.
+    pub fn pretty(&self) -> String {
+        let mut lines = vec![];
+        self.pretty_lines(0, &mut lines);
+        lines.join("\n")
+    }
+
+    fn pretty_lines(&self, depth: usize, lines: &mut Vec<String>) {
+        let indent = "  ".repeat(depth);
+        let mut node = |name: String, children: &[&Expression]| {
+            lines.push(format!("{indent}{name}"));
+            for child in children {
+                child.pretty_lines(depth + 1, lines);
+            }
+        };
+        match self {
+            Self::Literal(_) | Self::Column(_) => node(self.to_string(), &[]),
+            Self::Struct(exprs) => node("Struct".into(), &exprs.iter().collect_vec()),
+            Self::BinaryOperation { op, left, right } => node(format!("{op:?}"), &[left, right]),
+            Self::UnaryOperation { op, expr } => node(format!("{op:?}"), &[expr]),
+            Self::VariadicOperation { op, exprs } => {
+                node(format!("{op:?}"), &exprs.iter().collect_vec())
+            }
+            Self::InList { expr, list } => {
+                node("InList".into(), &[expr]);
+                lines.push(format!("{indent}  List"));
+                for value in list {
+                    value.pretty_lines(depth + 2, lines);
+                }
+            }
+            Self::Quantified {
+                op,
+                quantifier,
+                expr,
+                list,
+            } => {
+                node(format!("Quantified[{op:?} {quantifier}]"), &[expr]);
+                lines.push(format!("{indent}  List"));
+                for value in list {
+                    value.pretty_lines(depth + 2, lines);
+                }
+            }
+            Self::Between { expr, low, high } => node("Between".into(), &[expr, low, high]),
+            Self::CaseWhen {
+                branches,
+                otherwise,
+            } => {
+                lines.push(format!("{indent}CaseWhen"));
+                for (condition, value) in branches {
+                    lines.push(format!("{indent}  When"));
+                    condition.pretty_lines(depth + 2, lines);
+                    lines.push(format!("{indent}  Then"));
+                    value.pretty_lines(depth + 2, lines);
+                }
+                lines.push(format!("{indent}  Else"));
+                otherwise.pretty_lines(depth + 2, lines);
+            }
+            Self::Coalesce(exprs) => node("Coalesce".into(), &exprs.iter().collect_vec()),
+            Self::Greatest(exprs) => node("Greatest".into(), &exprs.iter().collect_vec()),
+            Self::Least(exprs) => node("Least".into(), &exprs.iter().collect_vec()),
+            Self::MapAccess { map, key } => node(format!("MapAccess[{key}]"), &[map]),
+            Self::ArraySize(array) => node("ArraySize".into(), &[array]),
+            Self::ArrayElement { array, index } => node(format!("ArrayElement[{index}]"), &[array]),
+            Self::Cast { expr, target_type } => node(format!("Cast[{target_type}]"), &[expr]),
+        }
+    }
+
+    pub(crate) fn walk(&self) -> impl Iterator<Item = &Self> + '_ {
         let mut stack = vec![self];
         std::iter::from_fn(move || {
             let expr = stack.pop()?;
             match expr {
                 Self::Literal(_) => {}
                 Self::Column { .. } => {}
-                Self::Struct(exprs) => {
+                Self::Struct(exprs)
+                | Self::Coalesce(exprs)
+                | Self::Greatest(exprs)
+                | Self::Least(exprs) => {
                     stack.extend(exprs.iter());
                 }
                 Self::BinaryOperation { left, right, .. } => {
                     stack.push(left);
                     stack.push(right);
                 }
-                Self::UnaryOperation { expr, .. } => {
+                Self::UnaryOperation { expr, .. }
+                | Self::MapAccess { map: expr, .. }
+                | Self::ArraySize(expr)
+                | Self::ArrayElement { array: expr, .. }
+                | Self::Cast { expr, .. } => {
                     stack.push(expr);
                 }
                 Self::VariadicOperation { exprs, .. } => {
                     stack.extend(exprs.iter());
                 }
+                Self::Between { expr, low, high } => {
+                    stack.push(expr);
+                    stack.push(low);
+                    stack.push(high);
+                }
+                Self::InList { expr, list } | Self::Quantified { expr, list, .. } => {
+                    stack.push(expr);
+                    stack.extend(list.iter());
+                }
+                Self::CaseWhen {
+                    branches,
+                    otherwise,
+                } => {
+                    for (condition, value) in branches {
+                        stack.push(condition);
+                        stack.push(value);
+                    }
+                    stack.push(otherwise);
+                }
             }
             Some(expr)
         })
@@ -348,9 +848,48 @@ impl std::ops::Div<Expression> for Expression {
     }
 }
 
+impl std::ops::Rem<Expression> for Expression {
+    type Output = Self;
+
+    fn rem(self, rhs: Expression) -> Self {
+        Self::binary(BinaryOperator::Modulo, self, rhs)
+    }
+}
+
 #[cfg(test)]
 mod tests {
+    use std::collections::HashSet;
+
     use super::Expression as Expr;
+    use crate::schema::DataType;
+
+    #[test]
+    fn test_references() {
+        let predicate = Expr::and_from([
+            Expr::column("a.b").gt(Expr::literal(1)),
+            Expr::column("a.c.d").is_null(),
+            Expr::column("e").lt(Expr::column("a.b")),
+        ]);
+        assert_eq!(predicate.references(), HashSet::from(["a.b", "a.c.d", "e"]));
+        assert_eq!(predicate.top_level_references(), HashSet::from(["a", "e"]));
+        assert!(Expr::literal(1).references().is_empty());
+        assert!(Expr::literal(1).top_level_references().is_empty());
+    }
+
+    #[test]
+    fn test_joined_column() {
+        let min = Expr::joined_column("minValues", "a.b");
+        assert_eq!(min, Expr::column("minValues.a.b"));
+        assert_eq!(min.references(), HashSet::from(["minValues.a.b"]));
+        assert_eq!(min.top_level_references(), HashSet::from(["minValues"]));
+
+        // accepts owned and borrowed names alike
+        let prefix = String::from("nullCount");
+        assert_eq!(
+            Expr::joined_column(&prefix, String::from("c")),
+            Expr::column("nullCount.c")
+        );
+    }
 
     #[test]
     fn test_expression_format() {
@@ -388,6 +927,70 @@ mod tests {
                     .or(col_ref.clone().lt(Expr::literal(10))),
                 "OR(Column(x) > 2, Column(x) < 10)",
             ),
+            (
+                col_ref
+                    .clone()
+                    .in_list([Expr::literal(1), Expr::literal(5), Expr::literal(9)]),
+                "Column(x) IN (1, 5, 9)",
+            ),
+            (
+                col_ref.clone().not_in_list([Expr::literal("foo")]),
+                "NOT Column(x) IN ('foo')",
+            ),
+            (
+                col_ref.clone().quantified(
+                    super::BinaryOperator::GreaterThan,
+                    super::Quantifier::All,
+                    [Expr::literal(1), Expr::literal(5)],
+                ),
+                "Column(x) > ALL (1, 5)",
+            ),
+            (
+                Expr::between(col_ref.clone(), Expr::literal(2), Expr::literal(10)),
+                "Column(x) BETWEEN 2 AND 10",
+            ),
+            (
+                !Expr::between(col_ref.clone(), Expr::literal(2), Expr::literal(10)),
+                "NOT Column(x) BETWEEN 2 AND 10",
+            ),
+            (
+                col_ref.clone().starts_with(Expr::literal("foo")),
+                "Column(x) STARTS WITH 'foo'",
+            ),
+            (
+                col_ref.clone().rlike(Expr::literal("^fo+$")),
+                "Column(x) RLIKE '^fo+$'",
+            ),
+            (
+                Expr::case_when(
+                    [
+                        (col_ref.clone().lt(Expr::literal(0)), Expr::literal("negative")),
+                        (col_ref.clone().eq(Expr::literal(0)), Expr::literal("zero")),
+                    ],
+                    Expr::literal("positive"),
+                ),
+                "CASE WHEN Column(x) < 0 THEN 'negative' WHEN Column(x) = 0 THEN 'zero' ELSE 'positive' END",
+            ),
+            (
+                Expr::coalesce([col_ref.clone(), Expr::column("y"), Expr::literal(0)]),
+                "COALESCE(Column(x), Column(y), 0)",
+            ),
+            (
+                Expr::greatest([col_ref.clone(), Expr::literal(0)]),
+                "GREATEST(Column(x), 0)",
+            ),
+            (
+                Expr::least([col_ref.clone(), Expr::column("y")]),
+                "LEAST(Column(x), Column(y))",
+            ),
+            (
+                col_ref.clone().null_safe_eq(Expr::literal(2)),
+                "Column(x) <=> 2",
+            ),
+            (
+                col_ref.clone().cast(DataType::LONG).gt(Expr::literal(5_i64)),
+                "CAST(Column(x) AS bigint) > 5",
+            ),
             (col_ref.eq(Expr::literal("foo")), "Column(x) = 'foo'"),
         ];
 
@@ -396,4 +999,119 @@ mod tests {
             assert_eq!(result, expected);
         }
     }
+
+    #[test]
+    fn test_expression_format_precedence() {
+        let col_ref = Expr::column("x");
+        let cases = [
+            (
+                (col_ref.clone() + Expr::literal(4)) / Expr::literal(10) * Expr::literal(42),
+                "(Column(x) + 4) / 10 * 42",
+            ),
+            (
+                col_ref.clone() - (Expr::literal(4) - Expr::literal(2)),
+                "Column(x) - (4 - 2)",
+            ),
+            (
+                (col_ref.clone() * Expr::literal(2)).lt(col_ref.clone() + Expr::literal(1)),
+                "Column(x) * 2 < Column(x) + 1",
+            ),
+            (!col_ref.clone().eq(Expr::literal(2)), "NOT (Column(x) = 2)"),
+            (
+                (col_ref.clone() + Expr::literal(1)).is_null(),
+                "Column(x) + 1 IS NULL",
+            ),
+            (
+                col_ref.clone().eq(Expr::literal(1)).is_null(),
+                "Column(x) = 1 IS NULL",
+            ),
+            (col_ref.clone().is_not_null(), "Column(x) IS NOT NULL"),
+            (
+                col_ref.clone().gt(Expr::literal(1)).eq(Expr::literal(true)),
+                "Column(x) > 1 = true",
+            ),
+            (
+                Expr::literal(true).eq(col_ref.clone().gt(Expr::literal(1))),
+                "true = (Column(x) > 1)",
+            ),
+            (
+                Expr::and_from([
+                    !col_ref.clone().in_list([Expr::literal(1)]),
+                    Expr::column("y").is_null(),
+                ]),
+                "AND(NOT (Column(x) IN (1)), Column(y) IS NULL)",
+            ),
+        ];
+        for (expr, expected) in cases {
+            assert_eq!(format!("{expr:#}"), expected);
+        }
+    }
+
+    #[test]
+    fn test_expression_pretty() {
+        let expr = Expr::and_from([
+            Expr::or_from([
+                Expr::column("a").lt(Expr::literal(5)),
+                !Expr::column("b").is_null(),
+            ]),
+            !Expr::and_from([
+                Expr::between(Expr::column("c"), Expr::literal(1), Expr::literal(10)),
+                Expr::or_from([
+                    Expr::column("d").in_list([Expr::literal("x"), Expr::literal("y")]),
+                    !(Expr::column("e") + Expr::literal(1)).eq(Expr::literal(2)),
+                ]),
+            ]),
+        ]);
+        let expected = [
+            "And",
+            "  Or",
+            "    LessThan",
+            "      Column(a)",
+            "      5",
+            "    Not",
+            "      IsNull",
+            "        Column(b)",
+            "  Not",
+            "    And",
+            "      Between",
+            "        Column(c)",
+            "        1",
+            "        10",
+            "      Or",
+            "        InList",
+            "          Column(d)",
+            "          List",
+            "            'x'",
+            "            'y'",
+            "        Not",
+            "          Equal",
+            "            Plus",
+            "              Column(e)",
+            "              1",
+            "            2",
+        ];
+        assert_eq!(expr.pretty(), expected.join("\n"));
+
+        let expr = Expr::case_when(
+            [(
+                Expr::column("a").gt(Expr::literal(0)),
+                Expr::literal("positive"),
+            )],
+            Expr::coalesce([Expr::column("b"), Expr::literal("none")]),
+        );
+        let expected = [
+            "CaseWhen",
+            "  When",
+            "    GreaterThan",
+            "      Column(a)",
+            "      0",
+            "  Then",
+            "    'positive'",
+            "  Else",
+            "    Coalesce",
+            "      Column(b)",
+            "      'none'",
+        ];
+        assert_eq!(expr.pretty(), expected.join("\n"));
+    }
 }
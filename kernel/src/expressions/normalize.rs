@@ -0,0 +1,563 @@
+//! Normalization of kernel expressions
+
+use std::cmp::Ordering;
+
+use super::{BinaryOperator, Expression, Quantifier, Scalar, UnaryOperator, VariadicOperator};
+
+/// Rewrites `expr` into negation normal form, where NOT is only applied to leaves of the boolean
+/// structure of the expression. NOT is pushed through AND and OR using De Morgan's laws, double
+/// negations are eliminated, and negated comparisons and NULL checks are replaced by their
+/// inverse (e.g. `NOT a < 5` becomes `a >= 5`). Any other negated expression (e.g. `NOT a IN
+/// (1, 2)`) is a leaf, and is kept as is.
+pub fn push_down_not(expr: &Expression) -> Expression {
+    match expr {
+        Expression::UnaryOperation {
+            op: UnaryOperator::Not,
+            expr,
+        } => negate(expr),
+        Expression::VariadicOperation { op, exprs } => {
+            Expression::variadic(op.clone(), exprs.iter().map(push_down_not))
+        }
+        _ => expr.clone(),
+    }
+}
+
+/// Returns the negation normal form of `NOT expr`.
+fn negate(expr: &Expression) -> Expression {
+    match expr {
+        Expression::UnaryOperation { op, expr } => match op.invert() {
+            Some(op) => Expression::unary(op, expr.as_ref().clone()),
+            // `NOT NOT a` is `a`
+            None => push_down_not(expr),
+        },
+        Expression::BinaryOperation { op, left, right } => match op.invert() {
+            Some(op) => Expression::binary(op, left.as_ref().clone(), right.as_ref().clone()),
+            None => !expr.clone(),
+        },
+        Expression::VariadicOperation { op, exprs } => {
+            Expression::variadic(op.invert(), exprs.iter().map(negate))
+        }
+        Expression::Literal(Scalar::Boolean(value)) => Expression::literal(!value),
+        _ => !expr.clone(),
+    }
+}
+
+/// Rewrites comparisons of a literal to another expression into the `expr op literal` form,
+/// commuting the operator where needed, so that equivalent predicates compare equal: both `5 < a`
+/// and `a > 5` become `a > 5`. Comparisons are rewritten anywhere in the expression, including
+/// inside arithmetic, `IN` lists and the boolean structure. Comparisons of two literals, and other
+/// operations (e.g. `5 + a`), are kept as is.
+pub fn canonicalize(expr: &Expression) -> Expression {
+    use BinaryOperator::*;
+    match expr {
+        Expression::BinaryOperation { op, left, right } => {
+            let left = canonicalize(left);
+            let right = canonicalize(right);
+            let is_comparison = matches!(
+                op,
+                LessThan
+                    | LessThanOrEqual
+                    | GreaterThan
+                    | GreaterThanOrEqual
+                    | Equal
+                    | NotEqual
+                    | Distinct
+                    | NullSafeEqual
+            );
+            match (&left, &right, op.commute()) {
+                (Expression::Literal(_), _, Some(commuted))
+                    if is_comparison && !matches!(right, Expression::Literal(_)) =>
+                {
+                    Expression::binary(commuted, right, left)
+                }
+                _ => Expression::binary(op.clone(), left, right),
+            }
+        }
+        Expression::UnaryOperation { op, expr } => {
+            Expression::unary(op.clone(), canonicalize(expr))
+        }
+        Expression::VariadicOperation { op, exprs } => {
+            Expression::variadic(op.clone(), exprs.iter().map(canonicalize))
+        }
+        Expression::InList { expr, list } => {
+            canonicalize(expr).in_list(list.iter().map(canonicalize))
+        }
+        Expression::Quantified {
+            op,
+            quantifier,
+            expr,
+            list,
+        } => canonicalize(expr).quantified(op.clone(), *quantifier, list.iter().map(canonicalize)),
+        Expression::Between { expr, low, high } => {
+            Expression::between(canonicalize(expr), canonicalize(low), canonicalize(high))
+        }
+        _ => expr.clone(),
+    }
+}
+
+/// Rewrites comparisons of struct expressions (tuples) into comparisons of their fields, so that
+/// each field can be compared on its own. `(a, b) = (1, 2)` becomes `a = 1 AND b = 2`, `(a, b) !=
+/// (1, 2)` becomes `a != 1 OR b != 2`, and a row-constructor IN like `(a, b) IN ((1, 2), (3, 4))`
+/// becomes `(a = 1 AND b = 2) OR (a = 3 AND b = 4)`. The rewrites follow the three-valued logic
+/// of SQL, so the result is equivalent to the original. Comparisons of tuples with a different
+/// number of fields, and orderings of tuples (e.g. `(a, b) < (1, 2)`), are kept as is.
+pub fn expand_tuple_comparisons(expr: &Expression) -> Expression {
+    use BinaryOperator::*;
+    match expr {
+        Expression::BinaryOperation { op, left, right } => {
+            let (Expression::Struct(left), Expression::Struct(right)) =
+                (left.as_ref(), right.as_ref())
+            else {
+                return expr.clone();
+            };
+            let combine = match op {
+                Equal | NullSafeEqual => VariadicOperator::And,
+                NotEqual | Distinct => VariadicOperator::Or,
+                _ => return expr.clone(),
+            };
+            match expand_fields(op, left, right) {
+                Some(fields) => Expression::variadic(combine, fields),
+                None => expr.clone(),
+            }
+        }
+        Expression::InList { expr: tuple, list } => {
+            let Expression::Struct(fields) = tuple.as_ref() else {
+                return expr.clone();
+            };
+            let alternatives: Option<Vec<_>> = list
+                .iter()
+                .map(|value| match value {
+                    Expression::Struct(values) => {
+                        Some(Expression::and_from(expand_fields(&Equal, fields, values)?))
+                    }
+                    _ => None,
+                })
+                .collect();
+            match alternatives {
+                Some(alternatives) if !alternatives.is_empty() => Expression::or_from(alternatives),
+                _ => expr.clone(),
+            }
+        }
+        Expression::UnaryOperation {
+            op: UnaryOperator::Not,
+            expr,
+        } => !expand_tuple_comparisons(expr),
+        Expression::VariadicOperation { op, exprs } => {
+            Expression::variadic(op.clone(), exprs.iter().map(expand_tuple_comparisons))
+        }
+        _ => expr.clone(),
+    }
+}
+
+/// Compares the fields of two tuples pairwise with `op`, recursing into nested tuples. Returns
+/// `None` if the tuples have a different number of fields.
+fn expand_fields(
+    op: &BinaryOperator,
+    left: &[Expression],
+    right: &[Expression],
+) -> Option<Vec<Expression>> {
+    if left.len() != right.len() || left.is_empty() {
+        return None;
+    }
+    let fields = left
+        .iter()
+        .zip(right)
+        .map(|(left, right)| {
+            expand_tuple_comparisons(&Expression::binary(op.clone(), left.clone(), right.clone()))
+        })
+        .collect();
+    Some(fields)
+}
+
+/// Rewrites quantified comparisons of an expression to a list of literals into simpler
+/// comparisons that data skipping can use. An ordering compared to `ALL` values only holds for
+/// the most extreme value, and compared to `ANY` value for the least extreme one, so `a > ALL (1,
+/// 5)` becomes `a > 5` and `a < ANY (1, 5)` becomes `a < 5`. `a = ANY (list...)` becomes `a IN
+/// (list...)`, and `a != ALL (list...)` becomes `NOT a IN (list...)`. Any other quantified
+/// comparison to literals (e.g. one to a list with a NULL value) becomes the OR (for `ANY`) or
+/// AND (for `ALL`) of the comparisons, so the result is equivalent to the original. Quantified
+/// comparisons to lists with values that are not literals are kept as is.
+pub fn rewrite_quantified_comparisons(expr: &Expression) -> Expression {
+    use BinaryOperator::*;
+    use Quantifier::*;
+    match expr {
+        Expression::Quantified {
+            op,
+            quantifier,
+            expr: value,
+            list,
+        } => {
+            let Some(literals) = list
+                .iter()
+                .map(|value| match value {
+                    Expression::Literal(value) => Some(value),
+                    _ => None,
+                })
+                .collect::<Option<Vec<_>>>()
+            else {
+                return expr.clone();
+            };
+            if literals.is_empty() {
+                return Expression::literal(*quantifier == All);
+            }
+            let extreme = match (op, quantifier) {
+                (LessThan | LessThanOrEqual, All) | (GreaterThan | GreaterThanOrEqual, Any) => {
+                    extreme_literal(&literals, Ordering::Less)
+                }
+                (GreaterThan | GreaterThanOrEqual, All) | (LessThan | LessThanOrEqual, Any) => {
+                    extreme_literal(&literals, Ordering::Greater)
+                }
+                (Equal, Any) => return value.as_ref().clone().in_list(list.clone()),
+                (NotEqual, All) => return value.as_ref().clone().not_in_list(list.clone()),
+                _ => None,
+            };
+            match extreme {
+                Some(extreme) => {
+                    Expression::binary(op.clone(), value.as_ref().clone(), extreme.clone())
+                }
+                None => expand_quantified(op, quantifier, value, list),
+            }
+        }
+        Expression::UnaryOperation {
+            op: UnaryOperator::Not,
+            expr,
+        } => !rewrite_quantified_comparisons(expr),
+        Expression::VariadicOperation { op, exprs } => {
+            Expression::variadic(op.clone(), exprs.iter().map(rewrite_quantified_comparisons))
+        }
+        _ => expr.clone(),
+    }
+}
+
+/// Returns the least (for `Ordering::Less`) or greatest (for `Ordering::Greater`) of the values,
+/// or `None` if a value is NULL or the values are not comparable (e.g. of different types, or
+/// NaN).
+fn extreme_literal<'a>(values: &[&'a Scalar], extreme: Ordering) -> Option<&'a Scalar> {
+    if values.iter().any(|value| matches!(value, Scalar::Null(_))) {
+        return None;
+    }
+    let (first, rest) = values.split_first()?;
+    rest.iter().try_fold(*first, |current, &value| {
+        match value.partial_cmp(current)? {
+            ordering if ordering == extreme => Some(value),
+            _ => Some(current),
+        }
+    })
+}
+
+/// Returns the OR (for `ANY`) or AND (for `ALL`) of the comparisons of `expr` to each value of
+/// `list` with `op`, which is the definition of the quantified comparison.
+pub(crate) fn expand_quantified(
+    op: &BinaryOperator,
+    quantifier: &Quantifier,
+    expr: &Expression,
+    list: &[Expression],
+) -> Expression {
+    let combine = match quantifier {
+        Quantifier::Any => VariadicOperator::Or,
+        Quantifier::All => VariadicOperator::And,
+    };
+    let comparisons = list
+        .iter()
+        .map(|value| Expression::binary(op.clone(), expr.clone(), value.clone()));
+    Expression::variadic(combine, comparisons)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::expressions::{BinaryOperator, VariadicOperator};
+    use crate::schema::DataType;
+
+    #[test]
+    fn test_invert_operators() {
+        use BinaryOperator::*;
+        let cases = [
+            (LessThan, Some(GreaterThanOrEqual)),
+            (LessThanOrEqual, Some(GreaterThan)),
+            (GreaterThan, Some(LessThanOrEqual)),
+            (GreaterThanOrEqual, Some(LessThan)),
+            (Equal, Some(NotEqual)),
+            (NotEqual, Some(Equal)),
+            (Distinct, Some(NullSafeEqual)),
+            (NullSafeEqual, Some(Distinct)),
+            (Plus, None),
+            (Modulo, None),
+            (StartsWith, None),
+            (RLike, None),
+        ];
+        for (op, expected) in cases {
+            assert_eq!(op.invert(), expected, "{op}");
+            if let Some(inverted) = expected {
+                assert_eq!(inverted.invert(), Some(op));
+            }
+        }
+        assert_eq!(
+            UnaryOperator::IsNull.invert(),
+            Some(UnaryOperator::IsNotNull)
+        );
+        assert_eq!(
+            UnaryOperator::IsNotNull.invert(),
+            Some(UnaryOperator::IsNull)
+        );
+        assert_eq!(UnaryOperator::Not.invert(), None);
+        assert_eq!(VariadicOperator::And.invert(), VariadicOperator::Or);
+        assert_eq!(VariadicOperator::Or.invert(), VariadicOperator::And);
+    }
+
+    #[test]
+    fn test_commute_operators() {
+        use BinaryOperator::*;
+        let cases = [
+            (LessThan, Some(GreaterThan)),
+            (LessThanOrEqual, Some(GreaterThanOrEqual)),
+            (GreaterThan, Some(LessThan)),
+            (GreaterThanOrEqual, Some(LessThanOrEqual)),
+            (Equal, Some(Equal)),
+            (NotEqual, Some(NotEqual)),
+            (Distinct, Some(Distinct)),
+            (NullSafeEqual, Some(NullSafeEqual)),
+            (Plus, Some(Plus)),
+            (Multiply, Some(Multiply)),
+            (Minus, None),
+            (Divide, None),
+            (Modulo, None),
+            (StartsWith, None),
+            (RLike, None),
+        ];
+        for (op, expected) in cases {
+            assert_eq!(op.commute(), expected, "{op}");
+            if let Some(commuted) = expected {
+                assert_eq!(commuted.commute(), Some(op));
+            }
+        }
+    }
+
+    #[test]
+    fn test_canonicalize() {
+        let a = || Expression::column("a");
+        let b = || Expression::column("b");
+        let lit = |value: i32| Expression::literal(value);
+
+        let cases = [
+            (lit(5).lt(a()), a().gt(lit(5))),
+            (a().gt(lit(5)), a().gt(lit(5))),
+            (lit(5).lt_eq(a()), a().gt_eq(lit(5))),
+            (lit(5).gt(a()), a().lt(lit(5))),
+            (lit(5).gt_eq(a()), a().lt_eq(lit(5))),
+            (lit(5).eq(a()), a().eq(lit(5))),
+            (lit(5).ne(a()), a().ne(lit(5))),
+            (lit(5).distinct(a()), a().distinct(lit(5))),
+            (lit(5).null_safe_eq(a()), a().null_safe_eq(lit(5))),
+            // other expressions than columns
+            (lit(5).lt(a() + lit(1)), (a() + lit(1)).gt(lit(5))),
+            // inside the boolean structure and other expressions
+            (
+                (!lit(5).lt(a())).and(lit(1).eq(b()).or(b().is_null())),
+                (!a().gt(lit(5))).and(b().eq(lit(1)).or(b().is_null())),
+            ),
+            (
+                lit(5).lt(a()).eq(Expression::literal(true)),
+                a().gt(lit(5)).eq(Expression::literal(true)),
+            ),
+            // kept as is
+            (lit(5).lt(lit(6)), lit(5).lt(lit(6))),
+            (b().lt(a()), b().lt(a())),
+            (lit(5) + a(), lit(5) + a()),
+            (
+                Expression::literal("x").starts_with(a()),
+                Expression::literal("x").starts_with(a()),
+            ),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(canonicalize(&input), expected, "{input}");
+        }
+        // equivalent predicates canonicalize to the same expression
+        assert_eq!(canonicalize(&lit(5).lt(a())), canonicalize(&a().gt(lit(5))));
+    }
+
+    #[test]
+    fn test_push_down_not() {
+        let a = || Expression::column("a");
+        let b = || Expression::column("b");
+        let lit = |value: i32| Expression::literal(value);
+
+        let cases = [
+            // double negation
+            (!!a(), a()),
+            (!!!a(), !a()),
+            (!!a().lt(lit(5)), a().lt(lit(5))),
+            // comparisons
+            (!a().lt(lit(5)), a().gt_eq(lit(5))),
+            (!a().lt_eq(lit(5)), a().gt(lit(5))),
+            (!a().gt(lit(5)), a().lt_eq(lit(5))),
+            (!a().gt_eq(lit(5)), a().lt(lit(5))),
+            (!a().eq(lit(5)), a().ne(lit(5))),
+            (!a().ne(lit(5)), a().eq(lit(5))),
+            (!a().distinct(b()), a().null_safe_eq(b())),
+            (!a().is_null(), a().is_not_null()),
+            (!a().is_not_null(), a().is_null()),
+            (!Expression::literal(true), Expression::literal(false)),
+            // And/Or
+            (
+                !a().lt(lit(5)).and(b().eq(lit(1))),
+                a().gt_eq(lit(5)).or(b().ne(lit(1))),
+            ),
+            (
+                !a().lt(lit(5)).or(!b().is_null()),
+                a().gt_eq(lit(5)).and(b().is_null()),
+            ),
+            (
+                !Expression::and_from([a().gt(lit(1)), !b().gt(lit(2)).or(a().eq(b()))]),
+                Expression::or_from([a().lt_eq(lit(1)), b().gt(lit(2)).or(a().eq(b()))]),
+            ),
+            // NOT stays on leaves that can't be inverted
+            (
+                !a().in_list([lit(1), lit(2)])
+                    .and(b().starts_with("x".into())),
+                (!a().in_list([lit(1), lit(2)])).or(!b().starts_with("x".into())),
+            ),
+            (
+                a().lt(lit(5)).and(!!b().gt(lit(5))),
+                a().lt(lit(5)).and(b().gt(lit(5))),
+            ),
+            (a() + lit(1), a() + lit(1)),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(push_down_not(&input), expected, "{input}");
+        }
+    }
+
+    #[test]
+    fn test_expand_tuple_comparisons() {
+        let col = |name: &str| Expression::column(name);
+        let lit = |value: i32| Expression::literal(value);
+        let tuple = |exprs: Vec<Expression>| Expression::struct_expr(exprs);
+        let ab = || tuple(vec![col("a"), col("b")]);
+
+        let cases = [
+            (
+                ab().eq(tuple(vec![lit(1), lit(2)])),
+                col("a").eq(lit(1)).and(col("b").eq(lit(2))),
+            ),
+            (
+                ab().ne(tuple(vec![lit(1), lit(2)])),
+                col("a").ne(lit(1)).or(col("b").ne(lit(2))),
+            ),
+            (
+                ab().null_safe_eq(tuple(vec![lit(1), lit(2)])),
+                col("a")
+                    .null_safe_eq(lit(1))
+                    .and(col("b").null_safe_eq(lit(2))),
+            ),
+            (
+                ab().in_list([tuple(vec![lit(1), lit(2)]), tuple(vec![lit(3), lit(4)])]),
+                Expression::or_from([
+                    col("a").eq(lit(1)).and(col("b").eq(lit(2))),
+                    col("a").eq(lit(3)).and(col("b").eq(lit(4))),
+                ]),
+            ),
+            // nested tuples and tuples inside the boolean structure
+            (
+                tuple(vec![col("a"), ab()]).eq(tuple(vec![lit(1), tuple(vec![lit(2), lit(3)])])),
+                Expression::and_from([
+                    col("a").eq(lit(1)),
+                    col("a").eq(lit(2)).and(col("b").eq(lit(3))),
+                ]),
+            ),
+            (
+                (!ab().in_list([tuple(vec![lit(1), lit(2)])])).and(col("c").lt(lit(5))),
+                (!Expression::or_from([col("a").eq(lit(1)).and(col("b").eq(lit(2)))]))
+                    .and(col("c").lt(lit(5))),
+            ),
+            // kept as is
+            (
+                ab().lt(tuple(vec![lit(1), lit(2)])),
+                ab().lt(tuple(vec![lit(1), lit(2)])),
+            ),
+            (ab().eq(tuple(vec![lit(1)])), ab().eq(tuple(vec![lit(1)]))),
+            (
+                ab().in_list([tuple(vec![lit(1), lit(2)]), tuple(vec![lit(3)])]),
+                ab().in_list([tuple(vec![lit(1), lit(2)]), tuple(vec![lit(3)])]),
+            ),
+            (
+                col("a").in_list([lit(1), lit(2)]),
+                col("a").in_list([lit(1), lit(2)]),
+            ),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(expand_tuple_comparisons(&input), expected, "{input}");
+        }
+    }
+
+    #[test]
+    fn test_rewrite_quantified_comparisons() {
+        use BinaryOperator::*;
+        use Quantifier::*;
+        let a = || Expression::column("a");
+        let lit = |value: i32| Expression::literal(value);
+        let list = || [lit(5), lit(1), lit(3)];
+        let quantified = |op, quantifier| a().quantified(op, quantifier, list());
+
+        let cases = [
+            // an ordering compared to ALL values holds for the most extreme value...
+            (quantified(LessThan, All), a().lt(lit(1))),
+            (quantified(LessThanOrEqual, All), a().lt_eq(lit(1))),
+            (quantified(GreaterThan, All), a().gt(lit(5))),
+            (quantified(GreaterThanOrEqual, All), a().gt_eq(lit(5))),
+            // ... and compared to ANY value for the least extreme one
+            (quantified(LessThan, Any), a().lt(lit(5))),
+            (quantified(LessThanOrEqual, Any), a().lt_eq(lit(5))),
+            (quantified(GreaterThan, Any), a().gt(lit(1))),
+            (quantified(GreaterThanOrEqual, Any), a().gt_eq(lit(1))),
+            (quantified(Equal, Any), a().in_list(list())),
+            (quantified(NotEqual, All), a().not_in_list(list())),
+            // other comparisons are expanded
+            (
+                quantified(Equal, All),
+                Expression::and_from(list().map(|value| a().eq(value))),
+            ),
+            (
+                quantified(NotEqual, Any),
+                Expression::or_from(list().map(|value| a().ne(value))),
+            ),
+            (
+                quantified(Distinct, All),
+                Expression::and_from(list().map(|value| a().distinct(value))),
+            ),
+            // ... as are orderings of lists with NULL or incomparable values
+            (
+                a().quantified(
+                    GreaterThan,
+                    All,
+                    [lit(1), Expression::literal(Scalar::Null(DataType::INTEGER))],
+                ),
+                a().gt(lit(1))
+                    .and(a().gt(Expression::literal(Scalar::Null(DataType::INTEGER)))),
+            ),
+            (
+                a().quantified(LessThan, Any, [lit(1), Expression::literal(2_i64)]),
+                a().lt(lit(1)).or(a().lt(Expression::literal(2_i64))),
+            ),
+            // empty lists
+            (
+                a().quantified(LessThan, Any, []),
+                Expression::literal(false),
+            ),
+            (a().quantified(LessThan, All, []), Expression::literal(true)),
+            // inside the boolean structure
+            (
+                (!quantified(GreaterThan, All)).and(a().is_not_null()),
+                (!a().gt(lit(5))).and(a().is_not_null()),
+            ),
+            // values that are not literals are kept as is
+            (
+                a().quantified(GreaterThan, All, [lit(1), Expression::column("b")]),
+                a().quantified(GreaterThan, All, [lit(1), Expression::column("b")]),
+            ),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(rewrite_quantified_comparisons(&input), expected, "{input}");
+        }
+    }
+}
@@ -4,7 +4,11 @@ use std::fmt::{Display, Formatter};
 use chrono::{DateTime, NaiveDate, NaiveDateTime, TimeZone, Utc};
 
 use crate::schema::{DataType, PrimitiveType};
-use crate::Error;
+use crate::{DeltaResult, Error};
+
+lazy_static::lazy_static! {
+    static ref UNIX_EPOCH: DateTime<Utc> = DateTime::from_timestamp(0, 0).unwrap();
+}
 
 /// A single value, which can be null. Used for representing literal values
 /// in [Expressions][crate::expressions::Expression].
@@ -22,6 +26,8 @@ pub enum Scalar {
     Boolean(bool),
     /// Microsecond precision timestamp, adjusted to UTC.
     Timestamp(i64),
+    /// Microsecond precision timestamp, without a timezone.
+    TimestampNtz(i64),
     /// Date stored as a signed 32bit int days since UNIX epoch 1970-01-01
     Date(i32),
     Binary(Vec<u8>),
@@ -41,12 +47,73 @@ impl Scalar {
             Self::String(_) => DataType::Primitive(PrimitiveType::String),
             Self::Boolean(_) => DataType::Primitive(PrimitiveType::Boolean),
             Self::Timestamp(_) => DataType::Primitive(PrimitiveType::Timestamp),
+            Self::TimestampNtz(_) => DataType::Primitive(PrimitiveType::TimestampNtz),
             Self::Date(_) => DataType::Primitive(PrimitiveType::Date),
             Self::Binary(_) => DataType::Primitive(PrimitiveType::Binary),
             Self::Decimal(_, precision, scale) => DataType::decimal(*precision, *scale),
             Self::Null(data_type) => data_type.clone(),
         }
     }
+
+    /// Creates a decimal with the given unscaled `value`, so `Scalar::decimal(12345, 5, 2)` is
+    /// `123.45`. Returns an error if the precision is not between 1 and 38, the scale is negative
+    /// or larger than the precision, or the value has more digits than the precision allows.
+    pub fn decimal(value: i128, precision: u8, scale: i8) -> DeltaResult<Self> {
+        if !(1..=38).contains(&precision) || !(0..=precision as i8).contains(&scale) {
+            return Err(Error::generic(format!(
+                "Invalid decimal precision and scale: ({precision}, {scale})"
+            )));
+        }
+        if value.unsigned_abs() >= 10_u128.pow(precision as u32) {
+            return Err(Error::generic(format!(
+                "Decimal value {value} does not fit in precision {precision}"
+            )));
+        }
+        Ok(Self::Decimal(value, precision, scale))
+    }
+
+    /// Creates a date from the number of days since the UNIX epoch 1970-01-01.
+    pub fn date(days: i32) -> Self {
+        Self::Date(days)
+    }
+
+    /// Creates a timestamp adjusted to UTC from the number of microseconds since the UNIX epoch.
+    pub fn timestamp(micros: i64) -> Self {
+        Self::Timestamp(micros)
+    }
+
+    /// Creates a timestamp without a timezone from the number of microseconds since the UNIX
+    /// epoch.
+    pub fn timestamp_ntz(micros: i64) -> Self {
+        Self::TimestampNtz(micros)
+    }
+
+    /// Compares this scalar with a scalar of the same type. Unlike [`PartialOrd`], this orders any
+    /// two scalars of a type: NULL sorts before all other values and equal to NULL, and floating
+    /// point values follow the IEEE 754 total order, so `-0.0` sorts before `0.0` and NaN after
+    /// all other values. Returns an error if the scalars have different types, including decimals
+    /// of different precision or scale.
+    pub fn try_cmp(&self, other: &Self) -> DeltaResult<Ordering> {
+        use Scalar::*;
+        let (data_type, other_data_type) = (self.data_type(), other.data_type());
+        if data_type != other_data_type {
+            return Err(Error::generic(format!(
+                "Cannot compare {self} of type {data_type} with {other} of type {other_data_type}"
+            )));
+        }
+        match (self, other) {
+            (Null(_), Null(_)) => Ok(Ordering::Equal),
+            (Null(_), _) => Ok(Ordering::Less),
+            (_, Null(_)) => Ok(Ordering::Greater),
+            (Float(a), Float(b)) => Ok(a.total_cmp(b)),
+            (Double(a), Double(b)) => Ok(a.total_cmp(b)),
+            _ => self.partial_cmp(other).ok_or_else(|| {
+                Error::generic(format!(
+                    "Cannot compare {self} with {other} of type {data_type}"
+                ))
+            }),
+        }
+    }
 }
 
 impl Display for Scalar {
@@ -61,6 +128,7 @@ impl Display for Scalar {
             Self::String(s) => write!(f, "'{}'", s),
             Self::Boolean(b) => write!(f, "{}", b),
             Self::Timestamp(ts) => write!(f, "{}", ts),
+            Self::TimestampNtz(ts) => write!(f, "{}", ts),
             Self::Date(d) => write!(f, "{}", d),
             Self::Binary(b) => write!(f, "{:?}", b),
             Self::Decimal(value, _, scale) => match scale.cmp(&0) {
@@ -133,8 +201,59 @@ impl From<String> for Scalar {
     }
 }
 
+impl From<NaiveDate> for Scalar {
+    fn from(date: NaiveDate) -> Self {
+        let days = date
+            .signed_duration_since(UNIX_EPOCH.date_naive())
+            .num_days();
+        Self::Date(days as i32)
+    }
+}
+
+impl From<DateTime<Utc>> for Scalar {
+    fn from(timestamp: DateTime<Utc>) -> Self {
+        Self::Timestamp(timestamp.timestamp_micros())
+    }
+}
+
+impl From<NaiveDateTime> for Scalar {
+    fn from(timestamp: NaiveDateTime) -> Self {
+        Self::TimestampNtz(Utc.from_utc_datetime(&timestamp).timestamp_micros())
+    }
+}
+
 // TODO: add more From impls
 
+impl PartialOrd for Scalar {
+    /// Scalars are only comparable to scalars of the same type, including the precision and scale
+    /// of decimals, consistent with `==`. A null scalar is equal to a null scalar of the same
+    /// type, but not comparable to any other value (see [`Scalar::try_cmp`] for a total order).
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        use Scalar::*;
+        match (self, other) {
+            (Null(a), Null(b)) if a == b => Some(Ordering::Equal),
+            (Integer(a), Integer(b)) => a.partial_cmp(b),
+            (Long(a), Long(b)) => a.partial_cmp(b),
+            (Short(a), Short(b)) => a.partial_cmp(b),
+            (Byte(a), Byte(b)) => a.partial_cmp(b),
+            (Float(a), Float(b)) => a.partial_cmp(b),
+            (Double(a), Double(b)) => a.partial_cmp(b),
+            (String(a), String(b)) => a.partial_cmp(b),
+            (Boolean(a), Boolean(b)) => a.partial_cmp(b),
+            (Timestamp(a), Timestamp(b)) => a.partial_cmp(b),
+            (TimestampNtz(a), TimestampNtz(b)) => a.partial_cmp(b),
+            (Date(a), Date(b)) => a.partial_cmp(b),
+            (Binary(a), Binary(b)) => a.partial_cmp(b),
+            (Decimal(a, a_precision, a_scale), Decimal(b, b_precision, b_scale))
+                if a_precision == b_precision && a_scale == b_scale =>
+            {
+                a.partial_cmp(b)
+            }
+            _ => None,
+        }
+    }
+}
+
 impl PrimitiveType {
     fn data_type(&self) -> DataType {
         DataType::Primitive(self.clone())
@@ -143,10 +262,6 @@ impl PrimitiveType {
     pub fn parse_scalar(&self, raw: &str) -> Result<Scalar, Error> {
         use PrimitiveType::*;
 
-        lazy_static::lazy_static! {
-            static ref UNIX_EPOCH: DateTime<Utc> = DateTime::from_timestamp(0, 0).unwrap();
-        }
-
         if raw.is_empty() {
             return Ok(Scalar::Null(self.data_type()));
         }
@@ -188,7 +303,10 @@ impl PrimitiveType {
                     .signed_duration_since(*UNIX_EPOCH)
                     .num_microseconds()
                     .ok_or(self.parse_error(raw))?;
-                Ok(Scalar::Timestamp(micros))
+                match self {
+                    Timestamp => Ok(Scalar::Timestamp(micros)),
+                    _ => Ok(Scalar::TimestampNtz(micros)),
+                }
             }
         }
     }
@@ -276,6 +394,156 @@ mod tests {
         assert_eq!(s.to_string(), "123000");
     }
 
+    #[test]
+    fn test_typed_constructors() {
+        let decimal = Scalar::decimal(12345, 5, 2).unwrap();
+        assert_eq!(decimal, Scalar::Decimal(12345, 5, 2));
+        assert_eq!(decimal.data_type(), DataType::decimal(5, 2));
+        assert_eq!(
+            PrimitiveType::Decimal(5, 2).parse_scalar("123.45").unwrap(),
+            decimal
+        );
+        assert!(Scalar::decimal(-99999, 5, 2).is_ok());
+        assert!(Scalar::decimal(123456, 5, 2).is_err());
+        assert!(Scalar::decimal(1, 0, 0).is_err());
+        assert!(Scalar::decimal(1, 39, 0).is_err());
+        assert!(Scalar::decimal(1, 5, 6).is_err());
+        assert!(Scalar::decimal(1, 5, -1).is_err());
+
+        let date = Scalar::from(NaiveDate::from_ymd_opt(2024, 1, 1).unwrap());
+        assert_eq!(date, Scalar::date(19723));
+        assert_eq!(date.data_type(), DataType::DATE);
+        assert_eq!(
+            PrimitiveType::Date.parse_scalar("2024-01-01").unwrap(),
+            date
+        );
+        let date = Scalar::from(NaiveDate::from_ymd_opt(1969, 12, 31).unwrap());
+        assert_eq!(date, Scalar::date(-1));
+
+        let raw = "2024-01-01 12:00:00.123456";
+        let naive = NaiveDateTime::parse_from_str(raw, "%Y-%m-%d %H:%M:%S%.f").unwrap();
+        let timestamp = Scalar::from(Utc.from_utc_datetime(&naive));
+        assert_eq!(timestamp, Scalar::timestamp(1704110400123456));
+        assert_eq!(timestamp.data_type(), DataType::TIMESTAMP);
+        assert_eq!(
+            PrimitiveType::Timestamp.parse_scalar(raw).unwrap(),
+            timestamp
+        );
+
+        let timestamp_ntz = Scalar::from(naive);
+        assert_eq!(timestamp_ntz, Scalar::timestamp_ntz(1704110400123456));
+        assert_eq!(timestamp_ntz.data_type(), DataType::TIMESTAMP_NTZ);
+        assert_eq!(
+            PrimitiveType::TimestampNtz.parse_scalar(raw).unwrap(),
+            timestamp_ntz
+        );
+        assert_eq!(timestamp_ntz.to_string(), "1704110400123456");
+
+        // timestamps with and without a timezone are not comparable
+        assert_eq!(timestamp.partial_cmp(&timestamp_ntz), None);
+        assert!(Scalar::timestamp_ntz(1) < timestamp_ntz);
+    }
+
+    #[test]
+    fn test_partial_cmp_consistent_with_eq() {
+        let scalars = [
+            Scalar::Null(DataType::INTEGER),
+            Scalar::Null(DataType::LONG),
+            Scalar::Integer(1),
+            Scalar::Long(1),
+            Scalar::Decimal(12345, 5, 2),
+            Scalar::Decimal(12345, 6, 2),
+            Scalar::Decimal(12345, 6, 3),
+            Scalar::Double(0.0),
+            Scalar::Double(-0.0),
+            Scalar::Double(f64::NAN),
+            Scalar::from("a"),
+        ];
+        for left in &scalars {
+            for right in &scalars {
+                assert_eq!(
+                    left == right,
+                    left.partial_cmp(right) == Some(Ordering::Equal),
+                    "{left:?} and {right:?}"
+                );
+            }
+        }
+        // nulls are equal to each other, but not ordered with other values
+        assert_eq!(
+            Scalar::Null(DataType::INTEGER).partial_cmp(&Scalar::Integer(1)),
+            None
+        );
+    }
+
+    #[test]
+    fn test_try_cmp() {
+        use Ordering::*;
+
+        let cases = [
+            (Scalar::Integer(1), Scalar::Integer(2), Less),
+            (Scalar::Long(-5), Scalar::Long(-5), Equal),
+            (Scalar::Short(3), Scalar::Short(-3), Greater),
+            (Scalar::Byte(i8::MIN), Scalar::Byte(i8::MAX), Less),
+            (Scalar::Double(1.5), Scalar::Double(2.5), Less),
+            (Scalar::Double(-0.0), Scalar::Double(0.0), Less),
+            (
+                Scalar::Double(f64::NAN),
+                Scalar::Double(f64::INFINITY),
+                Greater,
+            ),
+            (Scalar::Float(f32::NAN), Scalar::Float(f32::NAN), Equal),
+            (
+                Scalar::Decimal(12345, 5, 2),
+                Scalar::Decimal(-12345, 5, 2),
+                Greater,
+            ),
+            (Scalar::from("apple"), Scalar::from("banana"), Less),
+            (Scalar::from("b"), Scalar::from("abc"), Greater),
+            (Scalar::from(""), Scalar::from(""), Equal),
+            (Scalar::date(-1), Scalar::date(0), Less),
+            (Scalar::timestamp(1000), Scalar::timestamp(999), Greater),
+            (Scalar::timestamp_ntz(0), Scalar::timestamp_ntz(0), Equal),
+            (Scalar::Boolean(false), Scalar::Boolean(true), Less),
+            (
+                Scalar::Null(DataType::INTEGER),
+                Scalar::Integer(i32::MIN),
+                Less,
+            ),
+            (Scalar::Date(0), Scalar::Null(DataType::DATE), Greater),
+            (
+                Scalar::Null(DataType::STRING),
+                Scalar::Null(DataType::STRING),
+                Equal,
+            ),
+        ];
+        for (left, right, expected) in cases {
+            assert_eq!(left.try_cmp(&right).unwrap(), expected, "{left} vs {right}");
+            assert_eq!(
+                right.try_cmp(&left).unwrap(),
+                expected.reverse(),
+                "{right} vs {left}"
+            );
+        }
+
+        let errors = [
+            (Scalar::Integer(1), Scalar::Long(1)),
+            (Scalar::Float(1.0), Scalar::Double(1.0)),
+            (Scalar::from("1"), Scalar::Integer(1)),
+            (Scalar::date(0), Scalar::timestamp(0)),
+            (Scalar::timestamp(0), Scalar::timestamp_ntz(0)),
+            (Scalar::Decimal(1, 5, 2), Scalar::Decimal(1, 5, 3)),
+            (Scalar::Null(DataType::INTEGER), Scalar::Long(1)),
+            (
+                Scalar::Null(DataType::INTEGER),
+                Scalar::Null(DataType::LONG),
+            ),
+        ];
+        for (left, right) in errors {
+            assert!(left.try_cmp(&right).is_err(), "{left} vs {right}");
+            assert!(right.try_cmp(&left).is_err(), "{right} vs {left}");
+        }
+    }
+
     fn assert_decimal(
         raw: &str,
         expect_int: i128,
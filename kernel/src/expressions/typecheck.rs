@@ -0,0 +1,529 @@
+//! Type checking of kernel expressions against a schema
+
+use super::{BinaryOperator, Expression, UnaryOperator};
+use crate::schema::{DataType, PrimitiveType, StructField, StructType};
+use crate::{DeltaResult, Error};
+
+/// Resolves a (possibly nested, e.g. `a.b.c`) column name against `schema`.
+fn resolve_column<'a>(schema: &'a StructType, name: &str) -> DeltaResult<&'a DataType> {
+    let mut path = name.split('.');
+    // Safety: split always returns at least one item
+    let first = path.next().unwrap();
+    let mut field = schema
+        .field(first)
+        .ok_or_else(|| Error::missing_column(format!("Column {name} not found in schema")))?;
+    for step in path {
+        let DataType::Struct(struct_type) = field.data_type() else {
+            return Err(Error::missing_column(format!(
+                "Column {name} not found in schema: {} is not a struct",
+                field.name()
+            )));
+        };
+        field = struct_type
+            .field(step)
+            .ok_or_else(|| Error::missing_column(format!("Column {name} not found in schema")))?;
+    }
+    Ok(field.data_type())
+}
+
+/// Returns the type that numbers of both types are promoted to by arithmetic, or `None` if they
+/// are not both integer or floating point numbers. This matches the default engine, which
+/// promotes integers to the wider of the two types, and to the floating point type if the other
+/// operand is one.
+fn numeric_type(left: &DataType, right: &DataType) -> Option<DataType> {
+    use PrimitiveType::*;
+    let rank = |data_type: &DataType| match data_type {
+        DataType::Primitive(Byte) => Some(0),
+        DataType::Primitive(Short) => Some(1),
+        DataType::Primitive(Integer) => Some(2),
+        DataType::Primitive(Long) => Some(3),
+        DataType::Primitive(Float) => Some(4),
+        DataType::Primitive(Double) => Some(5),
+        _ => None,
+    };
+    if rank(left)? >= rank(right)? {
+        Some(left.clone())
+    } else {
+        Some(right.clone())
+    }
+}
+
+/// Returns whether casting values of type `from` to `to` is lossless and keeps their order, e.g.
+/// `INTEGER` to `LONG` or `DOUBLE`, but not `LONG` to `DOUBLE` (which rounds large values) or
+/// `LONG` to `INTEGER` (which overflows). Comparing the result of such a cast is the same as
+/// comparing the original value.
+pub(crate) fn is_widening_cast(from: &DataType, to: &DataType) -> bool {
+    use PrimitiveType::*;
+    // the number of decimal digits of the largest value of an integer type
+    let digits = |data_type: &PrimitiveType| match data_type {
+        Byte => Some(3),
+        Short => Some(5),
+        Integer => Some(10),
+        Long => Some(19),
+        _ => None,
+    };
+    let (DataType::Primitive(from), DataType::Primitive(to)) = (from, to) else {
+        return false;
+    };
+    match (from, to) {
+        _ if from == to => true,
+        (Byte, Short | Integer | Long | Float | Double)
+        | (Short, Integer | Long | Float | Double)
+        | (Integer, Long | Double)
+        | (Float, Double)
+        | (Date, TimestampNtz) => true,
+        // the target must have at least as many digits before and after the decimal point
+        (Decimal(precision, scale), Decimal(to_precision, to_scale)) => {
+            to_scale >= scale
+                && i16::from(*to_precision) - i16::from(*to_scale)
+                    >= i16::from(*precision) - i16::from(*scale)
+        }
+        (from, Decimal(precision, scale)) => match digits(from) {
+            Some(digits) => i16::from(*precision) - i16::from(*scale) >= digits,
+            None => false,
+        },
+        _ => false,
+    }
+}
+
+/// Returns the type that values of both types can be combined as (e.g. by CASE or COALESCE).
+fn common_type(left: &DataType, right: &DataType) -> Option<DataType> {
+    if left == right {
+        Some(left.clone())
+    } else {
+        numeric_type(left, right)
+    }
+}
+
+/// Returns the common type of the values of an expression, or an error naming the expression.
+fn unify<'a>(name: &str, mut types: impl Iterator<Item = &'a DataType>) -> DeltaResult<DataType> {
+    let first = types
+        .next()
+        .ok_or_else(|| Error::invalid_expression(format!("{name} requires at least one value")))?;
+    types.try_fold(first.clone(), |data_type, other| {
+        common_type(&data_type, other).ok_or_else(|| {
+            Error::invalid_expression(format!(
+                "{name} values have incompatible types {data_type} and {other}"
+            ))
+        })
+    })
+}
+
+/// Checks that an operand of `name` is a boolean.
+fn expect_boolean(name: &str, data_type: DataType) -> DeltaResult<()> {
+    match data_type {
+        DataType::BOOLEAN => Ok(()),
+        other => Err(Error::invalid_expression(format!(
+            "{name} expects boolean operands, got {other}"
+        ))),
+    }
+}
+
+/// Checks that two operands of `name` have the same type, so they can be compared.
+fn expect_comparable(name: &str, left: &DataType, right: &DataType) -> DeltaResult<()> {
+    if left != right {
+        return Err(Error::invalid_expression(format!(
+            "Cannot compare {left} and {right} with {name}"
+        )));
+    }
+    Ok(())
+}
+
+/// Type checks `expr` against `schema`, and returns the type of its result. Columns are resolved
+/// by their (possibly nested, e.g. `a.b.c`) name, and the operands of each operator must have
+/// types the operator supports:
+///
+/// - Arithmetic requires numbers, which are promoted to the wider type (e.g. `int + long` is a
+///   `long`), or decimals of the same type.
+/// - Comparisons, `IN` and `BETWEEN` require operands of the same type, and quantified
+///   comparisons (`ANY`/`ALL`) a comparison operator and values of the type of the expression.
+/// - `STARTS WITH` and `RLIKE` require strings, and `NOT`, `AND` and `OR` require booleans.
+/// - The values of `CASE` and `COALESCE` must have a common type, and conditions must be boolean.
+/// - A map lookup `map[key]` requires a map whose keys have the type of `key`.
+/// - `SIZE(array)` and `array[index]` require an array, and the index must not be negative.
+/// - `CAST` converts between primitive types.
+///
+/// A struct expression has a struct type with positional field names (`0`, `1`, ...).
+pub fn typecheck(expr: &Expression, schema: &StructType) -> DeltaResult<DataType> {
+    use BinaryOperator::*;
+    use Expression::*;
+
+    match expr {
+        Literal(value) => Ok(value.data_type()),
+        Column(name) => resolve_column(schema, name).cloned(),
+        Struct(exprs) => {
+            let fields = exprs
+                .iter()
+                .enumerate()
+                .map(|(i, expr)| {
+                    Ok(StructField::new(
+                        i.to_string(),
+                        typecheck(expr, schema)?,
+                        true,
+                    ))
+                })
+                .collect::<DeltaResult<_>>()?;
+            Ok(StructType::new(fields).into())
+        }
+        BinaryOperation { op, left, right } => {
+            let left = typecheck(left, schema)?;
+            let right = typecheck(right, schema)?;
+            match op {
+                Plus | Minus | Multiply | Divide | Modulo => match (&left, &right) {
+                    (DataType::Primitive(PrimitiveType::Decimal(..)), _) if left == right => {
+                        Ok(left)
+                    }
+                    _ => numeric_type(&left, &right).ok_or_else(|| {
+                        Error::invalid_expression(format!(
+                            "Cannot apply {op} to {left} and {right}"
+                        ))
+                    }),
+                },
+                StartsWith | RLike => match (&left, &right) {
+                    (&DataType::STRING, &DataType::STRING) => Ok(DataType::BOOLEAN),
+                    _ => Err(Error::invalid_expression(format!(
+                        "{op} expects string operands, got {left} and {right}"
+                    ))),
+                },
+                LessThan | LessThanOrEqual | GreaterThan | GreaterThanOrEqual | Equal
+                | NotEqual | Distinct | NullSafeEqual => {
+                    expect_comparable(&op.to_string(), &left, &right)?;
+                    Ok(DataType::BOOLEAN)
+                }
+            }
+        }
+        UnaryOperation { op, expr } => {
+            let data_type = typecheck(expr, schema)?;
+            if *op == UnaryOperator::Not {
+                expect_boolean("NOT", data_type)?;
+            }
+            Ok(DataType::BOOLEAN)
+        }
+        VariadicOperation { op, exprs } => {
+            for expr in exprs {
+                expect_boolean(&format!("{op:?}").to_uppercase(), typecheck(expr, schema)?)?;
+            }
+            Ok(DataType::BOOLEAN)
+        }
+        InList { expr, list } => {
+            let data_type = typecheck(expr, schema)?;
+            for value in list {
+                expect_comparable("IN", &data_type, &typecheck(value, schema)?)?;
+            }
+            Ok(DataType::BOOLEAN)
+        }
+        Quantified { op, expr, list, .. } => {
+            if !matches!(
+                op,
+                LessThan
+                    | LessThanOrEqual
+                    | GreaterThan
+                    | GreaterThanOrEqual
+                    | Equal
+                    | NotEqual
+                    | Distinct
+                    | NullSafeEqual
+            ) {
+                return Err(Error::invalid_expression(format!(
+                    "{op} is not a comparison operator"
+                )));
+            }
+            let data_type = typecheck(expr, schema)?;
+            for value in list {
+                expect_comparable(&op.to_string(), &data_type, &typecheck(value, schema)?)?;
+            }
+            Ok(DataType::BOOLEAN)
+        }
+        Between { expr, low, high } => {
+            let data_type = typecheck(expr, schema)?;
+            expect_comparable("BETWEEN", &data_type, &typecheck(low, schema)?)?;
+            expect_comparable("BETWEEN", &data_type, &typecheck(high, schema)?)?;
+            Ok(DataType::BOOLEAN)
+        }
+        CaseWhen {
+            branches,
+            otherwise,
+        } => {
+            let mut values = vec![typecheck(otherwise, schema)?];
+            for (condition, value) in branches {
+                expect_boolean("CASE WHEN", typecheck(condition, schema)?)?;
+                values.push(typecheck(value, schema)?);
+            }
+            unify("CASE", values.iter())
+        }
+        Coalesce(exprs) => {
+            let values: Vec<_> = exprs
+                .iter()
+                .map(|expr| typecheck(expr, schema))
+                .collect::<DeltaResult<_>>()?;
+            unify("COALESCE", values.iter())
+        }
+        Greatest(exprs) | Least(exprs) => {
+            let name = match expr {
+                Greatest(_) => "GREATEST",
+                _ => "LEAST",
+            };
+            let values: Vec<_> = exprs
+                .iter()
+                .map(|expr| typecheck(expr, schema))
+                .collect::<DeltaResult<_>>()?;
+            unify(name, values.iter())
+        }
+        MapAccess { map, key } => match typecheck(map, schema)? {
+            DataType::Map(map_type) => {
+                let key_type = key.data_type();
+                if &key_type != map_type.key_type() {
+                    return Err(Error::invalid_expression(format!(
+                        "Cannot look up a {key_type} key in a map with {} keys",
+                        map_type.key_type()
+                    )));
+                }
+                Ok(map_type.value_type().clone())
+            }
+            other => Err(Error::invalid_expression(format!(
+                "Cannot look up a key in {other}, which is not a map"
+            ))),
+        },
+        ArraySize(array) => match typecheck(array, schema)? {
+            DataType::Array(_) => Ok(DataType::INTEGER),
+            other => Err(Error::invalid_expression(format!(
+                "Cannot get the size of {other}, which is not an array"
+            ))),
+        },
+        ArrayElement { array, index } => match typecheck(array, schema)? {
+            _ if *index < 0 => Err(Error::invalid_expression(format!(
+                "Array index {index} must not be negative"
+            ))),
+            DataType::Array(array_type) => Ok(array_type.element_type().clone()),
+            other => Err(Error::invalid_expression(format!(
+                "Cannot get an element of {other}, which is not an array"
+            ))),
+        },
+        Cast { expr, target_type } => match (typecheck(expr, schema)?, target_type) {
+            (DataType::Primitive(_), DataType::Primitive(_)) => Ok(target_type.clone()),
+            (data_type, _) => Err(Error::invalid_expression(format!(
+                "Cannot cast {data_type} to {target_type}"
+            ))),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::expressions::Scalar;
+    use crate::schema::{ArrayType, MapType};
+
+    fn schema() -> StructType {
+        StructType::new(vec![
+            StructField::new("i", DataType::INTEGER, true),
+            StructField::new("l", DataType::LONG, true),
+            StructField::new("s", DataType::STRING, true),
+            StructField::new("b", DataType::BOOLEAN, true),
+            StructField::new("arr", ArrayType::new(DataType::STRING, true), true),
+            StructField::new(
+                "nested",
+                StructType::new(vec![
+                    StructField::new("d", DataType::decimal(10, 2), true),
+                    StructField::new(
+                        "metrics",
+                        MapType::new(DataType::STRING, DataType::LONG, true),
+                        true,
+                    ),
+                    StructField::new(
+                        "inner",
+                        StructType::new(vec![StructField::new("ts", DataType::TIMESTAMP, true)]),
+                        true,
+                    ),
+                ]),
+                true,
+            ),
+        ])
+    }
+
+    #[test]
+    fn test_typecheck() {
+        let schema = schema();
+        let col = Expression::column;
+        let lit = |value: i32| Expression::literal(value);
+
+        let cases = [
+            (col("i"), DataType::INTEGER),
+            (col("nested.d"), DataType::decimal(10, 2)),
+            (col("nested.inner.ts"), DataType::TIMESTAMP),
+            (col("nested.metrics").map_access("latency"), DataType::LONG),
+            (col("arr").array_size(), DataType::INTEGER),
+            (col("arr").array_element(0), DataType::STRING),
+            (
+                col("nested.metrics")
+                    .map_access("latency")
+                    .gt(Expression::literal(100_i64)),
+                DataType::BOOLEAN,
+            ),
+            (col("i") + lit(1), DataType::INTEGER),
+            (col("i") * col("l"), DataType::LONG),
+            (
+                col("i") / Expression::literal(Scalar::Double(2.0)),
+                DataType::DOUBLE,
+            ),
+            (
+                col("nested.d") + Expression::literal(Scalar::Decimal(1, 10, 2)),
+                DataType::decimal(10, 2),
+            ),
+            (col("i").lt(lit(5)), DataType::BOOLEAN),
+            (
+                col("nested.inner.ts").gt_eq(Expression::literal(Scalar::Timestamp(0))),
+                DataType::BOOLEAN,
+            ),
+            (
+                col("s").starts_with(Expression::literal("x")),
+                DataType::BOOLEAN,
+            ),
+            (
+                col("b").and(col("i").is_null()).or(!col("b")),
+                DataType::BOOLEAN,
+            ),
+            (col("i").in_list([lit(1), lit(2)]), DataType::BOOLEAN),
+            (
+                Expression::between(col("i"), lit(1), lit(2)),
+                DataType::BOOLEAN,
+            ),
+            (
+                Expression::case_when([(col("b"), col("i"))], col("l")),
+                DataType::LONG,
+            ),
+            (
+                Expression::coalesce([col("s"), Expression::literal("none")]),
+                DataType::STRING,
+            ),
+            (Expression::greatest([col("i"), col("l")]), DataType::LONG),
+            (Expression::least([lit(1), col("i")]), DataType::INTEGER),
+            (
+                Expression::struct_expr([col("i"), col("s")]),
+                StructType::new(vec![
+                    StructField::new("0", DataType::INTEGER, true),
+                    StructField::new("1", DataType::STRING, true),
+                ])
+                .into(),
+            ),
+            (col("i").cast(DataType::LONG), DataType::LONG),
+            (col("l").cast(DataType::INTEGER), DataType::INTEGER),
+            (col("s").cast(DataType::DATE), DataType::DATE),
+        ];
+        for (expr, expected) in cases {
+            assert_eq!(typecheck(&expr, &schema).unwrap(), expected, "{expr}");
+        }
+    }
+
+    #[test]
+    fn test_is_widening_cast() {
+        let cases = [
+            (DataType::BYTE, DataType::LONG, true),
+            (DataType::SHORT, DataType::FLOAT, true),
+            (DataType::INTEGER, DataType::LONG, true),
+            (DataType::INTEGER, DataType::DOUBLE, true),
+            (DataType::INTEGER, DataType::FLOAT, false),
+            (DataType::LONG, DataType::DOUBLE, false),
+            (DataType::LONG, DataType::INTEGER, false),
+            (DataType::FLOAT, DataType::DOUBLE, true),
+            (DataType::DOUBLE, DataType::FLOAT, false),
+            (DataType::DATE, DataType::TIMESTAMP_NTZ, true),
+            (DataType::DATE, DataType::TIMESTAMP, false),
+            (DataType::decimal(5, 2), DataType::decimal(7, 3), true),
+            (DataType::decimal(5, 2), DataType::decimal(6, 4), false),
+            (DataType::INTEGER, DataType::decimal(12, 2), true),
+            (DataType::INTEGER, DataType::decimal(11, 2), false),
+            (DataType::STRING, DataType::STRING, true),
+            (DataType::INTEGER, DataType::STRING, false),
+        ];
+        for (from, to, expected) in cases {
+            assert_eq!(is_widening_cast(&from, &to), expected, "{from} to {to}");
+        }
+    }
+
+    #[test]
+    fn test_typecheck_errors() {
+        let schema = schema();
+        let col = Expression::column;
+        let lit = |value: i32| Expression::literal(value);
+
+        let cases = [
+            // unknown columns
+            (col("x").lt(lit(5)), "Column x not found"),
+            (col("nested.x"), "Column nested.x not found"),
+            (col("i.x"), "i is not a struct"),
+            // type mismatches
+            (
+                col("i").lt(Expression::literal("5")),
+                "Cannot compare int and string with <",
+            ),
+            (
+                col("i").eq(col("l")),
+                "Cannot compare int and bigint with =",
+            ),
+            (col("s") + lit(1), "Cannot apply + to string and int"),
+            (
+                col("nested.d") + Expression::literal(Scalar::Decimal(1, 10, 3)),
+                "Cannot apply + to decimal(10, 2) and decimal(10, 3)",
+            ),
+            (
+                col("i").starts_with(lit(1)),
+                "STARTS WITH expects string operands",
+            ),
+            (
+                col("arr").cast(DataType::STRING),
+                "Cannot cast array<string>",
+            ),
+            (col("s").rlike(col("i")), "RLIKE expects string operands"),
+            (!col("i"), "NOT expects boolean operands, got int"),
+            (
+                col("b").and(col("s")),
+                "AND expects boolean operands, got string",
+            ),
+            (
+                col("i").in_list([lit(1), Expression::literal("2")]),
+                "with IN",
+            ),
+            (
+                Expression::between(col("s"), lit(1), lit(2)),
+                "Cannot compare string and int with BETWEEN",
+            ),
+            (
+                Expression::case_when([(col("i"), lit(1))], lit(0)),
+                "CASE WHEN expects boolean operands",
+            ),
+            (
+                Expression::case_when([(col("b"), col("s"))], lit(0)),
+                "CASE values have incompatible types int and string",
+            ),
+            (
+                Expression::coalesce([col("i"), col("s")]),
+                "COALESCE values have incompatible types int and string",
+            ),
+            (
+                Expression::coalesce([]),
+                "COALESCE requires at least one value",
+            ),
+            (
+                Expression::greatest([col("i"), col("s")]),
+                "GREATEST values have incompatible types int and string",
+            ),
+            (Expression::least([]), "LEAST requires at least one value"),
+            (
+                col("nested.metrics").map_access(1),
+                "Cannot look up a int key in a map with string keys",
+            ),
+            (col("i").map_access("x"), "Cannot look up a key in int"),
+            (col("s").array_size(), "Cannot get the size of string"),
+            (col("s").array_element(0), "Cannot get an element of string"),
+            (
+                col("arr").array_element(-1),
+                "Array index -1 must not be negative",
+            ),
+        ];
+        for (expr, expected) in cases {
+            let error = typecheck(&expr, &schema).unwrap_err().to_string();
+            assert!(error.contains(expected), "{expr}: {error}");
+        }
+    }
+}
@@ -106,7 +106,10 @@ pub struct FileMeta {
 /// It contains one Expression which can be evaluated on multiple ColumnarBatches.
 /// Connectors can implement this interface to optimize the evaluation using the
 /// connector specific capabilities.
-pub trait ExpressionEvaluator {
+///
+/// Evaluators must be `Send + Sync`, so that Delta Kernel can evaluate the same expression on
+/// several batches concurrently.
+pub trait ExpressionEvaluator: Send + Sync {
     /// Evaluate the expression on a given EngineData.
     ///
     /// Contains one value for each row of the input.
@@ -161,7 +164,10 @@ pub trait FileSystemClient: Send + Sync {
 /// Delta Kernel can use this client to parse JSON strings into Row or read content from JSON files.
 /// Connectors can leverage this interface to provide their best implementation of the JSON parsing
 /// capability to Delta Kernel.
-pub trait JsonHandler {
+///
+/// Json handlers must be `Send + Sync`, so that Delta Kernel can parse the stats of several
+/// batches of actions concurrently.
+pub trait JsonHandler: Send + Sync {
     /// Parse the given json strings and return the fields requested by output schema as columns in [`EngineData`].
     /// json_strings MUST be a single column batch of engine data, and the column type must be string
     fn parse_json(
@@ -0,0 +1,357 @@
+//! Caches of parsed stats and expression evaluators, which scans can share
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use crate::engine_data::GetData;
+use crate::error::DeltaResult;
+use crate::expressions::Expression as Expr;
+use crate::schema::{DataType, SchemaRef};
+use crate::{DataVisitor, EngineData, ExpressionEvaluator, ExpressionHandler};
+
+use super::filter::StatsPresenceVisitor;
+
+/// Collects the values of the first (string) column of a batch, e.g. the paths of its Add actions
+/// or their stats json.
+#[derive(Default)]
+pub(crate) struct StringColumnVisitor {
+    pub(crate) values: Vec<Option<String>>,
+}
+
+impl DataVisitor for StringColumnVisitor {
+    fn visit<'a>(&mut self, row_count: usize, getters: &[&'a dyn GetData<'a>]) -> DeltaResult<()> {
+        for i in 0..row_count {
+            let value = getters[0].get_str(i, "value")?;
+            self.values.push(value.map(String::from));
+        }
+        Ok(())
+    }
+}
+
+/// Identifies a batch of actions for the [`StatsCache`] by the path and the stats json of the Add
+/// action in each row (`None` for rows that are not Add actions, or have no stats). Batches with
+/// the same key have the same stats, no matter which log file (or which part of it) the engine
+/// read them from. The path alone isn't enough: a later commit may add the same path again with
+/// recomputed stats.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) struct BatchKey {
+    pub(crate) paths: Vec<Option<String>>,
+    pub(crate) stats: Vec<Option<String>>,
+}
+
+/// A cache of the stats parsed by data skipping, which scans of the same snapshot can share so that
+/// each scan doesn't parse the stats of the same actions again (see
+/// [`ScanBuilder::with_stats_cache`]).
+///
+/// The stats of a batch of actions are cached by the paths and stats json of its Add actions and
+/// the schema they were parsed with, so scans only reuse the stats of predicates that reference
+/// the same columns. A later commit may add a path again with recomputed stats, which is why the
+/// path alone doesn't identify the stats. Batches are identified by their contents rather than
+/// their position in the log replay, since engines may return different batches for scans with
+/// different predicates. Stats that add actions hold as a struct (`stats_parsed`) need no parsing,
+/// so they are not cached.
+///
+/// A cache created with [`StatsCache::new`] never evicts anything by itself: it holds the stats of
+/// every batch of actions it was applied to until it is dropped with the last scan that uses it,
+/// or emptied with [`StatsCache::clear`], so it grows with the size of the log and the number of
+/// distinct predicates. Use [`StatsCache::with_capacity`] to bound it instead.
+///
+/// [`ScanBuilder::with_stats_cache`]: crate::scan::ScanBuilder::with_stats_cache
+#[derive(Default)]
+pub struct StatsCache {
+    capacity: Option<usize>,
+    entries: Mutex<StatsCacheEntries>,
+}
+
+#[derive(Default)]
+struct StatsCacheEntries {
+    stats: HashMap<Arc<BatchKey>, Vec<CachedStats>>,
+    /// The key and schema of each cached batch of stats, from the least to the most recently
+    /// inserted.
+    order: VecDeque<(Arc<BatchKey>, SchemaRef)>,
+}
+
+/// The parsed stats of a batch of actions.
+#[derive(Clone)]
+pub(crate) struct CachedStats {
+    pub(crate) schema: SchemaRef,
+    pub(crate) parsed_stats: Arc<dyn EngineData>,
+    pub(crate) presence: StatsPresenceVisitor,
+}
+
+impl StatsCache {
+    /// Create an empty cache without a bound on the number of batches of stats it holds.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create an empty cache that holds the stats of at most `capacity` batches of actions, and
+    /// evicts the ones that were inserted first when it is full.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            capacity: Some(capacity),
+            entries: Default::default(),
+        }
+    }
+
+    /// The number of batches of parsed stats in the cache.
+    pub fn len(&self) -> usize {
+        self.entries().order.len()
+    }
+
+    /// Whether the cache is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Remove all stats from the cache.
+    pub fn clear(&self) {
+        let mut entries = self.entries();
+        entries.stats.clear();
+        entries.order.clear();
+    }
+
+    pub(crate) fn get(&self, key: &BatchKey, schema: &SchemaRef) -> Option<CachedStats> {
+        self.entries()
+            .stats
+            .get(key)?
+            .iter()
+            .find(|cached| cached.schema == *schema)
+            .cloned()
+    }
+
+    pub(crate) fn insert(&self, key: BatchKey, stats: CachedStats) {
+        if self.capacity == Some(0) {
+            return;
+        }
+        let mut entries = self.entries();
+        let cached = entries.stats.get(&key);
+        if cached.is_some_and(|cached| cached.iter().any(|cached| cached.schema == stats.schema)) {
+            return; // another scan parsed the same stats concurrently
+        }
+        if Some(entries.order.len()) == self.capacity {
+            if let Some((key, schema)) = entries.order.pop_front() {
+                if let Some(cached) = entries.stats.get_mut(&key) {
+                    cached.retain(|cached| cached.schema != schema);
+                    if cached.is_empty() {
+                        entries.stats.remove(&key);
+                    }
+                }
+            }
+        }
+        let key = Arc::new(key);
+        entries.order.push_back((key.clone(), stats.schema.clone()));
+        entries.stats.entry(key).or_default().push(stats);
+    }
+
+    fn entries(&self) -> MutexGuard<'_, StatsCacheEntries> {
+        // the entries are always consistent, even if another thread panicked
+        self.entries
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl std::fmt::Debug for StatsCache {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("StatsCache")
+            .field("capacity", &self.capacity)
+            .field("len", &self.len())
+            .finish()
+    }
+}
+
+/// A cache of expression evaluators, keyed by the input schema, expression and output type they
+/// were created with. Filters for predicates of the same shape (e.g. repeated scans with the same
+/// predicate) get the same evaluators from the cache, instead of each asking the engine to create
+/// new ones. The cache holds at most `capacity` evaluators, and evicts the least recently used one
+/// when it is full.
+pub struct EvaluatorCache {
+    capacity: usize,
+    entries: Mutex<VecDeque<CachedEvaluator>>,
+}
+
+/// An evaluator and the arguments it was created with.
+struct CachedEvaluator {
+    schema: SchemaRef,
+    expression: Expr,
+    output_type: DataType,
+    evaluator: Arc<dyn ExpressionEvaluator>,
+}
+
+impl EvaluatorCache {
+    /// Create an empty cache that holds at most `capacity` evaluators.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: Mutex::new(VecDeque::with_capacity(capacity)),
+        }
+    }
+
+    /// The number of evaluators in the cache.
+    pub fn len(&self) -> usize {
+        self.entries().len()
+    }
+
+    /// Whether the cache is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the cached evaluator for the given arguments, or creates one with `handler` (and
+    /// caches it) if there is none.
+    pub(crate) fn get_evaluator(
+        &self,
+        handler: &dyn ExpressionHandler,
+        schema: SchemaRef,
+        expression: Expr,
+        output_type: DataType,
+    ) -> Arc<dyn ExpressionEvaluator> {
+        let mut entries = self.entries();
+        let position = entries.iter().position(|cached| {
+            cached.schema == schema
+                && cached.expression == expression
+                && cached.output_type == output_type
+        });
+        if let Some(cached) = position.and_then(|position| entries.remove(position)) {
+            // move the evaluator to the back, which holds the most recently used ones
+            let evaluator = cached.evaluator.clone();
+            entries.push_back(cached);
+            return evaluator;
+        }
+
+        let evaluator =
+            handler.get_evaluator(schema.clone(), expression.clone(), output_type.clone());
+        if self.capacity > 0 {
+            if entries.len() == self.capacity {
+                entries.pop_front();
+            }
+            entries.push_back(CachedEvaluator {
+                schema,
+                expression,
+                output_type,
+                evaluator: evaluator.clone(),
+            });
+        }
+        evaluator
+    }
+
+    fn entries(&self) -> MutexGuard<'_, VecDeque<CachedEvaluator>> {
+        // the entries are always consistent, even if another thread panicked
+        self.entries
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl std::fmt::Debug for EvaluatorCache {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EvaluatorCache")
+            .field("capacity", &self.capacity)
+            .field("len", &self.len())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::sync::SyncEngineInterface;
+    use crate::scan::data_skipping::DataSkippingFilterBuilder;
+    use crate::scan::test_utils::add_actions_batch;
+    use crate::schema::{StructField, StructType};
+
+    #[test]
+    fn test_stats_cache_batch_identity() {
+        let engine_interface = SyncEngineInterface::new();
+        let table_schema = Arc::new(StructType::new(vec![StructField::new(
+            "a",
+            DataType::INTEGER,
+            true,
+        )]));
+        let predicate = Some(Expr::column("a").gt(Expr::literal(5)));
+        let stats_cache = Arc::new(StatsCache::new());
+        let filter = DataSkippingFilterBuilder::new(&table_schema, &predicate)
+            .with_stats_cache(Some(stats_cache.clone()))
+            .build(&engine_interface)
+            .unwrap();
+
+        // e.g. the first batch of the log replay of two scans, for which the engine returned
+        // different batches of actions
+        let small = r#"{"numRecords":1,"minValues":{"a":1},"maxValues":{"a":3}}"#;
+        let large = r#"{"numRecords":1,"minValues":{"a":6},"maxValues":{"a":9}}"#;
+        let batches = [
+            add_actions_batch(&engine_interface, &[small]),
+            add_actions_batch(&engine_interface, &[small, large]),
+        ];
+        let expected = [vec![false], vec![false, true]];
+        for _ in 0..2 {
+            for (batch, expected) in batches.iter().zip(&expected) {
+                assert_eq!(&filter.apply_cached(batch.as_ref()).unwrap(), expected);
+            }
+        }
+        assert_eq!(stats_cache.len(), 2);
+
+        // a later commit adds the first file again with other stats, which must not be mistaken
+        // for the cached ones of the same path
+        let readded = add_actions_batch(&engine_interface, &[large]);
+        assert_eq!(filter.apply_cached(readded.as_ref()).unwrap(), vec![true]);
+        assert_eq!(stats_cache.len(), 3);
+    }
+
+    #[test]
+    fn test_stats_cache_capacity() {
+        let engine_interface = SyncEngineInterface::new();
+        let table_schema = Arc::new(StructType::new(vec![StructField::new(
+            "a",
+            DataType::INTEGER,
+            true,
+        )]));
+        let predicate = Some(Expr::column("a").gt(Expr::literal(5)));
+        let stats = [
+            r#"{"numRecords":1,"minValues":{"a":1},"maxValues":{"a":1}}"#,
+            r#"{"numRecords":1,"minValues":{"a":2},"maxValues":{"a":2}}"#,
+            r#"{"numRecords":1,"minValues":{"a":3},"maxValues":{"a":3}}"#,
+        ];
+        let batches: Vec<_> = stats
+            .iter()
+            .map(|stats| add_actions_batch(&engine_interface, &[stats]))
+            .collect();
+
+        let stats_cache = Arc::new(StatsCache::with_capacity(2));
+        let filter = DataSkippingFilterBuilder::new(&table_schema, &predicate)
+            .with_stats_cache(Some(stats_cache.clone()))
+            .build(&engine_interface)
+            .unwrap();
+        for batch in &batches {
+            assert_eq!(filter.apply_cached(batch.as_ref()).unwrap(), vec![false]);
+        }
+        // the stats of the first batch were evicted
+        assert_eq!(stats_cache.len(), 2);
+        let cached_stats: Vec<_> = stats_cache
+            .entries()
+            .order
+            .iter()
+            .map(|(key, _)| key.stats.clone())
+            .collect();
+        assert_eq!(
+            cached_stats,
+            vec![
+                vec![Some(stats[1].to_string())],
+                vec![Some(stats[2].to_string())]
+            ]
+        );
+
+        // a cache without capacity holds nothing
+        let stats_cache = Arc::new(StatsCache::with_capacity(0));
+        let filter = DataSkippingFilterBuilder::new(&table_schema, &predicate)
+            .with_stats_cache(Some(stats_cache.clone()))
+            .build(&engine_interface)
+            .unwrap();
+        assert_eq!(
+            filter.apply_cached(batches[0].as_ref()).unwrap(),
+            vec![false]
+        );
+        assert!(stats_cache.is_empty());
+    }
+}
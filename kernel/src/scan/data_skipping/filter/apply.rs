@@ -0,0 +1,1472 @@
+//! Applying a data skipping filter to batches of actions, in the shapes engines need
+
+use std::ops::ControlFlow;
+use std::sync::Arc;
+use std::time::Instant;
+
+#[cfg(feature = "arrow-expression")]
+use arrow_array::{cast::AsArray, BooleanArray, RecordBatch};
+use tracing::debug;
+
+use super::{DataSkippingFilter, DataSkippingResult};
+use crate::actions::visitors::SelectionVectorVisitor;
+use crate::actions::{get_log_schema, ADD_NAME};
+#[cfg(feature = "arrow-expression")]
+use crate::client::arrow_expression::evaluate_expression;
+use crate::engine_data::GetData;
+use crate::error::{DeltaResult, Error};
+#[cfg(feature = "arrow-expression")]
+use crate::expressions::Expression as Expr;
+use crate::scan::data_skipping::cache::{BatchKey, CachedStats, StringColumnVisitor};
+use crate::scan::data_skipping::{MissingStatsPolicy, SkipDecision};
+use crate::scan::selection::SelectionBitmap;
+use crate::schema::{DataType, StructField, StructType};
+use crate::{DataVisitor, EngineData};
+
+impl DataSkippingFilter {
+    /// Apply the DataSkippingFilter to an EngineData batch of actions. Returns a selection vector
+    /// which can be applied to the actions to find those that passed data skipping.
+    pub fn apply(&self, actions: &dyn EngineData) -> DeltaResult<Vec<bool>> {
+        Ok(self.apply_with_stats(actions)?.selection_vector)
+    }
+
+    /// Like [`Self::apply`], but also returns the stats parsed from the actions, so that callers
+    /// (e.g. for cost estimation) don't have to parse them again. The stats have one row per
+    /// action, and follow the filter's [stats schema](Self::stats_schema). Filters that only parse
+    /// the stats their predicate needs, like those of a [`ScanFilter`](crate::scan::ScanFilter),
+    /// only include `numRecords` if the predicate references it. The result also says whether the
+    /// filter skipped the whole batch (see [`DataSkippingResult`]).
+    pub fn apply_with_stats(&self, actions: &dyn EngineData) -> DeltaResult<DataSkippingResult> {
+        let start = self.start_observing();
+        let (parsed_stats, presence) = self.parse_stats(actions)?;
+        let selection_vector = self.select(parsed_stats.as_ref(), &presence)?;
+        self.observe(start, &selection_vector);
+        Ok(DataSkippingResult {
+            skipped_whole_batch: selection_vector.count_selected() == 0,
+            selection_vector: selection_vector.into(),
+            parsed_stats,
+        })
+    }
+
+    /// Like [`Self::apply`], but for engines that already parsed the stats of a batch of actions
+    /// (e.g. while reading the log), which saves selecting and parsing them again. The parsed
+    /// stats have one row per action, and must have the columns of the filter's
+    /// [stats schema](Self::stats_schema) with the same types; other columns are ignored, so stats
+    /// parsed once with a wider schema can be passed to several filters. Like
+    /// [`Self::apply_mask`], this always evaluates the predicate, even if no action has stats.
+    ///
+    /// Returns an error if a column of the stats schema is missing or has another type. Only
+    /// arrow engine data can be checked up front; for other engine data, the engine's evaluator
+    /// reports missing columns.
+    pub fn apply_parsed(&self, parsed_stats: &dyn EngineData) -> DeltaResult<Vec<bool>> {
+        let start = self.start_observing();
+        check_parsed_stats_schema(parsed_stats, &self.stats_schema)?;
+        let selection_vector = self.evaluate_stats(parsed_stats)?;
+        self.observe(start, &selection_vector);
+        Ok(selection_vector.into())
+    }
+
+    /// Like [`Self::apply`], but returns what the stats say about each action instead of folding
+    /// [`SkipDecision::Unknown`] into keep (or skip, see [`MissingStatsPolicy`]), e.g. so that
+    /// engines can read the files that definitely match first. The missing stats policy of the
+    /// filter doesn't affect the result.
+    pub fn apply_tristate(&self, actions: &dyn EngineData) -> DeltaResult<Vec<SkipDecision>> {
+        let (parsed_stats, _) = self.parse_stats(actions)?;
+        let predicate = self.evaluate_predicate(parsed_stats.as_ref())?;
+        let mut visitor = SkipDecisionVisitor::default();
+        let schema = StructType::new(vec![StructField::new("predicate", DataType::BOOLEAN, true)]);
+        predicate.extract(Arc::new(schema), &mut visitor)?;
+        Ok(visitor.decisions)
+    }
+
+    /// Estimates the number of rows of the files of a batch of actions that survive data skipping,
+    /// by summing the `numRecords` stat of the files the filter keeps. Kept files without a
+    /// `numRecords` stat count as empty, so the estimate is a lower bound if some files have no
+    /// stats. Returns an error if the filter doesn't parse `numRecords`, which is the case for
+    /// the filter of a [`ScanFilter`](crate::scan::ScanFilter) that keeps files without records
+    /// (see [`ScanFilterBuilder::with_skip_empty_files`]) and whose predicate doesn't reference it.
+    ///
+    /// [`ScanFilterBuilder::with_skip_empty_files`]:
+    ///     crate::scan::ScanFilterBuilder::with_skip_empty_files
+    pub fn estimated_surviving_rows(&self, actions: &dyn EngineData) -> DeltaResult<u64> {
+        let num_records = &self.stats_column_names.num_records;
+        let Some(field) = self.stats_schema.field(num_records) else {
+            return Err(Error::generic(format!(
+                "Data skipping filter doesn't parse the {num_records} stat"
+            )));
+        };
+        let DataSkippingResult {
+            selection_vector,
+            parsed_stats,
+            ..
+        } = self.apply_with_stats(actions)?;
+        let mut visitor = SurvivingRowsVisitor {
+            selection_vector: &selection_vector,
+            offset: 0,
+            rows: 0,
+        };
+        let schema = StructType::new(vec![field.clone()]);
+        parsed_stats.extract(Arc::new(schema), &mut visitor)?;
+        Ok(visitor.rows)
+    }
+
+    /// Like [`Self::apply`], but evaluates the filter on chunks of (at most) `chunk_size` actions
+    /// at a time, and passes the selection vector of each chunk to `on_chunk`. The callback can
+    /// stop the evaluation early by returning [`ControlFlow::Break`], e.g. once a `LIMIT` scan has
+    /// found enough files. Returns the selection vector of the actions evaluated until then, which
+    /// is shorter than the batch if the evaluation stopped early.
+    ///
+    /// Requires the engine's data to support [`EngineData::slice`].
+    pub fn apply_chunked(
+        &self,
+        actions: &dyn EngineData,
+        chunk_size: usize,
+        mut on_chunk: impl FnMut(&[bool]) -> ControlFlow<()>,
+    ) -> DeltaResult<Vec<bool>> {
+        let chunk_size = chunk_size.max(1);
+        let mut selection_vector = Vec::with_capacity(actions.length());
+        for offset in (0..actions.length()).step_by(chunk_size) {
+            let length = chunk_size.min(actions.length() - offset);
+            let chunk = self.apply(actions.slice(offset, length)?.as_ref())?;
+            selection_vector.extend_from_slice(&chunk);
+            if on_chunk(&chunk).is_break() {
+                break;
+            }
+        }
+        Ok(selection_vector)
+    }
+
+    /// Like [`Self::apply`], but returns the selection vector as a [`SelectionBitmap`], which
+    /// takes an eighth of the memory of a `Vec<bool>` for large batches of actions.
+    pub fn apply_bitmap(&self, actions: &dyn EngineData) -> DeltaResult<SelectionBitmap> {
+        let start = self.start_observing();
+        let (parsed_stats, presence) = self.parse_stats(actions)?;
+        let selection_vector = self.select(parsed_stats.as_ref(), &presence)?;
+        self.observe(start, &selection_vector);
+        Ok(selection_vector)
+    }
+
+    /// Like [`Self::apply`], but reuses the stats the filter's [`StatsCache`] (if any) has parsed
+    /// with the filter's stats schema for a batch with the same paths and stats json of its Add
+    /// actions (see [`BatchKey`]), or else parses the stats and adds them to the cache. Stats that
+    /// are read from `stats_parsed` need no parsing, so they aren't cached.
+    pub(crate) fn apply_cached(&self, actions: &dyn EngineData) -> DeltaResult<Vec<bool>> {
+        let Some(cache) = self.stats_cache.as_ref().filter(|_| !self.stats_parsed) else {
+            return self.apply(actions);
+        };
+        let start = self.start_observing();
+        let stats = self.select_stats(actions)?;
+        let mut paths = StringColumnVisitor::default();
+        actions.extract(get_log_schema().project(&[ADD_NAME])?, &mut paths)?;
+        let mut stats_json = StringColumnVisitor::default();
+        let schema = StructType::new(vec![StructField::new("output", DataType::STRING, true)]);
+        stats.extract(Arc::new(schema), &mut stats_json)?;
+        let key = BatchKey {
+            paths: paths.values,
+            stats: stats_json.values,
+        };
+        let cached = match cache.get(&key, &self.stats_schema) {
+            Some(cached) => cached,
+            None => {
+                let (parsed_stats, presence) = self.parse_selected_stats(stats)?;
+                let cached = CachedStats {
+                    schema: self.stats_schema.clone(),
+                    parsed_stats: parsed_stats.into(),
+                    presence,
+                };
+                cache.insert(key, cached.clone());
+                cached
+            }
+        };
+        let selection_vector = self.select(cached.parsed_stats.as_ref(), &cached.presence)?;
+        self.observe(start, &selection_vector);
+        Ok(selection_vector.into())
+    }
+
+    /// Returns when the filter started to apply to a batch of actions, if an observer wants to know
+    /// how long that took, so that filters without an observer don't read the clock.
+    fn start_observing(&self) -> Option<Instant> {
+        self.observer.as_ref().map(|_| Instant::now())
+    }
+
+    /// Reports the selection vector of a batch of actions that the filter started to apply to at
+    /// `start` to the observer, if any.
+    fn observe(&self, start: Option<Instant>, selection_vector: &SelectionBitmap) {
+        if let (Some(observer), Some(start)) = (&self.observer, start) {
+            let scanned = selection_vector.len();
+            let skipped = scanned - selection_vector.count_selected();
+            observer.on_batch_applied(scanned, skipped, start.elapsed());
+        }
+    }
+
+    /// Retrieves and parses the stats of a batch of actions.
+    fn parse_stats(
+        &self,
+        actions: &dyn EngineData,
+    ) -> DeltaResult<(Box<dyn EngineData>, StatsPresenceVisitor)> {
+        let stats = self.select_stats(actions)?;
+        self.parse_selected_stats(stats)
+    }
+
+    /// Retrieves the stats of a batch of actions, as json strings or as parsed structs.
+    fn select_stats(&self, actions: &dyn EngineData) -> DeltaResult<Box<dyn EngineData>> {
+        let stats = self.select_stats_evaluator.evaluate(actions)?;
+        check_row_count("stats selector", stats.as_ref(), actions.length())?;
+        Ok(stats)
+    }
+
+    /// Parses the stats retrieved by [`Self::select_stats`].
+    fn parse_selected_stats(
+        &self,
+        stats: Box<dyn EngineData>,
+    ) -> DeltaResult<(Box<dyn EngineData>, StatsPresenceVisitor)> {
+        if self.stats_parsed {
+            // the stats are already parsed, so evaluate the predicate like for files with stats
+            let presence = StatsPresenceVisitor {
+                row_count: stats.length(),
+                any_stats: true,
+            };
+            return Ok((stats, presence));
+        }
+        let mut presence_visitor = StatsPresenceVisitor::default();
+        let schema = StructType::new(vec![StructField::new("output", DataType::STRING, true)]);
+        stats
+            .as_ref()
+            .extract(Arc::new(schema), &mut presence_visitor)?;
+        let parsed_stats = self.parse_json(stats)?;
+        Ok((parsed_stats, presence_visitor))
+    }
+
+    /// Computes the selection vector of a batch of actions from their parsed stats.
+    fn select(
+        &self,
+        parsed_stats: &dyn EngineData,
+        presence: &StatsPresenceVisitor,
+    ) -> DeltaResult<SelectionBitmap> {
+        // Files without stats can't be decided, so when no file in the batch has stats (e.g.
+        // because the writer didn't collect them), keep (or skip) them all according to the
+        // missing stats policy without evaluating the predicate.
+        let selection_vector = if presence.any_stats {
+            self.evaluate_stats(parsed_stats)?
+        } else {
+            let keep = self.missing_stats_policy == MissingStatsPolicy::Keep;
+            SelectionBitmap::repeat(keep, presence.row_count)
+        };
+
+        debug!(
+            "number of actions before/after data skipping: {} / {}",
+            selection_vector.len(),
+            selection_vector.count_selected()
+        );
+        Ok(selection_vector)
+    }
+
+    /// Apply the DataSkippingFilter to several batches of actions in parallel. Returns one
+    /// selection vector per batch, in the same order as `batches`, or the first error encountered.
+    pub fn apply_many(&self, batches: &[&dyn EngineData]) -> DeltaResult<Vec<Vec<bool>>> {
+        let parallelism = std::thread::available_parallelism().map_or(1, |n| n.get());
+        let chunk_size = batches.len().div_ceil(parallelism).max(1);
+        std::thread::scope(|scope| {
+            let handles: Vec<_> = batches
+                .chunks(chunk_size)
+                .map(|chunk| {
+                    scope.spawn(move || {
+                        chunk
+                            .iter()
+                            .map(|batch| self.apply(*batch))
+                            .collect::<DeltaResult<Vec<_>>>()
+                    })
+                })
+                .collect();
+            let mut selection_vectors = Vec::with_capacity(batches.len());
+            for handle in handles {
+                let chunk = handle
+                    .join()
+                    .unwrap_or_else(|panic| std::panic::resume_unwind(panic))?;
+                selection_vectors.extend(chunk);
+            }
+            Ok(selection_vectors)
+        })
+    }
+
+    /// Apply the DataSkippingFilter to an EngineData batch of actions, like [`Self::apply`], but
+    /// return the selection vector as the engine's own data: a single non-nullable boolean column,
+    /// which engines can use directly (e.g. as an arrow filter mask) instead of a `Vec<bool>`.
+    /// Unlike `apply`, this always evaluates the predicate, even if no action has stats.
+    pub fn apply_mask(&self, actions: &dyn EngineData) -> DeltaResult<Box<dyn EngineData>> {
+        let stats = self.select_stats(actions)?;
+        let parsed_stats = if self.stats_parsed {
+            stats
+        } else {
+            self.parse_json(stats)?
+        };
+        self.evaluate_mask(parsed_stats.as_ref())
+    }
+
+    /// Like [`Self::apply_mask`], but for engines that already hold the parsed stats of a batch of
+    /// actions as an arrow [`RecordBatch`] with the filter's [stats schema](Self::stats_schema).
+    /// The skipping predicate is evaluated with arrow's compute kernels directly, without parsing
+    /// json or visiting the selection vector. Like `apply_mask`, this always evaluates the
+    /// predicate, even if no action has stats.
+    #[cfg(feature = "arrow-expression")]
+    pub fn apply_arrow(&self, stats: &RecordBatch) -> DeltaResult<BooleanArray> {
+        let predicate = self.skipping_predicate.clone();
+        let filter = match self.missing_stats_policy {
+            MissingStatsPolicy::Keep => predicate.distinct(Expr::literal(false)),
+            MissingStatsPolicy::Skip => predicate.null_safe_eq(Expr::literal(true)),
+        };
+        let selection_vector = evaluate_expression(&filter, stats, Some(&DataType::BOOLEAN))
+            .map_err(|err| Error::skipping_evaluation(&self.skipping_predicate, err))?;
+        selection_vector
+            .as_boolean_opt()
+            .cloned()
+            .ok_or_else(|| Error::unexpected_column_type("selection vector is not boolean"))
+    }
+
+    /// Parses a batch of (unparsed) stats with the filter's stats schema. If that fails, the
+    /// error names the row whose stats are malformed, or the whole batch if the engine's data
+    /// doesn't support [`EngineData::slice`] to locate it.
+    fn parse_json(&self, stats: Box<dyn EngineData>) -> DeltaResult<Box<dyn EngineData>> {
+        let num_rows = stats.length();
+        // json handlers take ownership of the stats, so keep a (cheap) slice to locate errors
+        let retained = stats.slice(0, num_rows).ok();
+        let parsed_stats = self
+            .json_handler
+            .parse_json(stats, self.stats_schema.clone())
+            .map_err(|err| {
+                let rows = match retained.and_then(|stats| self.find_malformed_row(stats.as_ref()))
+                {
+                    Some(row) => row..row + 1,
+                    None => 0..num_rows,
+                };
+                let schema = DataType::from(self.stats_schema.as_ref().clone());
+                Error::stats_parse(schema, rows, err)
+            })?;
+        check_row_count("json handler", parsed_stats.as_ref(), num_rows)?;
+        Ok(parsed_stats)
+    }
+
+    /// Returns the first row of a batch of (unparsed) stats that fails to parse on its own, if the
+    /// batch can be sliced into rows.
+    fn find_malformed_row(&self, stats: &dyn EngineData) -> Option<usize> {
+        for row in 0..stats.length() {
+            let parsed = self
+                .json_handler
+                .parse_json(stats.slice(row, 1).ok()?, self.stats_schema.clone());
+            if parsed.is_err() {
+                return Some(row);
+            }
+        }
+        None
+    }
+
+    /// Evaluates the skipping predicate on a batch of parsed stats, and returns the engine's
+    /// boolean selection vector.
+    fn evaluate_mask(&self, parsed_stats: &dyn EngineData) -> DeltaResult<Box<dyn EngineData>> {
+        let predicate = self.evaluate_predicate(parsed_stats)?;
+        let selection_vector = self
+            .filter_evaluator
+            .evaluate(predicate.as_ref())
+            .map_err(|err| Error::skipping_evaluation(&self.skipping_predicate, err))?;
+        check_row_count(
+            "filter evaluator",
+            selection_vector.as_ref(),
+            predicate.length(),
+        )?;
+        Ok(selection_vector)
+    }
+
+    /// Evaluates the skipping predicate on a batch of parsed stats, and returns a struct with its
+    /// (nullable) result as the `predicate` column.
+    fn evaluate_predicate(
+        &self,
+        parsed_stats: &dyn EngineData,
+    ) -> DeltaResult<Box<dyn EngineData>> {
+        let predicate = self
+            .skipping_evaluator
+            .evaluate(parsed_stats)
+            .map_err(|err| Error::skipping_evaluation(&self.skipping_predicate, err))?;
+        check_row_count(
+            "skipping evaluator",
+            predicate.as_ref(),
+            parsed_stats.length(),
+        )?;
+        Ok(predicate)
+    }
+
+    /// Evaluates the skipping predicate on a batch of parsed stats.
+    fn evaluate_stats(&self, parsed_stats: &dyn EngineData) -> DeltaResult<SelectionBitmap> {
+        let selection_vector = self.evaluate_mask(parsed_stats)?;
+
+        // visit the engine's selection vector to produce a SelectionBitmap
+        let mut visitor = SelectionVectorVisitor::default();
+        let schema = StructType::new(vec![StructField::new("output", DataType::BOOLEAN, false)]);
+        selection_vector
+            .as_ref()
+            .extract(Arc::new(schema), &mut visitor)?;
+        Ok(visitor.selection_vector)
+    }
+}
+
+/// Checks that an engine's evaluator (or json handler) returned one row per row of its input,
+/// since the selection vector must have exactly one entry per action.
+fn check_row_count(name: &str, output: &dyn EngineData, expected: usize) -> DeltaResult<()> {
+    match output.length() {
+        actual if actual == expected => Ok(()),
+        actual => Err(Error::internal_error(format!(
+            "The {name} returned {actual} rows for {expected} input rows"
+        ))),
+    }
+}
+
+/// Checks that the arrow `parsed_stats` passed to [`DataSkippingFilter::apply_parsed`] have the
+/// (possibly nested) columns of `stats_schema`, with the same types. Nullability is not checked,
+/// since engines may parse stats as non-nullable columns.
+#[cfg(any(feature = "default-client", feature = "sync-client"))]
+fn check_parsed_stats_schema(
+    parsed_stats: &dyn EngineData,
+    stats_schema: &StructType,
+) -> DeltaResult<()> {
+    use crate::client::arrow_data::ArrowEngineData;
+    use arrow_schema::{DataType as ArrowDataType, Fields, Schema as ArrowSchema};
+
+    fn check_fields(actual: &Fields, expected: &Fields, path_prefix: &str) -> DeltaResult<()> {
+        for expected in expected {
+            let path = format!("{path_prefix}{}", expected.name());
+            let Some((_, actual)) = actual.find(expected.name()) else {
+                return Err(Error::missing_column(format!(
+                    "Parsed stats have no column {path}"
+                )));
+            };
+            match (actual.data_type(), expected.data_type()) {
+                (ArrowDataType::Struct(actual), ArrowDataType::Struct(expected)) => {
+                    check_fields(actual, expected, &format!("{path}."))?
+                }
+                (actual, expected) if actual == expected => {}
+                (actual, expected) => {
+                    return Err(Error::unexpected_column_type(format!(
+                        "Parsed stats column {path} has type {actual}, expected {expected}"
+                    )))
+                }
+            }
+        }
+        Ok(())
+    }
+
+    let Some(parsed_stats) = parsed_stats.as_any().downcast_ref::<ArrowEngineData>() else {
+        return Ok(()); // other engine data can't be inspected
+    };
+    let expected: ArrowSchema = stats_schema.try_into()?;
+    check_fields(
+        parsed_stats.record_batch().schema().fields(),
+        expected.fields(),
+        "",
+    )
+}
+
+#[cfg(not(any(feature = "default-client", feature = "sync-client")))]
+fn check_parsed_stats_schema(
+    _parsed_stats: &dyn EngineData,
+    _stats_schema: &StructType,
+) -> DeltaResult<()> {
+    Ok(())
+}
+
+/// Checks whether any row of a batch of (unparsed) stats is non-null.
+#[derive(Debug, Default, Clone)]
+pub(crate) struct StatsPresenceVisitor {
+    pub(crate) row_count: usize,
+    pub(crate) any_stats: bool,
+}
+
+impl DataVisitor for StatsPresenceVisitor {
+    fn visit<'a>(&mut self, row_count: usize, getters: &[&'a dyn GetData<'a>]) -> DeltaResult<()> {
+        self.row_count += row_count;
+        if !self.any_stats {
+            for i in 0..row_count {
+                if getters[0].get_str(i, "stats")?.is_some() {
+                    self.any_stats = true;
+                    break;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Sums the number of records of the files that a selection vector keeps, from a batch of parsed
+/// stats with only the `numRecords` column.
+struct SurvivingRowsVisitor<'a> {
+    selection_vector: &'a [bool],
+    offset: usize,
+    rows: u64,
+}
+
+impl DataVisitor for SurvivingRowsVisitor<'_> {
+    fn visit<'a>(&mut self, row_count: usize, getters: &[&'a dyn GetData<'a>]) -> DeltaResult<()> {
+        for i in 0..row_count {
+            if self.selection_vector[self.offset + i] {
+                let num_records = getters[0].get_long(i, "numRecords")?.unwrap_or(0);
+                self.rows += u64::try_from(num_records).unwrap_or(0);
+            }
+        }
+        self.offset += row_count;
+        Ok(())
+    }
+}
+
+/// Collects the [`SkipDecision`] of each file from the output of the skipping evaluator.
+#[derive(Default)]
+struct SkipDecisionVisitor {
+    decisions: Vec<SkipDecision>,
+}
+
+impl DataVisitor for SkipDecisionVisitor {
+    fn visit<'a>(&mut self, row_count: usize, getters: &[&'a dyn GetData<'a>]) -> DeltaResult<()> {
+        for i in 0..row_count {
+            let decision = match getters[0].get_bool(i, "predicate")? {
+                Some(true) => SkipDecision::Keep,
+                Some(false) => SkipDecision::Skip,
+                None => SkipDecision::Unknown,
+            };
+            self.decisions.push(decision);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+    use std::time::Duration;
+
+    use arrow_array::cast::AsArray;
+    use arrow_array::types::Int64Type;
+    use arrow_array::{Int64Array, RecordBatch, StringArray};
+    use arrow_schema::Schema as ArrowSchema;
+
+    use super::*;
+    use crate::actions::REMOVE_NAME;
+    use crate::client::arrow_data::ArrowEngineData;
+    use crate::client::sync::SyncEngineInterface;
+    use crate::scan::data_skipping::{DataSkippingFilterBuilder, SkippingObserver};
+    use crate::scan::test_utils::{
+        add_actions_batch, single_column_schema, string_array_to_engine_data, SkippingFixture,
+    };
+    use crate::schema::SchemaRef;
+    use crate::{EngineInterface, ExpressionEvaluator, JsonHandler};
+
+    #[test]
+    fn test_apply_mask() {
+        let engine_interface = SyncEngineInterface::new();
+        let table_schema = single_column_schema("a", DataType::INTEGER);
+        let batch = add_actions_batch(
+            &engine_interface,
+            &[
+                r#"{"numRecords":1,"minValues":{"a":1},"maxValues":{"a":3}}"#,
+                r#"{"numRecords":1,"minValues":{"a":5},"maxValues":{"a":7}}"#,
+                r#"{"numRecords":1}"#,
+            ],
+        );
+        let no_stats = add_actions_batch(&engine_interface, &[r#"{"numRecords":1}"#; 2]);
+
+        for policy in [MissingStatsPolicy::Keep, MissingStatsPolicy::Skip] {
+            let predicate = Some(Expr::column("a").gt(Expr::literal(4)));
+            let filter = DataSkippingFilterBuilder::new(&table_schema, &predicate)
+                .with_missing_stats_policy(policy)
+                .build(&engine_interface)
+                .unwrap();
+            for batch in [&batch, &no_stats] {
+                let mask: RecordBatch = ArrowEngineData::try_from_engine_data(
+                    filter.apply_mask(batch.as_ref()).unwrap(),
+                )
+                .unwrap()
+                .into();
+                assert_eq!(mask.num_columns(), 1);
+                let mask: Vec<_> = mask.column(0).as_boolean().iter().collect();
+                let expected: Vec<_> = filter
+                    .apply(batch.as_ref())
+                    .unwrap()
+                    .into_iter()
+                    .map(Some)
+                    .collect();
+                assert_eq!(mask, expected, "{policy:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_skipping_errors() {
+        let engine_interface = SyncEngineInterface::new();
+        let table_schema = Arc::new(StructType::new(vec![
+            StructField::new("a", DataType::INTEGER, true),
+            StructField::new("b", DataType::STRING, true),
+        ]));
+        let build = |predicate: Expr| {
+            DataSkippingFilterBuilder::new(&table_schema, &Some(predicate))
+                .build(&engine_interface)
+                .unwrap()
+        };
+        let filter = build(Expr::column("a").lt(Expr::literal(5)));
+
+        // stats that are malformed json, or don't match the types of the columns
+        for stats in [
+            r#"{"numRecords":1,"minValues":{"a":1"#,
+            r#"{"numRecords":1,"minValues":{"a":"one"}}"#,
+        ] {
+            let batch = add_actions_batch(&engine_interface, &[stats]);
+            match filter.apply(batch.as_ref()) {
+                Err(Error::StatsParse { schema, rows, .. }) => {
+                    assert!(schema.contains("minValues: struct<a: int>"), "{schema}");
+                    assert_eq!(rows, 0..1);
+                }
+                other => panic!("expected a stats parse error, got {other:?}"),
+            }
+        }
+
+        // the stats of an int column can't be compared with a string
+        let filter = build(Expr::column("a").lt(Expr::literal("5")));
+        let batch = add_actions_batch(
+            &engine_interface,
+            &[r#"{"numRecords":1,"minValues":{"a":1},"maxValues":{"a":3}}"#],
+        );
+        match filter.apply(batch.as_ref()) {
+            Err(Error::SkippingEvaluation { predicate, .. }) => {
+                assert_eq!(predicate, "Column(minValues.a) < '5'")
+            }
+            other => panic!("expected a skipping evaluation error, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_stats_parse_error_rows() {
+        let valid = r#"{"numRecords":1,"minValues":{"a":1},"maxValues":{"a":3}}"#;
+        let malformed = r#"{"numRecords":1,"minValues":{"a":1"#;
+        let fixture = SkippingFixture::new(
+            single_column_schema("a", DataType::INTEGER),
+            &[valid, valid, malformed, valid],
+        );
+        let filter = fixture
+            .filter(&Expr::column("a").lt(Expr::literal(5)))
+            .unwrap();
+
+        // the error names the action whose stats are malformed
+        let err = filter.apply(fixture.actions.as_ref()).unwrap_err();
+        assert!(
+            matches!(&err, Error::StatsParse { rows, .. } if *rows == (2..3)),
+            "{err:?}"
+        );
+        assert!(err.to_string().contains("stats of actions 2..3"), "{err}");
+    }
+
+    #[test]
+    fn test_apply_chunked() {
+        let stats: Vec<_> = (0..10)
+            .map(|i| {
+                format!(r#"{{"numRecords":1,"minValues":{{"a":{i}}},"maxValues":{{"a":{i}}}}}"#)
+            })
+            .collect();
+        let stats: Vec<_> = stats.iter().map(String::as_str).collect();
+        let fixture = SkippingFixture::new(single_column_schema("a", DataType::INTEGER), &stats);
+        let filter = fixture
+            .filter(&Expr::column("a").gt_eq(Expr::literal(3)))
+            .unwrap();
+        let expected: Vec<_> = (0..10).map(|i| i >= 3).collect();
+
+        // without stopping, every chunk is evaluated
+        for chunk_size in [0, 1, 3, 10, 100] {
+            let mut chunks = 0;
+            let selection_vector = filter
+                .apply_chunked(fixture.actions.as_ref(), chunk_size, |_| {
+                    chunks += 1;
+                    ControlFlow::Continue(())
+                })
+                .unwrap();
+            assert_eq!(selection_vector, expected);
+            assert_eq!(chunks, 10_usize.div_ceil(chunk_size.max(1)));
+        }
+
+        // stop once 3 files are kept, which happens in the third chunk of 2 actions
+        let mut kept = 0;
+        let selection_vector = filter
+            .apply_chunked(fixture.actions.as_ref(), 2, |chunk| {
+                kept += chunk.iter().filter(|keep| **keep).count();
+                match kept >= 3 {
+                    true => ControlFlow::Break(()),
+                    false => ControlFlow::Continue(()),
+                }
+            })
+            .unwrap();
+        assert_eq!(selection_vector, expected[..6]);
+        assert_eq!(kept, 3);
+    }
+
+    #[test]
+    fn test_apply_bitmap() {
+        let stats: Vec<_> = (0..100)
+            .map(|i| {
+                format!(r#"{{"numRecords":1,"minValues":{{"a":{i}}},"maxValues":{{"a":{i}}}}}"#)
+            })
+            .collect();
+        let stats: Vec<_> = stats.iter().map(String::as_str).collect();
+        let fixture = SkippingFixture::new(single_column_schema("a", DataType::INTEGER), &stats);
+        let no_stats = add_actions_batch(&fixture.engine_interface, &[r#"{"numRecords":1}"#; 3]);
+
+        let filter = fixture
+            .filter(&Expr::column("a").lt(Expr::literal(70)))
+            .unwrap();
+        for batch in [&fixture.actions, &no_stats] {
+            let bitmap = filter.apply_bitmap(batch.as_ref()).unwrap();
+            let selection_vector = filter.apply(batch.as_ref()).unwrap();
+            assert_eq!(Vec::from(bitmap.clone()), selection_vector);
+            assert_eq!(bitmap, SelectionBitmap::from(selection_vector.as_slice()));
+        }
+        assert_eq!(
+            filter
+                .apply_bitmap(fixture.actions.as_ref())
+                .unwrap()
+                .count_selected(),
+            70
+        );
+    }
+
+    #[test]
+    fn test_custom_json_handler() {
+        use std::sync::atomic::{AtomicUsize, Ordering as AtomicOrdering};
+
+        /// Counts how often stats are parsed.
+        struct CountingJsonHandler {
+            inner: Arc<dyn JsonHandler>,
+            count: AtomicUsize,
+        }
+
+        impl JsonHandler for CountingJsonHandler {
+            fn parse_json(
+                &self,
+                json_strings: Box<dyn EngineData>,
+                output_schema: SchemaRef,
+            ) -> DeltaResult<Box<dyn EngineData>> {
+                self.count.fetch_add(1, AtomicOrdering::SeqCst);
+                self.inner.parse_json(json_strings, output_schema)
+            }
+
+            fn read_json_files(
+                &self,
+                _files: &[crate::FileMeta],
+                _physical_schema: SchemaRef,
+                _predicate: Option<Expr>,
+            ) -> DeltaResult<crate::FileDataReadResultIterator> {
+                unimplemented!("stats are never read from files")
+            }
+        }
+
+        let fixture = SkippingFixture::new(
+            single_column_schema("a", DataType::INTEGER),
+            &[
+                r#"{"numRecords":1,"minValues":{"a":1},"maxValues":{"a":3}}"#,
+                r#"{"numRecords":1,"minValues":{"a":5},"maxValues":{"a":7}}"#,
+            ],
+        );
+        let json_handler = Arc::new(CountingJsonHandler {
+            inner: fixture.engine_interface.get_json_handler(),
+            count: AtomicUsize::new(0),
+        });
+        let predicate = Some(Expr::column("a").lt(Expr::literal(5)));
+        let filter = DataSkippingFilterBuilder::new(&fixture.table_schema, &predicate)
+            .with_json_handler(Some(json_handler.clone()))
+            .build(&fixture.engine_interface)
+            .unwrap();
+
+        for i in 1..=3 {
+            assert_eq!(
+                filter.apply(fixture.actions.as_ref()).unwrap(),
+                vec![true, false]
+            );
+            assert_eq!(json_handler.count.load(AtomicOrdering::SeqCst), i);
+        }
+    }
+
+    #[test]
+    fn test_stats_parsed() {
+        /// Fails every attempt to parse stats.
+        struct FailingJsonHandler;
+
+        impl JsonHandler for FailingJsonHandler {
+            fn parse_json(
+                &self,
+                _json_strings: Box<dyn EngineData>,
+                _output_schema: SchemaRef,
+            ) -> DeltaResult<Box<dyn EngineData>> {
+                Err(Error::generic("stats must not be parsed"))
+            }
+
+            fn read_json_files(
+                &self,
+                _files: &[crate::FileMeta],
+                _physical_schema: SchemaRef,
+                _predicate: Option<Expr>,
+            ) -> DeltaResult<crate::FileDataReadResultIterator> {
+                unimplemented!("stats are never read from files")
+            }
+        }
+
+        let engine_interface = SyncEngineInterface::new();
+        let table_schema = Arc::new(StructType::new(vec![
+            StructField::new("a", DataType::INTEGER, true),
+            StructField::new("b", DataType::INTEGER, true),
+        ]));
+        let column_stats = |fields: Vec<(&str, DataType)>| {
+            let fields = fields
+                .into_iter()
+                .map(|(name, data_type)| StructField::new(name, data_type, true))
+                .collect();
+            StructType::new(fields)
+        };
+        // the writer stored the max of b as a long, so it can't be used to skip
+        let stats_parsed = StructType::new(vec![
+            StructField::new("numRecords", DataType::LONG, true),
+            StructField::new(
+                "nullCount",
+                column_stats(vec![("a", DataType::LONG), ("b", DataType::LONG)]),
+                true,
+            ),
+            StructField::new(
+                "minValues",
+                column_stats(vec![("a", DataType::INTEGER), ("b", DataType::INTEGER)]),
+                true,
+            ),
+            StructField::new(
+                "maxValues",
+                column_stats(vec![("a", DataType::INTEGER), ("b", DataType::LONG)]),
+                true,
+            ),
+        ]);
+        let add_schema = StructType::new(vec![
+            StructField::new("path", DataType::STRING, true),
+            StructField::new("stats", DataType::STRING, true),
+            StructField::new("stats_parsed", stats_parsed, true),
+        ]);
+        let actions_schema = StructType::new(vec![StructField::new("add", add_schema, true)]);
+        let json_strings: StringArray = vec![
+            r#"{"add":{"path":"a","stats_parsed":{"numRecords":1,"minValues":{"a":1,"b":1},"maxValues":{"a":3,"b":1}}}}"#,
+            r#"{"add":{"path":"b","stats_parsed":{"numRecords":1,"minValues":{"a":5,"b":200},"maxValues":{"a":7,"b":300}}}}"#,
+            r#"{"add":{"path":"c"}}"#,
+        ]
+        .into();
+        let actions = engine_interface
+            .get_json_handler()
+            .parse_json(
+                string_array_to_engine_data(json_strings),
+                Arc::new(actions_schema.clone()),
+            )
+            .unwrap();
+
+        let build = |predicate: Expr| {
+            DataSkippingFilterBuilder::new(&table_schema, &Some(predicate))
+                .with_actions_schema(Some(&actions_schema))
+                .with_json_handler(Some(Arc::new(FailingJsonHandler)))
+                .build(&engine_interface)
+                .unwrap()
+        };
+        let filter = build(Expr::column("a").lt(Expr::literal(5)));
+        assert_eq!(
+            filter.apply(actions.as_ref()).unwrap(),
+            vec![true, false, true]
+        );
+        let result = filter.apply_with_stats(actions.as_ref()).unwrap();
+        assert_eq!(result.parsed_stats.length(), 3);
+
+        let filter = build(Expr::column("b").gt(Expr::literal(100)));
+        assert_eq!(
+            filter.apply(actions.as_ref()).unwrap(),
+            vec![true, true, true]
+        );
+        let filter = build(Expr::column("b").lt(Expr::literal(100)));
+        assert_eq!(
+            filter.apply(actions.as_ref()).unwrap(),
+            vec![true, false, true]
+        );
+
+        // without stats_parsed, the filter parses the json stats
+        let filter = DataSkippingFilterBuilder::new(
+            &table_schema,
+            &Some(Expr::column("a").lt(Expr::literal(5))),
+        )
+        .with_json_handler(Some(Arc::new(FailingJsonHandler)))
+        .build(&engine_interface)
+        .unwrap();
+        assert!(filter.apply(actions.as_ref()).is_err());
+    }
+
+    /// Records the callbacks of data skipping.
+    #[derive(Default)]
+    struct RecordingObserver {
+        stats_schemas: Mutex<Vec<SchemaRef>>,
+        batches: Mutex<Vec<(usize, usize)>>,
+    }
+
+    impl SkippingObserver for RecordingObserver {
+        fn on_filter_built(&self, stats_schema: &SchemaRef) {
+            self.stats_schemas
+                .lock()
+                .unwrap()
+                .push(stats_schema.clone());
+        }
+
+        fn on_batch_applied(&self, scanned: usize, skipped: usize, _duration: Duration) {
+            self.batches.lock().unwrap().push((scanned, skipped));
+        }
+    }
+
+    #[test]
+    fn test_skipping_observer() {
+        let fixture = SkippingFixture::new(
+            single_column_schema("a", DataType::INTEGER),
+            &[
+                r#"{"numRecords":1,"minValues":{"a":1},"maxValues":{"a":3}}"#,
+                r#"{"numRecords":1,"minValues":{"a":5},"maxValues":{"a":7}}"#,
+                r#"{"numRecords":1,"minValues":{"a":6},"maxValues":{"a":9}}"#,
+            ],
+        );
+        let observer = Arc::new(RecordingObserver::default());
+        let predicate = Expr::column("a").lt(Expr::literal(5));
+        let filter =
+            DataSkippingFilterBuilder::new(&fixture.table_schema, &Some(predicate.clone()))
+                .with_observer(Some(observer.clone()))
+                .build(&fixture.engine_interface)
+                .unwrap();
+        assert_eq!(
+            *observer.stats_schemas.lock().unwrap(),
+            vec![filter.stats_schema().clone()]
+        );
+        assert!(observer.batches.lock().unwrap().is_empty());
+
+        let no_stats = add_actions_batch(&fixture.engine_interface, &[r#"{"numRecords":1}"#]);
+        filter.apply(fixture.actions.as_ref()).unwrap();
+        filter.apply_bitmap(no_stats.as_ref()).unwrap();
+        assert_eq!(*observer.batches.lock().unwrap(), vec![(3, 2), (1, 0)]);
+
+        // filters without an observer report nothing
+        fixture.apply(&predicate);
+        assert_eq!(observer.stats_schemas.lock().unwrap().len(), 1);
+        assert_eq!(observer.batches.lock().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_apply_many() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<DataSkippingFilter>();
+
+        let engine_interface = SyncEngineInterface::new();
+        let table_schema = single_column_schema("a", DataType::INTEGER);
+        let predicate = Expr::column("a").lt(Expr::literal(5));
+        let filter = DataSkippingFilterBuilder::new(&table_schema, &Some(predicate))
+            .build(&engine_interface)
+            .unwrap();
+
+        let batches: Vec<_> = (0..16)
+            .map(|i| {
+                let stats = |min: i32| {
+                    format!(
+                        r#"{{"numRecords":1,"minValues":{{"a":{min}}},"maxValues":{{"a":{}}}}}"#,
+                        min + 1
+                    )
+                };
+                add_actions_batch(
+                    &engine_interface,
+                    &[&stats(i), &stats(i + 4), r#"{"numRecords":1}"#],
+                )
+            })
+            .collect();
+        let expected: Vec<_> = (0..16).map(|i| vec![i < 5, i + 4 < 5, true]).collect();
+
+        let batch_refs: Vec<_> = batches.iter().map(|batch| batch.as_ref()).collect();
+        assert_eq!(filter.apply_many(&batch_refs).unwrap(), expected);
+        assert!(filter.apply_many(&[]).unwrap().is_empty());
+
+        // the same filter can also be shared by threads of the engine
+        std::thread::scope(|scope| {
+            let handles: Vec<_> = batches
+                .iter()
+                .map(|batch| scope.spawn(|| filter.apply(batch.as_ref()).unwrap()))
+                .collect();
+            let selection_vectors: Vec<_> =
+                handles.into_iter().map(|h| h.join().unwrap()).collect();
+            assert_eq!(selection_vectors, expected);
+        });
+    }
+
+    #[test]
+    #[allow(clippy::arc_with_non_send_sync)]
+    fn test_missing_stats_short_circuit() {
+        use std::sync::atomic::{AtomicUsize, Ordering as AtomicOrdering};
+
+        /// Counts how often the wrapped evaluator is invoked.
+        struct CountingEvaluator {
+            inner: Arc<dyn ExpressionEvaluator>,
+            count: Arc<AtomicUsize>,
+        }
+
+        impl ExpressionEvaluator for CountingEvaluator {
+            fn evaluate(&self, batch: &dyn EngineData) -> DeltaResult<Box<dyn EngineData>> {
+                self.count.fetch_add(1, AtomicOrdering::SeqCst);
+                self.inner.evaluate(batch)
+            }
+        }
+
+        let engine_interface = SyncEngineInterface::new();
+        let table_schema = single_column_schema("a", DataType::INTEGER);
+        let predicate = Some(Expr::column("a").lt(Expr::literal(5)));
+        let mut filter = DataSkippingFilterBuilder::new(&table_schema, &predicate)
+            .build(&engine_interface)
+            .unwrap();
+        let count = Arc::new(AtomicUsize::new(0));
+        filter.skipping_evaluator = Arc::new(CountingEvaluator {
+            inner: filter.skipping_evaluator.clone(),
+            count: count.clone(),
+        });
+        filter.filter_evaluator = Arc::new(CountingEvaluator {
+            inner: filter.filter_evaluator.clone(),
+            count: count.clone(),
+        });
+
+        let json_strings: StringArray = vec![
+            r#"{"add":{"path":"part-00000.parquet","partitionValues":{},"size":1024,"modificationTime":1677811178336,"dataChange":true}}"#,
+            r#"{"add":{"path":"part-00001.parquet","partitionValues":{},"size":1024,"modificationTime":1677811178336,"dataChange":true,"stats":null}}"#,
+            r#"{"remove":{"path":"part-00002.parquet","deletionTimestamp":1677811178336,"dataChange":true}}"#,
+        ]
+        .into();
+        let batch = engine_interface
+            .get_json_handler()
+            .parse_json(
+                string_array_to_engine_data(json_strings),
+                get_log_schema().project(&[ADD_NAME, REMOVE_NAME]).unwrap(),
+            )
+            .unwrap();
+        assert_eq!(filter.apply(batch.as_ref()).unwrap(), vec![true; 3]);
+        assert_eq!(count.load(AtomicOrdering::SeqCst), 0);
+
+        // A single file with stats means the batch has to be evaluated.
+        let batch = add_actions_batch(
+            &engine_interface,
+            &[
+                r#"{"numRecords":1,"minValues":{"a":10},"maxValues":{"a":20}}"#,
+                r#"{"numRecords":1,"minValues":{"a":1},"maxValues":{"a":2}}"#,
+            ],
+        );
+        assert_eq!(filter.apply(batch.as_ref()).unwrap(), vec![false, true]);
+        assert_eq!(count.load(AtomicOrdering::SeqCst), 2);
+    }
+
+    #[test]
+    fn test_wrong_row_count() {
+        /// Drops the last row of the wrapped evaluator's output, like a buggy engine might.
+        struct TruncatingEvaluator(Arc<dyn ExpressionEvaluator>);
+
+        impl ExpressionEvaluator for TruncatingEvaluator {
+            fn evaluate(&self, batch: &dyn EngineData) -> DeltaResult<Box<dyn EngineData>> {
+                let output = self.0.evaluate(batch)?;
+                output.slice(0, output.length() - 1)
+            }
+        }
+
+        let table_schema = single_column_schema("a", DataType::INTEGER);
+        let predicate = Some(Expr::column("a").lt(Expr::literal(5)));
+        let fixture = SkippingFixture::new(
+            table_schema,
+            &[
+                r#"{"numRecords":1,"minValues":{"a":10},"maxValues":{"a":20}}"#,
+                r#"{"numRecords":1,"minValues":{"a":1},"maxValues":{"a":2}}"#,
+            ],
+        );
+        type Truncate = fn(&mut DataSkippingFilter);
+        let cases: [(Truncate, &str); 3] = [
+            (
+                |filter| {
+                    let inner = filter.select_stats_evaluator.clone();
+                    filter.select_stats_evaluator = Arc::new(TruncatingEvaluator(inner));
+                },
+                "stats selector",
+            ),
+            (
+                |filter| {
+                    let inner = filter.skipping_evaluator.clone();
+                    filter.skipping_evaluator = Arc::new(TruncatingEvaluator(inner));
+                },
+                "skipping evaluator",
+            ),
+            (
+                |filter| {
+                    let inner = filter.filter_evaluator.clone();
+                    filter.filter_evaluator = Arc::new(TruncatingEvaluator(inner));
+                },
+                "filter evaluator",
+            ),
+        ];
+        for (truncate, name) in cases {
+            let mut filter = DataSkippingFilterBuilder::new(&fixture.table_schema, &predicate)
+                .build(&fixture.engine_interface)
+                .unwrap();
+            truncate(&mut filter);
+            match filter.apply(fixture.actions.as_ref()) {
+                Err(Error::InternalError(msg)) => {
+                    assert_eq!(msg, format!("The {name} returned 1 rows for 2 input rows"))
+                }
+                other => panic!("expected an internal error, got {other:?}"),
+            }
+            assert!(filter.apply_mask(fixture.actions.as_ref()).is_err());
+        }
+    }
+
+    #[test]
+    fn test_apply_with_stats() {
+        let engine_interface = SyncEngineInterface::new();
+        let table_schema = single_column_schema("a", DataType::INTEGER);
+        let predicate = Some(Expr::column("a").lt(Expr::literal(5)));
+        let filter = DataSkippingFilterBuilder::new(&table_schema, &predicate)
+            .build(&engine_interface)
+            .unwrap();
+
+        // files with and without stats
+        let json_strings: StringArray = vec![
+            r#"{"add":{"path":"part-00000.parquet","partitionValues":{},"size":1024,"modificationTime":1677811178336,"dataChange":true,"stats":"{\"numRecords\":3,\"minValues\":{\"a\":10},\"maxValues\":{\"a\":20}}"}}"#,
+            r#"{"add":{"path":"part-00001.parquet","partitionValues":{},"size":1024,"modificationTime":1677811178336,"dataChange":true}}"#,
+            r#"{"add":{"path":"part-00002.parquet","partitionValues":{},"size":1024,"modificationTime":1677811178336,"dataChange":true,"stats":"{\"numRecords\":7,\"minValues\":{\"a\":1},\"maxValues\":{\"a\":2}}"}}"#,
+        ]
+        .into();
+        let batch = engine_interface
+            .get_json_handler()
+            .parse_json(
+                string_array_to_engine_data(json_strings),
+                get_log_schema().project(&[ADD_NAME]).unwrap(),
+            )
+            .unwrap();
+
+        let result = filter.apply_with_stats(batch.as_ref()).unwrap();
+        assert_eq!(result.selection_vector, vec![false, true, true]);
+        assert_eq!(
+            result.selection_vector,
+            filter.apply(batch.as_ref()).unwrap()
+        );
+        assert!(!result.skipped_whole_batch);
+
+        let stats: RecordBatch = ArrowEngineData::try_from_engine_data(result.parsed_stats)
+            .unwrap()
+            .into();
+        let expected_schema: ArrowSchema = filter.stats_schema.as_ref().try_into().unwrap();
+        assert_eq!(stats.schema().as_ref(), &expected_schema);
+        let num_records = stats
+            .column_by_name("numRecords")
+            .unwrap()
+            .as_primitive::<Int64Type>();
+        assert_eq!(num_records, &Int64Array::from(vec![Some(3), None, Some(7)]));
+    }
+
+    #[test]
+    fn test_estimated_surviving_rows() {
+        let fixture = SkippingFixture::new(
+            single_column_schema("a", DataType::INTEGER),
+            &[
+                r#"{"numRecords":10,"minValues":{"a":1},"maxValues":{"a":4}}"#,
+                r#"{"numRecords":20,"minValues":{"a":5},"maxValues":{"a":9}}"#,
+                r#"{"numRecords":30,"minValues":{"a":2},"maxValues":{"a":6}}"#,
+                r#"{"minValues":{"a":1},"maxValues":{"a":1}}"#,
+            ],
+        );
+        let predicate = Some(Expr::column("a").lt(Expr::literal(5)));
+        let filter = DataSkippingFilterBuilder::new(&fixture.table_schema, &predicate)
+            .build(&fixture.engine_interface)
+            .unwrap();
+        assert_eq!(
+            filter.apply(fixture.actions.as_ref()).unwrap(),
+            vec![true, false, true, true]
+        );
+        // the file without numRecords counts as empty
+        assert_eq!(
+            filter
+                .estimated_surviving_rows(fixture.actions.as_ref())
+                .unwrap(),
+            40
+        );
+
+        // numRecords is only parsed if the predicate references it
+        let filter = DataSkippingFilterBuilder::new(&fixture.table_schema, &predicate)
+            .with_referenced_stats_only(true)
+            .build(&fixture.engine_interface)
+            .unwrap();
+        assert!(filter
+            .estimated_surviving_rows(fixture.actions.as_ref())
+            .is_err());
+    }
+
+    #[test]
+    fn test_apply_arrow() {
+        let table_schema = Arc::new(StructType::new(vec![
+            StructField::new("a", DataType::INTEGER, true),
+            StructField::new("b", DataType::STRING, true),
+        ]));
+        let fixture = SkippingFixture::new(
+            table_schema,
+            &[
+                r#"{"numRecords":2,"nullCount":{"a":0,"b":1},"minValues":{"a":1,"b":"apple"},"maxValues":{"a":4,"b":"apple"}}"#,
+                r#"{"numRecords":2,"nullCount":{"a":0,"b":0},"minValues":{"a":5,"b":"banana"},"maxValues":{"a":9,"b":"cherry"}}"#,
+                r#"{"numRecords":2,"nullCount":{"a":2,"b":0},"minValues":{"b":"date"},"maxValues":{"b":"fig"}}"#,
+                r#"{"numRecords":2}"#,
+            ],
+        );
+        let col = Expr::column;
+        let predicates = [
+            col("a").lt(Expr::literal(5)),
+            col("a")
+                .eq(Expr::literal(5))
+                .or(col("b").gt(Expr::literal("e"))),
+            col("a").is_null(),
+            col("b").is_not_null().and(col("a").gt_eq(Expr::literal(2))),
+            col("a").in_list([Expr::literal(3), Expr::literal(7)]),
+        ];
+        for predicate in predicates {
+            for policy in [MissingStatsPolicy::Keep, MissingStatsPolicy::Skip] {
+                let filter =
+                    DataSkippingFilterBuilder::new(&fixture.table_schema, &Some(predicate.clone()))
+                        .with_missing_stats_policy(policy)
+                        .build(&fixture.engine_interface)
+                        .unwrap();
+                let (parsed_stats, _) = filter.parse_stats(fixture.actions.as_ref()).unwrap();
+                let stats: RecordBatch = ArrowEngineData::try_from_engine_data(parsed_stats)
+                    .unwrap()
+                    .into();
+                let selection_vector: Vec<_> = filter
+                    .apply_arrow(&stats)
+                    .unwrap()
+                    .iter()
+                    .map(|selected| selected.unwrap())
+                    .collect();
+                assert_eq!(
+                    selection_vector,
+                    filter.apply(fixture.actions.as_ref()).unwrap(),
+                    "{predicate} with {policy:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_batch_all_skipped() {
+        let engine_interface = SyncEngineInterface::new();
+        let table_schema = single_column_schema("a", DataType::INTEGER);
+        let predicate = Some(Expr::column("a").lt(Expr::literal(5)));
+        let filter = DataSkippingFilterBuilder::new(&table_schema, &predicate)
+            .build(&engine_interface)
+            .unwrap();
+
+        let all_skipped = add_actions_batch(
+            &engine_interface,
+            &[
+                r#"{"numRecords":1,"minValues":{"a":10},"maxValues":{"a":20}}"#,
+                r#"{"numRecords":1,"minValues":{"a":5},"maxValues":{"a":8}}"#,
+            ],
+        );
+        let result = filter.apply_with_stats(all_skipped.as_ref()).unwrap();
+        assert_eq!(result.selection_vector, vec![false, false]);
+        assert!(result.skipped_whole_batch);
+
+        let partially_skipped = add_actions_batch(
+            &engine_interface,
+            &[
+                r#"{"numRecords":1,"minValues":{"a":10},"maxValues":{"a":20}}"#,
+                r#"{"numRecords":1,"minValues":{"a":1},"maxValues":{"a":8}}"#,
+            ],
+        );
+        let result = filter.apply_with_stats(partially_skipped.as_ref()).unwrap();
+        assert_eq!(result.selection_vector, vec![false, true]);
+        assert!(!result.skipped_whole_batch);
+    }
+
+    #[test]
+    fn test_apply_tristate() {
+        let fixture = SkippingFixture::new(
+            single_column_schema("a", DataType::INTEGER),
+            &[
+                r#"{"numRecords":1,"minValues":{"a":1},"maxValues":{"a":8}}"#,
+                r#"{"numRecords":1,"minValues":{"a":10},"maxValues":{"a":20}}"#,
+                r#"{"numRecords":1}"#,
+            ],
+        );
+        let predicate = Some(Expr::column("a").lt(Expr::literal(5)));
+        for policy in [MissingStatsPolicy::Keep, MissingStatsPolicy::Skip] {
+            let filter = DataSkippingFilterBuilder::new(&fixture.table_schema, &predicate)
+                .with_missing_stats_policy(policy)
+                .build(&fixture.engine_interface)
+                .unwrap();
+            assert_eq!(
+                filter.apply_tristate(fixture.actions.as_ref()).unwrap(),
+                vec![
+                    SkipDecision::Keep,
+                    SkipDecision::Skip,
+                    SkipDecision::Unknown
+                ],
+                "{policy:?}"
+            );
+
+            // the policy decides what happens to the unknown file
+            let keep_unknown = policy == MissingStatsPolicy::Keep;
+            assert_eq!(
+                filter.apply(fixture.actions.as_ref()).unwrap(),
+                vec![true, false, keep_unknown],
+                "{policy:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_apply_parsed() {
+        let engine_interface = SyncEngineInterface::new();
+        let table_schema = Arc::new(StructType::new(vec![
+            StructField::new("a", DataType::INTEGER, true),
+            StructField::new("b", DataType::INTEGER, true),
+        ]));
+        let actions = add_actions_batch(
+            &engine_interface,
+            &[
+                r#"{"numRecords":1,"minValues":{"a":1,"b":1},"maxValues":{"a":8,"b":8}}"#,
+                r#"{"numRecords":1,"minValues":{"a":10,"b":1},"maxValues":{"a":20,"b":8}}"#,
+                r#"{"numRecords":1}"#,
+            ],
+        );
+        let build = |predicate: Expr, table_schema: &SchemaRef| {
+            DataSkippingFilterBuilder::new(table_schema, &Some(predicate))
+                .build(&engine_interface)
+                .unwrap()
+        };
+        let filter = build(Expr::column("a").lt(Expr::literal(5)), &table_schema);
+        let DataSkippingResult {
+            selection_vector,
+            parsed_stats,
+            ..
+        } = filter.apply_with_stats(actions.as_ref()).unwrap();
+        assert_eq!(selection_vector, vec![true, false, true]);
+        assert_eq!(
+            filter.apply_parsed(parsed_stats.as_ref()).unwrap(),
+            filter.apply(actions.as_ref()).unwrap()
+        );
+
+        // stats parsed with a wider schema can be reused
+        let wider = build(
+            Expr::and_from([
+                Expr::column("a").gt(Expr::literal(0)),
+                Expr::column("b").gt(Expr::literal(0)),
+            ]),
+            &table_schema,
+        );
+        let wider_stats = wider
+            .apply_with_stats(actions.as_ref())
+            .unwrap()
+            .parsed_stats;
+        assert_eq!(
+            filter.apply_parsed(wider_stats.as_ref()).unwrap(),
+            selection_vector
+        );
+
+        // stats without the filter's columns, or with other types, are rejected
+        let other = build(Expr::column("b").lt(Expr::literal(5)), &table_schema);
+        let other_stats = other
+            .apply_with_stats(actions.as_ref())
+            .unwrap()
+            .parsed_stats;
+        assert!(matches!(
+            filter.apply_parsed(other_stats.as_ref()),
+            Err(Error::MissingColumn(_))
+        ));
+        let string_schema = single_column_schema("a", DataType::STRING);
+        let strings = build(Expr::column("a").lt(Expr::literal("x")), &string_schema);
+        let string_actions = add_actions_batch(
+            &engine_interface,
+            &[r#"{"numRecords":1,"minValues":{"a":"a"},"maxValues":{"a":"b"}}"#],
+        );
+        let string_stats = strings
+            .apply_with_stats(string_actions.as_ref())
+            .unwrap()
+            .parsed_stats;
+        assert!(matches!(
+            filter.apply_parsed(string_stats.as_ref()),
+            Err(Error::UnexpectedColumnType(_))
+        ));
+    }
+
+    #[test]
+    fn test_missing_stats_policy() {
+        let engine_interface = SyncEngineInterface::new();
+        let table_schema = Arc::new(StructType::new(vec![
+            StructField::new("a", DataType::INTEGER, true),
+            StructField::new("b", DataType::INTEGER, true),
+        ]));
+        let predicate = Some(Expr::column("a").lt(Expr::literal(5)));
+        let add = |stats: Option<&str>| {
+            let mut add = serde_json::json!({
+                "path": "part-00000.parquet",
+                "partitionValues": {},
+                "size": 1024,
+                "modificationTime": 1677811178336_i64,
+                "dataChange": true,
+            });
+            if let Some(stats) = stats {
+                add["stats"] = stats.into();
+            }
+            serde_json::json!({ "add": add }).to_string()
+        };
+        let actions_batch = |actions: Vec<String>| {
+            let json_strings: StringArray = actions.into();
+            engine_interface
+                .get_json_handler()
+                .parse_json(
+                    string_array_to_engine_data(json_strings),
+                    get_log_schema().project(&[ADD_NAME]).unwrap(),
+                )
+                .unwrap()
+        };
+        let mixed = actions_batch(vec![
+            add(Some(
+                r#"{"numRecords":1,"minValues":{"a":10},"maxValues":{"a":20}}"#,
+            )),
+            add(Some(
+                r#"{"numRecords":1,"minValues":{"a":1},"maxValues":{"a":2}}"#,
+            )),
+            // stats for other columns only
+            add(Some(
+                r#"{"numRecords":1,"minValues":{"b":1},"maxValues":{"b":2}}"#,
+            )),
+            // no stats at all
+            add(None),
+        ]);
+        let no_stats = actions_batch(vec![add(None), add(None)]);
+
+        let cases = [
+            (
+                MissingStatsPolicy::Keep,
+                vec![false, true, true, true],
+                vec![true, true],
+            ),
+            (
+                MissingStatsPolicy::Skip,
+                vec![false, true, false, false],
+                vec![false, false],
+            ),
+        ];
+        for (policy, expected_mixed, expected_no_stats) in cases {
+            let filter = DataSkippingFilterBuilder::new(&table_schema, &predicate)
+                .with_missing_stats_policy(policy)
+                .build(&engine_interface)
+                .unwrap();
+            assert_eq!(
+                filter.apply(mixed.as_ref()).unwrap(),
+                expected_mixed,
+                "{policy:?}"
+            );
+            assert_eq!(
+                filter.apply(no_stats.as_ref()).unwrap(),
+                expected_no_stats,
+                "{policy:?}"
+            );
+        }
+    }
+}
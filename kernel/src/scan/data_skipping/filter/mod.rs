@@ -0,0 +1,1207 @@
+//! The filter that skips files of a scan based on the stats of their add actions
+
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use tracing::debug;
+
+use crate::expressions::{fold_constants, Expression as Expr, Scalar, VariadicOperator};
+use crate::scan::parsed_stats::ParsedStats;
+use crate::schema::{DataType, SchemaRef, StructField, StructType};
+use crate::{DeltaResult, EngineData, EngineInterface, ExpressionEvaluator, JsonHandler};
+
+use super::cache::{EvaluatorCache, StatsCache};
+use super::resolve::{
+    adjust_truncated_timestamp_max, resolve_column, resolve_predicate, widen_float_bounds,
+};
+use super::rewrite::{
+    as_data_skipping_predicate, as_null_skipping_predicate, ineligibility_reason,
+};
+use super::{
+    build_stats_schema, referenced_stat_kinds, EligibilityReason, MissingStatsPolicy,
+    SkippingAnalysis, SkippingObserver, StatsColumnNames, STAT_KINDS,
+};
+
+mod apply;
+
+pub(crate) use self::apply::StatsPresenceVisitor;
+
+/// Skips files of a scan based on the stats of their add actions.
+///
+/// A filter is built once per scan and can be applied to any number of batches of actions. Since
+/// evaluators and json handlers are `Send + Sync`, so is the filter, which allows applying it to
+/// several batches concurrently (see [`DataSkippingFilter::apply_many`]).
+///
+/// Engines get the filter of a scan from
+/// [`ScanFilter::data_filter`](crate::scan::ScanFilter::data_filter), e.g. to inspect which stats
+/// it parses.
+pub struct DataSkippingFilter {
+    stats_schema: SchemaRef,
+    skipping_predicate: Expr,
+    referenced_stat_columns: Vec<String>,
+    missing_stats_policy: MissingStatsPolicy,
+    stats_cache: Option<Arc<StatsCache>>,
+    select_stats_evaluator: Arc<dyn ExpressionEvaluator>,
+    skipping_evaluator: Arc<dyn ExpressionEvaluator>,
+    filter_evaluator: Arc<dyn ExpressionEvaluator>,
+    json_handler: Arc<dyn JsonHandler>,
+    stats_column_names: StatsColumnNames,
+    stats_parsed: bool,
+    column_eligibility: Option<Vec<(String, EligibilityReason)>>,
+    observer: Option<Arc<dyn SkippingObserver>>,
+}
+
+/// The result of applying a [`DataSkippingFilter`] to a batch of actions with
+/// [`DataSkippingFilter::apply_with_stats`].
+pub struct DataSkippingResult {
+    /// The selection vector of the actions, which is true for the actions that passed data
+    /// skipping.
+    pub selection_vector: Vec<bool>,
+    /// The stats parsed from the actions, with one row per action, following the filter's
+    /// [stats schema](DataSkippingFilter::stats_schema).
+    pub parsed_stats: Box<dyn EngineData>,
+    /// Whether the filter skipped every action of the batch (which includes an empty batch), so
+    /// that the caller can drop the whole batch without looking at the selection vector.
+    pub skipped_whole_batch: bool,
+}
+
+/// Reports for each column that `predicate` references whether data skipping uses its stats in
+/// `skipping_predicate`, which was rewritten from the `resolved` predicate, and why not otherwise.
+fn column_eligibility(
+    predicate: &Expr,
+    resolved: &Expr,
+    skipping_predicate: &Expr,
+    table_schema: &StructType,
+) -> Vec<(String, EligibilityReason)> {
+    // `minValues.a` etc. are stats of column `a`
+    let stats_columns: HashSet<&str> = skipping_predicate
+        .references()
+        .into_iter()
+        .filter_map(|name| {
+            STAT_KINDS
+                .iter()
+                .find_map(|kind| name.strip_prefix(kind)?.strip_prefix('.'))
+        })
+        .collect();
+    let ineligible = DataSkippingFilter::analyze(resolved).ineligible;
+    let unsupported_reason = |col: &str| {
+        ineligible
+            .iter()
+            .find(|(expr, _)| expr.references().contains(col))
+            .map_or_else(
+                || "not used by the skipping predicate".to_string(),
+                |(_, reason)| reason.clone(),
+            )
+    };
+    let mut columns: Vec<_> = predicate.references().into_iter().collect();
+    columns.sort();
+    columns
+        .into_iter()
+        .map(|col| {
+            let reason = match resolve_column(table_schema, col).map(|field| field.data_type()) {
+                None => EligibilityReason::NotInSchema,
+                Some(DataType::Map(_) | DataType::Array(_)) => EligibilityReason::NoStats,
+                Some(_) if stats_columns.contains(col) => EligibilityReason::Eligible,
+                Some(_) => EligibilityReason::UnsupportedPredicate(unsupported_reason(col)),
+            };
+            (col.to_string(), reason)
+        })
+        .collect()
+}
+
+/// Builder for a [`DataSkippingFilter`].
+///
+/// By default the filter parses every kind of stats for the columns the predicate references. For
+/// tables with many columns, [`Self::with_referenced_stats_only`] can be used to only parse the
+/// kinds of stats the rewritten predicate actually needs (e.g. only `minValues` for `a < 5`).
+pub(crate) struct DataSkippingFilterBuilder<'a> {
+    table_schema: &'a SchemaRef,
+    predicate: &'a Option<Expr>,
+    referenced_stats_only: bool,
+    null_skipping_only: bool,
+    missing_stats_policy: MissingStatsPolicy,
+    stats_cache: Option<Arc<StatsCache>>,
+    evaluator_cache: Option<Arc<EvaluatorCache>>,
+    json_handler: Option<Arc<dyn JsonHandler>>,
+    stats_column_names: StatsColumnNames,
+    actions_schema: Option<&'a StructType>,
+    eligibility_report: bool,
+    observer: Option<Arc<dyn SkippingObserver>>,
+    skip_empty_files: bool,
+    skip_empty_files_without_predicate: bool,
+    float_tolerance: f64,
+}
+
+impl<'a> DataSkippingFilterBuilder<'a> {
+    pub(crate) fn new(table_schema: &'a SchemaRef, predicate: &'a Option<Expr>) -> Self {
+        Self {
+            table_schema,
+            predicate,
+            referenced_stats_only: false,
+            null_skipping_only: false,
+            missing_stats_policy: MissingStatsPolicy::default(),
+            stats_cache: None,
+            evaluator_cache: None,
+            json_handler: None,
+            stats_column_names: StatsColumnNames::default(),
+            actions_schema: None,
+            eligibility_report: false,
+            observer: None,
+            skip_empty_files: false,
+            skip_empty_files_without_predicate: false,
+            float_tolerance: 0.0,
+        }
+    }
+
+    /// Whether to only parse the kinds of stats that are referenced by the rewritten predicate.
+    pub(crate) fn with_referenced_stats_only(mut self, referenced_stats_only: bool) -> Self {
+        self.referenced_stats_only = referenced_stats_only;
+        self
+    }
+
+    /// Whether to only skip files based on their `nullCount` and `numRecords` stats, e.g. for
+    /// queries that only care whether a column is entirely (non-)null in a file. Parts of the
+    /// predicate that need the min/max values are ignored (see [`as_null_skipping_predicate`]), so
+    /// that only those two kinds of stats are parsed.
+    pub(crate) fn with_null_skipping_only(mut self, null_skipping_only: bool) -> Self {
+        self.null_skipping_only = null_skipping_only;
+        self
+    }
+
+    /// What to do with files whose stats can't decide the predicate (see [`MissingStatsPolicy`]).
+    pub(crate) fn with_missing_stats_policy(mut self, policy: MissingStatsPolicy) -> Self {
+        self.missing_stats_policy = policy;
+        self
+    }
+
+    /// The cache [`DataSkippingFilter::apply_cached`] reuses parsed stats from, if any.
+    pub(crate) fn with_stats_cache(mut self, stats_cache: Option<Arc<StatsCache>>) -> Self {
+        self.stats_cache = stats_cache;
+        self
+    }
+
+    /// The cache to get the filter's evaluators from, so that filters for predicates of the same
+    /// shape share their evaluators instead of each creating new ones.
+    pub(crate) fn with_evaluator_cache(
+        mut self,
+        evaluator_cache: Option<Arc<EvaluatorCache>>,
+    ) -> Self {
+        self.evaluator_cache = evaluator_cache;
+        self
+    }
+
+    /// The names under which the actions store their stats, instead of the Delta names.
+    pub(crate) fn with_stats_column_names(mut self, names: StatsColumnNames) -> Self {
+        self.stats_column_names = names;
+        self
+    }
+
+    /// The schema of the batches of actions the filter is applied to. If their add actions have
+    /// a `stats_parsed` struct column (see [`StatsColumnNames::stats_parsed`]), the filter reads
+    /// the stats from it instead of parsing the `stats` json. Stats that `stats_parsed` lacks, or
+    /// stores with another type, are treated as missing.
+    pub(crate) fn with_actions_schema(mut self, actions_schema: Option<&'a StructType>) -> Self {
+        self.actions_schema = actions_schema;
+        self
+    }
+
+    /// The json handler to parse stats with, instead of the engine's own (e.g. one that is more
+    /// lenient with malformed stats).
+    pub(crate) fn with_json_handler(mut self, json_handler: Option<Arc<dyn JsonHandler>>) -> Self {
+        self.json_handler = json_handler;
+        self
+    }
+
+    /// Whether to report for each column the predicate references whether data skipping uses its
+    /// stats, and why not otherwise (see [`DataSkippingFilter::column_eligibility`]). This helps to
+    /// understand why a predicate on some column doesn't skip any files.
+    pub(crate) fn with_eligibility_report(mut self, eligibility_report: bool) -> Self {
+        self.eligibility_report = eligibility_report;
+        self
+    }
+
+    /// The observer to report metrics of the filter to, if any (see [`SkippingObserver`]).
+    pub(crate) fn with_observer(mut self, observer: Option<Arc<dyn SkippingObserver>>) -> Self {
+        self.observer = observer;
+        self
+    }
+
+    /// Whether to skip files without records (`numRecords = 0`, e.g. left behind by compactions)
+    /// whatever the rest of the predicate says. This is always correct, since such files have no
+    /// rows that could match, and cheap, since the filter of an eligible predicate only needs to
+    /// parse the `numRecords` stat in addition. Without an eligible predicate there is no filter
+    /// to add this to (see [`Self::with_skip_empty_files_without_predicate`]).
+    pub(crate) fn with_skip_empty_files(mut self, skip_empty_files: bool) -> Self {
+        self.skip_empty_files = skip_empty_files;
+        self
+    }
+
+    /// Whether to build a filter that skips files without records even if there is no predicate,
+    /// or none that is eligible for data skipping. Such a filter parses the stats of every add
+    /// action just to read their `numRecords`, so this is opt-in.
+    pub(crate) fn with_skip_empty_files_without_predicate(
+        mut self,
+        skip_empty_files_without_predicate: bool,
+    ) -> Self {
+        self.skip_empty_files_without_predicate = skip_empty_files_without_predicate;
+        self
+    }
+
+    /// How much the min/max stats of `FLOAT` and `DOUBLE` columns may differ from the actual
+    /// min/max values, e.g. because writers computed them with rounding. Comparisons against
+    /// these stats are widened by this amount, so a file is only skipped if its stats rule out
+    /// the predicate by more than it. Defaults to zero, which trusts the stats exactly.
+    pub(crate) fn with_float_tolerance(mut self, epsilon: f64) -> Self {
+        self.float_tolerance = epsilon;
+        self
+    }
+
+    /// Builds the data skipping filter. Returns None if there is no predicate, or the predicate
+    /// is ineligible for data skipping, unless files without records are skipped anyway (see
+    /// [`Self::with_skip_empty_files_without_predicate`]). Partition columns have no stats, so
+    /// this includes predicates that only reference partition columns: scans prune files with
+    /// those using a [`PartitionFilter`](crate::scan::partition_pruning::PartitionFilter) instead.
+    ///
+    /// NOTE: None is equivalent to a trivial filter that always returns TRUE (= keeps all files),
+    /// but using an Option lets the engine easily avoid the overhead of applying trivial filters.
+    pub(crate) fn build(self, table_client: &dyn EngineInterface) -> Option<DataSkippingFilter> {
+        let Self {
+            table_schema,
+            predicate,
+            referenced_stats_only,
+            null_skipping_only,
+            missing_stats_policy,
+            stats_cache,
+            evaluator_cache,
+            json_handler,
+            stats_column_names,
+            actions_schema,
+            eligibility_report,
+            observer,
+            skip_empty_files,
+            skip_empty_files_without_predicate,
+            float_tolerance,
+        } = self;
+        // Files without records can be skipped whatever the predicate says, so even without a
+        // predicate that is eligible for data skipping if asked to.
+        let empty_files_predicate = (skip_empty_files || skip_empty_files_without_predicate)
+            .then(|| Expr::column("numRecords").ne(Expr::literal(0_i64)));
+        let prepared = predicate.as_ref().and_then(|original_predicate| {
+            debug!(
+                "Creating a data skipping filter for {:#}",
+                &original_predicate
+            );
+            let (predicate, data_schema) = resolve_predicate(original_predicate, table_schema)?;
+            let skipping_predicate = if null_skipping_only {
+                as_null_skipping_predicate(&predicate)?
+            } else {
+                let skipping_predicate = adjust_truncated_timestamp_max(
+                    as_data_skipping_predicate(&predicate)?,
+                    &data_schema,
+                );
+                if float_tolerance > 0.0 {
+                    widen_float_bounds(skipping_predicate, float_tolerance)
+                } else {
+                    skipping_predicate
+                }
+            };
+            Some((
+                original_predicate,
+                predicate,
+                data_schema,
+                skipping_predicate,
+            ))
+        });
+
+        let (skipping_predicate, data_schema, column_eligibility, stat_kinds) =
+            match (prepared, empty_files_predicate) {
+                (Some((original_predicate, predicate, data_schema, skipping_predicate)), empty) => {
+                    // A NULL record count is unknown, so the rest of the predicate decides such
+                    // files
+                    let skipping_predicate = match empty {
+                        Some(empty) => skipping_predicate.and(empty),
+                        None => skipping_predicate,
+                    };
+                    let column_eligibility = eligibility_report.then(|| {
+                        column_eligibility(
+                            original_predicate,
+                            &predicate,
+                            &skipping_predicate,
+                            table_schema,
+                        )
+                    });
+                    let stat_kinds = if referenced_stats_only || null_skipping_only {
+                        referenced_stat_kinds(&skipping_predicate)
+                    } else {
+                        STAT_KINDS.into_iter().collect()
+                    };
+                    (
+                        skipping_predicate,
+                        data_schema,
+                        column_eligibility,
+                        stat_kinds,
+                    )
+                }
+                (None, Some(empty)) if skip_empty_files_without_predicate => {
+                    debug!("Creating a data skipping filter for files without records");
+                    let stat_kinds = referenced_stat_kinds(&empty);
+                    (empty, StructType::new(vec![]), None, stat_kinds)
+                }
+                (None, _) => return None,
+            };
+        let stats_schema = Arc::new(build_stats_schema(
+            &data_schema,
+            &stat_kinds,
+            &stats_column_names,
+        )?);
+
+        // The predicate is rewritten in terms of the Delta names, which the actions may not use.
+        let skipping_predicate = stats_column_names.rename_references(&skipping_predicate);
+        let min_prefix = format!("{}.", stats_column_names.min_values);
+        let max_prefix = format!("{}.", stats_column_names.max_values);
+        let mut referenced_stat_columns: Vec<_> = skipping_predicate
+            .references()
+            .into_iter()
+            .filter(|name| name.starts_with(&min_prefix) || name.starts_with(&max_prefix))
+            .map(String::from)
+            .collect();
+        referenced_stat_columns.sort();
+
+        let parsed_stats_schema = actions_schema
+            .and_then(|schema| resolve_column(schema, &stats_column_names.stats_parsed))
+            .and_then(|field| match field.data_type() {
+                DataType::Struct(parsed_stats_schema) => Some(parsed_stats_schema.as_ref()),
+                _ => None,
+            });
+        let json_handler = json_handler.unwrap_or_else(|| table_client.get_json_handler());
+        let filter = DataSkippingFilter::new(
+            table_client,
+            stats_schema,
+            skipping_predicate,
+            referenced_stat_columns,
+            missing_stats_policy,
+            stats_cache,
+            evaluator_cache,
+            json_handler,
+            stats_column_names,
+            parsed_stats_schema,
+        );
+        if let Some(observer) = &observer {
+            observer.on_filter_built(&filter.stats_schema);
+        }
+        Some(DataSkippingFilter {
+            column_eligibility,
+            observer,
+            ..filter
+        })
+    }
+}
+
+impl DataSkippingFilter {
+    /// Reports which conjuncts of `predicate` data skipping can use, and why it drops the others
+    /// (e.g. because they compare two columns for equality). Constant sub-expressions are folded
+    /// first, like when building a filter, so the conjuncts are reported in their folded form.
+    ///
+    /// This only looks at the shape of the predicate: a filter built for a table also ignores
+    /// comparisons with columns that the table doesn't have, or with literals that can't be
+    /// converted to the column's type exactly.
+    pub fn analyze(predicate: &Expr) -> SkippingAnalysis {
+        fn conjuncts(expr: Expr) -> Vec<Expr> {
+            match expr {
+                Expr::VariadicOperation {
+                    op: VariadicOperator::And,
+                    exprs,
+                } => exprs.into_iter().flat_map(conjuncts).collect(),
+                expr => vec![expr],
+            }
+        }
+
+        let mut analysis = SkippingAnalysis::default();
+        for conjunct in conjuncts(fold_constants(predicate)) {
+            // a rewrite to an empty conjunction (e.g. of `a + 1 BETWEEN 1 AND 2`) is always true
+            match as_data_skipping_predicate(&conjunct) {
+                Some(Expr::VariadicOperation {
+                    op: VariadicOperator::And,
+                    exprs,
+                }) if exprs.is_empty() => {
+                    let reason = ineligibility_reason(&conjunct);
+                    analysis.ineligible.push((conjunct, reason));
+                }
+                Some(_) => analysis.eligible.push(conjunct),
+                None => {
+                    let reason = ineligibility_reason(&conjunct);
+                    analysis.ineligible.push((conjunct, reason));
+                }
+            }
+        }
+        analysis
+    }
+
+    /// Creates a filter that evaluates the rewritten `skipping_predicate` on stats parsed with
+    /// `stats_schema` from the actions, which store them under `stats_column_names`. Its
+    /// evaluators are taken from `evaluator_cache`, if any. If `parsed_stats_schema` is given, the
+    /// actions hold their stats as a struct of that type, which the filter reads instead of
+    /// parsing json.
+    #[allow(clippy::too_many_arguments)]
+    fn new(
+        table_client: &dyn EngineInterface,
+        stats_schema: SchemaRef,
+        skipping_predicate: Expr,
+        referenced_stat_columns: Vec<String>,
+        missing_stats_policy: MissingStatsPolicy,
+        stats_cache: Option<Arc<StatsCache>>,
+        evaluator_cache: Option<Arc<EvaluatorCache>>,
+        json_handler: Arc<dyn JsonHandler>,
+        stats_column_names: StatsColumnNames,
+        parsed_stats_schema: Option<&StructType>,
+    ) -> Self {
+        lazy_static::lazy_static!(
+            static ref PREDICATE_SCHEMA: DataType = StructType::new(vec![
+                StructField::new("predicate", DataType::BOOLEAN, true),
+            ]).into();
+            static ref FILTER_EXPR: Expr = Expr::column("predicate").distinct(Expr::literal(false));
+            static ref STRICT_FILTER_EXPR: Expr =
+                Expr::column("predicate").null_safe_eq(Expr::literal(true));
+        );
+
+        // Skipping happens in several steps:
+        //
+        // 1. The stats selector fetches the stats column (add.stats by default) from the metadata,
+        //    or projects the stats schema out of the parsed stats (add.stats_parsed by default)
+        //
+        // 2. The predicate (skipping evaluator) produces false for any file whose stats prove we
+        //    can safely skip it. A value of true means the stats say we must keep the file, and
+        //    null means we could not determine whether the file is safe to skip, because its stats
+        //    were missing/null.
+        //
+        // 3. The selection evaluator does DISTINCT(col(predicate), 'false') to produce true
+        //    (= keep) when the predicate is true/null and false (= skip) when the predicate is
+        //    false. With MissingStatsPolicy::Skip, it instead does
+        //    NULL_SAFE_EQUAL(col(predicate), 'true') so that null also produces false (= skip).
+        let expression_handler = table_client.get_expression_handler();
+        let get_evaluator = |schema, expression, output_type| match &evaluator_cache {
+            Some(cache) => {
+                cache.get_evaluator(expression_handler.as_ref(), schema, expression, output_type)
+            }
+            None => expression_handler.get_evaluator(schema, expression, output_type),
+        };
+        let select_stats_evaluator = match parsed_stats_schema {
+            Some(parsed_stats_schema) => get_evaluator(
+                stats_schema.clone(),
+                project_parsed_stats(
+                    &stats_column_names.stats_parsed,
+                    &stats_schema,
+                    parsed_stats_schema,
+                ),
+                stats_schema.as_ref().clone().into(),
+            ),
+            None => get_evaluator(
+                stats_schema.clone(),
+                Expr::column(stats_column_names.stats.clone()),
+                DataType::STRING,
+            ),
+        };
+
+        let skipping_evaluator = get_evaluator(
+            stats_schema.clone(),
+            Expr::struct_expr([skipping_predicate.clone()]),
+            PREDICATE_SCHEMA.clone(),
+        );
+
+        let filter_expr = match missing_stats_policy {
+            MissingStatsPolicy::Keep => FILTER_EXPR.clone(),
+            MissingStatsPolicy::Skip => STRICT_FILTER_EXPR.clone(),
+        };
+        let filter_evaluator = get_evaluator(stats_schema.clone(), filter_expr, DataType::BOOLEAN);
+
+        DataSkippingFilter {
+            stats_schema,
+            skipping_predicate,
+            referenced_stat_columns,
+            missing_stats_policy,
+            stats_cache,
+            select_stats_evaluator,
+            skipping_evaluator,
+            filter_evaluator,
+            json_handler,
+            stats_column_names,
+            stats_parsed: parsed_stats_schema.is_some(),
+            column_eligibility: None,
+            observer: None,
+        }
+    }
+
+    /// The schema the filter parses the `stats` of add actions with. It has (some of) the fields
+    /// `numRecords` (a long), `nullCount` (the referenced columns, with every leaf a long), and
+    /// `minValues` and `maxValues` (the referenced columns), in that order, or the names they were
+    /// overridden with (see [`StatsColumnNames`]).
+    pub fn stats_schema(&self) -> &SchemaRef {
+        &self.stats_schema
+    }
+
+    /// Reads the stats returned by [`Self::apply_with_stats`] into [`ParsedStats`], for typed
+    /// access to the stats of each action by column.
+    pub fn parsed_stats(
+        &self,
+        engine_interface: &dyn EngineInterface,
+        stats: &dyn EngineData,
+    ) -> DeltaResult<ParsedStats> {
+        ParsedStats::try_new(
+            engine_interface,
+            &self.stats_schema,
+            &self.stats_column_names,
+            stats,
+        )
+    }
+
+    /// For each column the predicate references, sorted by name, whether data skipping uses its
+    /// stats and why not otherwise. Only available if the filter was built with
+    /// [`ScanFilterBuilder::with_eligibility_report`].
+    ///
+    /// [`ScanFilterBuilder::with_eligibility_report`]:
+    ///     crate::scan::ScanFilterBuilder::with_eligibility_report
+    pub fn column_eligibility(&self) -> Option<&[(String, EligibilityReason)]> {
+        self.column_eligibility.as_deref()
+    }
+
+    /// The min/max stat columns the rewritten predicate references (e.g. `minValues.a` for `a <
+    /// 5`), sorted by name. A scan can order files by these stats to read them with more locality.
+    pub fn referenced_stat_columns(&self) -> Vec<String> {
+        self.referenced_stat_columns.clone()
+    }
+}
+
+/// Returns the expression that projects `stats_schema` out of the parsed stats in the struct
+/// column `path` of the actions, whose type is `parsed_stats_schema`. Stats that the parsed stats
+/// lack, or store with another type, are null.
+fn project_parsed_stats(
+    path: &str,
+    stats_schema: &StructType,
+    parsed_stats_schema: &StructType,
+) -> Expr {
+    Expr::struct_expr(stats_schema.fields().map(|field| {
+        let path = format!("{path}.{}", field.name());
+        let parsed_type = parsed_stats_schema
+            .field(field.name())
+            .map(|parsed_field| parsed_field.data_type());
+        match (field.data_type(), parsed_type) {
+            (DataType::Struct(child_schema), Some(DataType::Struct(parsed_child_schema))) => {
+                project_parsed_stats(&path, child_schema, parsed_child_schema)
+            }
+            (DataType::Struct(child_schema), _) => {
+                project_parsed_stats(&path, child_schema, &StructType::new(vec![]))
+            }
+            (data_type, Some(parsed_type)) if data_type == parsed_type => Expr::column(path),
+            (data_type, _) => Expr::literal(Scalar::Null(data_type.clone())),
+        }
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::sync::SyncEngineInterface;
+    use crate::scan::test_utils::{add_actions_batch, single_column_schema, SkippingFixture};
+
+    #[test]
+    fn test_stats_schema() {
+        let table_schema = Arc::new(StructType::new(vec![
+            StructField::new("a", DataType::INTEGER, true),
+            StructField::new(
+                "s",
+                StructType::new(vec![
+                    StructField::new("b", DataType::STRING, true),
+                    StructField::new("c", DataType::DATE, true),
+                ]),
+                true,
+            ),
+            StructField::new("unused", DataType::LONG, true),
+        ]));
+        let predicate = Some(
+            Expr::column("a")
+                .lt(Expr::literal(5))
+                .and(Expr::column("s.b").eq(Expr::literal("x"))),
+        );
+        let filter = DataSkippingFilterBuilder::new(&table_schema, &predicate)
+            .build(&SyncEngineInterface::new())
+            .unwrap();
+
+        let values = StructType::new(vec![
+            StructField::new("a", DataType::INTEGER, true),
+            StructField::new(
+                "s",
+                StructType::new(vec![StructField::new("b", DataType::STRING, true)]),
+                true,
+            ),
+        ]);
+        let null_counts = StructType::new(vec![
+            StructField::new("a", DataType::LONG, true),
+            StructField::new(
+                "s",
+                StructType::new(vec![StructField::new("b", DataType::LONG, true)]),
+                true,
+            ),
+        ]);
+        let expected = StructType::new(vec![
+            StructField::new("numRecords", DataType::LONG, true),
+            StructField::new("nullCount", null_counts, true),
+            StructField::new("minValues", values.clone(), true),
+            StructField::new("maxValues", values, true),
+        ]);
+        assert_eq!(filter.stats_schema().as_ref(), &expected);
+    }
+
+    #[test]
+    fn test_column_eligibility() {
+        let table_schema = Arc::new(StructType::new(vec![
+            StructField::new("a", DataType::INTEGER, true),
+            StructField::new("b", DataType::INTEGER, true),
+            StructField::new("c", DataType::INTEGER, true),
+            StructField::new(
+                "metrics",
+                crate::schema::MapType::new(DataType::STRING, DataType::LONG, true),
+                true,
+            ),
+        ]));
+        let predicate = Some(Expr::and_from([
+            Expr::column("a").lt(Expr::literal(5)),
+            Expr::column("b").eq(Expr::column("c")),
+            Expr::column("metrics").is_not_null(),
+            Expr::column("missing").eq(Expr::literal(1)),
+        ]));
+        let engine_interface = SyncEngineInterface::new();
+
+        let filter = DataSkippingFilterBuilder::new(&table_schema, &predicate)
+            .build(&engine_interface)
+            .unwrap();
+        assert_eq!(filter.column_eligibility(), None);
+
+        let filter = DataSkippingFilterBuilder::new(&table_schema, &predicate)
+            .with_eligibility_report(true)
+            .build(&engine_interface)
+            .unwrap();
+        let unsupported =
+            || EligibilityReason::UnsupportedPredicate("column-to-column comparison with =".into());
+        let expected = [
+            ("a".to_string(), EligibilityReason::Eligible),
+            ("b".to_string(), unsupported()),
+            ("c".to_string(), unsupported()),
+            ("metrics".to_string(), EligibilityReason::NoStats),
+            ("missing".to_string(), EligibilityReason::NotInSchema),
+        ];
+        assert_eq!(filter.column_eligibility(), Some(expected.as_slice()));
+    }
+
+    #[test]
+    fn test_referenced_stat_columns() {
+        let engine_interface = SyncEngineInterface::new();
+        let table_schema = Arc::new(StructType::new(vec![
+            StructField::new("a", DataType::INTEGER, true),
+            StructField::new("b", DataType::STRING, true),
+            StructField::new(
+                "c",
+                StructType::new(vec![StructField::new("d", DataType::LONG, true)]),
+                true,
+            ),
+        ]));
+        let predicate = Some(Expr::and_from([
+            Expr::column("a").lt(Expr::literal(5)),
+            Expr::column("b").eq(Expr::literal("x")),
+            Expr::column("c.d").gt(Expr::literal(3_i64)),
+            Expr::column("a").is_null(),
+        ]));
+        let filter = DataSkippingFilterBuilder::new(&table_schema, &predicate)
+            .build(&engine_interface)
+            .unwrap();
+        assert_eq!(
+            filter.referenced_stat_columns(),
+            vec!["maxValues.b", "maxValues.c.d", "minValues.a", "minValues.b"]
+        );
+    }
+
+    #[test]
+    fn test_referenced_stats_only() {
+        // A wide table, where each file has stats for every column.
+        let num_columns = 200;
+        let table_schema = Arc::new(StructType::new(
+            (0..num_columns)
+                .map(|i| StructField::new(format!("c{i}"), DataType::INTEGER, true))
+                .collect(),
+        ));
+        let stats: Vec<_> = [(1, 3), (5, 7), (10, 20)]
+            .into_iter()
+            .map(|(min, max)| {
+                let values = |value: i32| {
+                    (0..num_columns)
+                        .map(|i| (format!("c{i}"), serde_json::json!(value)))
+                        .collect::<serde_json::Map<_, _>>()
+                };
+                serde_json::json!({
+                    "numRecords": 1,
+                    "nullCount": values(0),
+                    "minValues": values(min),
+                    "maxValues": values(max),
+                })
+                .to_string()
+            })
+            .collect();
+        let stats: Vec<_> = stats.iter().map(String::as_str).collect();
+        let fixture = SkippingFixture::new(table_schema, &stats);
+        let stats_kinds = |filter: &DataSkippingFilter| -> Vec<String> {
+            filter
+                .stats_schema
+                .fields()
+                .map(|f| f.name.clone())
+                .collect()
+        };
+
+        let cases = [
+            (Expr::column("c0").lt(Expr::literal(5)), vec!["minValues"]),
+            (Expr::column("c1").gt(Expr::literal(15)), vec!["maxValues"]),
+            (
+                Expr::column("c2").eq(Expr::literal(6)),
+                vec!["minValues", "maxValues"],
+            ),
+        ];
+        for (predicate, expected_kinds) in cases {
+            let full = fixture.filter(&predicate).unwrap();
+            let restricted =
+                DataSkippingFilterBuilder::new(&fixture.table_schema, &Some(predicate.clone()))
+                    .with_referenced_stats_only(true)
+                    .build(&fixture.engine_interface)
+                    .unwrap();
+            assert_eq!(
+                stats_kinds(&full),
+                vec!["numRecords", "nullCount", "minValues", "maxValues"]
+            );
+            assert_eq!(stats_kinds(&restricted), expected_kinds);
+            assert_eq!(
+                full.apply(fixture.actions.as_ref()).unwrap(),
+                restricted.apply(fixture.actions.as_ref()).unwrap(),
+                "selection vectors differ for {predicate}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_null_skipping_only() {
+        let table_schema = Arc::new(StructType::new(vec![
+            StructField::new("a", DataType::INTEGER, true),
+            StructField::new("b", DataType::INTEGER, true),
+        ]));
+        let stats = [
+            // only NULL values of `a`
+            r#"{"numRecords":3,"nullCount":{"a":3,"b":0},"minValues":{"b":1},"maxValues":{"b":3}}"#,
+            // no NULL values of `a`
+            r#"{"numRecords":3,"nullCount":{"a":0,"b":0},"minValues":{"a":1,"b":1},"maxValues":{"a":3,"b":3}}"#,
+            // some NULL values of `a`
+            r#"{"numRecords":3,"nullCount":{"a":1,"b":0},"minValues":{"a":1,"b":1},"maxValues":{"a":3,"b":3}}"#,
+        ];
+        let fixture = SkippingFixture::new(table_schema, &stats);
+        let build = |predicate: Expr| {
+            DataSkippingFilterBuilder::new(&fixture.table_schema, &Some(predicate))
+                .with_null_skipping_only(true)
+                .build(&fixture.engine_interface)
+        };
+
+        let cases = [
+            (Expr::column("a").is_null(), vec![true, false, true]),
+            (Expr::column("a").is_not_null(), vec![false, true, true]),
+            (
+                Expr::column("a").eq(Expr::literal(Scalar::Null(DataType::INTEGER))),
+                vec![true, false, true],
+            ),
+            (!Expr::column("a").is_null(), vec![false, true, true]),
+            // the comparison of `b` needs its min/max values, so it's dropped
+            (
+                Expr::and(
+                    Expr::column("a").is_null(),
+                    Expr::column("b").gt(Expr::literal(100)),
+                ),
+                vec![true, false, true],
+            ),
+        ];
+        for (predicate, expected) in cases {
+            let filter = build(predicate.clone()).unwrap();
+            let kinds: Vec<_> = filter
+                .stats_schema
+                .fields()
+                .map(|f| f.name.clone())
+                .collect();
+            assert!(
+                kinds
+                    .iter()
+                    .all(|kind| kind == "numRecords" || kind == "nullCount"),
+                "stats kinds for {predicate}: {kinds:?}"
+            );
+            assert_eq!(
+                filter.apply(fixture.actions.as_ref()).unwrap(),
+                expected,
+                "{predicate}"
+            );
+        }
+
+        // the stats of each column only include its null counts and the number of records
+        let filter = build(Expr::or(
+            Expr::column("a").is_null(),
+            Expr::column("a").is_not_null(),
+        ))
+        .unwrap();
+        let expected = StructType::new(vec![
+            StructField::new("numRecords", DataType::LONG, true),
+            StructField::new(
+                "nullCount",
+                StructType::new(vec![StructField::new("a", DataType::LONG, true)]),
+                true,
+            ),
+        ]);
+        assert_eq!(filter.stats_schema.as_ref(), &expected);
+
+        // predicates that need the min/max values aren't eligible at all
+        assert!(build(Expr::column("a").gt(Expr::literal(1))).is_none());
+        assert!(build(Expr::or(
+            Expr::column("a").is_null(),
+            Expr::column("b").gt(Expr::literal(100)),
+        ))
+        .is_none());
+    }
+
+    #[test]
+    fn test_analyze() {
+        let column = |name| Expr::column(name);
+        let eligible = [
+            column("a").lt(Expr::literal(5)),
+            column("b").is_null(),
+            Expr::literal(1).lt(column("c")),
+        ];
+        let ineligible = [
+            (
+                column("a").eq(column("b")),
+                "column-to-column comparison with =",
+            ),
+            (
+                column("a").lt(Expr::literal(Scalar::Null(DataType::INTEGER))),
+                "< comparison with NULL",
+            ),
+            (
+                column("f").gt(Expr::literal(Scalar::Double(f64::NAN))),
+                "comparison with NaN",
+            ),
+            (
+                (column("a") + column("b")).gt(Expr::literal(2)),
+                "operands are not a column and a literal",
+            ),
+            (
+                !column("s").starts_with(Expr::literal("x")),
+                "negation of STARTS WITH",
+            ),
+            (
+                column("a").in_list([column("b"), Expr::literal(1)]),
+                "IN list with values that are not literals",
+            ),
+            (
+                Expr::or_from([
+                    column("a").lt(Expr::literal(1)),
+                    column("a").eq(column("b")),
+                ]),
+                "operand of OR is ineligible: column-to-column comparison with =",
+            ),
+            (
+                Expr::between(
+                    column("a") + column("b"),
+                    Expr::literal(1),
+                    Expr::literal(2),
+                ),
+                "operands are not a column and a literal",
+            ),
+            (
+                Expr::coalesce([column("p"), Expr::literal(false)]),
+                "unsupported expression",
+            ),
+        ];
+
+        let predicate = Expr::and_from(
+            eligible
+                .iter()
+                .cloned()
+                .chain(ineligible.iter().map(|(expr, _)| expr.clone())),
+        );
+        let analysis = DataSkippingFilter::analyze(&predicate);
+        assert_eq!(analysis.eligible, eligible);
+        assert_eq!(analysis.ineligible.len(), ineligible.len());
+        for ((expr, reason), (expected_expr, expected_reason)) in
+            analysis.ineligible.iter().zip(&ineligible)
+        {
+            // NaN literals are not equal to themselves, so compare the expressions as text
+            assert_eq!(expr.to_string(), expected_expr.to_string());
+            assert_eq!(reason, expected_reason, "{expr}");
+        }
+
+        // nested conjunctions are analyzed by their conjuncts, after folding constants
+        let predicate = Expr::and_from([
+            column("a").lt(Expr::literal(1) + Expr::literal(2)),
+            Expr::and_from([
+                column("a").eq(column("b")),
+                column("c").gt(Expr::literal(3)),
+            ]),
+        ]);
+        let analysis = DataSkippingFilter::analyze(&predicate);
+        assert_eq!(
+            analysis.eligible,
+            vec![
+                column("a").lt(Expr::literal(3)),
+                column("c").gt(Expr::literal(3))
+            ]
+        );
+        assert_eq!(analysis.ineligible.len(), 1);
+        assert_eq!(analysis.ineligible[0].0, column("a").eq(column("b")));
+    }
+
+    #[test]
+    fn test_stats_column_names() {
+        let engine_interface = SyncEngineInterface::new();
+        let table_schema = single_column_schema("a", DataType::INTEGER);
+        let names = StatsColumnNames {
+            num_records: "rows".to_string(),
+            null_count: "nulls".to_string(),
+            min_values: "mins".to_string(),
+            max_values: "maxs".to_string(),
+            ..Default::default()
+        };
+        let build = |predicate: Expr| {
+            DataSkippingFilterBuilder::new(&table_schema, &Some(predicate))
+                .with_stats_column_names(names.clone())
+                .build(&engine_interface)
+                .unwrap()
+        };
+
+        let filter = build(Expr::or_from([
+            Expr::column("a").lt(Expr::literal(5)),
+            Expr::column("a").is_not_null(),
+        ]));
+        assert_eq!(
+            filter.skipping_predicate,
+            Expr::or_from([
+                Expr::lt(Expr::column("mins.a"), Expr::literal(5)),
+                Expr::lt(Expr::column("nulls.a"), Expr::column("rows")),
+            ])
+        );
+        let field_names: Vec<_> = filter.stats_schema.fields().map(|f| f.name()).collect();
+        assert_eq!(field_names, vec!["rows", "nulls", "mins", "maxs"]);
+        assert_eq!(filter.referenced_stat_columns(), vec!["mins.a"]);
+
+        let filter = build(Expr::column("a").eq(Expr::literal(5)));
+        assert_eq!(filter.referenced_stat_columns(), vec!["maxs.a", "mins.a"]);
+        let actions = add_actions_batch(
+            &engine_interface,
+            &[
+                r#"{"rows":2,"nulls":{"a":0},"mins":{"a":1},"maxs":{"a":4}}"#,
+                r#"{"rows":2,"nulls":{"a":0},"mins":{"a":3},"maxs":{"a":8}}"#,
+                // the Delta names are not parsed, so the stats of this file are missing
+                r#"{"numRecords":2,"nullCount":{"a":0},"minValues":{"a":1},"maxValues":{"a":4}}"#,
+            ],
+        );
+        assert_eq!(
+            filter.apply(actions.as_ref()).unwrap(),
+            vec![false, true, true]
+        );
+    }
+
+    #[test]
+    fn test_float_tolerance() {
+        let table_schema = Arc::new(StructType::new(vec![
+            StructField::new("x", DataType::DOUBLE, true),
+            StructField::new("f", DataType::FLOAT, true),
+        ]));
+        let fixture = SkippingFixture::new(
+            table_schema,
+            &[
+                r#"{"numRecords":2,"minValues":{"x":1.0,"f":1.0},"maxValues":{"x":2.0,"f":2.0},"nullCount":{"x":0,"f":0}}"#,
+                r#"{"numRecords":1,"minValues":{"x":1.5,"f":1.5},"maxValues":{"x":1.5,"f":1.5},"nullCount":{"x":0,"f":0}}"#,
+            ],
+        );
+        let double = |value: f64| Expr::literal(Scalar::Double(value));
+        let float = |value: f32| Expr::literal(Scalar::Float(value));
+
+        // (predicate, expected without tolerance, expected with a tolerance of 0.01)
+        let cases = [
+            (
+                Expr::column("x").eq(double(2.001)),
+                vec![false, false],
+                vec![true, false],
+            ),
+            (
+                Expr::column("x").gt(double(2.001)),
+                vec![false, false],
+                vec![true, false],
+            ),
+            (
+                Expr::column("x").le(double(0.999)),
+                vec![false, false],
+                vec![true, false],
+            ),
+            (
+                Expr::column("f").gt_eq(float(2.001)),
+                vec![false, false],
+                vec![true, false],
+            ),
+            (
+                Expr::column("x").distinct(double(1.5)),
+                vec![true, false],
+                vec![true, true],
+            ),
+            // values beyond the tolerance are still skipped
+            (
+                Expr::column("x").eq(double(3.0)),
+                vec![false, false],
+                vec![false, false],
+            ),
+        ];
+        for (predicate, expected, expected_with_tolerance) in cases {
+            let predicate = Some(predicate);
+            for (tolerance, expected) in [(0.0, &expected), (0.01, &expected_with_tolerance)] {
+                let filter = DataSkippingFilterBuilder::new(&fixture.table_schema, &predicate)
+                    .with_float_tolerance(tolerance)
+                    .build(&fixture.engine_interface)
+                    .unwrap();
+                assert_eq!(
+                    &filter.apply(fixture.actions.as_ref()).unwrap(),
+                    expected,
+                    "{} with tolerance {tolerance}",
+                    predicate.as_ref().unwrap()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_skip_empty_files() {
+        let table_schema = single_column_schema("a", DataType::INTEGER);
+        // keeps every file that has records
+        let predicate = Some(Expr::column("a").gt(Expr::literal(0)));
+        let fixture = SkippingFixture::new(
+            table_schema,
+            &[
+                r#"{"numRecords":0,"minValues":{"a":1},"maxValues":{"a":3}}"#,
+                r#"{"numRecords":0}"#,
+                r#"{"numRecords":3,"minValues":{"a":1},"maxValues":{"a":3}}"#,
+                r#"{"minValues":{"a":1},"maxValues":{"a":3}}"#,
+            ],
+        );
+
+        let cases = [
+            (false, vec![true, true, true, true]),
+            (true, vec![false, false, true, true]),
+        ];
+        for (skip_empty_files, expected) in cases {
+            let filter = DataSkippingFilterBuilder::new(&fixture.table_schema, &predicate)
+                .with_skip_empty_files(skip_empty_files)
+                .build(&fixture.engine_interface)
+                .unwrap();
+            assert_eq!(
+                filter.apply(fixture.actions.as_ref()).unwrap(),
+                expected,
+                "{skip_empty_files}"
+            );
+        }
+
+        // without a predicate, or with one that can't skip any files, there is no filter to skip
+        // the empty files with, unless one is built just for them
+        let predicates = [
+            None,
+            Some(Expr::column("a").eq(Expr::column("a"))),
+            Some(Expr::column("missing").gt(Expr::literal(0))),
+        ];
+        for predicate in predicates {
+            assert!(
+                DataSkippingFilterBuilder::new(&fixture.table_schema, &predicate)
+                    .with_skip_empty_files(true)
+                    .build(&fixture.engine_interface)
+                    .is_none()
+            );
+            let filter = DataSkippingFilterBuilder::new(&fixture.table_schema, &predicate)
+                .with_skip_empty_files_without_predicate(true)
+                .build(&fixture.engine_interface)
+                .unwrap();
+            assert_eq!(
+                filter.apply(fixture.actions.as_ref()).unwrap(),
+                vec![false, false, true, true],
+                "{predicate:?}"
+            );
+            assert_eq!(filter.referenced_stat_columns(), Vec::<String>::new());
+        }
+    }
+
+    #[test]
+    fn test_evaluator_cache() {
+        let engine_interface = SyncEngineInterface::new();
+        let table_schema = single_column_schema("a", DataType::INTEGER);
+        let cache = Arc::new(EvaluatorCache::new(8));
+        let build = |predicate: Expr| {
+            DataSkippingFilterBuilder::new(&table_schema, &Some(predicate))
+                .with_evaluator_cache(Some(cache.clone()))
+                .build(&engine_interface)
+                .unwrap()
+        };
+        let batch = add_actions_batch(
+            &engine_interface,
+            &[
+                r#"{"numRecords":1,"minValues":{"a":10},"maxValues":{"a":20}}"#,
+                r#"{"numRecords":1,"minValues":{"a":1},"maxValues":{"a":5}}"#,
+            ],
+        );
+
+        let first = build(Expr::column("a").lt(Expr::literal(5)));
+        assert_eq!(cache.len(), 3);
+
+        // an identical filter gets all of its evaluators from the cache
+        let second = build(Expr::column("a").lt(Expr::literal(5)));
+        assert_eq!(cache.len(), 3);
+        assert!(Arc::ptr_eq(
+            &first.select_stats_evaluator,
+            &second.select_stats_evaluator
+        ));
+        assert!(Arc::ptr_eq(
+            &first.skipping_evaluator,
+            &second.skipping_evaluator
+        ));
+        assert!(Arc::ptr_eq(
+            &first.filter_evaluator,
+            &second.filter_evaluator
+        ));
+        assert_eq!(second.apply(batch.as_ref()).unwrap(), vec![false, true]);
+
+        // a different predicate only needs a skipping evaluator of its own
+        let third = build(Expr::column("a").lt(Expr::literal(1)));
+        assert_eq!(cache.len(), 4);
+        assert!(Arc::ptr_eq(
+            &first.select_stats_evaluator,
+            &third.select_stats_evaluator
+        ));
+        assert!(!Arc::ptr_eq(
+            &first.skipping_evaluator,
+            &third.skipping_evaluator
+        ));
+        assert_eq!(third.apply(batch.as_ref()).unwrap(), vec![false, false]);
+
+        // a full cache evicts the least recently used evaluator
+        let cache = EvaluatorCache::new(2);
+        let handler = engine_interface.get_expression_handler();
+        let get = |value: i32| {
+            let expression = Expr::column("a").lt(Expr::literal(value));
+            cache.get_evaluator(
+                handler.as_ref(),
+                table_schema.clone(),
+                expression,
+                DataType::BOOLEAN,
+            )
+        };
+        let one = get(1);
+        let two = get(2);
+        assert!(Arc::ptr_eq(&get(1), &one));
+        get(3);
+        assert_eq!(cache.len(), 2);
+        assert!(Arc::ptr_eq(&get(1), &one));
+        assert!(!Arc::ptr_eq(&get(2), &two));
+    }
+}
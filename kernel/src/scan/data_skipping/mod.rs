@@ -0,0 +1,613 @@
+//! Data skipping: skipping the files of a scan whose stats prove that they contain no rows
+//! matching the scan's predicate
+
+use std::borrow::Cow;
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::expressions::{simplify, Expression as Expr};
+use crate::schema::{DataType, PrimitiveType, SchemaRef, SchemaTransform, StructField, StructType};
+
+mod cache;
+mod filter;
+mod resolve;
+mod rewrite;
+
+pub use self::cache::{EvaluatorCache, StatsCache};
+pub(crate) use self::filter::DataSkippingFilterBuilder;
+pub use self::filter::{DataSkippingFilter, DataSkippingResult};
+use self::resolve::resolve_predicate;
+use self::rewrite::as_data_skipping_predicate;
+
+/// Rewrites `predicate` into a predicate over the file statistics of a Delta table, which can be
+/// used to skip files that can't contain any rows matching `predicate`. Returns `None` if the
+/// predicate is not eligible for data skipping.
+///
+/// The rewritten predicate references the per-file statistics by the same names they have in the
+/// `stats` field of an `add` action: `minValues.<column>` and `maxValues.<column>` for the minimum
+/// and maximum value of a (possibly nested, e.g. `minValues.a.b`) column, `nullCount.<column>` for
+/// its number of NULL values, and `numRecords` for the number of rows. Evaluated against the
+/// parsed statistics of a file, a result of `false` means the file can be skipped, while `true` or
+/// `NULL` (e.g. because the file has no statistics) means the file must be read.
+///
+/// Unlike the kernel's own data skipping, this rewrite doesn't look at the table schema, so the
+/// literals of `predicate` must already have the types of the columns they are compared to.
+///
+/// ```
+/// # use delta_kernel::expressions::Expression;
+/// # use delta_kernel::scan::to_data_skipping_predicate;
+/// let predicate = Expression::column("a").lt(Expression::literal(10));
+/// assert_eq!(
+///     to_data_skipping_predicate(&predicate),
+///     Some(Expression::column("minValues.a").lt(Expression::literal(10))),
+/// );
+/// ```
+pub fn to_data_skipping_predicate(predicate: &Expr) -> Option<Expr> {
+    as_data_skipping_predicate(predicate)
+}
+
+/// Whether `predicate` can skip any files of a table with the given schema, i.e. whether some part
+/// of it can be rewritten in terms of the stats of the columns it references. This checks the
+/// predicate like the kernel's own data skipping does, so that it can be validated before
+/// scanning, without an engine: comparisons with columns the schema doesn't have, or with literals
+/// that can't be converted to the column's type exactly, can't skip files.
+///
+/// ```
+/// # use delta_kernel::expressions::Expression;
+/// # use delta_kernel::scan::can_skip;
+/// # use delta_kernel::schema::{DataType, StructField, StructType};
+/// let schema = StructType::new(vec![StructField::new("a", DataType::INTEGER, true)]);
+/// assert!(can_skip(&Expression::column("a").lt(Expression::literal(10)), &schema));
+/// assert!(!can_skip(&Expression::column("b").lt(Expression::literal(10)), &schema));
+/// ```
+pub fn can_skip(predicate: &Expr, schema: &StructType) -> bool {
+    resolve_predicate(predicate, schema)
+        .and_then(|(predicate, _)| as_data_skipping_predicate(&predicate))
+        // e.g. the rewrite of `a + b < 5` is an empty AND, which keeps every file
+        .is_some_and(|skipping_predicate| simplify(&skipping_predicate) != Expr::literal(true))
+}
+
+/// Returns the schema of the stats of add actions for all columns of `table_schema`, regardless
+/// of any predicate, e.g. for checkpoint writers and validators of stats. It has the fields
+/// `numRecords` (a long), `nullCount` (the columns, with every leaf a long), and `minValues` and
+/// `maxValues` (the columns), like the schema the kernel's own data skipping parses stats with.
+/// For the stats of some of the columns, pass a projection of the schema instead (see
+/// [`StructType::project`]).
+///
+/// ```
+/// # use delta_kernel::scan::stats_schema_for;
+/// # use delta_kernel::schema::{DataType, StructField, StructType};
+/// let schema = StructType::new(vec![StructField::new("a", DataType::INTEGER, true)]);
+/// let stats_schema = stats_schema_for(&schema);
+/// let null_count = StructType::new(vec![StructField::new("a", DataType::LONG, true)]);
+/// assert_eq!(
+///     stats_schema.field("nullCount").unwrap().data_type(),
+///     &DataType::from(null_count),
+/// );
+/// assert_eq!(
+///     stats_schema.field("minValues").unwrap().data_type(),
+///     &DataType::from(schema),
+/// );
+/// ```
+pub fn stats_schema_for(table_schema: &StructType) -> SchemaRef {
+    let stats_schema = build_stats_schema(
+        table_schema,
+        &STAT_KINDS.into_iter().collect(),
+        &StatsColumnNames::default(),
+    );
+    // only a schema without columns has no null counts
+    Arc::new(stats_schema.unwrap_or_else(|| {
+        StructType::new(vec![StructField::new("numRecords", DataType::LONG, true)])
+    }))
+}
+
+/// Builds the schema of the given kinds of stats (see [`STAT_KINDS`]) of the columns of
+/// `data_schema`, named by `stats_column_names`. Returns `None` if `data_schema` has no columns
+/// to count nulls of.
+fn build_stats_schema(
+    data_schema: &StructType,
+    stat_kinds: &HashSet<&str>,
+    stats_column_names: &StatsColumnNames,
+) -> Option<StructType> {
+    let stats_fields = STAT_KINDS
+        .into_iter()
+        .filter(|kind| stat_kinds.contains(kind))
+        .map(|kind| {
+            let stats_type = match kind {
+                "numRecords" => DataType::LONG,
+                "nullCount" => NullCountStatsTransform
+                    .transform_struct(data_schema)?
+                    .into_owned()
+                    .into(),
+                _ => data_schema.clone().into(),
+            };
+            let name = stats_column_names.name_of(kind);
+            Some(StructField::new(name, stats_type, true))
+        })
+        .collect::<Option<Vec<_>>>()?;
+    Some(StructType::new(stats_fields))
+}
+
+/// Transforms a data schema into the schema of the `nullCount` stats, which has the same shape
+/// but a `LONG` count at every leaf. Array and map columns are leaves too: their stats record how
+/// many of the arrays (or maps) themselves are null, not the nulls among their elements.
+struct NullCountStatsTransform;
+
+impl<'a> SchemaTransform<'a> for NullCountStatsTransform {
+    fn transform_primitive(&mut self, _ptype: &'a PrimitiveType) -> Option<Cow<'a, PrimitiveType>> {
+        Some(Cow::Owned(PrimitiveType::Long))
+    }
+
+    fn transform_struct_field(&mut self, field: &'a StructField) -> Option<Cow<'a, StructField>> {
+        match field.data_type() {
+            DataType::Array(_) | DataType::Map(_) => Some(Cow::Owned(StructField::new(
+                field.name(),
+                DataType::LONG,
+                true,
+            ))),
+            _ => self.recurse_into_struct_field(field),
+        }
+    }
+}
+
+/// Logs with fewer add actions than this are scanned without data skipping by default (see
+/// [`SkippingThresholds`]).
+const DEFAULT_MIN_SKIPPING_ACTIONS: usize = 32;
+
+/// Thresholds for deciding whether data skipping is worth its overhead. Building a
+/// [`DataSkippingFilter`] creates several evaluators, and applying it parses the stats of every
+/// action, which for a small log can cost more than reading the few files it would skip.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SkippingThresholds {
+    /// The least number of add actions for which data skipping is applied. Defaults to 32.
+    pub min_actions: usize,
+}
+
+impl Default for SkippingThresholds {
+    fn default() -> Self {
+        Self {
+            min_actions: DEFAULT_MIN_SKIPPING_ACTIONS,
+        }
+    }
+}
+
+impl SkippingThresholds {
+    /// Whether data skipping is worthwhile for `predicate` on a log with `num_actions` add
+    /// actions: the log must have at least [`Self::min_actions`] actions, and some conjunct of the
+    /// predicate must be eligible for data skipping (see [`DataSkippingFilter::analyze`]).
+    pub fn should_apply_skipping(&self, predicate: &Expr, num_actions: usize) -> bool {
+        num_actions >= self.min_actions
+            && !DataSkippingFilter::analyze(predicate).eligible.is_empty()
+    }
+}
+
+/// Whether data skipping is worthwhile for `predicate` on a log with `num_actions` add actions,
+/// with the default [`SkippingThresholds`].
+pub fn should_apply_skipping(predicate: &Expr, num_actions: usize) -> bool {
+    SkippingThresholds::default().should_apply_skipping(predicate, num_actions)
+}
+
+/// Which conjuncts of a predicate are eligible for data skipping, and why the others are not (see
+/// [`DataSkippingFilter::analyze`]).
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SkippingAnalysis {
+    /// The conjuncts that data skipping uses.
+    pub eligible: Vec<Expr>,
+    /// The conjuncts that data skipping drops, with the reason why.
+    pub ineligible: Vec<(Expr, String)>,
+}
+
+/// Whether data skipping uses the stats of a column that a predicate references, or why not (see
+/// [`ScanFilterBuilder::with_eligibility_report`]).
+///
+/// [`ScanFilterBuilder::with_eligibility_report`]:
+///     super::ScanFilterBuilder::with_eligibility_report
+#[derive(Debug, Clone, PartialEq)]
+pub enum EligibilityReason {
+    /// The stats of the column are used for data skipping.
+    Eligible,
+    /// The table has no such column, e.g. because it was added after the scanned version.
+    NotInSchema,
+    /// The table doesn't collect stats for columns of this type (e.g. maps and arrays).
+    NoStats,
+    /// The column only appears in parts of the predicate that data skipping can't use, for the
+    /// given reason (e.g. a comparison with another column).
+    UnsupportedPredicate(String),
+}
+
+/// The kinds of stats a [`DataSkippingFilter`] can parse from the `stats` field of add actions.
+pub(crate) const STAT_KINDS: [&str; 4] = ["numRecords", "nullCount", "minValues", "maxValues"];
+
+/// Returns the kinds of stats (see [`STAT_KINDS`]) referenced by a rewritten skipping predicate.
+fn referenced_stat_kinds(skipping_predicate: &Expr) -> HashSet<&'static str> {
+    let references = skipping_predicate.top_level_references();
+    STAT_KINDS
+        .into_iter()
+        .filter(|kind| references.contains(kind))
+        .collect()
+}
+
+/// The names under which add actions store their stats, which default to those of the Delta
+/// protocol. Writers of compatible logs that use other names can override them (see
+/// [`ScanBuilder::with_stats_column_names`](super::ScanBuilder::with_stats_column_names)).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StatsColumnNames {
+    /// The column of the actions that holds the stats as a json string (`add.stats`).
+    pub stats: String,
+    /// The column of the actions that holds the stats as a struct (`add.stats_parsed`), which some
+    /// writers store in checkpoints instead of (or next to) the json string.
+    pub stats_parsed: String,
+    /// The number of records in the file (`numRecords`).
+    pub num_records: String,
+    /// The number of null values of each column (`nullCount`).
+    pub null_count: String,
+    /// The smallest value of each column (`minValues`).
+    pub min_values: String,
+    /// The largest value of each column (`maxValues`).
+    pub max_values: String,
+}
+
+impl Default for StatsColumnNames {
+    fn default() -> Self {
+        Self {
+            stats: "add.stats".to_string(),
+            stats_parsed: "add.stats_parsed".to_string(),
+            num_records: "numRecords".to_string(),
+            null_count: "nullCount".to_string(),
+            min_values: "minValues".to_string(),
+            max_values: "maxValues".to_string(),
+        }
+    }
+}
+
+impl StatsColumnNames {
+    /// The name of a kind of stats (see [`STAT_KINDS`]).
+    pub(crate) fn name_of<'a>(&'a self, kind: &'a str) -> &'a str {
+        match kind {
+            "numRecords" => &self.num_records,
+            "nullCount" => &self.null_count,
+            "minValues" => &self.min_values,
+            "maxValues" => &self.max_values,
+            _ => kind,
+        }
+    }
+
+    /// Renames the stats a rewritten skipping predicate references from the Delta names to these
+    /// names, e.g. `minValues.a` to `{min_values}.a`.
+    fn rename_references(&self, skipping_predicate: &Expr) -> Expr {
+        use Expr::*;
+        match skipping_predicate {
+            Column(name) => match name.split_once('.') {
+                Some((kind, column)) => Expr::joined_column(self.name_of(kind), column),
+                None => Column(self.name_of(name).to_string()),
+            },
+            VariadicOperation { op, exprs } => Expr::variadic(
+                op.clone(),
+                exprs.iter().map(|expr| self.rename_references(expr)),
+            ),
+            UnaryOperation { op, expr } => Expr::unary(op.clone(), self.rename_references(expr)),
+            BinaryOperation { op, left, right } => Expr::binary(
+                op.clone(),
+                self.rename_references(left),
+                self.rename_references(right),
+            ),
+            _ => skipping_predicate.clone(),
+        }
+    }
+}
+
+/// What a [`DataSkippingFilter`] does with files whose stats can't decide the predicate, e.g.
+/// because the stats are missing or null for a referenced column.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MissingStatsPolicy {
+    /// Keep such files. This is always correct, since the files are read and filtered later.
+    #[default]
+    Keep,
+    /// Skip such files, which is only correct for tables where every file has stats for every
+    /// column the predicate references. The skipping predicate is also NULL when the stats can't
+    /// answer it even though they are present, e.g. for `ts != x` on a truncated timestamp max,
+    /// or `a IS NULL OR a < 5` on a file whose values of `a` are all null (and so have no
+    /// min/max). This policy wrongly skips such files, which may drop rows from the scan.
+    Skip,
+}
+
+/// What the stats of a file say about whether it can be skipped (see
+/// [`DataSkippingFilter::apply_tristate`]).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SkipDecision {
+    /// The stats say the file may contain matching rows, so it must be read.
+    Keep,
+    /// The stats prove that the file contains no matching rows.
+    Skip,
+    /// The stats can't decide, e.g. because they are missing or null for a referenced column.
+    Unknown,
+}
+
+/// Receives metrics of data skipping, e.g. for engines to export them as telemetry instead of
+/// parsing debug logs (see [`ScanBuilder::with_skipping_observer`]). Every callback does nothing
+/// by default, so observers only implement the ones they need.
+///
+/// [`ScanBuilder::with_skipping_observer`]: crate::scan::ScanBuilder::with_skipping_observer
+pub trait SkippingObserver: Send + Sync {
+    /// Called when a data skipping filter was built, with the schema it parses stats with.
+    fn on_filter_built(&self, _stats_schema: &SchemaRef) {}
+
+    /// Called when a data skipping filter was applied to a batch of actions, with the number of
+    /// actions in the batch, how many of them it skipped, and how long it took (including parsing
+    /// the stats).
+    fn on_batch_applied(&self, _scanned: usize, _skipped: usize, _duration: Duration) {}
+}
+
+/// Counts of the actions that a [`DataSkippingFilter`] kept and skipped.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct DataSkippingStats {
+    /// The number of actions data skipping was applied to.
+    pub(crate) files_scanned: usize,
+    /// The number of actions data skipping proved can be skipped.
+    pub(crate) files_skipped: usize,
+}
+
+impl DataSkippingStats {
+    /// Counts the actions kept and skipped by a selection vector returned by
+    /// [`DataSkippingFilter::apply`].
+    pub(crate) fn from_selection_vector(selection_vector: &[bool]) -> Self {
+        Self {
+            files_scanned: selection_vector.len(),
+            files_skipped: selection_vector.iter().filter(|keep| !**keep).count(),
+        }
+    }
+}
+
+impl std::ops::AddAssign for DataSkippingStats {
+    fn add_assign(&mut self, other: Self) {
+        self.files_scanned += other.files_scanned;
+        self.files_skipped += other.files_skipped;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use arrow_array::cast::AsArray;
+    use arrow_array::types::Int64Type;
+    use arrow_array::{Int64Array, RecordBatch, StringArray};
+
+    use super::*;
+    use crate::client::arrow_data::ArrowEngineData;
+    use crate::client::sync::SyncEngineInterface;
+    use crate::expressions::Scalar;
+    use crate::scan::test_utils::{
+        single_column_schema, string_array_to_engine_data, SkippingFixture,
+    };
+    use crate::EngineInterface;
+
+    #[test]
+    fn test_stats_schema_for() {
+        let table_schema = Arc::new(StructType::new(vec![
+            StructField::new("a", DataType::INTEGER, true),
+            StructField::new(
+                "s",
+                StructType::new(vec![
+                    StructField::new("b", DataType::STRING, true),
+                    StructField::new("c", DataType::DOUBLE, true),
+                ]),
+                true,
+            ),
+            StructField::new(
+                "tags",
+                crate::schema::ArrayType::new(DataType::STRING, true),
+                true,
+            ),
+        ]));
+        let stats_schema = stats_schema_for(&table_schema);
+        let names: Vec<_> = stats_schema.fields().map(|field| field.name()).collect();
+        assert_eq!(names, ["numRecords", "nullCount", "minValues", "maxValues"]);
+        let null_count = StructType::new(vec![
+            StructField::new("a", DataType::LONG, true),
+            StructField::new(
+                "s",
+                StructType::new(vec![
+                    StructField::new("b", DataType::LONG, true),
+                    StructField::new("c", DataType::LONG, true),
+                ]),
+                true,
+            ),
+            StructField::new("tags", DataType::LONG, true),
+        ]);
+        assert_eq!(
+            stats_schema.field("nullCount").unwrap().data_type(),
+            &DataType::from(null_count)
+        );
+
+        // the same schema as the one data skipping parses stats with, if the predicate references
+        // every column
+        let predicate = Some(Expr::and_from([
+            Expr::column("a").lt(Expr::literal(5)),
+            Expr::column("s.b").eq(Expr::literal("x")),
+            Expr::column("s.c").gt(Expr::literal(Scalar::Double(1.0))),
+            Expr::column("tags").is_null(),
+        ]));
+        let filter = DataSkippingFilterBuilder::new(&table_schema, &predicate)
+            .build(&SyncEngineInterface::new())
+            .unwrap();
+        assert_eq!(filter.stats_schema(), &stats_schema);
+
+        // a subset of the columns
+        let stats_schema = stats_schema_for(&table_schema.project(&["a"]).unwrap());
+        let a = StructType::new(vec![StructField::new("a", DataType::INTEGER, true)]);
+        assert_eq!(
+            stats_schema.field("maxValues").unwrap().data_type(),
+            &DataType::from(a)
+        );
+    }
+
+    #[test]
+    fn test_data_skipping_stats() {
+        let fixture = SkippingFixture::new(
+            single_column_schema("a", DataType::INTEGER),
+            &[
+                r#"{"numRecords":1,"minValues":{"a":1},"maxValues":{"a":3}}"#,
+                r#"{"numRecords":1,"minValues":{"a":5},"maxValues":{"a":5}}"#,
+                r#"{"numRecords":1,"minValues":{"a":20},"maxValues":{"a":30}}"#,
+            ],
+        );
+        let predicate = Expr::column("a").gt(Expr::literal(4));
+
+        let mut stats = DataSkippingStats::default();
+        for _ in 0..2 {
+            let selection_vector = fixture.apply(&predicate);
+            assert_eq!(selection_vector, vec![false, true, true]);
+            stats += DataSkippingStats::from_selection_vector(&selection_vector);
+        }
+        let expected = DataSkippingStats {
+            files_scanned: 6,
+            files_skipped: 2,
+        };
+        assert_eq!(stats, expected);
+    }
+
+    #[test]
+    fn test_null_count_stats_schema() {
+        use crate::schema::{ArrayType, MapType};
+
+        let struct_type = StructType::new(vec![
+            StructField::new("x", DataType::INTEGER, true),
+            StructField::new("y", DataType::STRING, true),
+        ]);
+        let table_schema = StructType::new(vec![
+            StructField::new("id", DataType::INTEGER, false),
+            StructField::new(
+                "arr",
+                ArrayType::new(struct_type.clone().into(), true),
+                true,
+            ),
+            StructField::new(
+                "m",
+                MapType::new(DataType::STRING, struct_type.into(), true),
+                true,
+            ),
+            StructField::new(
+                "s",
+                StructType::new(vec![
+                    StructField::new("a", DataType::DATE, true),
+                    StructField::new("b", ArrayType::new(DataType::LONG, true), true),
+                ]),
+                true,
+            ),
+        ]);
+
+        let null_count_schema = NullCountStatsTransform
+            .transform_struct(&table_schema)
+            .unwrap()
+            .into_owned();
+        let expected = StructType::new(vec![
+            StructField::new("id", DataType::LONG, false),
+            StructField::new("arr", DataType::LONG, true),
+            StructField::new("m", DataType::LONG, true),
+            StructField::new(
+                "s",
+                StructType::new(vec![
+                    StructField::new("a", DataType::LONG, true),
+                    StructField::new("b", DataType::LONG, true),
+                ]),
+                true,
+            ),
+        ]);
+        assert_eq!(null_count_schema, expected);
+
+        let stats_schema = Arc::new(StructType::new(vec![StructField::new(
+            "nullCount",
+            null_count_schema,
+            true,
+        )]));
+        let stats: StringArray = vec![
+            r#"{"numRecords":3,"nullCount":{"id":0,"arr":1,"m":2,"s":{"a":0,"b":3}}}"#,
+            r#"{"numRecords":3,"nullCount":{"id":0,"arr":0,"m":0}}"#,
+        ]
+        .into();
+        let parsed = SyncEngineInterface::new()
+            .get_json_handler()
+            .parse_json(string_array_to_engine_data(stats), stats_schema)
+            .unwrap();
+        let batch: RecordBatch = ArrowEngineData::try_from_engine_data(parsed)
+            .unwrap()
+            .into();
+        let null_counts = batch.column(0).as_struct();
+        let arr_null_counts = null_counts
+            .column_by_name("arr")
+            .unwrap()
+            .as_primitive::<Int64Type>();
+        assert_eq!(arr_null_counts, &Int64Array::from(vec![1, 0]));
+        let m_null_counts = null_counts
+            .column_by_name("m")
+            .unwrap()
+            .as_primitive::<Int64Type>();
+        assert_eq!(m_null_counts, &Int64Array::from(vec![2, 0]));
+    }
+
+    #[test]
+    fn test_should_apply_skipping() {
+        let eligible = Expr::column("a").lt(Expr::literal(5));
+        let ineligible = Expr::column("a").eq(Expr::column("b"));
+        let partially_eligible = Expr::and_from([eligible.clone(), ineligible.clone()]);
+
+        let min_actions = DEFAULT_MIN_SKIPPING_ACTIONS;
+        assert!(!should_apply_skipping(&eligible, min_actions - 1));
+        assert!(should_apply_skipping(&eligible, min_actions));
+        assert!(should_apply_skipping(&partially_eligible, min_actions));
+        assert!(!should_apply_skipping(&ineligible, min_actions));
+        assert!(!should_apply_skipping(&ineligible, usize::MAX));
+
+        let thresholds = SkippingThresholds { min_actions: 2 };
+        assert!(!thresholds.should_apply_skipping(&eligible, 1));
+        assert!(thresholds.should_apply_skipping(&eligible, 2));
+        let thresholds = SkippingThresholds { min_actions: 0 };
+        assert!(thresholds.should_apply_skipping(&eligible, 0));
+        assert!(!thresholds.should_apply_skipping(&ineligible, 0));
+    }
+
+    #[test]
+    fn test_can_skip() {
+        let schema = StructType::new(vec![
+            StructField::new("a", DataType::INTEGER, true),
+            StructField::new("b", DataType::INTEGER, true),
+            StructField::new("d", DataType::decimal(5, 2), true),
+            StructField::new(
+                "s",
+                StructType::new(vec![StructField::new("x", DataType::STRING, true)]),
+                true,
+            ),
+        ]);
+        let a = || Expr::column("a");
+        let eligible = [
+            a().lt(Expr::literal(10)),
+            Expr::column("s.x").starts_with(Expr::literal("abc")),
+            a().is_null(),
+            a().lt(Expr::column("b")),
+            (Expr::literal(1) + Expr::literal(2)).lt(a()),
+            Expr::and(
+                a().gt(Expr::literal(5)),
+                (a() + Expr::column("b")).lt(Expr::literal(3)),
+            ),
+            Expr::or(a().gt(Expr::literal(5)), Expr::column("b").is_not_null()),
+            Expr::column("d").lt(Expr::literal(Scalar::Decimal(150, 3, 2))),
+        ];
+        for predicate in eligible {
+            assert!(can_skip(&predicate, &schema), "{predicate}");
+        }
+
+        let ineligible = [
+            Expr::column("c").lt(Expr::literal(10)),
+            Expr::column("s.y").lt(Expr::literal("abc")),
+            a().eq(Expr::column("b")),
+            (a() + Expr::column("b")).lt(Expr::literal(3)),
+            Expr::or(a().gt(Expr::literal(5)), Expr::column("c").is_null()),
+            Expr::literal(1).lt(Expr::literal(2)),
+            // can't be compared exactly at the column's scale
+            Expr::column("d").lt(Expr::literal(Scalar::Decimal(1501, 4, 3))),
+        ];
+        for predicate in ineligible {
+            assert!(!can_skip(&predicate, &schema), "{predicate}");
+        }
+    }
+}
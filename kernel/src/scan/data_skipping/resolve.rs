@@ -0,0 +1,1285 @@
+//! Preparation of predicates for the data skipping rewrite against the table schema
+
+use std::collections::HashSet;
+
+use tracing::{debug, warn};
+
+use crate::expressions::{
+    cast_scalar, expand_tuple_comparisons, fold_constants, is_widening_cast,
+    rewrite_quantified_comparisons, typecheck, BinaryOperator, Expression as Expr, Scalar,
+    UnaryOperator,
+};
+use crate::schema::{DataType, PrimitiveType, StructField, StructType};
+
+/// Prepares `predicate` for the data skipping rewrite against the table schema. Returns the
+/// prepared predicate and the schema of the columns it references, or `None` if it doesn't
+/// reference any column of the table.
+pub(crate) fn resolve_predicate(
+    predicate: &Expr,
+    table_schema: &StructType,
+) -> Option<(Expr, StructType)> {
+    // Only comparisons between columns and literals can be rewritten, so evaluate the
+    // sub-expressions that only involve literals first (e.g. `1 + 2 < a` becomes `3 < a`).
+    // Comparisons of tuples (e.g. `(a, b) IN ((1, 2), (3, 4))`) are compared field by field, and
+    // quantified comparisons to literals (e.g. `a > ALL (1, 5)`) to the most extreme literal.
+    let predicate = &fold_constants(&rewrite_quantified_comparisons(&expand_tuple_comparisons(
+        predicate,
+    )));
+    let field_names: HashSet<_> = predicate.references();
+
+    // Build the stats read schema by extracting the (possibly nested) column names referenced
+    // by the predicate, extracting the corresponding field from the table schema, and
+    // inserting that field.
+    let data_fields = get_referenced_fields(table_schema, "", &field_names);
+    if data_fields.is_empty() {
+        // The predicate didn't reference any eligible stats columns, so skip it.
+        return None;
+    }
+    let data_schema = StructType::new(data_fields);
+
+    // Comparisons against columns that don't exist in the table have no stats to skip on.
+    let predicate = mask_unresolved_columns(predicate, &data_schema);
+
+    // Comparisons of a lossless cast of a column can be compared as the column itself (e.g.
+    // `CAST(a AS LONG) > 5` as `a > 5` for an INTEGER column).
+    let predicate = unwrap_widening_casts(&predicate, &data_schema);
+
+    // Comparisons of integer arithmetic on a column can be compared as the column itself (e.g.
+    // `a * 2 > 10` as `a > 5`).
+    let predicate = normalize_monotonic(&predicate, &data_schema);
+
+    // Stats are parsed as the column's type (e.g. decimals at the column's scale), so literals
+    // must be compared as it too.
+    let predicate = coerce_literals(&predicate, &data_schema);
+    Some((predicate, data_schema))
+}
+
+/// Returns the fields of `schema` that are referenced by `column_names`, in schema order. Nested
+/// columns are named by their dotted path (e.g. `a.b.c`), and a struct field that is only
+/// referenced through some of its children is projected down to just those children. Names that
+/// don't resolve to a field are ignored.
+fn get_referenced_fields(
+    schema: &StructType,
+    path_prefix: &str,
+    column_names: &HashSet<&str>,
+) -> Vec<StructField> {
+    schema
+        .fields()
+        .filter_map(|field| {
+            let path = format!("{}{}", path_prefix, field.name());
+            if column_names.contains(path.as_str()) {
+                return Some(field.clone());
+            }
+            let DataType::Struct(child_schema) = field.data_type() else {
+                return None;
+            };
+            let child_fields =
+                get_referenced_fields(child_schema, &format!("{path}."), column_names);
+            (!child_fields.is_empty()).then(|| {
+                StructField::new(
+                    field.name(),
+                    StructType::new(child_fields),
+                    field.is_nullable(),
+                )
+            })
+        })
+        .collect()
+}
+
+/// Resolves a dotted column path (e.g. `a.b.c`) against `schema`, descending into nested structs.
+pub(crate) fn resolve_column<'a>(schema: &'a StructType, name: &str) -> Option<&'a StructField> {
+    let mut path = name.split('.');
+    let mut field = schema.field(path.next()?)?;
+    for step in path {
+        match field.data_type() {
+            DataType::Struct(child_schema) => field = child_schema.field(step)?,
+            _ => return None,
+        }
+    }
+    Some(field)
+}
+
+/// Returns true if `predicate` compares two columns of `schema` whose stats can't be compared with
+/// each other, because the columns have different types, floating point types (whose stats may be
+/// NaN, see [`nan_safe`]), or string types (whose stats may be truncated, see
+/// [`truncated_string_stat`]).
+fn has_unsupported_column_comparison(predicate: &Expr, schema: &StructType) -> bool {
+    match predicate {
+        Expr::BinaryOperation { left, right, .. } => match (left.as_ref(), right.as_ref()) {
+            (Expr::Column(left), Expr::Column(right)) => {
+                let data_type = |name| resolve_column(schema, name).map(|field| field.data_type());
+                data_type(left) != data_type(right)
+                    || matches!(
+                        data_type(left),
+                        Some(&DataType::FLOAT | &DataType::DOUBLE | &DataType::STRING)
+                    )
+            }
+            _ => false,
+        },
+        Expr::UnaryOperation { expr, .. } => has_unsupported_column_comparison(expr, schema),
+        Expr::VariadicOperation { exprs, .. } => exprs
+            .iter()
+            .any(|expr| has_unsupported_column_comparison(expr, schema)),
+        _ => false,
+    }
+}
+
+/// Replaces every operand of `predicate` that references a column missing from `schema`, or that
+/// compares columns whose stats can't be compared, with a NULL literal.
+/// [`as_data_skipping_predicate`] considers such operands ineligible, so an AND still skips using
+/// its remaining operands instead of referencing stats that don't exist (or can't be compared).
+///
+/// A predicate may reference a column that is missing from the schema because of schema evolution
+/// (e.g. it was added after the version being scanned). Such operands are unknown, so the files
+/// they would have skipped are kept, and the missing column is logged as a warning.
+fn mask_unresolved_columns(predicate: &Expr, schema: &StructType) -> Expr {
+    match predicate {
+        Expr::VariadicOperation { op, exprs } => Expr::variadic(
+            op.clone(),
+            exprs
+                .iter()
+                .map(|expr| mask_unresolved_columns(expr, schema)),
+        ),
+        _ => match predicate
+            .references()
+            .into_iter()
+            .find(|name| resolve_column(schema, name).is_none())
+        {
+            Some(name) => {
+                warn!("Column {name} not found in table schema, ignoring it for data skipping");
+                Expr::literal(Scalar::Null(DataType::BOOLEAN))
+            }
+            None if has_unsupported_column_comparison(predicate, schema) => {
+                debug!(
+                    "{predicate} compares columns with incomparable stats, \
+                     ignoring it for data skipping"
+                );
+                Expr::literal(Scalar::Null(DataType::BOOLEAN))
+            }
+            None => predicate.clone(),
+        },
+    }
+}
+
+/// Converts a decimal literal compared against the column `col` to the column's precision and
+/// scale, which is how the column's stats are parsed. Returns `None` if the literal can't be
+/// represented exactly at the column's precision and scale. Literals that aren't decimals or are
+/// not compared against a decimal column are returned unchanged.
+fn rescale_decimal_literal(col: &str, literal: &Scalar, schema: &StructType) -> Option<Scalar> {
+    let (
+        Scalar::Decimal(value, _, scale),
+        Some(DataType::Primitive(PrimitiveType::Decimal(precision, target_scale))),
+    ) = (
+        literal,
+        resolve_column(schema, col).map(|field| field.data_type()),
+    )
+    else {
+        return Some(literal.clone());
+    };
+    let value = match target_scale.checked_sub(*scale)? {
+        diff if diff >= 0 => value.checked_mul(10_i128.checked_pow(diff as u32)?)?,
+        diff => {
+            let divisor = 10_i128.checked_pow(diff.unsigned_abs() as u32)?;
+            // scaling down must not drop any digits
+            (value % divisor == 0).then(|| value / divisor)?
+        }
+    };
+    // the value must fit in the column's precision
+    (value.unsigned_abs() < 10_u128.checked_pow(*precision as u32)?)
+        .then(|| Scalar::Decimal(value, *precision, *target_scale))
+}
+
+/// Widens an integer literal compared against the integer column `col` to the column's type, e.g.
+/// an `INTEGER` literal compared against a `LONG` column to a `LONG`, which is how the column's
+/// stats are parsed. Other literals are returned unchanged.
+fn widen_integer_literal(col: &str, literal: Scalar, schema: &StructType) -> Scalar {
+    use PrimitiveType::*;
+    let Some(DataType::Primitive(column_type)) = resolve_column(schema, col).map(|f| f.data_type())
+    else {
+        return literal;
+    };
+    match (literal, column_type) {
+        (Scalar::Byte(value), Short) => Scalar::Short(value.into()),
+        (Scalar::Byte(value), Integer) => Scalar::Integer(value.into()),
+        (Scalar::Byte(value), Long) => Scalar::Long(value.into()),
+        (Scalar::Short(value), Integer) => Scalar::Integer(value.into()),
+        (Scalar::Short(value), Long) => Scalar::Long(value.into()),
+        (Scalar::Integer(value), Long) => Scalar::Long(value.into()),
+        (literal, _) => literal,
+    }
+}
+
+/// Converts the literals that `predicate` compares against columns of `schema` to the type the
+/// column's stats are parsed as: decimal literals to the precision and scale of a decimal column
+/// (see [`rescale_decimal_literal`]), and integer literals to the type of a wider integer column
+/// (see [`widen_integer_literal`]). A comparison whose literal can't be represented exactly is
+/// replaced with a NULL literal, so no data skipping is attempted for it.
+fn coerce_literals(predicate: &Expr, schema: &StructType) -> Expr {
+    use Expr::*;
+    let rescale = |col: &str, literal: &Scalar| {
+        let rescaled = rescale_decimal_literal(col, literal, schema)
+            .map(|literal| widen_integer_literal(col, literal, schema));
+        if rescaled.is_none() {
+            debug!("Literal {literal} can't be compared against stats of column {col} exactly");
+        }
+        rescaled
+    };
+    let masked = || Expr::literal(Scalar::Null(DataType::BOOLEAN));
+    match predicate {
+        VariadicOperation { op, exprs } => Expr::variadic(
+            op.clone(),
+            exprs.iter().map(|expr| coerce_literals(expr, schema)),
+        ),
+        UnaryOperation { op, expr } => Expr::unary(op.clone(), coerce_literals(expr, schema)),
+        BinaryOperation { op, left, right } => match (left.as_ref(), right.as_ref()) {
+            (Column(col), Literal(val)) => match rescale(col, val) {
+                Some(val) => Expr::binary(op.clone(), Column(col.clone()), val),
+                None => masked(),
+            },
+            (Literal(val), Column(col)) => match rescale(col, val) {
+                Some(val) => Expr::binary(op.clone(), val, Column(col.clone())),
+                None => masked(),
+            },
+            _ => predicate.clone(),
+        },
+        InList { expr, list } => match expr.as_ref() {
+            Column(col) => {
+                let list = list
+                    .iter()
+                    .map(|value| match value {
+                        Literal(val) => rescale(col, val).map(Literal),
+                        _ => Some(value.clone()),
+                    })
+                    .collect::<Option<Vec<_>>>();
+                match list {
+                    Some(list) => Column(col.clone()).in_list(list),
+                    None => masked(),
+                }
+            }
+            _ => predicate.clone(),
+        },
+        Between { expr, low, high } => match expr.as_ref() {
+            Column(col) => {
+                let rescale_bound = |bound: &Expr| match bound {
+                    Literal(val) => rescale(col, val).map(Literal),
+                    _ => Some(bound.clone()),
+                };
+                match (rescale_bound(low), rescale_bound(high)) {
+                    (Some(low), Some(high)) => Expr::between(Column(col.clone()), low, high),
+                    _ => masked(),
+                }
+            }
+            _ => predicate.clone(),
+        },
+        _ => predicate.clone(),
+    }
+}
+
+/// Rewrites the comparisons of a widening cast of a column with literals in `predicate`, like
+/// `CAST(a AS LONG) > 5`, as comparisons of the column itself, like `a > 5`, so that they can skip
+/// on the column's stats. Widening casts (see [`is_widening_cast`]) are lossless and keep the order
+/// of values, so comparing the cast is the same as comparing the column against the literal
+/// converted to the column's type. A comparison whose literal can't be converted exactly is
+/// replaced with a NULL literal, so no data skipping is attempted for it. Comparisons of other
+/// casts (e.g. `CAST(l AS INTEGER)` of a LONG column, which overflows) are left unchanged.
+fn unwrap_widening_casts(predicate: &Expr, schema: &StructType) -> Expr {
+    use Expr::*;
+    // The column under a widening cast, and the column's type
+    let widened_column = |expr: &Expr| match expr {
+        Cast { expr, target_type } => match expr.as_ref() {
+            Column(col) => {
+                let data_type = resolve_column(schema, col)?.data_type();
+                is_widening_cast(data_type, target_type).then_some((col.clone(), data_type))
+            }
+            _ => None,
+        },
+        _ => None,
+    };
+    let narrow = |value: &Expr, data_type: &DataType| match value {
+        Literal(value) => cast_scalar(value, data_type).map(Literal),
+        _ => None,
+    };
+    let masked = || Expr::literal(Scalar::Null(DataType::BOOLEAN));
+    match predicate {
+        VariadicOperation { op, exprs } => Expr::variadic(
+            op.clone(),
+            exprs.iter().map(|expr| unwrap_widening_casts(expr, schema)),
+        ),
+        UnaryOperation { op, expr } => Expr::unary(op.clone(), unwrap_widening_casts(expr, schema)),
+        BinaryOperation { op, left, right } => {
+            match (widened_column(left), widened_column(right)) {
+                (Some((col, data_type)), None) => match narrow(right, data_type) {
+                    Some(value) => Expr::binary(op.clone(), Column(col), value),
+                    None => masked(),
+                },
+                (None, Some((col, data_type))) => match narrow(left, data_type) {
+                    Some(value) => Expr::binary(op.clone(), value, Column(col)),
+                    None => masked(),
+                },
+                _ => predicate.clone(),
+            }
+        }
+        InList { expr, list } => match widened_column(expr) {
+            Some((col, data_type)) => {
+                let list: Option<Vec<_>> =
+                    list.iter().map(|value| narrow(value, data_type)).collect();
+                match list {
+                    Some(list) => Column(col).in_list(list),
+                    None => masked(),
+                }
+            }
+            None => predicate.clone(),
+        },
+        Between { expr, low, high } => match widened_column(expr) {
+            Some((col, data_type)) => match (narrow(low, data_type), narrow(high, data_type)) {
+                (Some(low), Some(high)) => Expr::between(Column(col), low, high),
+                _ => masked(),
+            },
+            None => predicate.clone(),
+        },
+        _ => predicate.clone(),
+    }
+}
+
+/// Rewrites the comparisons of integer arithmetic with a literal in `predicate`, like `a * 2 > 10`,
+/// as comparisons of the arithmetic's operand, like `a > 5`, so that they can skip on the stats of
+/// a column.
+///
+/// Adding, subtracting, multiplying and dividing by an integer literal are monotonic, so they are
+/// undone by applying the inverse operation to the compared literal. Multiplying or dividing by a
+/// negative literal flips the comparison, e.g. `a * -2 > 10` becomes `a < -5`. The rewrites are
+/// exact for integer operands: e.g. `a * 2 >= 11` becomes `a >= 6`, and `a / 2 = 3` (which
+/// truncates) becomes `a BETWEEN 6 AND 7`. Comparisons of other arithmetic, of operands that are
+/// not integers (whose arithmetic rounds), and whose rewritten literal would overflow are left
+/// unchanged.
+fn normalize_monotonic(predicate: &Expr, schema: &StructType) -> Expr {
+    use Expr::*;
+    match predicate {
+        VariadicOperation { op, exprs } => Expr::variadic(
+            op.clone(),
+            exprs.iter().map(|expr| normalize_monotonic(expr, schema)),
+        ),
+        UnaryOperation {
+            op: UnaryOperator::Not,
+            expr,
+        } => !normalize_monotonic(expr, schema),
+        BinaryOperation { op, left, right } => {
+            let rewritten = match (left.as_ref(), right.as_ref()) {
+                (arithmetic @ BinaryOperation { .. }, Literal(val)) => {
+                    invert_arithmetic(op.clone(), arithmetic, val, schema)
+                }
+                (Literal(val), arithmetic @ BinaryOperation { .. }) => op
+                    .commute()
+                    .and_then(|op| invert_arithmetic(op, arithmetic, val, schema)),
+                _ => None,
+            };
+            match rewritten {
+                // the operand may be arithmetic itself, e.g. `a * 2 + 1 > 10`
+                Some(rewritten) => normalize_monotonic(&rewritten, schema),
+                None => predicate.clone(),
+            }
+        }
+        _ => predicate.clone(),
+    }
+}
+
+/// Rewrites `arithmetic op val` as a comparison of the non-literal operand of `arithmetic`, or
+/// returns `None` if that isn't possible (see [`normalize_monotonic`]).
+fn invert_arithmetic(
+    op: BinaryOperator,
+    arithmetic: &Expr,
+    val: &Scalar,
+    schema: &StructType,
+) -> Option<Expr> {
+    use BinaryOperator::*;
+    use Expr::*;
+
+    if !matches!(
+        op,
+        LessThan
+            | LessThanOrEqual
+            | GreaterThan
+            | GreaterThanOrEqual
+            | Equal
+            | NotEqual
+            | NullSafeEqual
+            | Distinct
+    ) {
+        return None;
+    }
+    let BinaryOperation {
+        op: arithmetic_op,
+        left,
+        right,
+    } = arithmetic
+    else {
+        return None;
+    };
+    let (operand, c, operand_first) = match (left.as_ref(), right.as_ref()) {
+        (operand, Literal(c)) => (operand, integer_value(c)?, true),
+        (Literal(c), operand) => (operand, integer_value(c)?, false),
+        _ => return None, // not arithmetic with a literal
+    };
+    let v = integer_value(val)?;
+    let operand_type = match typecheck(operand, schema) {
+        Ok(DataType::Primitive(
+            operand_type @ (PrimitiveType::Byte
+            | PrimitiveType::Short
+            | PrimitiveType::Integer
+            | PrimitiveType::Long),
+        )) => operand_type,
+        _ => return None, // the arithmetic may round
+    };
+    // the rewritten literal is compared with the operand, so it takes the operand's type, which
+    // may be narrower than the type of `val`
+    let literal = |value: i64| integer_scalar(&operand_type, value).map(Expr::literal);
+    let compare = |op, value: i64| Some(Expr::binary(op, operand.clone(), literal(value)?));
+    // `operand * c` and `operand / c` compare like `operand * -c` and `operand / -c` with the
+    // comparison flipped and `-v`
+    let make_positive = |op: BinaryOperator| match c {
+        c if c < 0 => Some((op.commute()?, c.checked_neg()?, v.checked_neg()?)),
+        c => Some((op, c, v)),
+    };
+    match (arithmetic_op, operand_first) {
+        (Plus, _) => compare(op, v.checked_sub(c)?),
+        (Minus, true) => compare(op, v.checked_add(c)?),
+        // `c - a` is `v` iff `a` is `c - v`, and decreases as `a` increases
+        (Minus, false) => compare(op.commute()?, c.checked_sub(v)?),
+        (Multiply, _) => {
+            let (op, c, v) = make_positive(op)?;
+            let floor = v.checked_div_euclid(c)?;
+            let ceil = floor + i64::from(v.rem_euclid(c) != 0);
+            match op {
+                GreaterThan | LessThanOrEqual => compare(op, floor),
+                GreaterThanOrEqual | LessThan => compare(op, ceil),
+                // the product is never `v` if `v` isn't a multiple of `c`
+                _ if v.rem_euclid(c) == 0 => compare(op, floor),
+                _ => None,
+            }
+        }
+        (Divide, true) => {
+            let (op, c, v) = make_positive(op)?;
+            if c == 0 {
+                return None;
+            }
+            // `a / c` truncates towards zero, so it is at least `k` iff `a >= lower(k)`, and at
+            // most `k` iff `a <= upper(k)`
+            let lower = |k: i64| match k {
+                k if k > 0 => k.checked_mul(c),
+                k => k.checked_sub(1)?.checked_mul(c)?.checked_add(1),
+            };
+            let upper = |k: i64| match k {
+                k if k >= 0 => k.checked_add(1)?.checked_mul(c)?.checked_sub(1),
+                k => k.checked_mul(c),
+            };
+            match op {
+                GreaterThan => compare(GreaterThanOrEqual, lower(v.checked_add(1)?)?),
+                GreaterThanOrEqual => compare(GreaterThanOrEqual, lower(v)?),
+                LessThan => compare(LessThanOrEqual, upper(v.checked_sub(1)?)?),
+                LessThanOrEqual => compare(LessThanOrEqual, upper(v)?),
+                Equal | NotEqual => {
+                    let between =
+                        Expr::between(operand.clone(), literal(lower(v)?)?, literal(upper(v)?)?);
+                    Some(if op == Equal { between } else { !between })
+                }
+                _ => None, // NULL-safe comparisons aren't ranges
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Returns the value of an integer literal.
+fn integer_value(value: &Scalar) -> Option<i64> {
+    match value {
+        Scalar::Byte(value) => Some((*value).into()),
+        Scalar::Short(value) => Some((*value).into()),
+        Scalar::Integer(value) => Some((*value).into()),
+        Scalar::Long(value) => Some(*value),
+        _ => None,
+    }
+}
+
+/// Returns `value` as a literal of the integer type `data_type`, if it fits.
+fn integer_scalar(data_type: &PrimitiveType, value: i64) -> Option<Scalar> {
+    match data_type {
+        PrimitiveType::Byte => value.try_into().ok().map(Scalar::Byte),
+        PrimitiveType::Short => value.try_into().ok().map(Scalar::Short),
+        PrimitiveType::Integer => value.try_into().ok().map(Scalar::Integer),
+        PrimitiveType::Long => Some(Scalar::Long(value)),
+        _ => None,
+    }
+}
+
+/// Delta truncates the max stats of timestamp columns to milliseconds, so the actual max value of
+/// a file can be up to 999 microseconds larger than its stat. Adjusts the comparisons against such
+/// stats in a rewritten data skipping `predicate` so that they don't skip files which may contain
+/// larger values than their max stat says.
+pub(crate) fn adjust_truncated_timestamp_max(predicate: Expr, schema: &StructType) -> Expr {
+    use BinaryOperator::*;
+    use Expr::*;
+
+    match predicate {
+        VariadicOperation { op, exprs } => Expr::variadic(
+            op,
+            exprs
+                .into_iter()
+                .map(|expr| adjust_truncated_timestamp_max(expr, schema)),
+        ),
+        BinaryOperation { op, left, right } => {
+            let is_timestamp_max = |expr: &Expr| match expr {
+                Column(name) => name
+                    .strip_prefix("maxValues.")
+                    .and_then(|col| resolve_column(schema, col))
+                    .is_some_and(|field| {
+                        matches!(
+                            field.data_type(),
+                            &DataType::TIMESTAMP | &DataType::TIMESTAMP_NTZ
+                        )
+                    }),
+                _ => false,
+            };
+            let is_column = |expr: &Expr| matches!(expr, Column(_));
+            if is_column(&left) && is_column(&right) {
+                // the actual max could be larger than the stat, which can't be accounted for in a
+                // comparison with another column's stat
+                return match is_timestamp_max(&left) || is_timestamp_max(&right) {
+                    true => Expr::literal(Scalar::Null(DataType::BOOLEAN)),
+                    false => BinaryOperation { op, left, right },
+                };
+            }
+            let is_timestamp_max = is_timestamp_max(&left);
+            match (&op, right.as_ref()) {
+                (GreaterThan | GreaterThanOrEqual, Literal(Scalar::Timestamp(value)))
+                    if is_timestamp_max =>
+                {
+                    let value = Scalar::Timestamp(value.saturating_sub(999));
+                    Expr::binary(op, *left, value)
+                }
+                (GreaterThan | GreaterThanOrEqual, Literal(Scalar::TimestampNtz(value)))
+                    if is_timestamp_max =>
+                {
+                    let value = Scalar::TimestampNtz(value.saturating_sub(999));
+                    Expr::binary(op, *left, value)
+                }
+                // the actual max could be any value in the millisecond after the stat
+                (NotEqual, Literal(Scalar::Timestamp(_) | Scalar::TimestampNtz(_)))
+                    if is_timestamp_max =>
+                {
+                    Expr::literal(Scalar::Null(DataType::BOOLEAN))
+                }
+                _ => BinaryOperation { op, left, right },
+            }
+        }
+        _ => predicate,
+    }
+}
+
+/// Writers may compute the min/max stats of floating point columns with rounding, so the actual
+/// min/max value of a file can differ from its stat by a small amount. Widens the comparisons
+/// between such stats and floating point literals in a rewritten data skipping `predicate` by
+/// `epsilon`, so that they don't skip files whose values are only out of range due to rounding.
+pub(crate) fn widen_float_bounds(predicate: Expr, epsilon: f64) -> Expr {
+    use BinaryOperator::*;
+    use Expr::*;
+
+    match predicate {
+        VariadicOperation { op, exprs } => Expr::variadic(
+            op,
+            exprs
+                .into_iter()
+                .map(|expr| widen_float_bounds(expr, epsilon)),
+        ),
+        BinaryOperation { op, left, right } => {
+            let widen = |value: &Scalar, by: f64| match value {
+                Scalar::Float(value) => Some(Scalar::Float(value + by as f32)),
+                Scalar::Double(value) => Some(Scalar::Double(value + by)),
+                _ => None,
+            };
+            let Literal(value) = right.as_ref() else {
+                return BinaryOperation { op, left, right };
+            };
+            let widened = match op {
+                LessThan | LessThanOrEqual => widen(value, epsilon),
+                GreaterThan | GreaterThanOrEqual => widen(value, -epsilon),
+                // a stat that differs from the literal may still be the literal before rounding
+                NotEqual if matches!(value, Scalar::Float(_) | Scalar::Double(_)) => {
+                    return Expr::literal(Scalar::Null(DataType::BOOLEAN));
+                }
+                _ => None,
+            };
+            match widened {
+                Some(value) => Expr::binary(op, *left, value),
+                None => BinaryOperation { op, left, right },
+            }
+        }
+        _ => predicate,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::scan::test_utils::{nested_table_schema, single_column_schema, SkippingFixture};
+
+    #[test]
+    fn test_get_referenced_fields() {
+        let table_schema = nested_table_schema();
+        let cases = [
+            (
+                vec!["a.b"],
+                vec![StructField::new(
+                    "a",
+                    StructType::new(vec![StructField::new("b", DataType::INTEGER, true)]),
+                    true,
+                )],
+            ),
+            (
+                vec!["f", "a.c.d"],
+                vec![
+                    StructField::new(
+                        "a",
+                        StructType::new(vec![StructField::new(
+                            "c",
+                            StructType::new(vec![StructField::new("d", DataType::LONG, true)]),
+                            true,
+                        )]),
+                        true,
+                    ),
+                    StructField::new("f", DataType::INTEGER, true),
+                ],
+            ),
+            (vec!["a.x", "a.b.x", "x"], vec![]),
+        ];
+        for (names, expected) in cases {
+            let names = names.into_iter().collect();
+            assert_eq!(get_referenced_fields(&table_schema, "", &names), expected);
+        }
+    }
+
+    #[test]
+    fn test_rescale_decimal_literals() {
+        let schema = StructType::new(vec![
+            StructField::new("price", DataType::decimal(5, 2), true),
+            StructField::new("a", DataType::INTEGER, true),
+        ]);
+        let price = Expr::column("price");
+        let decimal =
+            |value, precision, scale| Expr::literal(Scalar::Decimal(value, precision, scale));
+        let masked = Expr::literal(Scalar::Null(DataType::BOOLEAN));
+
+        let cases = [
+            // scale up: 10.5 => 10.50
+            (
+                price.clone().gt(decimal(105, 3, 1)),
+                price.clone().gt(decimal(1050, 5, 2)),
+            ),
+            (
+                decimal(105, 3, 1).lt(price.clone()),
+                decimal(1050, 5, 2).lt(price.clone()),
+            ),
+            // scale down: 10.500 => 10.50
+            (
+                price.clone().eq(decimal(10500, 5, 3)),
+                price.clone().eq(decimal(1050, 5, 2)),
+            ),
+            (
+                price.clone().eq(decimal(-10500, 5, 3)),
+                price.clone().eq(decimal(-1050, 5, 2)),
+            ),
+            // already at the column's scale, but with a different precision
+            (
+                price.clone().le(decimal(1050, 10, 2)),
+                price.clone().le(decimal(1050, 5, 2)),
+            ),
+            // scale down would drop digits: 10.505
+            (price.clone().gt(decimal(10505, 5, 3)), masked.clone()),
+            // out of precision: 1000.00 and 10^37 don't fit in decimal(5, 2)
+            (price.clone().lt(decimal(1000, 4, 0)), masked.clone()),
+            (
+                price.clone().lt(decimal(10_i128.pow(37), 38, 0)),
+                masked.clone(),
+            ),
+            (
+                price
+                    .clone()
+                    .in_list([decimal(105, 3, 1), decimal(2, 1, 0)]),
+                price
+                    .clone()
+                    .in_list([decimal(1050, 5, 2), decimal(200, 5, 2)]),
+            ),
+            (
+                price
+                    .clone()
+                    .not_in_list([decimal(105, 3, 1), decimal(10505, 5, 3)]),
+                !masked.clone(),
+            ),
+            (
+                Expr::and_from([
+                    price.clone().gt(decimal(10505, 5, 3)),
+                    price.clone().lt(decimal(2, 1, 0)),
+                ]),
+                Expr::and_from([masked.clone(), price.clone().lt(decimal(200, 5, 2))]),
+            ),
+            (
+                Expr::between(price.clone(), decimal(105, 3, 1), decimal(2, 1, 0)),
+                Expr::between(price.clone(), decimal(1050, 5, 2), decimal(200, 5, 2)),
+            ),
+            (
+                Expr::between(price.clone(), decimal(105, 3, 1), decimal(10505, 5, 3)),
+                masked.clone(),
+            ),
+            // literals compared against other columns are left alone
+            (
+                Expr::column("a").gt(decimal(10505, 5, 3)),
+                Expr::column("a").gt(decimal(10505, 5, 3)),
+            ),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(coerce_literals(&input, &schema), expected, "{input}");
+        }
+    }
+
+    #[test]
+    fn test_widen_integer_literals() {
+        let schema = StructType::new(vec![
+            StructField::new("l", DataType::LONG, true),
+            StructField::new("i", DataType::INTEGER, true),
+            StructField::new("s", DataType::SHORT, true),
+        ]);
+        let col = Expr::column;
+        let short = |value: i16| Expr::literal(value);
+
+        let cases = [
+            (
+                col("l").lt(Expr::literal(5)),
+                col("l").lt(Expr::literal(5_i64)),
+            ),
+            (
+                Expr::literal(5).lt(col("l")),
+                Expr::literal(5_i64).lt(col("l")),
+            ),
+            (col("i").eq(short(-3)), col("i").eq(Expr::literal(-3))),
+            (
+                col("l").in_list([short(1), Expr::literal(2)]),
+                col("l").in_list([Expr::literal(1_i64), Expr::literal(2_i64)]),
+            ),
+            (
+                Expr::between(col("i"), short(1), short(2)),
+                Expr::between(col("i"), Expr::literal(1), Expr::literal(2)),
+            ),
+            // literals are never narrowed
+            (
+                col("s").lt(Expr::literal(5_i64)),
+                col("s").lt(Expr::literal(5_i64)),
+            ),
+            (col("s").lt(short(5)), col("s").lt(short(5))),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(coerce_literals(&input, &schema), expected, "{input}");
+        }
+    }
+
+    #[test]
+    fn test_normalize_monotonic() {
+        use std::ops::{Add, Div, Mul, Sub};
+
+        let schema = StructType::new(vec![
+            StructField::new("i", DataType::INTEGER, true),
+            StructField::new("l", DataType::LONG, true),
+            StructField::new("d", DataType::DOUBLE, true),
+        ]);
+        let i = || Expr::column("i");
+        let lit = |value: i32| Expr::literal(value);
+
+        let cases = [
+            (i().mul(lit(2)).gt(lit(10)), i().gt(lit(5))),
+            (i().sub(lit(3)).lt(lit(7)), i().lt(lit(10))),
+            // multiplying by a negative literal flips the comparison
+            (i().mul(lit(-2)).gt(lit(10)), i().lt(lit(-5))),
+            (lit(2).mul(i()).ge(lit(11)), i().ge(lit(6))),
+            (i().mul(lit(2)).lt(lit(11)), i().lt(lit(6))),
+            (i().mul(lit(2)).eq(lit(10)), i().eq(lit(5))),
+            (lit(10).lt(i().add(lit(3))), i().gt(lit(7))),
+            (lit(10).sub(i()).gt(lit(3)), i().lt(lit(7))),
+            (
+                Expr::column("l")
+                    .add(Expr::literal(1_i64))
+                    .ne(Expr::literal(5_i64)),
+                Expr::column("l").ne(Expr::literal(4_i64)),
+            ),
+            // the rewritten literal takes the type of the operand
+            (
+                i().add(Expr::literal(1_i64)).gt(Expr::literal(5_i64)),
+                i().gt(lit(4)),
+            ),
+            (
+                Expr::column("l").mul(lit(2)).lt(lit(10)),
+                Expr::column("l").lt(Expr::literal(5_i64)),
+            ),
+            // integer division truncates
+            (
+                i().div(lit(2)).eq(lit(3)),
+                Expr::between(i(), lit(6), lit(7)),
+            ),
+            (i().div(lit(2)).gt(lit(3)), i().ge(lit(8))),
+            (i().div(lit(-2)).lt(lit(-3)), i().ge(lit(8))),
+            (i().div(lit(2)).le(lit(-1)), i().le(lit(-2))),
+            // nested arithmetic and logical operators
+            (i().mul(lit(2)).add(lit(1)).gt(lit(10)), i().gt(lit(4))),
+            (!i().mul(lit(2)).gt(lit(10)), !i().gt(lit(5))),
+            (
+                Expr::and_from([i().add(lit(1)).gt(lit(1)), i().sub(lit(1)).lt(lit(1))]),
+                Expr::and_from([i().gt(lit(0)), i().lt(lit(2))]),
+            ),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(normalize_monotonic(&input, &schema), expected, "{input}");
+        }
+
+        // non-constant transforms, rounding arithmetic, products that are never equal to the
+        // literal and overflowing literals are left unchanged
+        let cases = [
+            i().mul(Expr::column("l")).gt(Expr::literal(10_i64)),
+            Expr::column("d").mul(lit(2)).gt(lit(10)),
+            i().mul(lit(2)).eq(lit(11)),
+            i().mul(lit(0)).gt(lit(1)),
+            i().div(lit(0)).gt(lit(1)),
+            i().sub(lit(1)).gt(lit(i32::MAX)),
+            i().add(Expr::literal(1_i64))
+                .gt(Expr::literal(i64::from(i32::MAX) + 2)),
+            i().div(lit(2)).null_safe_eq(lit(3)),
+        ];
+        for input in cases {
+            assert_eq!(normalize_monotonic(&input, &schema), input, "{input}");
+        }
+    }
+
+    #[test]
+    fn test_adjust_truncated_timestamp_max() {
+        let schema = StructType::new(vec![
+            StructField::new("ts", DataType::TIMESTAMP, true),
+            StructField::new("ts_ntz", DataType::TIMESTAMP_NTZ, true),
+            StructField::new("a", DataType::LONG, true),
+        ]);
+        let ts = |value| Expr::literal(Scalar::Timestamp(value));
+        let ts_ntz = |value| Expr::literal(Scalar::TimestampNtz(value));
+
+        let cases = [
+            (
+                Expr::gt(Expr::column("maxValues.ts"), ts(5000)),
+                Expr::gt(Expr::column("maxValues.ts"), ts(4001)),
+            ),
+            (
+                Expr::ge(Expr::column("maxValues.ts_ntz"), ts(5000)),
+                Expr::ge(Expr::column("maxValues.ts_ntz"), ts(4001)),
+            ),
+            (
+                Expr::ne(Expr::column("maxValues.ts"), ts(5000)),
+                Expr::literal(Scalar::Null(DataType::BOOLEAN)),
+            ),
+            (
+                Expr::gt(Expr::column("maxValues.ts_ntz"), ts_ntz(5000)),
+                Expr::gt(Expr::column("maxValues.ts_ntz"), ts_ntz(4001)),
+            ),
+            (
+                Expr::ne(Expr::column("maxValues.ts_ntz"), ts_ntz(5000)),
+                Expr::literal(Scalar::Null(DataType::BOOLEAN)),
+            ),
+            (
+                Expr::and_from([
+                    Expr::le(Expr::column("minValues.ts"), ts(5000)),
+                    Expr::ge(Expr::column("maxValues.ts"), ts(5000)),
+                ]),
+                Expr::and_from([
+                    Expr::le(Expr::column("minValues.ts"), ts(5000)),
+                    Expr::ge(Expr::column("maxValues.ts"), ts(4001)),
+                ]),
+            ),
+            // truncated max stats can't be compared with other columns
+            (
+                Expr::lt(Expr::column("minValues.a"), Expr::column("maxValues.ts")),
+                Expr::literal(Scalar::Null(DataType::BOOLEAN)),
+            ),
+            (
+                Expr::gt(
+                    Expr::column("maxValues.ts"),
+                    Expr::column("minValues.ts_ntz"),
+                ),
+                Expr::literal(Scalar::Null(DataType::BOOLEAN)),
+            ),
+            (
+                Expr::lt(Expr::column("minValues.ts"), Expr::column("maxValues.a")),
+                Expr::lt(Expr::column("minValues.ts"), Expr::column("maxValues.a")),
+            ),
+            // min stats and other types are not truncated
+            (
+                Expr::gt(Expr::column("minValues.ts"), ts(5000)),
+                Expr::gt(Expr::column("minValues.ts"), ts(5000)),
+            ),
+            (
+                Expr::lt(Expr::column("maxValues.ts"), ts(5000)),
+                Expr::lt(Expr::column("maxValues.ts"), ts(5000)),
+            ),
+            (
+                Expr::gt(Expr::column("maxValues.a"), Expr::literal(5000_i64)),
+                Expr::gt(Expr::column("maxValues.a"), Expr::literal(5000_i64)),
+            ),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(adjust_truncated_timestamp_max(input, &schema), expected);
+        }
+    }
+
+    #[test]
+    fn test_widened_integer_skipping() {
+        let table_schema = Arc::new(StructType::new(vec![
+            StructField::new("l", DataType::LONG, true),
+            StructField::new("i", DataType::INTEGER, true),
+        ]));
+        let fixture = SkippingFixture::new(
+            table_schema,
+            &[
+                r#"{"numRecords":2,"minValues":{"l":1,"i":1},"maxValues":{"l":10,"i":10}}"#,
+                r#"{"numRecords":2,"minValues":{"l":20,"i":20},"maxValues":{"l":30,"i":30}}"#,
+            ],
+        );
+
+        let cases = [
+            (Expr::column("l").lt(Expr::literal(5)), vec![true, false]),
+            (Expr::column("l").eq(Expr::literal(25)), vec![false, true]),
+            (
+                Expr::column("i").gt(Expr::literal(15_i16)),
+                vec![false, true],
+            ),
+            (
+                Expr::column("i").in_list([Expr::literal(3_i16)]),
+                vec![true, false],
+            ),
+        ];
+        fixture.assert_cases(cases);
+    }
+
+    #[test]
+    fn test_monotonic_skipping() {
+        use std::ops::{Add, Div, Mul, Sub};
+
+        let fixture = SkippingFixture::new(
+            single_column_schema("i", DataType::INTEGER),
+            &[
+                r#"{"numRecords":2,"minValues":{"i":1},"maxValues":{"i":10}}"#,
+                r#"{"numRecords":2,"minValues":{"i":20},"maxValues":{"i":30}}"#,
+            ],
+        );
+        let i = || Expr::column("i");
+        let lit = |value: i32| Expr::literal(value);
+
+        let cases = [
+            (i().mul(lit(2)).gt(lit(30)), vec![false, true]),
+            (i().sub(lit(3)).lt(lit(7)), vec![true, false]),
+            (i().mul(lit(-2)).gt(lit(-10)), vec![true, false]),
+            (i().div(lit(10)).eq(lit(1)), vec![true, false]),
+            // a literal wider than the column
+            (
+                i().add(Expr::literal(1_i64)).gt(Expr::literal(15_i64)),
+                vec![false, true],
+            ),
+        ];
+        fixture.assert_cases(cases);
+
+        // arithmetic with another column can't skip
+        let predicate = i().mul(i()).gt(lit(30));
+        assert!(fixture.filter(&predicate).is_none());
+    }
+
+    #[test]
+    fn test_cast_skipping() {
+        let table_schema = Arc::new(StructType::new(vec![
+            StructField::new("i", DataType::INTEGER, true),
+            StructField::new("l", DataType::LONG, true),
+        ]));
+        let fixture = SkippingFixture::new(
+            table_schema,
+            &[
+                r#"{"numRecords":2,"minValues":{"i":1,"l":1},"maxValues":{"i":10,"l":10}}"#,
+                r#"{"numRecords":2,"minValues":{"i":20,"l":20},"maxValues":{"i":30,"l":30}}"#,
+            ],
+        );
+        let i = || Expr::column("i");
+        let double = |value: f64| Expr::literal(Scalar::Double(value));
+
+        // widening casts skip on the stats of the column
+        let cases = [
+            (
+                i().cast(DataType::LONG).gt(Expr::literal(15_i64)),
+                vec![false, true],
+            ),
+            (
+                Expr::literal(15_i64).gt(i().cast(DataType::LONG)),
+                vec![true, false],
+            ),
+            (
+                i().cast(DataType::DOUBLE).lt_eq(double(10.0)),
+                vec![true, false],
+            ),
+            (
+                i().cast(DataType::LONG)
+                    .in_list([Expr::literal(25_i64), Expr::literal(40_i64)]),
+                vec![false, true],
+            ),
+            (
+                Expr::between(
+                    i().cast(DataType::decimal(12, 2)),
+                    Expr::literal(Scalar::Decimal(1500, 12, 2)),
+                    Expr::literal(Scalar::Decimal(1900, 12, 2)),
+                ),
+                vec![false, false],
+            ),
+        ];
+        fixture.assert_cases(cases);
+
+        // narrowing casts (which may overflow) and literals that the column's type can't
+        // represent exactly can't skip
+        let cases = [
+            Expr::column("l")
+                .cast(DataType::INTEGER)
+                .gt(Expr::literal(15)),
+            Expr::column("l").cast(DataType::DOUBLE).gt(double(15.0)),
+            i().cast(DataType::DOUBLE).gt(double(15.5)),
+        ];
+        for predicate in cases {
+            assert!(fixture.filter(&predicate).is_none(), "{predicate}");
+        }
+    }
+
+    #[test]
+    fn test_decimal_skipping() {
+        let fixture = SkippingFixture::new(
+            single_column_schema("price", DataType::decimal(10, 2)),
+            &[
+                r#"{"numRecords":2,"minValues":{"price":1.25},"maxValues":{"price":10.25}}"#,
+                r#"{"numRecords":2,"minValues":{"price":10.5},"maxValues":{"price":20.75}}"#,
+            ],
+        );
+        let decimal =
+            |value, precision, scale| Expr::literal(Scalar::Decimal(value, precision, scale));
+
+        let cases = [
+            (
+                Expr::column("price").gt(decimal(105, 3, 1)),
+                vec![false, true],
+            ),
+            (
+                Expr::column("price").lt(decimal(105000, 6, 4)),
+                vec![true, false],
+            ),
+            (
+                Expr::column("price").eq(decimal(1025, 4, 2)),
+                vec![true, false],
+            ),
+        ];
+        fixture.assert_cases(cases);
+
+        // a literal that can't be represented exactly can't be used for skipping
+        let predicate = Expr::column("price").gt(decimal(305, 3, 3));
+        assert!(fixture.filter(&predicate).is_none());
+    }
+
+    #[test]
+    fn test_timestamp_and_date_skipping() {
+        let table_schema = Arc::new(StructType::new(vec![
+            StructField::new("ts", DataType::TIMESTAMP, true),
+            StructField::new("ts_ntz", DataType::TIMESTAMP_NTZ, true),
+            StructField::new("d", DataType::DATE, true),
+        ]));
+        // stats store timestamps with millisecond precision
+        let fixture = SkippingFixture::new(
+            table_schema,
+            &[
+                r#"{"numRecords":2,"minValues":{"ts":"2024-01-01T00:00:00.000Z","ts_ntz":"2024-01-01T00:00:00.000","d":"2024-01-01"},"maxValues":{"ts":"2024-01-01T12:00:00.123Z","ts_ntz":"2024-01-01T12:00:00.123","d":"2024-01-31"}}"#,
+                r#"{"numRecords":2,"minValues":{"ts":"2024-02-01T00:00:00.000Z","ts_ntz":"2024-02-01T00:00:00.000","d":"2024-02-01"},"maxValues":{"ts":"2024-02-01T12:00:00.000Z","ts_ntz":"2024-02-01T12:00:00.000","d":"2024-02-29"}}"#,
+            ],
+        );
+        let timestamp = |raw| PrimitiveType::Timestamp.parse_scalar(raw).unwrap();
+        let date = |raw| PrimitiveType::Date.parse_scalar(raw).unwrap();
+
+        let cases = [
+            (
+                Expr::column("ts").lt(Expr::literal(timestamp("2024-01-15 00:00:00"))),
+                vec![true, false],
+            ),
+            (
+                Expr::column("ts").gt_eq(Expr::literal(timestamp("2024-01-15 00:00:00"))),
+                vec![false, true],
+            ),
+            (
+                Expr::between(
+                    Expr::column("ts"),
+                    Expr::literal(timestamp("2024-02-01 06:00:00")),
+                    Expr::literal(timestamp("2024-02-02 00:00:00")),
+                ),
+                vec![false, true],
+            ),
+            // the max stat is truncated to milliseconds, so the file may contain a larger value
+            (
+                Expr::column("ts").gt(Expr::literal(timestamp("2024-01-01 12:00:00.123"))),
+                vec![true, true],
+            ),
+            (
+                Expr::column("ts").gt(Expr::literal(timestamp("2024-01-01 12:00:00.124"))),
+                vec![false, true],
+            ),
+            (
+                Expr::column("ts_ntz").lt(Expr::literal(timestamp("2024-01-15 00:00:00"))),
+                vec![true, false],
+            ),
+            (
+                Expr::column("ts_ntz").eq(Expr::literal(timestamp("2024-02-01 12:00:00.000500"))),
+                vec![false, true],
+            ),
+            (
+                Expr::column("ts_ntz").eq(Expr::literal(timestamp("2024-02-01 12:00:00.001"))),
+                vec![false, false],
+            ),
+            (
+                Expr::column("ts_ntz").not_in_list([
+                    Expr::literal(timestamp("2024-01-01 00:00:00")),
+                    Expr::literal(timestamp("2024-02-01 00:00:00")),
+                ]),
+                vec![true, true],
+            ),
+            (
+                Expr::column("d").gt(Expr::literal(date("2024-01-31"))),
+                vec![false, true],
+            ),
+            (
+                Expr::column("d").eq(Expr::literal(date("2024-01-31"))),
+                vec![true, false],
+            ),
+            (
+                Expr::between(
+                    Expr::column("d"),
+                    Expr::literal(date("2023-01-01")),
+                    Expr::literal(date("2023-12-31")),
+                ),
+                vec![false, false],
+            ),
+        ];
+        fixture.assert_cases(cases);
+    }
+
+    #[test]
+    fn test_typed_literal_skipping() {
+        use chrono::{NaiveDate, NaiveDateTime, TimeZone, Utc};
+
+        let table_schema = Arc::new(StructType::new(vec![
+            StructField::new("price", DataType::decimal(10, 2), true),
+            StructField::new("d", DataType::DATE, true),
+            StructField::new("ts", DataType::TIMESTAMP, true),
+            StructField::new("ts_ntz", DataType::TIMESTAMP_NTZ, true),
+        ]));
+        let fixture = SkippingFixture::new(
+            table_schema,
+            &[
+                r#"{"numRecords":2,"minValues":{"price":1.25,"d":"2024-01-01","ts":"2024-01-01T00:00:00.000Z","ts_ntz":"2024-01-01T00:00:00.000"},"maxValues":{"price":10.25,"d":"2024-01-31","ts":"2024-01-31T00:00:00.000Z","ts_ntz":"2024-01-31T00:00:00.000"}}"#,
+                r#"{"numRecords":2,"minValues":{"price":10.5,"d":"2024-02-01","ts":"2024-02-01T00:00:00.000Z","ts_ntz":"2024-02-01T00:00:00.000"},"maxValues":{"price":20.75,"d":"2024-02-29","ts":"2024-02-29T00:00:00.000Z","ts_ntz":"2024-02-29T00:00:00.000"}}"#,
+            ],
+        );
+        let date = |day| NaiveDate::from_ymd_opt(2024, 2, day).unwrap();
+        let timestamp = |day| -> NaiveDateTime { date(day).and_hms_opt(0, 0, 0).unwrap() };
+
+        let cases = [
+            (
+                Expr::column("price").gt(Expr::literal(Scalar::decimal(1050, 6, 2).unwrap())),
+                vec![false, true],
+            ),
+            (
+                Expr::column("price").eq(Expr::literal(Scalar::decimal(1025, 4, 2).unwrap())),
+                vec![true, false],
+            ),
+            (
+                Expr::column("d").lt(Expr::literal(Scalar::date(19754))),
+                vec![true, false],
+            ),
+            (
+                Expr::column("d").gt_eq(Expr::literal(date(1))),
+                vec![false, true],
+            ),
+            (
+                Expr::column("ts").lt(Expr::literal(Utc.from_utc_datetime(&timestamp(1)))),
+                vec![true, false],
+            ),
+            (
+                Expr::column("ts").gt(Expr::literal(Scalar::timestamp(0))),
+                vec![true, true],
+            ),
+            (
+                Expr::column("ts_ntz").gt_eq(Expr::literal(timestamp(15))),
+                vec![false, true],
+            ),
+            (
+                Expr::column("ts_ntz").eq(Expr::literal(Scalar::timestamp_ntz(0))),
+                vec![false, false],
+            ),
+        ];
+        fixture.assert_cases(cases);
+    }
+
+    #[test]
+    fn test_absent_column_skipping() {
+        let table_schema = Arc::new(StructType::new(vec![
+            StructField::new("a", DataType::INTEGER, true),
+            StructField::new(
+                "s",
+                StructType::new(vec![StructField::new("x", DataType::INTEGER, true)]),
+                true,
+            ),
+        ]));
+        let fixture = SkippingFixture::new(
+            table_schema,
+            &[
+                r#"{"numRecords":1,"minValues":{"a":1,"s":{"x":1}},"maxValues":{"a":4,"s":{"x":4}}}"#,
+                r#"{"numRecords":1,"minValues":{"a":5,"s":{"x":5}},"maxValues":{"a":8,"s":{"x":8}}}"#,
+            ],
+        );
+        let apply = |predicate: Expr| {
+            fixture
+                .filter(&predicate)
+                .map(|filter| filter.apply(fixture.actions.as_ref()).unwrap())
+        };
+
+        // the conjunct on the absent column is unknown, so the other one still skips
+        for absent in [Expr::column("b"), Expr::column("s.y"), Expr::column("a.b")] {
+            let predicate = Expr::and(
+                absent.clone().gt(Expr::literal(100)),
+                Expr::column("a").lt(Expr::literal(5)),
+            );
+            assert_eq!(apply(predicate), Some(vec![true, false]), "{absent}");
+
+            // a disjunction with an unknown operand can't skip anything, so there is no filter
+            let predicate = Expr::or(
+                absent.clone().gt(Expr::literal(100)),
+                Expr::column("s.x").lt(Expr::literal(5)),
+            );
+            assert_eq!(apply(predicate), None, "{absent}");
+        }
+
+        // no filter at all if the predicate only references absent columns
+        assert_eq!(apply(Expr::column("b").gt(Expr::literal(100))), None);
+    }
+
+    #[test]
+    fn test_folded_predicate_skipping() {
+        let fixture = SkippingFixture::new(
+            single_column_schema("a", DataType::INTEGER),
+            &[
+                r#"{"numRecords":1,"minValues":{"a":1},"maxValues":{"a":3}}"#,
+                r#"{"numRecords":1,"minValues":{"a":5},"maxValues":{"a":7}}"#,
+            ],
+        );
+        let predicate = (Expr::literal(1) + Expr::literal(2)).lt(Expr::column("a"));
+        assert_eq!(fixture.apply(&predicate), vec![false, true]);
+    }
+}
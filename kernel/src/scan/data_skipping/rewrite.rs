@@ -0,0 +1,2015 @@
+//! Rewriting of predicates into predicates over the stats of files
+
+use std::cmp::Ordering;
+
+use crate::expressions::{
+    BinaryOperator, Expression as Expr, Scalar, UnaryOperator, VariadicOperator,
+};
+
+use super::referenced_stat_kinds;
+
+/// Returns true if `value` is a floating point NaN.
+fn is_nan(value: &Scalar) -> bool {
+    match value {
+        Scalar::Float(value) => value.is_nan(),
+        Scalar::Double(value) => value.is_nan(),
+        _ => false,
+    }
+}
+
+/// Makes a comparison between the stat `stats_col` and a floating point literal keep files whose
+/// stat is NaN. Writers don't agree on how NaN values affect min/max stats (e.g. a NaN min could
+/// hide smaller values), so a NaN stat doesn't bound the values of a file. Like the default
+/// engine, this relies on NaN being equal to NaN. Comparisons with other literals are returned
+/// unchanged.
+pub(crate) fn nan_safe(comparison: Expr, stats_col: &str, value: &Scalar) -> Expr {
+    let nan = match value {
+        Scalar::Float(_) => Scalar::Float(f32::NAN),
+        Scalar::Double(_) => Scalar::Double(f64::NAN),
+        _ => return comparison,
+    };
+    Expr::or_from([
+        comparison,
+        Expr::eq(Expr::column(stats_col), Expr::literal(nan)),
+    ])
+}
+
+/// Rewrites `col op val` for a `>`, `>=` or `=` comparison as a check of the max stat of `col`,
+/// i.e. whether the file may contain a value greater than (or equal to) `val`.
+fn get_max_check(op: BinaryOperator, col: &str, val: &Scalar) -> Expr {
+    let max_col = format!("maxValues.{}", col);
+    match (op, val) {
+        // The actual max may be greater than a truncated max stat, but it starts with it
+        (_, Scalar::String(value)) => Expr::ge(
+            Expr::column(max_col),
+            Expr::literal(truncated_string_stat(value)),
+        ),
+        (BinaryOperator::GreaterThan, _) => nan_safe(
+            Expr::gt(Expr::column(max_col.clone()), Expr::literal(val.clone())),
+            &max_col,
+            val,
+        ),
+        _ => nan_safe(
+            Expr::ge(Expr::column(max_col.clone()), Expr::literal(val.clone())),
+            &max_col,
+            val,
+        ),
+    }
+}
+
+/// Writers may truncate the min/max stats of string columns to this many characters, as Delta's
+/// `delta.dataSkippingStringPrefixLength` does by default.
+pub(crate) const STRING_STATS_PREFIX_LENGTH: usize = 32;
+
+/// Returns the prefix of `value` that a max stat would be truncated to.
+///
+/// A truncated min stat is a prefix of the actual min, and so still a lower bound of the values of
+/// a file. A truncated max stat is a prefix of the actual max, but may be smaller than it, so it
+/// only bounds the values of a file up to its length: a file can only contain a value greater
+/// than (or equal to) `value` if its max stat is at least this prefix of `value`. Writers that
+/// instead round a truncated max up (e.g. by appending a tie-breaker character) only make this
+/// check more conservative.
+pub(crate) fn truncated_string_stat(value: &str) -> String {
+    value.chars().take(STRING_STATS_PREFIX_LENGTH).collect()
+}
+
+/// Whether `value` is a string that is long enough that a min/max stat equal to it may have been
+/// truncated from a longer value.
+fn is_truncatable_string(value: &Scalar) -> bool {
+    matches!(value, Scalar::String(value) if value.chars().count() >= STRING_STATS_PREFIX_LENGTH)
+}
+
+/// Returns the literal prefix that all strings matching the regular expression `pattern` start
+/// with, or `None` if the pattern isn't anchored at the start with `^` or begins with anything
+/// other than a literal.
+///
+/// This is deliberately conservative: the prefix ends at the first metacharacter or escape
+/// sequence other than an escaped metacharacter, a character that a quantifier may
+/// repeat zero times is dropped, and patterns with an alternation are not supported at all since
+/// `^a|b` only anchors its first branch.
+fn regex_literal_prefix(pattern: &str) -> Option<String> {
+    let rest = pattern.strip_prefix('^')?;
+    let mut chars = rest.chars().peekable();
+    let mut escaped = false;
+    if rest.chars().any(|c| {
+        let is_alternation = c == '|' && !escaped;
+        escaped = c == '\\' && !escaped;
+        is_alternation
+    }) {
+        return None;
+    }
+    let mut prefix = String::new();
+    while let Some(c) = chars.next() {
+        let literal = match c {
+            '\\' => match chars.next() {
+                Some(c) if r"\.+*?()|[]{}^$".contains(c) => c,
+                _ => break, // an escape sequence like `\d` or `\<`
+            },
+            '.' | '[' | '(' | ')' | '^' | '$' | '*' | '+' | '?' | '{' => break,
+            c => c,
+        };
+        if matches!(chars.peek(), Some('*' | '?' | '{')) {
+            break; // the character may not be repeated at all
+        }
+        prefix.push(literal);
+    }
+    (!prefix.is_empty()).then_some(prefix)
+}
+
+/// Returns the smallest string that is greater than every string starting with `prefix`, or `None`
+/// if there is no such string (e.g. because the prefix is empty).
+///
+/// The last character of the prefix is replaced by its successor, which makes the result greater
+/// than all strings that start with the prefix, and smaller than or equal to all other strings
+/// that are greater than the prefix. A last character that has no successor is dropped instead,
+/// carrying the increment over to the previous character. Ordering strings by code point is the
+/// same as ordering them by their UTF-8 bytes, so this matches how string stats are compared.
+fn increment_prefix(prefix: &str) -> Option<String> {
+    let mut chars: Vec<char> = prefix.chars().collect();
+    while let Some(last) = chars.pop() {
+        let successor = match last {
+            // skip over the surrogate code points, which are not valid chars
+            '\u{D7FF}' => Some('\u{E000}'),
+            _ => char::from_u32(last as u32 + 1),
+        };
+        if let Some(successor) = successor {
+            chars.push(successor);
+            return Some(chars.into_iter().collect());
+        }
+    }
+    None
+}
+
+/// IN-lists with at most this many values are rewritten exactly, as a disjunction of equality
+/// comparisons. Longer lists are approximated by the range spanned by their values.
+pub(crate) const IN_LIST_EXPANSION_LIMIT: usize = 8;
+
+/// Returns the smallest and largest value of `values`, or `None` if the values are not comparable
+/// with each other (e.g. because they have different types).
+fn in_list_bounds<'a>(values: &[&'a Scalar]) -> Option<(&'a Scalar, &'a Scalar)> {
+    let (first, rest) = values.split_first()?;
+    rest.iter().try_fold((*first, *first), |(min, max), value| {
+        let min = match value.partial_cmp(&min)? {
+            Ordering::Less => *value,
+            _ => min,
+        };
+        let max = match value.partial_cmp(&max)? {
+            Ordering::Greater => *value,
+            _ => max,
+        };
+        Some((min, max))
+    })
+}
+
+/// Returns the literal values of an IN-list, or `None` if the list contains a non-literal value.
+fn in_list_values(list: &[Expr]) -> Option<Vec<&Scalar>> {
+    list.iter()
+        .map(|value| match value {
+            Expr::Literal(value) => Some(value),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Returns a predicate over the stats of `col` that is false for files without NULL values.
+fn get_is_null_predicate(col: &str) -> Expr {
+    Expr::gt(Expr::joined_column("nullCount", col), Expr::literal(0_i64))
+}
+
+/// Returns a predicate over the stats of `col` that is false for files with only NULL values.
+fn get_is_not_null_predicate(col: &str) -> Expr {
+    Expr::lt(
+        Expr::joined_column("nullCount", col),
+        Expr::column("numRecords"),
+    )
+}
+
+/// Rewrites a predicate to a predicate that can be used to skip files based on their stats.
+/// Returns `None` if the predicate is not eligible for data skipping.
+///
+/// We normalize each binary operation to a comparison between a column and a literal value
+/// and rewite that in terms of the min/max values of the column.
+/// For example, `1 < a` is rewritten as `minValues.a > 1`. A NULL literal can't be compared to
+/// the min/max values, so `a = NULL` is rewritten as `nullCount.a > 0` and `a != NULL` as
+/// `nullCount.a < numRecords` instead. The null-safe `a <=> NULL` is true for exactly the NULL
+/// values, so it is rewritten like `a = NULL`, while `a <=> value` is rewritten like `a = value`.
+/// Conversely, `a IS DISTINCT FROM NULL` is rewritten like `a != NULL`, while `a IS DISTINCT FROM
+/// value` is true for NULL values and values other than `value`, so it is rewritten as
+/// `nullCount.a > 0 OR minValues.a != value OR maxValues.a != value` (a string `value` must be
+/// shorter than truncated string stats, which can't prove that all values are `value`).
+/// Likewise, `a IS NULL` and `NOT a IS NOT NULL` are rewritten as `nullCount.a > 0`, and `a IS NOT
+/// NULL` and `NOT a IS NULL` as `nullCount.a < numRecords`.
+///
+/// `a != value` can only skip a file whose min and max are both `value`, so it is rewritten as
+/// `minValues.a != value OR maxValues.a != value`, like `NOT a IN (value)`. A file with values on
+/// both sides of `value` has a range that contains `value`, but also values other than it.
+///
+/// A negated comparison is rewritten like the inverted comparison, e.g. `NOT a < 5` like `a >= 5`,
+/// `NOT a = 5` like `a != 5` and `NOT a != 5` like `a = 5`. Both are NULL for NULL values of `a`,
+/// so they keep and skip the same rows, and thus the same files.
+///
+/// Booleans order false before true, so `a = true` is rewritten as `maxValues.a = true` and `a =
+/// false` as `minValues.a = false`, while `a != true` is rewritten like `a = false` (and vice
+/// versa).
+///
+/// Comparisons with a floating point literal also keep files whose min/max stat is NaN, e.g. `a <
+/// 1.5` is rewritten as `minValues.a < 1.5 OR minValues.a = NaN`, and comparisons with a NaN
+/// literal are not eligible for data skipping.
+///
+/// String min/max stats may be truncated (see [`truncated_string_stat`]), so `a > value` and `a >=
+/// value` on strings are both rewritten as `maxValues.a >= value'`, where `value'` is `value`
+/// truncated to the length of a max stat. The max check of `a STARTS WITH prefix` is likewise
+/// done with the truncated prefix.
+///
+/// The variadic operations are rewritten as follows:
+/// - `AND` is rewritten as a conjunction of the rewritten operands where we just skip
+///   operands that are not eligible for data skipping.
+/// - `OR` is rewritten only if all operands are eligible for data skipping. Otherwise,
+///   the whole OR expression is dropped.
+///
+/// A comparison between two columns is rewritten as a comparison between the range of values of
+/// both columns: `a < b` as `minValues.a < maxValues.b` and `a > b` as `maxValues.a >
+/// minValues.b` (likewise for `<=` and `>=`). Equality and inequality between columns are not
+/// eligible for data skipping, since overlapping ranges can't rule out (in)equal values.
+///
+/// `a STARTS WITH prefix` is rewritten as `maxValues.a >= prefix AND minValues.a < next_prefix`,
+/// where `next_prefix` is the smallest string greater than every string starting with `prefix`.
+///
+/// `a BETWEEN low AND high` is rewritten as `a >= low AND a <= high`, and `NOT a BETWEEN low AND
+/// high` as `a < low OR a > high`.
+///
+/// `a IN (list)` is rewritten as the disjunction of `a = value` for each value of a short list,
+/// and as `maxValues.a >= min(list) AND minValues.a <= max(list)` for a long list. `NOT a IN
+/// (list)` can only skip a file whose min and max are the same value from the list, so it is
+/// rewritten as `minValues.a != value OR maxValues.a != value` for each value of a short list.
+/// NULL values never match anything, so `a IN (list)` ignores them, while `NOT a IN (list)` is
+/// never true if the list contains a NULL.
+pub(crate) fn as_data_skipping_predicate(expr: &Expr) -> Option<Expr> {
+    use BinaryOperator::*;
+    use Expr::*;
+
+    match expr {
+        BinaryOperation { op, left, right } => {
+            let (op, col, val) = match (left.as_ref(), right.as_ref()) {
+                (Column(left), Column(right)) => {
+                    // Some value of `a` can only be smaller than some value of `b` if the smallest
+                    // value of `a` is smaller than the largest value of `b`, and vice versa.
+                    let (left_stats, right_stats) = match op {
+                        LessThan | LessThanOrEqual => ("minValues", "maxValues"),
+                        GreaterThan | GreaterThanOrEqual => ("maxValues", "minValues"),
+                        _ => return None, // unsupported operation
+                    };
+                    return Some(Expr::binary(
+                        op.clone(),
+                        Expr::joined_column(left_stats, left),
+                        Expr::joined_column(right_stats, right),
+                    ));
+                }
+                (Column(col), Literal(val)) => (op.clone(), col, val),
+                (Literal(val), Column(col)) => (op.commute()?, col, val),
+                _ => return None, // unsupported combination of operands
+            };
+            if let Scalar::Null(_) = val {
+                // The min/max values can't be compared to NULL, so check the null counts instead,
+                // like `a IS NULL` and `a IS NOT NULL` would.
+                return match op {
+                    Equal | NullSafeEqual => Some(get_is_null_predicate(col)),
+                    NotEqual | Distinct => Some(get_is_not_null_predicate(col)),
+                    _ => None, // unsupported operation
+                };
+            }
+            if is_nan(val) {
+                return None; // NaN doesn't compare like a number
+            }
+            if let (Equal | NullSafeEqual | NotEqual, Scalar::Boolean(value)) = (&op, val) {
+                // Booleans order false before true, so a file contains a true value iff its max is
+                // true, and a false value iff its min is false. A boolean other than `value` is
+                // `!value`.
+                let value = if op == NotEqual { !value } else { *value };
+                let stats_col = if value { "maxValues" } else { "minValues" };
+                return Some(Expr::eq(
+                    Expr::joined_column(stats_col, col),
+                    Expr::literal(value),
+                ));
+            }
+            let stats_col = match op {
+                LessThan | LessThanOrEqual => "minValues",
+                GreaterThan | GreaterThanOrEqual => "maxValues",
+                // NULL values never equal a non-NULL literal, so null-safe equality is the same as
+                // equality here
+                Equal | NullSafeEqual => {
+                    let min_col = format!("minValues.{}", col);
+                    let min_check = nan_safe(
+                        Expr::le(Column(min_col.clone()), Literal(val.clone())),
+                        &min_col,
+                        val,
+                    );
+                    return Some(Expr::and_from([min_check, get_max_check(op, col, val)]));
+                }
+                StartsWith => {
+                    // A file can only contain a string that starts with the prefix if its max is
+                    // not smaller than the prefix, and its min is smaller than the next prefix.
+                    let Scalar::String(prefix) = val else {
+                        return None; // unsupported prefix
+                    };
+                    let max_check = Expr::ge(
+                        Expr::joined_column("maxValues", col),
+                        Expr::literal(truncated_string_stat(prefix)),
+                    );
+                    return match increment_prefix(prefix) {
+                        Some(next_prefix) => Some(Expr::and_from([
+                            max_check,
+                            Expr::lt(
+                                Expr::joined_column("minValues", col),
+                                Expr::literal(next_prefix),
+                            ),
+                        ])),
+                        None => Some(max_check),
+                    };
+                }
+                // A pattern anchored at the start of the string only matches strings that start
+                // with its literal prefix, so it can skip like STARTS WITH the prefix.
+                RLike => {
+                    let Scalar::String(pattern) = val else {
+                        return None; // unsupported pattern
+                    };
+                    let prefix = regex_literal_prefix(pattern)?;
+                    return as_data_skipping_predicate(&Expr::binary(
+                        StartsWith,
+                        Column(col.clone()),
+                        Expr::literal(prefix),
+                    ));
+                }
+                // Only files whose values are all `val` can be skipped: a file with values on both
+                // sides of `val` has values other than `val`, even though its range contains it.
+                NotEqual => {
+                    if is_truncatable_string(val) {
+                        return None; // truncated stats can't prove that all values are `val`
+                    }
+                    return Some(Expr::or_from([
+                        Expr::ne(Expr::joined_column("minValues", col), Literal(val.clone())),
+                        Expr::ne(Expr::joined_column("maxValues", col), Literal(val.clone())),
+                    ]));
+                }
+                // Only files whose values are all `val` (and so have no NULL values) can be skipped
+                Distinct => {
+                    if is_truncatable_string(val) {
+                        return None; // truncated stats can't prove that all values are `val`
+                    }
+                    return Some(Expr::or_from([
+                        get_is_null_predicate(col),
+                        Expr::ne(Expr::joined_column("minValues", col), Literal(val.clone())),
+                        Expr::ne(Expr::joined_column("maxValues", col), Literal(val.clone())),
+                    ]));
+                }
+                _ => return None, // unsupported operation
+            };
+            if stats_col == "maxValues" {
+                return Some(get_max_check(op, col, val));
+            }
+            let col = format!("{}.{}", stats_col, col);
+            let predicate = Expr::binary(op, Column(col.clone()), Literal(val.clone()));
+            Some(nan_safe(predicate, &col, val))
+        }
+        InList { expr, list } => {
+            let Column(col) = expr.as_ref() else {
+                return None; // unsupported lookup expression
+            };
+            let values: Vec<_> = in_list_values(list)?
+                .into_iter()
+                .filter(|value| !matches!(value, Scalar::Null(_)))
+                .collect();
+            if values.is_empty() {
+                // `a IN ()` is always false, so every file can be skipped
+                return Some(Expr::literal(false));
+            }
+            if values.len() <= IN_LIST_EXPANSION_LIMIT {
+                let exprs = values
+                    .into_iter()
+                    .map(|value| Expr::eq(Column(col.clone()), Literal(value.clone())));
+                return as_data_skipping_predicate(&Expr::or_from(exprs));
+            }
+            let (min, max) = in_list_bounds(&values)?;
+            let exprs = [
+                Expr::ge(Column(col.clone()), Literal(min.clone())),
+                Expr::le(Column(col.clone()), Literal(max.clone())),
+            ];
+            as_data_skipping_predicate(&Expr::and_from(exprs))
+        }
+        Between { expr, low, high } => {
+            let exprs = [
+                Expr::ge(expr.as_ref().clone(), low.as_ref().clone()),
+                Expr::le(expr.as_ref().clone(), high.as_ref().clone()),
+            ];
+            as_data_skipping_predicate(&Expr::and_from(exprs))
+        }
+        UnaryOperation {
+            op: op @ (UnaryOperator::IsNull | UnaryOperator::IsNotNull),
+            expr,
+        } => {
+            let Column(col) = expr.as_ref() else {
+                return None; // unsupported operand
+            };
+            match op {
+                UnaryOperator::IsNull => Some(get_is_null_predicate(col)),
+                _ => Some(get_is_not_null_predicate(col)),
+            }
+        }
+        UnaryOperation {
+            op: UnaryOperator::Not,
+            expr,
+        } => match expr.as_ref() {
+            // `NOT NOT a` is `a`, and `NOT a IS NULL` is `a IS NOT NULL` (and vice versa)
+            UnaryOperation { op, expr } => match op.invert() {
+                Some(op) => as_data_skipping_predicate(&Expr::unary(op, expr.as_ref().clone())),
+                None => as_data_skipping_predicate(expr),
+            },
+            BinaryOperation { op, left, right } => as_data_skipping_predicate(&Expr::binary(
+                op.invert()?,
+                left.as_ref().clone(),
+                right.as_ref().clone(),
+            )),
+            Between { expr, low, high } => {
+                let exprs = [
+                    Expr::lt(expr.as_ref().clone(), low.as_ref().clone()),
+                    Expr::gt(expr.as_ref().clone(), high.as_ref().clone()),
+                ];
+                as_data_skipping_predicate(&Expr::or_from(exprs))
+            }
+            InList { expr, list } => {
+                let Column(col) = expr.as_ref() else {
+                    return None; // unsupported lookup expression
+                };
+                let values = in_list_values(list)?;
+                if values.iter().any(|value| matches!(value, Scalar::Null(_))) {
+                    // `a NOT IN (.., NULL, ..)` is never true, so every file can be skipped
+                    return Some(Expr::literal(false));
+                }
+                if values.len() > IN_LIST_EXPANSION_LIMIT {
+                    return None; // too expensive to check for single-valued files
+                }
+                // NOTE: `a NOT IN ()` is always true, and so is the empty conjunction
+                let exprs = values.into_iter().map(|value| {
+                    Expr::or_from([
+                        Expr::ne(
+                            Expr::joined_column("minValues", col),
+                            Literal(value.clone()),
+                        ),
+                        Expr::ne(
+                            Expr::joined_column("maxValues", col),
+                            Literal(value.clone()),
+                        ),
+                    ])
+                });
+                Some(Expr::and_from(exprs))
+            }
+            _ => None,
+        },
+        VariadicOperation {
+            op: op @ VariadicOperator::And,
+            exprs,
+        } => Some(VariadicOperation {
+            op: op.clone(),
+            exprs: exprs
+                .iter()
+                .filter_map(as_data_skipping_predicate)
+                .collect::<Vec<_>>(),
+        }),
+        VariadicOperation {
+            op: op @ VariadicOperator::Or,
+            exprs,
+        } => Some(VariadicOperation {
+            op: op.clone(),
+            exprs: exprs
+                .iter()
+                .map(as_data_skipping_predicate)
+                .collect::<Option<Vec<_>>>()?,
+        }),
+        _ => None,
+    }
+}
+
+/// Like [`as_data_skipping_predicate`], but only rewrites predicates that can be decided by the
+/// `nullCount` and `numRecords` stats alone, e.g. `a IS NULL` or `a != NULL`, so that the stats
+/// schema doesn't need the min/max values. Other operands of an `AND` are dropped, while an `OR`
+/// with such an operand is not eligible as a whole.
+pub(crate) fn as_null_skipping_predicate(expr: &Expr) -> Option<Expr> {
+    match expr {
+        Expr::VariadicOperation {
+            op: VariadicOperator::And,
+            exprs,
+        } => Some(Expr::and_from(
+            exprs.iter().filter_map(as_null_skipping_predicate),
+        )),
+        Expr::VariadicOperation {
+            op: VariadicOperator::Or,
+            exprs,
+        } => Some(Expr::or_from(
+            exprs
+                .iter()
+                .map(as_null_skipping_predicate)
+                .collect::<Option<Vec<_>>>()?,
+        )),
+        _ => as_data_skipping_predicate(expr).filter(|skipping_predicate| {
+            referenced_stat_kinds(skipping_predicate)
+                .iter()
+                .all(|kind| matches!(*kind, "numRecords" | "nullCount"))
+        }),
+    }
+}
+
+/// Explains why [`as_data_skipping_predicate`] can't rewrite `expr` (see
+/// [`DataSkippingFilter::analyze`](super::DataSkippingFilter::analyze)).
+pub(crate) fn ineligibility_reason(expr: &Expr) -> String {
+    use BinaryOperator::*;
+    use Expr::*;
+
+    let in_list_reason = |expr: &Expr, list: &[Expr]| match (expr, in_list_values(list)) {
+        (Column(_), None) => "IN list with values that are not literals".to_string(),
+        (Column(_), Some(_)) => "IN list with values that are not comparable".to_string(),
+        _ => "IN on an expression that is not a column".to_string(),
+    };
+    match expr {
+        Literal(_) => "constant predicate".to_string(),
+        BinaryOperation { op, left, right } => match (left.as_ref(), right.as_ref()) {
+            (Column(_), Column(_)) => format!("column-to-column comparison with {op}"),
+            (Column(_), Literal(val)) | (Literal(val), Column(_)) => match (op, val) {
+                (_, Scalar::Null(_)) => format!("{op} comparison with NULL"),
+                (_, val) if is_nan(val) => "comparison with NaN".to_string(),
+                (StartsWith, _) => "STARTS WITH a prefix that is not a string".to_string(),
+                (RLike, _) => "RLIKE with a pattern that has no literal prefix".to_string(),
+                (Distinct, val) if is_truncatable_string(val) => {
+                    "IS DISTINCT FROM a string as long as truncated stats".to_string()
+                }
+                _ => format!("unsupported operator {op}"),
+            },
+            _ => "operands are not a column and a literal".to_string(),
+        },
+        InList { expr, list } => in_list_reason(expr, list),
+        Quantified { quantifier, .. } => {
+            format!("{quantifier} comparison with values that are not literals")
+        }
+        Between { expr, low, .. } => {
+            ineligibility_reason(&Expr::ge(expr.as_ref().clone(), low.as_ref().clone()))
+        }
+        UnaryOperation {
+            op: UnaryOperator::Not,
+            expr,
+        } => match expr.as_ref() {
+            BinaryOperation { op, left, right } => match op.invert() {
+                Some(op) => ineligibility_reason(&Expr::binary(
+                    op,
+                    left.as_ref().clone(),
+                    right.as_ref().clone(),
+                )),
+                None => format!("negation of {op}"),
+            },
+            Between { expr, low, .. } => {
+                ineligibility_reason(&Expr::lt(expr.as_ref().clone(), low.as_ref().clone()))
+            }
+            InList { expr, list } => match (expr.as_ref(), in_list_values(list)) {
+                (Column(_), Some(_)) => {
+                    format!("NOT IN list with more than {IN_LIST_EXPANSION_LIMIT} values")
+                }
+                _ => in_list_reason(expr, list),
+            },
+            UnaryOperation { .. } => "operand is not a column".to_string(),
+            _ => "unsupported negation".to_string(),
+        },
+        UnaryOperation { .. } => "operand is not a column".to_string(),
+        VariadicOperation {
+            op: VariadicOperator::Or,
+            exprs,
+        } => match exprs
+            .iter()
+            .find(|expr| as_data_skipping_predicate(expr).is_none())
+        {
+            Some(expr) => format!(
+                "operand of OR is ineligible: {}",
+                ineligibility_reason(expr)
+            ),
+            None => "unsupported expression".to_string(),
+        },
+        _ => "unsupported expression".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::scan::test_utils::{
+        assert_skipping_sound, nested_table_schema, single_column_schema, SkippingFixture,
+    };
+    use crate::schema::{DataType, StructField, StructType};
+
+    #[test]
+    fn test_rewrite_distinct() {
+        let column = Expr::column("a");
+        let lit_int = Expr::literal(5_i32);
+        let expected = Expr::or_from([
+            Expr::gt(Expr::column("nullCount.a"), Expr::literal(0_i64)),
+            Expr::ne(Expr::column("minValues.a"), lit_int.clone()),
+            Expr::ne(Expr::column("maxValues.a"), lit_int.clone()),
+        ]);
+        for predicate in [
+            column.clone().distinct(lit_int.clone()),
+            lit_int.clone().distinct(column.clone()),
+            !column.clone().null_safe_eq(lit_int.clone()),
+        ] {
+            assert_eq!(
+                as_data_skipping_predicate(&predicate),
+                Some(expected.clone()),
+                "{predicate}"
+            );
+        }
+
+        let null = Expr::literal(Scalar::Null(DataType::INTEGER));
+        assert_eq!(
+            as_data_skipping_predicate(&column.clone().distinct(null)),
+            Some(Expr::lt(
+                Expr::column("nullCount.a"),
+                Expr::column("numRecords")
+            ))
+        );
+
+        // a max stat equal to a long string may be the prefix of a larger value
+        let long_string = Expr::literal("x".repeat(STRING_STATS_PREFIX_LENGTH));
+        assert_eq!(
+            as_data_skipping_predicate(&Expr::column("s").distinct(long_string)),
+            None
+        );
+    }
+
+    #[test]
+    fn test_rewrite_nested_equality() {
+        let lit_int = Expr::literal(1_i32);
+        let expected = Expr::and_from([
+            Expr::le(Expr::column("minValues.a.b"), lit_int.clone()),
+            Expr::ge(Expr::column("maxValues.a.b"), lit_int.clone()),
+        ]);
+        for predicate in [
+            Expr::column("a.b").eq(lit_int.clone()),
+            lit_int.clone().eq(Expr::column("a.b")),
+        ] {
+            assert_eq!(
+                as_data_skipping_predicate(&predicate),
+                Some(expected.clone())
+            );
+        }
+    }
+
+    #[test]
+    fn test_rewrite_negated_comparison() {
+        let column = Expr::column("a");
+        let lit_int = Expr::literal(5_i32);
+        let cases = [
+            (
+                !column.clone().ne(lit_int.clone()),
+                column.clone().eq(lit_int.clone()),
+            ),
+            (
+                !column.clone().eq(lit_int.clone()),
+                column.clone().ne(lit_int.clone()),
+            ),
+            (
+                !lit_int.clone().eq(column.clone()),
+                column.clone().ne(lit_int.clone()),
+            ),
+            (
+                !column.clone().lt(lit_int.clone()),
+                column.clone().gt_eq(lit_int.clone()),
+            ),
+            (
+                !column.clone().distinct(lit_int.clone()),
+                column.clone().null_safe_eq(lit_int.clone()),
+            ),
+            (
+                !column.clone().lt(Expr::column("b")),
+                column.clone().gt_eq(Expr::column("b")),
+            ),
+        ];
+        for (negated, inverted) in cases {
+            let rewritten = as_data_skipping_predicate(&negated);
+            assert!(rewritten.is_some(), "{negated}");
+            assert_eq!(
+                rewritten,
+                as_data_skipping_predicate(&inverted),
+                "{negated}"
+            );
+        }
+        assert_eq!(
+            as_data_skipping_predicate(&!column.clone().ne(lit_int.clone())),
+            Some(Expr::and_from([
+                Expr::le(Expr::column("minValues.a"), lit_int.clone()),
+                Expr::ge(Expr::column("maxValues.a"), lit_int.clone()),
+            ]))
+        );
+        assert_eq!(
+            as_data_skipping_predicate(&!column.clone().eq(lit_int.clone())),
+            Some(Expr::or_from([
+                Expr::ne(Expr::column("minValues.a"), lit_int.clone()),
+                Expr::ne(Expr::column("maxValues.a"), lit_int.clone()),
+            ]))
+        );
+
+        // negated NULL checks and double negations
+        let cases = [
+            (!column.clone().is_null(), column.clone().is_not_null()),
+            (!column.clone().is_not_null(), column.clone().is_null()),
+            (
+                !!column.clone().lt(lit_int.clone()),
+                column.clone().lt(lit_int.clone()),
+            ),
+        ];
+        for (negated, inverted) in cases {
+            let rewritten = as_data_skipping_predicate(&negated);
+            assert!(rewritten.is_some(), "{negated}");
+            assert_eq!(
+                rewritten,
+                as_data_skipping_predicate(&inverted),
+                "{negated}"
+            );
+        }
+
+        // operations without an inverse are not eligible
+        let predicate = !Expr::column("s").starts_with(Expr::literal("x"));
+        assert_eq!(as_data_skipping_predicate(&predicate), None);
+    }
+
+    #[test]
+    fn test_rewrite_basic_comparison() {
+        let column = Expr::column("a");
+        let lit_int = Expr::literal(1_i32);
+        let min_col = Expr::column("minValues.a");
+        let max_col = Expr::column("maxValues.a");
+
+        let cases = [
+            (
+                column.clone().lt(lit_int.clone()),
+                Expr::lt(min_col.clone(), lit_int.clone()),
+            ),
+            (
+                lit_int.clone().lt(column.clone()),
+                Expr::gt(max_col.clone(), lit_int.clone()),
+            ),
+            (
+                column.clone().gt(lit_int.clone()),
+                Expr::gt(max_col.clone(), lit_int.clone()),
+            ),
+            (
+                lit_int.clone().gt(column.clone()),
+                Expr::lt(min_col.clone(), lit_int.clone()),
+            ),
+            (
+                column.clone().lt_eq(lit_int.clone()),
+                Expr::le(min_col.clone(), lit_int.clone()),
+            ),
+            (
+                lit_int.clone().lt_eq(column.clone()),
+                Expr::ge(max_col.clone(), lit_int.clone()),
+            ),
+            (
+                column.clone().gt_eq(lit_int.clone()),
+                Expr::ge(max_col.clone(), lit_int.clone()),
+            ),
+            (
+                lit_int.clone().gt_eq(column.clone()),
+                Expr::le(min_col.clone(), lit_int.clone()),
+            ),
+            (
+                column.clone().eq(lit_int.clone()),
+                Expr::and_from([
+                    Expr::le(min_col.clone(), lit_int.clone()),
+                    Expr::ge(max_col.clone(), lit_int.clone()),
+                ]),
+            ),
+            (
+                lit_int.clone().eq(column.clone()),
+                Expr::and_from([
+                    Expr::le(min_col.clone(), lit_int.clone()),
+                    Expr::ge(max_col.clone(), lit_int.clone()),
+                ]),
+            ),
+            (
+                column.clone().ne(lit_int.clone()),
+                Expr::or_from([
+                    Expr::ne(min_col.clone(), lit_int.clone()),
+                    Expr::ne(max_col.clone(), lit_int.clone()),
+                ]),
+            ),
+            (
+                lit_int.clone().ne(column.clone()),
+                Expr::or_from([
+                    Expr::ne(min_col.clone(), lit_int.clone()),
+                    Expr::ne(max_col.clone(), lit_int.clone()),
+                ]),
+            ),
+        ];
+
+        for (input, expected) in cases {
+            let rewritten = as_data_skipping_predicate(&input).unwrap();
+            assert_eq!(rewritten, expected)
+        }
+    }
+
+    #[test]
+    fn test_rewrite_column_comparison() {
+        let (a, b) = (Expr::column("a"), Expr::column("b"));
+        let cases = [
+            (
+                a.clone().lt(b.clone()),
+                Expr::lt(Expr::column("minValues.a"), Expr::column("maxValues.b")),
+            ),
+            (
+                a.clone().lt_eq(b.clone()),
+                Expr::le(Expr::column("minValues.a"), Expr::column("maxValues.b")),
+            ),
+            (
+                a.clone().gt(b.clone()),
+                Expr::gt(Expr::column("maxValues.a"), Expr::column("minValues.b")),
+            ),
+            (
+                a.clone().gt_eq(b.clone()),
+                Expr::ge(Expr::column("maxValues.a"), Expr::column("minValues.b")),
+            ),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(
+                as_data_skipping_predicate(&input),
+                Some(expected),
+                "{input}"
+            );
+        }
+
+        // (in)equality between columns can't be answered from ranges
+        for input in [a.clone().eq(b.clone()), a.ne(b)] {
+            assert_eq!(as_data_skipping_predicate(&input), None, "{input}");
+        }
+    }
+
+    #[test]
+    fn test_rewrite_float_comparison() {
+        let column = Expr::column("a");
+        let cases = [
+            (
+                column.clone().lt(Expr::literal(Scalar::Double(1.5))),
+                "OR(Column(minValues.a) < 1.5, Column(minValues.a) = NaN)",
+            ),
+            (
+                Expr::literal(Scalar::Float(1.5)).lt(column.clone()),
+                "OR(Column(maxValues.a) > 1.5, Column(maxValues.a) = NaN)",
+            ),
+            (
+                column.clone().eq(Expr::literal(Scalar::Double(1.5))),
+                "AND(OR(Column(minValues.a) <= 1.5, Column(minValues.a) = NaN), \
+                 OR(Column(maxValues.a) >= 1.5, Column(maxValues.a) = NaN))",
+            ),
+            // a NaN stat is not equal to the literal, so it keeps the file without a NaN check
+            (
+                column.clone().ne(Expr::literal(Scalar::Double(1.5))),
+                "OR(Column(minValues.a) != 1.5, Column(maxValues.a) != 1.5)",
+            ),
+        ];
+        for (input, expected) in cases {
+            let rewritten = as_data_skipping_predicate(&input).unwrap();
+            assert_eq!(rewritten.to_string(), expected, "{input}");
+        }
+
+        // NaN literals are ineligible
+        for input in [
+            column.clone().lt(Expr::literal(Scalar::Double(f64::NAN))),
+            column.clone().eq(Expr::literal(Scalar::Float(f32::NAN))),
+            column.clone().ne(Expr::literal(Scalar::Double(f64::NAN))),
+        ] {
+            assert_eq!(as_data_skipping_predicate(&input), None, "{input}");
+        }
+    }
+
+    #[test]
+    fn test_rewrite_nested_comparison() {
+        let cases = [
+            (
+                Expr::column("a.b").lt(Expr::literal(5)),
+                Expr::lt(Expr::column("minValues.a.b"), Expr::literal(5)),
+            ),
+            (
+                Expr::literal(5_i64).lt(Expr::column("a.c.d")),
+                Expr::gt(Expr::column("maxValues.a.c.d"), Expr::literal(5_i64)),
+            ),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(as_data_skipping_predicate(&input).unwrap(), expected);
+        }
+    }
+
+    #[test]
+    fn test_rewrite_in_list() {
+        let column = Expr::column("a");
+        let min_col = Expr::column("minValues.a");
+        let max_col = Expr::column("maxValues.a");
+        let lit = |value: i32| Expr::literal(value);
+        let long_list: Vec<_> = (0..=IN_LIST_EXPANSION_LIMIT as i32)
+            .rev()
+            .map(|value| lit(value * 10))
+            .collect();
+
+        let cases = [
+            // short lists are expanded into exact equality checks
+            (
+                column.clone().in_list([lit(1), lit(5)]),
+                Expr::or_from([
+                    Expr::and_from([
+                        Expr::le(min_col.clone(), lit(1)),
+                        Expr::ge(max_col.clone(), lit(1)),
+                    ]),
+                    Expr::and_from([
+                        Expr::le(min_col.clone(), lit(5)),
+                        Expr::ge(max_col.clone(), lit(5)),
+                    ]),
+                ]),
+            ),
+            (
+                column
+                    .clone()
+                    .in_list([lit(1), Expr::literal(Scalar::Null(DataType::INTEGER))]),
+                Expr::or_from([Expr::and_from([
+                    Expr::le(min_col.clone(), lit(1)),
+                    Expr::ge(max_col.clone(), lit(1)),
+                ])]),
+            ),
+            // long lists are approximated by their range
+            (
+                column.clone().in_list(long_list.clone()),
+                Expr::and_from([
+                    Expr::ge(max_col.clone(), lit(0)),
+                    Expr::le(min_col.clone(), lit(IN_LIST_EXPANSION_LIMIT as i32 * 10)),
+                ]),
+            ),
+            (column.clone().in_list([]), Expr::literal(false)),
+            (
+                column
+                    .clone()
+                    .in_list([Expr::literal(Scalar::Null(DataType::INTEGER))]),
+                Expr::literal(false),
+            ),
+            (
+                column.clone().not_in_list([lit(1)]),
+                Expr::and_from([Expr::or_from([
+                    Expr::ne(min_col.clone(), lit(1)),
+                    Expr::ne(max_col.clone(), lit(1)),
+                ])]),
+            ),
+            (column.clone().not_in_list([]), Expr::and_from([])),
+            (
+                column
+                    .clone()
+                    .not_in_list([lit(1), Expr::literal(Scalar::Null(DataType::INTEGER))]),
+                Expr::literal(false),
+            ),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(as_data_skipping_predicate(&input).unwrap(), expected);
+        }
+
+        // lists that can't be used for skipping
+        let cases = [
+            column.clone().in_list([lit(1), Expr::column("b")]),
+            lit(1).in_list([column.clone()]),
+            column
+                .clone()
+                .in_list(long_list.iter().cloned().chain([Expr::literal("foo")])),
+            column.clone().not_in_list(long_list),
+        ];
+        for input in cases {
+            assert_eq!(as_data_skipping_predicate(&input), None);
+        }
+    }
+
+    #[test]
+    fn test_increment_prefix() {
+        let cases = [
+            ("abc", Some("abd")),
+            ("ab\u{7F}", Some("ab\u{80}")),
+            ("caf\u{E9}", Some("caf\u{EA}")),
+            ("\u{65E5}\u{672C}", Some("\u{65E5}\u{672D}")),
+            ("a\u{D7FF}", Some("a\u{E000}")),
+            ("a\u{10FFFF}", Some("b")),
+            ("a\u{10FFFF}\u{10FFFF}", Some("b")),
+            ("\u{10FFFF}", None),
+            ("", None),
+        ];
+        for (prefix, expected) in cases {
+            assert_eq!(increment_prefix(prefix).as_deref(), expected, "{prefix:?}");
+        }
+    }
+
+    #[test]
+    fn test_rewrite_starts_with() {
+        let column = Expr::column("a");
+        let min_col = Expr::column("minValues.a");
+        let max_col = Expr::column("maxValues.a");
+
+        let cases = [
+            (
+                column.clone().starts_with(Expr::literal("abc")),
+                Expr::and_from([
+                    Expr::ge(max_col.clone(), Expr::literal("abc")),
+                    Expr::lt(min_col.clone(), Expr::literal("abd")),
+                ]),
+            ),
+            (
+                column.clone().starts_with(Expr::literal("\u{10FFFF}")),
+                Expr::ge(max_col.clone(), Expr::literal("\u{10FFFF}")),
+            ),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(as_data_skipping_predicate(&input).unwrap(), expected);
+        }
+
+        // the prefix must be a string literal on the right-hand side
+        let cases = [
+            column.clone().starts_with(Expr::literal(1)),
+            Expr::literal("abc").starts_with(column.clone()),
+            column.clone().starts_with(Expr::column("b")),
+        ];
+        for input in cases {
+            assert_eq!(as_data_skipping_predicate(&input), None);
+        }
+    }
+
+    #[test]
+    fn test_rewrite_truncated_string() {
+        let column = Expr::column("a");
+        let max_col = Expr::column("maxValues.a");
+        let long = "x".repeat(STRING_STATS_PREFIX_LENGTH + 8);
+        let prefix = "x".repeat(STRING_STATS_PREFIX_LENGTH);
+
+        let cases = [
+            (
+                column.clone().gt(Expr::literal("abc")),
+                Expr::ge(max_col.clone(), Expr::literal("abc")),
+            ),
+            (
+                column.clone().gt_eq(Expr::literal(long.as_str())),
+                Expr::ge(max_col.clone(), Expr::literal(prefix.as_str())),
+            ),
+            (
+                Expr::literal(long.as_str()).lt(column.clone()),
+                Expr::ge(max_col.clone(), Expr::literal(prefix.as_str())),
+            ),
+            (
+                column.clone().eq(Expr::literal(long.as_str())),
+                Expr::and_from([
+                    Expr::le(Expr::column("minValues.a"), Expr::literal(long.as_str())),
+                    Expr::ge(max_col.clone(), Expr::literal(prefix.as_str())),
+                ]),
+            ),
+            (
+                column.clone().starts_with(Expr::literal(long.as_str())),
+                Expr::and_from([
+                    Expr::ge(max_col.clone(), Expr::literal(prefix.as_str())),
+                    Expr::lt(
+                        Expr::column("minValues.a"),
+                        Expr::literal(increment_prefix(&long).unwrap()),
+                    ),
+                ]),
+            ),
+            // min stats are still lower bounds when truncated
+            (
+                column.clone().lt(Expr::literal(long.as_str())),
+                Expr::lt(Expr::column("minValues.a"), Expr::literal(long.as_str())),
+            ),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(
+                as_data_skipping_predicate(&input).unwrap(),
+                expected,
+                "{input}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_regex_literal_prefix() {
+        let cases = [
+            ("^abc", Some("abc")),
+            ("^abc.*", Some("abc")),
+            ("^abc$", Some("abc")),
+            ("^abc[0-9]+", Some("abc")),
+            ("^ab[cd]", Some("ab")),
+            ("^abc+", Some("abc")),
+            ("^abc*", Some("ab")),
+            ("^abc?d", Some("ab")),
+            ("^abc{2}", Some("ab")),
+            (r"^a\.b\d", Some("a.b")),
+            (r"^a\.*", Some("a")),
+            (r"^a\|b", Some("a|b")),
+            ("^caf\u{E9}s", Some("caf\u{E9}s")),
+            // unanchored patterns, alternations and patterns without a literal prefix
+            ("abc", None),
+            ("(?i)^abc", None),
+            ("^abc|def", None),
+            ("^ab(c|d)", None),
+            ("^a*bc", None),
+            ("^.abc", None),
+            (r"^\dabc", None),
+            ("^", None),
+        ];
+        for (pattern, expected) in cases {
+            assert_eq!(
+                regex_literal_prefix(pattern).as_deref(),
+                expected,
+                "{pattern}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_rewrite_between() {
+        let column = Expr::column("a");
+        let min_col = Expr::column("minValues.a");
+        let max_col = Expr::column("maxValues.a");
+        let low = Expr::literal(10);
+        let high = Expr::literal(20);
+
+        let cases = [
+            (
+                Expr::between(column.clone(), low.clone(), high.clone()),
+                Expr::and_from([
+                    Expr::ge(max_col.clone(), low.clone()),
+                    Expr::le(min_col.clone(), high.clone()),
+                ]),
+            ),
+            (
+                !Expr::between(column.clone(), low.clone(), high.clone()),
+                Expr::or_from([
+                    Expr::lt(min_col.clone(), low.clone()),
+                    Expr::gt(max_col.clone(), high.clone()),
+                ]),
+            ),
+            // `5 BETWEEN a AND 20` is `a <= 5 AND 5 <= 20`
+            (
+                Expr::between(low.clone(), column.clone(), high.clone()),
+                Expr::and_from([Expr::le(min_col.clone(), low.clone())]),
+            ),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(as_data_skipping_predicate(&input).unwrap(), expected);
+        }
+
+        // NOT BETWEEN needs both bounds to be eligible for data skipping
+        let input = !Expr::between(
+            column.clone(),
+            low.clone(),
+            Expr::column("b") + Expr::literal(1),
+        );
+        assert_eq!(as_data_skipping_predicate(&input), None);
+    }
+
+    #[test]
+    fn test_rewrite_null_literal() {
+        let column = Expr::column("a");
+        let null = Expr::literal(Scalar::Null(DataType::INTEGER));
+        let is_null = Expr::gt(Expr::column("nullCount.a"), Expr::literal(0_i64));
+        let is_not_null = Expr::lt(Expr::column("nullCount.a"), Expr::column("numRecords"));
+
+        let cases = [
+            (column.clone().eq(null.clone()), Some(is_null.clone())),
+            (null.clone().eq(column.clone()), Some(is_null)),
+            (column.clone().ne(null.clone()), Some(is_not_null.clone())),
+            (null.clone().ne(column.clone()), Some(is_not_null)),
+            (column.clone().lt(null.clone()), None),
+            (null.clone().gt_eq(column.clone()), None),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(as_data_skipping_predicate(&input), expected, "{input}");
+        }
+    }
+
+    #[test]
+    fn test_rewrite_is_not_null() {
+        let column = Expr::column("a");
+        let is_null = Expr::gt(Expr::column("nullCount.a"), Expr::literal(0_i64));
+        let is_not_null = Expr::lt(Expr::column("nullCount.a"), Expr::column("numRecords"));
+
+        let cases = [
+            (column.clone().is_null(), Some(is_null.clone())),
+            (column.clone().is_not_null(), Some(is_not_null.clone())),
+            (!column.clone().is_null(), Some(is_not_null)),
+            (!column.clone().is_not_null(), Some(is_null)),
+            ((column.clone() + Expr::literal(1)).is_not_null(), None),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(as_data_skipping_predicate(&input), expected, "{input}");
+        }
+    }
+
+    #[test]
+    fn test_skipping_soundness() {
+        let table_schema = Arc::new(StructType::new(vec![
+            StructField::new("a", DataType::INTEGER, true),
+            StructField::new("b", DataType::STRING, true),
+            StructField::new(
+                "c",
+                StructType::new(vec![StructField::new("d", DataType::LONG, true)]),
+                true,
+            ),
+        ]));
+        let files: [&[&str]; 3] = [
+            &[
+                r#"{"a":1,"b":"apple","c":{"d":10}}"#,
+                r#"{"a":3,"b":"banana","c":{"d":20}}"#,
+                r#"{"a":null,"b":null,"c":{"d":null}}"#,
+            ],
+            &[r#"{"a":5,"b":"cherry","c":{"d":30}}"#],
+            &[r#"{"a":null,"b":"date","c":null}"#],
+        ];
+
+        let col = Expr::column;
+        let lit = |value: i32| Expr::literal(value);
+        let predicates = [
+            col("a").lt(lit(1)),
+            col("a").lt_eq(lit(1)),
+            col("a").gt(lit(3)),
+            col("a").gt_eq(lit(5)),
+            col("a").eq(lit(2)),
+            col("a").eq(lit(3)),
+            lit(4).lt(col("a")),
+            col("a").is_null(),
+            col("a").is_not_null(),
+            col("b").eq(Expr::literal("banana")),
+            col("b").gt(Expr::literal("cherry")),
+            col("c.d").lt_eq(Expr::literal(10_i64)),
+            col("a").gt(lit(1)).and(col("b").lt(Expr::literal("b"))),
+            col("a").lt(lit(1)).or(col("c.d").gt(Expr::literal(25_i64))),
+            col("a")
+                .eq(lit(5))
+                .or(col("b").eq(Expr::literal("date")).and(col("a").is_null())),
+            col("a").distinct(lit(5)),
+            col("a").distinct(lit(3)),
+            col("a").null_safe_eq(lit(5)),
+            col("b").distinct(Expr::literal("date")),
+            col("a").ne(lit(2)),
+            col("a").ne(lit(5)),
+            !col("a").eq(lit(2)),
+            !col("a").eq(lit(5)),
+            col("b").ne(Expr::literal("cherry")),
+            !col("c.d").eq(Expr::literal(20_i64)),
+        ];
+        for predicate in &predicates {
+            for rows in files {
+                assert_skipping_sound(&table_schema, predicate, rows);
+            }
+        }
+    }
+
+    #[test]
+    fn test_distinct_skipping() {
+        let fixture = SkippingFixture::new(
+            single_column_schema("a", DataType::INTEGER),
+            &[
+                // only 5s, without and with nulls
+                r#"{"numRecords":2,"nullCount":{"a":0},"minValues":{"a":5},"maxValues":{"a":5}}"#,
+                r#"{"numRecords":2,"nullCount":{"a":1},"minValues":{"a":5},"maxValues":{"a":5}}"#,
+                // a range that contains 5, without and with nulls
+                r#"{"numRecords":2,"nullCount":{"a":0},"minValues":{"a":3},"maxValues":{"a":8}}"#,
+                r#"{"numRecords":2,"nullCount":{"a":1},"minValues":{"a":3},"maxValues":{"a":8}}"#,
+                // a range without 5, without and with nulls
+                r#"{"numRecords":2,"nullCount":{"a":0},"minValues":{"a":6},"maxValues":{"a":8}}"#,
+                r#"{"numRecords":2,"nullCount":{"a":1},"minValues":{"a":6},"maxValues":{"a":6}}"#,
+                // only nulls
+                r#"{"numRecords":2,"nullCount":{"a":2}}"#,
+            ],
+        );
+        let cases = [
+            (
+                Expr::column("a").distinct(Expr::literal(5)),
+                vec![false, true, true, true, true, true, true],
+            ),
+            (
+                Expr::column("a").null_safe_eq(Expr::literal(5)),
+                vec![true, true, true, true, false, false, true],
+            ),
+            (
+                Expr::column("a").distinct(Expr::literal(Scalar::Null(DataType::INTEGER))),
+                vec![true, true, true, true, true, true, false],
+            ),
+        ];
+        fixture.assert_cases(cases);
+    }
+
+    #[test]
+    fn test_boolean_skipping() {
+        let flag = || Expr::column("flag");
+        assert_eq!(
+            as_data_skipping_predicate(&flag().eq(Expr::literal(true))),
+            Some(Expr::eq(
+                Expr::column("maxValues.flag"),
+                Expr::literal(true)
+            ))
+        );
+        assert_eq!(
+            as_data_skipping_predicate(&flag().ne(Expr::literal(true))),
+            Some(Expr::eq(
+                Expr::column("minValues.flag"),
+                Expr::literal(false)
+            ))
+        );
+
+        let files: [&[&str]; 4] = [
+            &[r#"{"flag":false}"#, r#"{"flag":false}"#],
+            &[r#"{"flag":true}"#, r#"{"flag":null}"#],
+            &[r#"{"flag":false}"#, r#"{"flag":true}"#],
+            &[r#"{"flag":null}"#],
+        ];
+        let cases = [
+            (flag().eq(Expr::literal(true)), [false, true, true, true]),
+            (flag().eq(Expr::literal(false)), [true, false, true, true]),
+            (flag().ne(Expr::literal(true)), [true, false, true, true]),
+            (flag().ne(Expr::literal(false)), [false, true, true, true]),
+            (
+                flag().null_safe_eq(Expr::literal(true)),
+                [false, true, true, true],
+            ),
+        ];
+        let fixture =
+            SkippingFixture::with_files(single_column_schema("flag", DataType::BOOLEAN), &files);
+        for (predicate, expected) in cases {
+            assert_eq!(fixture.apply(&predicate), expected, "{predicate}");
+            for rows in files {
+                assert_skipping_sound(&fixture.table_schema, &predicate, rows);
+            }
+        }
+    }
+
+    #[test]
+    fn test_negated_equality_skipping() {
+        let fixture = SkippingFixture::new(
+            single_column_schema("a", DataType::INTEGER),
+            &[
+                r#"{"numRecords":2,"minValues":{"a":1},"maxValues":{"a":4}}"#,
+                r#"{"numRecords":2,"minValues":{"a":5},"maxValues":{"a":5}}"#,
+                r#"{"numRecords":2,"minValues":{"a":6},"maxValues":{"a":8}}"#,
+                // a range that contains the value, but also values on both sides of it
+                r#"{"numRecords":2,"minValues":{"a":4},"maxValues":{"a":6}}"#,
+            ],
+        );
+        let cases = [
+            (
+                !Expr::column("a").ne(Expr::literal(5)),
+                vec![false, true, false, true],
+            ),
+            (
+                !Expr::column("a").eq(Expr::literal(5)),
+                vec![true, false, true, true],
+            ),
+            (
+                Expr::column("a").ne(Expr::literal(5)),
+                vec![true, false, true, true],
+            ),
+            // the first and last files have values below and above 2
+            (
+                Expr::column("a").ne(Expr::literal(2)),
+                vec![true, true, true, true],
+            ),
+        ];
+        fixture.assert_cases(cases);
+    }
+
+    #[test]
+    fn test_column_comparison_skipping() {
+        let table_schema = Arc::new(StructType::new(vec![
+            StructField::new("a", DataType::INTEGER, true),
+            StructField::new("b", DataType::INTEGER, true),
+            StructField::new("c", DataType::STRING, true),
+        ]));
+        let fixture = SkippingFixture::new(
+            table_schema,
+            &[
+                // a entirely below b
+                r#"{"numRecords":1,"minValues":{"a":1,"b":5},"maxValues":{"a":3,"b":7}}"#,
+                // a entirely above b
+                r#"{"numRecords":1,"minValues":{"a":5,"b":1},"maxValues":{"a":7,"b":3}}"#,
+                // ranges touch at 3
+                r#"{"numRecords":1,"minValues":{"a":3,"b":1},"maxValues":{"a":5,"b":3}}"#,
+                // ranges overlap
+                r#"{"numRecords":1,"minValues":{"a":1,"b":2},"maxValues":{"a":4,"b":5}}"#,
+                // no stats
+                r#"{"numRecords":1}"#,
+            ],
+        );
+        let (a, b) = (Expr::column("a"), Expr::column("b"));
+        let cases = [
+            (
+                a.clone().lt(b.clone()),
+                vec![true, false, false, true, true],
+            ),
+            (
+                a.clone().lt_eq(b.clone()),
+                vec![true, false, true, true, true],
+            ),
+            (a.clone().gt(b.clone()), vec![false, true, true, true, true]),
+            (
+                a.clone().gt_eq(b.clone()),
+                vec![false, true, true, true, true],
+            ),
+            (
+                b.clone().gt(a.clone()),
+                vec![true, false, false, true, true],
+            ),
+        ];
+        fixture.assert_cases(cases);
+
+        // (in)equality and comparisons between columns of different types are ineligible
+        for predicate in [
+            a.clone().eq(b.clone()),
+            a.clone().ne(b),
+            a.lt(Expr::column("c")),
+        ] {
+            assert!(fixture.filter(&predicate).is_none(), "{predicate}");
+        }
+    }
+
+    #[test]
+    fn test_map_access_skipping() {
+        let table_schema = Arc::new(StructType::new(vec![
+            StructField::new("a", DataType::INTEGER, true),
+            StructField::new(
+                "metrics",
+                crate::schema::MapType::new(DataType::STRING, DataType::INTEGER, true),
+                true,
+            ),
+        ]));
+        let fixture = SkippingFixture::new(
+            table_schema,
+            &[
+                r#"{"numRecords":1,"minValues":{"a":1},"maxValues":{"a":3}}"#,
+                r#"{"numRecords":1,"minValues":{"a":5},"maxValues":{"a":7}}"#,
+            ],
+        );
+        let latency = Expr::column("metrics").map_access("latency");
+
+        // map values have no stats, so they are ineligible for data skipping
+        let predicate = latency.clone().gt(Expr::literal(100));
+        assert_eq!(as_data_skipping_predicate(&predicate), None);
+        assert!(fixture.filter(&predicate).is_none());
+
+        let predicate = predicate.and(Expr::column("a").lt(Expr::literal(5)));
+        assert_eq!(fixture.apply(&predicate), vec![true, false]);
+    }
+
+    #[test]
+    fn test_nan_skipping() {
+        let table_schema = Arc::new(StructType::new(vec![
+            StructField::new("d", DataType::DOUBLE, true),
+            StructField::new("f", DataType::FLOAT, true),
+        ]));
+        let fixture = SkippingFixture::new(
+            table_schema,
+            &[
+                // no NaN
+                r#"{"numRecords":2,"minValues":{"d":1.0,"f":1.0},"maxValues":{"d":3.0,"f":3.0}}"#,
+                // NaN min and max
+                r#"{"numRecords":2,"minValues":{"d":"NaN","f":"NaN"},"maxValues":{"d":"NaN","f":"NaN"}}"#,
+                // NaN max
+                r#"{"numRecords":2,"minValues":{"d":1.0,"f":1.0},"maxValues":{"d":"NaN","f":"NaN"}}"#,
+                // NaN min
+                r#"{"numRecords":2,"minValues":{"d":"NaN","f":"NaN"},"maxValues":{"d":3.0,"f":3.0}}"#,
+            ],
+        );
+        let (d, f) = (Expr::column("d"), Expr::column("f"));
+        let cases = [
+            // files with a NaN stat are kept wherever that stat is needed to skip them
+            (
+                d.clone().lt(Expr::literal(Scalar::Double(0.5))),
+                vec![false, true, false, true],
+            ),
+            (
+                f.clone().lt(Expr::literal(Scalar::Float(0.5))),
+                vec![false, true, false, true],
+            ),
+            (
+                d.clone().gt(Expr::literal(Scalar::Double(5.0))),
+                vec![false, true, true, false],
+            ),
+            (
+                f.clone().gt_eq(Expr::literal(Scalar::Float(5.0))),
+                vec![false, true, true, false],
+            ),
+            (
+                d.clone().eq(Expr::literal(Scalar::Double(5.0))),
+                vec![false, true, true, false],
+            ),
+            (
+                d.clone().eq(Expr::literal(Scalar::Double(2.0))),
+                vec![true; 4],
+            ),
+            // ... while files without NaN stats are still skipped
+            (
+                d.clone().lt(Expr::literal(Scalar::Double(2.0))),
+                vec![true; 4],
+            ),
+            (
+                d.clone().gt(Expr::literal(Scalar::Double(3.0))),
+                vec![false, true, true, false],
+            ),
+            // NaN literals can't be used to skip any file
+            (
+                d.clone()
+                    .lt(Expr::literal(Scalar::Double(f64::NAN)))
+                    .and(f.lt(Expr::literal(Scalar::Float(0.5)))),
+                vec![false, true, false, true],
+            ),
+        ];
+        fixture.assert_cases(cases);
+
+        // NaN stats can't be compared between columns
+        assert!(fixture.filter(&d.lt(Expr::column("f"))).is_none());
+    }
+
+    #[test]
+    fn test_nested_skipping() {
+        let table_schema = nested_table_schema();
+        let fixture = SkippingFixture::new(
+            table_schema,
+            &[
+                r#"{"numRecords":1,"minValues":{"a":{"b":1,"c":{"d":10}}},"maxValues":{"a":{"b":3,"c":{"d":20}}}}"#,
+                r#"{"numRecords":1,"minValues":{"a":{"b":5,"c":{"d":30}}},"maxValues":{"a":{"b":7,"c":{"d":40}}}}"#,
+            ],
+        );
+
+        let cases = [
+            // two levels of nesting
+            (Expr::column("a.b").lt(Expr::literal(4)), vec![true, false]),
+            (Expr::column("a.b").gt(Expr::literal(4)), vec![false, true]),
+            // three levels of nesting
+            (
+                Expr::column("a.c.d").eq(Expr::literal(35_i64)),
+                vec![false, true],
+            ),
+            (
+                Expr::column("a.c.d").lt_eq(Expr::literal(5_i64)),
+                vec![false, false],
+            ),
+            // a non-existent nested field is dropped from the conjunction
+            (
+                Expr::and_from([
+                    Expr::column("a.x").gt(Expr::literal(100)),
+                    Expr::column("a.c.y").gt(Expr::literal(100)),
+                    Expr::column("a.b").lt(Expr::literal(4)),
+                ]),
+                vec![true, false],
+            ),
+        ];
+        fixture.assert_cases(cases);
+
+        // a predicate that only references non-existent nested fields can't skip anything
+        let predicate = Expr::column("a.c.x").lt(Expr::literal(4));
+        assert!(fixture.filter(&predicate).is_none());
+    }
+
+    #[test]
+    fn test_in_list_skipping() {
+        let fixture = SkippingFixture::new(
+            single_column_schema("a", DataType::INTEGER),
+            &[
+                r#"{"numRecords":1,"minValues":{"a":1},"maxValues":{"a":3}}"#,
+                r#"{"numRecords":1,"minValues":{"a":5},"maxValues":{"a":5}}"#,
+                r#"{"numRecords":1,"minValues":{"a":20},"maxValues":{"a":30}}"#,
+            ],
+        );
+        let lit = |value: i32| Expr::literal(value);
+        let column = Expr::column("a");
+
+        let cases = [
+            (
+                column.clone().in_list([lit(2), lit(25)]),
+                vec![true, false, true],
+            ),
+            (
+                column.clone().in_list([lit(4), lit(9)]),
+                vec![false, false, false],
+            ),
+            // the range approximation keeps files that hold none of the values
+            (
+                column
+                    .clone()
+                    .in_list((0..=IN_LIST_EXPANSION_LIMIT as i32).map(|v| lit(v * 4))),
+                vec![true, true, true],
+            ),
+            (
+                column
+                    .clone()
+                    .in_list((0..=IN_LIST_EXPANSION_LIMIT as i32).map(|v| lit(v + 4))),
+                vec![false, true, false],
+            ),
+            (column.clone().in_list([]), vec![false, false, false]),
+            (
+                column.clone().not_in_list([lit(5)]),
+                vec![true, false, true],
+            ),
+            (column.clone().not_in_list([]), vec![true, true, true]),
+        ];
+        fixture.assert_cases(cases);
+    }
+
+    #[test]
+    fn test_truncated_string_skipping() {
+        let table_schema = Arc::new(StructType::new(vec![
+            StructField::new("a", DataType::STRING, true),
+            StructField::new("b", DataType::STRING, true),
+        ]));
+        let prefix = "b".repeat(STRING_STATS_PREFIX_LENGTH);
+        let stats = [
+            // the max of `a` is truncated, e.g. from `bbb...bbz`
+            serde_json::json!({
+                "numRecords": 2,
+                "minValues": {"a": "aaa", "b": "a"},
+                "maxValues": {"a": prefix, "b": "z"},
+            })
+            .to_string(),
+            // stats that aren't truncated
+            serde_json::json!({
+                "numRecords": 2,
+                "minValues": {"a": "x", "b": "a"},
+                "maxValues": {"a": "z", "b": "z"},
+            })
+            .to_string(),
+        ];
+        let fixture = SkippingFixture::new(table_schema, &[&stats[0], &stats[1]]);
+
+        // a literal sharing the truncated prefix, which the actual max could be greater than
+        let long = format!("{prefix}c");
+        let column = Expr::column("a");
+        let cases = [
+            (
+                column.clone().gt(Expr::literal(long.as_str())),
+                vec![true, true],
+            ),
+            (
+                column.clone().gt_eq(Expr::literal(long.as_str())),
+                vec![true, true],
+            ),
+            (
+                column.clone().eq(Expr::literal(long.as_str())),
+                vec![true, false],
+            ),
+            (
+                column.clone().starts_with(Expr::literal(long.as_str())),
+                vec![true, false],
+            ),
+            (
+                column.clone().gt(Expr::literal(prefix.as_str())),
+                vec![true, true],
+            ),
+            // literals beyond the truncated prefix still skip
+            (column.clone().gt(Expr::literal("c")), vec![false, true]),
+            (column.clone().eq(Expr::literal("y")), vec![false, true]),
+            (column.clone().lt(Expr::literal("b")), vec![true, false]),
+        ];
+        fixture.assert_cases(cases);
+
+        // truncated stats can't be compared between columns
+        assert!(fixture.filter(&column.gt(Expr::column("b"))).is_none());
+    }
+
+    #[test]
+    fn test_starts_with_skipping() {
+        let fixture = SkippingFixture::new(
+            single_column_schema("name", DataType::STRING),
+            &[
+                r#"{"numRecords":2,"minValues":{"name":"aaa"},"maxValues":{"name":"abb"}}"#,
+                r#"{"numRecords":2,"minValues":{"name":"abc"},"maxValues":{"name":"abcz"}}"#,
+                r#"{"numRecords":2,"minValues":{"name":"abd"},"maxValues":{"name":"zzz"}}"#,
+                r#"{"numRecords":2,"minValues":{"name":"caf\u00e9"},"maxValues":{"name":"caf\u00e9s"}}"#,
+                r#"{"numRecords":2,"minValues":{"name":"caf\u00ea"},"maxValues":{"name":"d"}}"#,
+            ],
+        );
+
+        let cases = [
+            ("abc", vec![false, true, false, false, false]),
+            ("ab", vec![true, true, true, false, false]),
+            ("caf\u{E9}", vec![false, false, true, true, false]),
+            ("caf\u{EA}", vec![false, false, true, false, true]),
+        ];
+        for (prefix, expected) in cases {
+            let predicate = Expr::column("name").starts_with(Expr::literal(prefix));
+            assert_eq!(fixture.apply(&predicate), expected, "{prefix}");
+        }
+    }
+
+    #[test]
+    fn test_rlike_skipping() {
+        let fixture = SkippingFixture::new(
+            single_column_schema("name", DataType::STRING),
+            &[
+                r#"{"numRecords":2,"minValues":{"name":"aaa"},"maxValues":{"name":"abb"}}"#,
+                r#"{"numRecords":2,"minValues":{"name":"abc"},"maxValues":{"name":"abcz"}}"#,
+                r#"{"numRecords":2,"minValues":{"name":"abd"},"maxValues":{"name":"zzz"}}"#,
+            ],
+        );
+
+        // anchored patterns skip like STARTS WITH their literal prefix
+        let cases = [
+            ("^abc", vec![false, true, false]),
+            ("^abc[0-9]*$", vec![false, true, false]),
+            ("^ab[cd]", vec![true, true, true]),
+        ];
+        for (pattern, expected) in cases {
+            let predicate = Expr::column("name").rlike(Expr::literal(pattern));
+            assert_eq!(fixture.apply(&predicate), expected, "{pattern}");
+        }
+
+        // unanchored patterns may match anywhere in the string, so they can't skip
+        for pattern in ["abc", "^abc|xyz", "(?i)^abc"] {
+            let predicate = Expr::column("name").rlike(Expr::literal(pattern));
+            assert!(
+                as_data_skipping_predicate(&predicate).is_none(),
+                "{pattern}"
+            );
+            assert!(fixture.filter(&predicate).is_none(), "{pattern}");
+        }
+    }
+
+    #[test]
+    fn test_tuple_in_skipping() {
+        let table_schema = Arc::new(StructType::new(vec![
+            StructField::new("a", DataType::INTEGER, true),
+            StructField::new("b", DataType::INTEGER, true),
+        ]));
+        let fixture = SkippingFixture::new(
+            table_schema,
+            &[
+                r#"{"numRecords":1,"minValues":{"a":1,"b":2},"maxValues":{"a":1,"b":2}}"#,
+                r#"{"numRecords":2,"minValues":{"a":5,"b":0},"maxValues":{"a":9,"b":9}}"#,
+                r#"{"numRecords":2,"minValues":{"a":3,"b":5},"maxValues":{"a":3,"b":9}}"#,
+                r#"{"numRecords":2,"minValues":{"a":0,"b":0},"maxValues":{"a":9,"b":9}}"#,
+                // no stats for b, so (3, 4) may be in the file
+                r#"{"numRecords":2,"minValues":{"a":3},"maxValues":{"a":3}}"#,
+            ],
+        );
+
+        let tuple = |values: [i32; 2]| Expr::struct_expr(values.map(Expr::literal));
+        let predicate = Expr::struct_expr([Expr::column("a"), Expr::column("b")])
+            .in_list([tuple([1, 2]), tuple([3, 4])]);
+        assert_eq!(
+            fixture.apply(&predicate),
+            vec![true, false, false, true, true]
+        );
+
+        let predicate = Expr::struct_expr([Expr::column("a"), Expr::column("b")]).eq(tuple([3, 4]));
+        assert_eq!(
+            fixture.apply(&predicate),
+            vec![false, false, false, true, true]
+        );
+    }
+
+    #[test]
+    fn test_quantified_skipping() {
+        use crate::expressions::Quantifier;
+
+        let table_schema = Arc::new(StructType::new(vec![
+            StructField::new("a", DataType::INTEGER, true),
+            StructField::new("b", DataType::INTEGER, true),
+        ]));
+        let fixture = SkippingFixture::new(
+            table_schema,
+            &[
+                r#"{"numRecords":1,"minValues":{"a":0},"maxValues":{"a":4}}"#,
+                r#"{"numRecords":1,"minValues":{"a":5},"maxValues":{"a":9}}"#,
+                r#"{"numRecords":1,"minValues":{"a":10},"maxValues":{"a":20}}"#,
+            ],
+        );
+        let quantified =
+            |op, quantifier, list: Vec<Expr>| Expr::column("a").quantified(op, quantifier, list);
+        let list = || vec![Expr::literal(3), Expr::literal(7)];
+
+        let cases = [
+            (
+                quantified(BinaryOperator::GreaterThan, Quantifier::All, list()),
+                vec![false, true, true],
+            ),
+            (
+                quantified(BinaryOperator::LessThan, Quantifier::All, list()),
+                vec![true, false, false],
+            ),
+            (
+                quantified(BinaryOperator::GreaterThan, Quantifier::Any, list()),
+                vec![true, true, true],
+            ),
+            (
+                quantified(BinaryOperator::LessThan, Quantifier::Any, list()),
+                vec![true, true, false],
+            ),
+            (
+                quantified(BinaryOperator::Equal, Quantifier::Any, list()),
+                vec![true, true, false],
+            ),
+        ];
+        fixture.assert_cases(cases);
+
+        // values that are not literals can't be compared to the stats
+        let predicate = quantified(
+            BinaryOperator::GreaterThan,
+            Quantifier::All,
+            vec![Expr::literal(3), Expr::column("b")],
+        );
+        assert!(fixture.filter(&predicate).is_none());
+    }
+
+    #[test]
+    fn test_between_skipping() {
+        let fixture = SkippingFixture::new(
+            single_column_schema("a", DataType::INTEGER),
+            &[
+                r#"{"numRecords":2,"minValues":{"a":1},"maxValues":{"a":9}}"#,
+                r#"{"numRecords":2,"minValues":{"a":5},"maxValues":{"a":10}}"#,
+                r#"{"numRecords":2,"minValues":{"a":12},"maxValues":{"a":18}}"#,
+                r#"{"numRecords":2,"minValues":{"a":20},"maxValues":{"a":25}}"#,
+                r#"{"numRecords":2,"minValues":{"a":21},"maxValues":{"a":25}}"#,
+            ],
+        );
+        let between = Expr::between(Expr::column("a"), Expr::literal(10), Expr::literal(20));
+
+        let cases = [
+            // both bounds are inclusive
+            (between.clone(), vec![false, true, true, true, false]),
+            (!between, vec![true, true, false, true, true]),
+        ];
+        fixture.assert_cases(cases);
+    }
+
+    #[test]
+    fn test_null_literal_skipping() {
+        let fixture = SkippingFixture::new(
+            single_column_schema("a", DataType::INTEGER),
+            &[
+                // no nulls
+                r#"{"numRecords":2,"nullCount":{"a":0},"minValues":{"a":1},"maxValues":{"a":3}}"#,
+                // only nulls
+                r#"{"numRecords":2,"nullCount":{"a":2}}"#,
+                // some nulls
+                r#"{"numRecords":2,"nullCount":{"a":1},"minValues":{"a":1},"maxValues":{"a":1}}"#,
+                // no stats
+                r#"{"numRecords":2}"#,
+            ],
+        );
+        let null = Expr::literal(Scalar::Null(DataType::INTEGER));
+
+        let cases = [
+            (
+                Expr::column("a").eq(null.clone()),
+                vec![false, true, true, true],
+            ),
+            (
+                null.clone().eq(Expr::column("a")),
+                vec![false, true, true, true],
+            ),
+            (
+                Expr::column("a").ne(null.clone()),
+                vec![true, false, true, true],
+            ),
+            (
+                null.clone().ne(Expr::column("a")),
+                vec![true, false, true, true],
+            ),
+        ];
+        fixture.assert_cases(cases);
+    }
+
+    #[test]
+    fn test_null_column_stats() {
+        let table_schema = Arc::new(StructType::new(vec![
+            StructField::new("a", DataType::INTEGER, true),
+            StructField::new("b", DataType::INTEGER, true),
+            StructField::new("s", DataType::STRING, true),
+        ]));
+        // the files have stats, but not (all) of b and s, e.g. because they were added later
+        let fixture = SkippingFixture::new(
+            table_schema,
+            &[
+                r#"{"numRecords":2,"nullCount":{"a":0},"minValues":{"a":1},"maxValues":{"a":3}}"#,
+                r#"{"numRecords":2,"nullCount":{"a":0,"b":null,"s":null},
+                    "minValues":{"a":1,"b":null,"s":null},"maxValues":{"a":3,"b":null,"s":null}}"#,
+                r#"{"numRecords":2,"nullCount":{"a":0},
+                    "minValues":{"a":1,"b":null},"maxValues":{"a":3,"b":10}}"#,
+            ],
+        );
+        let b = || Expr::column("b");
+        let lit = |value: i32| Expr::literal(value);
+
+        // comparisons with a NULL stat are NULL, which must keep the file
+        let cases = [
+            (b().gt(lit(5)), vec![true, true, true]),
+            (b().gt(lit(20)), vec![true, true, false]),
+            (b().lt(lit(5)), vec![true, true, true]),
+            (b().eq(lit(5)), vec![true, true, true]),
+            (b().ne(lit(5)), vec![true, true, true]),
+            (!b().lt(lit(5)), vec![true, true, true]),
+            (b().in_list([lit(1), lit(2)]), vec![true, true, true]),
+            (Expr::between(b(), lit(1), lit(2)), vec![true, true, true]),
+            (b().is_null(), vec![true, true, true]),
+            (b().is_not_null(), vec![true, true, true]),
+            (
+                Expr::column("s").starts_with(Expr::literal("x")),
+                vec![true, true, true],
+            ),
+            // ... unless the stats of another column prove that no row matches
+            (
+                Expr::column("a").gt(lit(5)).and(b().gt(lit(5))),
+                vec![false, false, false],
+            ),
+            (
+                Expr::column("a").gt(lit(5)).or(b().gt(lit(5))),
+                vec![true, true, true],
+            ),
+        ];
+        fixture.assert_cases(cases);
+    }
+
+    #[test]
+    fn test_is_not_null_skipping() {
+        let fixture = SkippingFixture::new(
+            single_column_schema("a", DataType::INTEGER),
+            &[
+                // no nulls
+                r#"{"numRecords":2,"nullCount":{"a":0},"minValues":{"a":1},"maxValues":{"a":3}}"#,
+                // only nulls
+                r#"{"numRecords":2,"nullCount":{"a":2}}"#,
+                // some nulls
+                r#"{"numRecords":2,"nullCount":{"a":1},"minValues":{"a":1},"maxValues":{"a":1}}"#,
+                // no stats
+                r#"{"numRecords":2}"#,
+            ],
+        );
+        let column = Expr::column("a");
+        let is_null = vec![false, true, true, true];
+        let is_not_null = vec![true, false, true, true];
+
+        // IS NOT NULL skips like NOT IS NULL, and NOT IS NOT NULL like IS NULL
+        let cases = [
+            (column.clone().is_null(), is_null.clone()),
+            (!column.clone().is_not_null(), is_null),
+            (column.clone().is_not_null(), is_not_null.clone()),
+            (!column.clone().is_null(), is_not_null),
+        ];
+        fixture.assert_cases(cases);
+    }
+
+    #[test]
+    fn test_null_safe_equal_skipping() {
+        let fixture = SkippingFixture::new(
+            single_column_schema("a", DataType::INTEGER),
+            &[
+                // no nulls, out of range
+                r#"{"numRecords":2,"nullCount":{"a":0},"minValues":{"a":5},"maxValues":{"a":7}}"#,
+                // no nulls, in range
+                r#"{"numRecords":2,"nullCount":{"a":0},"minValues":{"a":1},"maxValues":{"a":3}}"#,
+                // some nulls, out of range
+                r#"{"numRecords":2,"nullCount":{"a":1},"minValues":{"a":5},"maxValues":{"a":5}}"#,
+                // only nulls, without min/max values to skip on
+                r#"{"numRecords":2,"nullCount":{"a":2}}"#,
+            ],
+        );
+        let null = Expr::literal(Scalar::Null(DataType::INTEGER));
+
+        let cases = [
+            (
+                Expr::column("a").null_safe_eq(Expr::literal(2)),
+                Expr::and_from([
+                    Expr::le(Expr::column("minValues.a"), Expr::literal(2)),
+                    Expr::ge(Expr::column("maxValues.a"), Expr::literal(2)),
+                ]),
+                vec![false, true, false, true],
+            ),
+            (
+                Expr::literal(2).null_safe_eq(Expr::column("a")),
+                Expr::and_from([
+                    Expr::le(Expr::column("minValues.a"), Expr::literal(2)),
+                    Expr::ge(Expr::column("maxValues.a"), Expr::literal(2)),
+                ]),
+                vec![false, true, false, true],
+            ),
+            (
+                Expr::column("a").null_safe_eq(null.clone()),
+                Expr::gt(Expr::column("nullCount.a"), Expr::literal(0_i64)),
+                vec![false, false, true, true],
+            ),
+            (
+                null.null_safe_eq(Expr::column("a")),
+                Expr::gt(Expr::column("nullCount.a"), Expr::literal(0_i64)),
+                vec![false, false, true, true],
+            ),
+        ];
+        for (predicate, expected_rewrite, expected) in cases {
+            assert_eq!(
+                as_data_skipping_predicate(&predicate),
+                Some(expected_rewrite),
+                "{predicate}"
+            );
+            assert_eq!(fixture.apply(&predicate), expected, "{predicate}");
+        }
+    }
+}
@@ -3,16 +3,18 @@ use std::collections::HashSet;
 use either::Either;
 use tracing::debug;
 
-use super::data_skipping::DataSkippingFilter;
+use super::data_skipping::DataSkippingStats;
+use super::scan_filter::ScanFilter;
 use crate::actions::{get_log_schema, ADD_NAME, REMOVE_NAME};
 use crate::actions::{visitors::AddVisitor, visitors::RemoveVisitor, Add, Remove};
 use crate::engine_data::{GetData, TypedGetData};
-use crate::expressions::Expression;
-use crate::schema::SchemaRef;
-use crate::{DataVisitor, DeltaResult, EngineData, EngineInterface};
+use crate::{DataVisitor, DeltaResult, EngineData};
 
 struct LogReplayScanner {
-    filter: Option<DataSkippingFilter>,
+    filter: ScanFilter,
+
+    /// Running totals of the actions data skipping was applied to and skipped so far.
+    skipping_stats: DataSkippingStats,
 
     /// A set of (data file path, dv_unique_id) pairs that have been seen thus
     /// far in the log. This is used to filter out files with Remove actions as
@@ -74,13 +76,10 @@ impl DataVisitor for AddRemoveVisitor {
 
 impl LogReplayScanner {
     /// Create a new [`LogReplayScanner`] instance
-    fn new(
-        table_client: &dyn EngineInterface,
-        table_schema: &SchemaRef,
-        predicate: &Option<Expression>,
-    ) -> Self {
+    fn new(filter: ScanFilter) -> Self {
         Self {
-            filter: DataSkippingFilter::new(table_client, table_schema, predicate),
+            filter,
+            skipping_stats: Default::default(),
             seen: Default::default(),
         }
     }
@@ -97,9 +96,22 @@ impl LogReplayScanner {
         // note: None implies all files passed data skipping.
         let selection_vector = self
             .filter
+            .data_filter
             .as_ref()
-            .map(|filter| filter.apply(actions))
+            .map(|filter| filter.apply_cached(actions))
             .transpose()?;
+        if let Some(selection_vector) = &selection_vector {
+            self.skipping_stats += DataSkippingStats::from_selection_vector(selection_vector);
+            debug!(
+                "data skipping has skipped {} of {} actions so far",
+                self.skipping_stats.files_skipped, self.skipping_stats.files_scanned
+            );
+        }
+
+        // combine with the selection vectors of actions that passed partition pruning
+        let selection_vector = self
+            .filter
+            .apply_partition_filters(actions, selection_vector)?;
 
         let schema_to_use = if is_log_batch {
             // NB: We _must_ pass these in the order `ADD_NAME, REMOVE_NAME` as the visitor assumes
@@ -144,15 +156,16 @@ impl LogReplayScanner {
     }
 }
 
-/// Given an iterator of (record batch, bool) tuples and a predicate, returns an iterator of `Adds`.
-/// The boolean flag indicates whether the record batch is a log or checkpoint batch.
+/// Given an iterator of (record batch, bool) tuples and a filter, returns an iterator of `Adds`.
+/// The boolean flag indicates whether the record batch is a log or checkpoint batch. Files that
+/// the filter skips by their stats or partition values are left out (see [`ScanFilterBuilder`]).
+///
+/// [`ScanFilterBuilder`]: crate::scan::ScanFilterBuilder
 pub fn log_replay_iter(
-    engine_client: &dyn EngineInterface,
     action_iter: impl Iterator<Item = DeltaResult<(Box<dyn EngineData>, bool)>>,
-    table_schema: &SchemaRef,
-    predicate: &Option<Expression>,
+    filter: ScanFilter,
 ) -> impl Iterator<Item = DeltaResult<Add>> {
-    let mut log_scanner = LogReplayScanner::new(engine_client, table_schema, predicate);
+    let mut log_scanner = LogReplayScanner::new(filter);
 
     action_iter.flat_map(move |actions| match actions {
         Ok((batch, is_log_batch)) => {
@@ -164,3 +177,162 @@ pub fn log_replay_iter(
         Err(err) => Either::Right(std::iter::once(Err(err))),
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use arrow_array::{RecordBatch, StringArray};
+    use arrow_schema::{DataType as ArrowDataType, Field, Schema as ArrowSchema};
+    use itertools::Itertools;
+
+    use super::*;
+    use crate::client::arrow_data::ArrowEngineData;
+    use crate::client::sync::SyncEngineInterface;
+    use crate::expressions::Expression;
+    use crate::scan::ScanFilterBuilder;
+    use crate::schema::{DataType, StructField, StructType};
+    use crate::EngineInterface;
+
+    fn parse_actions(
+        engine_interface: &dyn EngineInterface,
+        json: &[String],
+    ) -> Box<dyn EngineData> {
+        let string_field = Arc::new(Field::new("a", ArrowDataType::Utf8, true));
+        let schema = Arc::new(ArrowSchema::new(vec![string_field]));
+        let batch = RecordBatch::try_new(schema, vec![Arc::new(StringArray::from(json.to_vec()))])
+            .expect("Can't convert to record batch");
+        engine_interface
+            .get_json_handler()
+            .parse_json(
+                Box::new(ArrowEngineData::new(batch)),
+                get_log_schema().project(&[ADD_NAME, REMOVE_NAME]).unwrap(),
+            )
+            .unwrap()
+    }
+
+    fn add_action(path: &str, part: &str, min: i32, max: i32) -> String {
+        let stats = format!(
+            r#"{{"numRecords":1,"minValues":{{"value":{min}}},"maxValues":{{"value":{max}}}}}"#
+        );
+        serde_json::json!({
+            "add": {
+                "path": path,
+                "partitionValues": { "part": part },
+                "size": 1024,
+                "modificationTime": 1677811178336_i64,
+                "dataChange": true,
+                "stats": stats,
+            }
+        })
+        .to_string()
+    }
+
+    #[test]
+    fn test_partition_only_predicate() {
+        let engine_interface = SyncEngineInterface::new();
+        let table_schema = Arc::new(StructType::new(vec![
+            StructField::new("part", DataType::STRING, true),
+            StructField::new("value", DataType::INTEGER, true),
+        ]));
+        let partition_columns = vec!["part".to_string()];
+        let predicate = Some(Expression::column("part").eq(Expression::literal("x")));
+
+        // there are no stats to skip on, but the scan still prunes by partition
+        let mut scanner = LogReplayScanner::new(
+            ScanFilterBuilder::new(
+                &engine_interface,
+                &table_schema,
+                &partition_columns,
+                &predicate,
+            )
+            .build(),
+        );
+        assert!(scanner.filter.data_filter.is_none());
+        assert!(scanner.filter.partition_filter.is_some());
+
+        let actions = parse_actions(
+            &engine_interface,
+            &[
+                add_action("part=x/file", "x", 1, 3),
+                add_action("part=y/file", "y", 1, 3),
+            ],
+        );
+        let paths: Vec<_> = scanner
+            .process_batch(actions.as_ref(), true)
+            .unwrap()
+            .into_iter()
+            .map(|add| add.path)
+            .collect();
+        assert_eq!(paths, vec!["part=x/file"]);
+    }
+
+    #[test]
+    fn test_partition_and_data_skipping() {
+        let engine_interface = SyncEngineInterface::new();
+        let table_schema = Arc::new(StructType::new(vec![
+            StructField::new("part", DataType::STRING, true),
+            StructField::new("value", DataType::INTEGER, true),
+        ]));
+        let partition_columns = vec!["part".to_string()];
+        let actions = parse_actions(
+            &engine_interface,
+            &[
+                add_action("part=x/small", "x", 1, 3),
+                add_action("part=x/large", "x", 6, 9),
+                add_action("part=y/small", "y", 1, 3),
+                add_action("part=y/large", "y", 6, 9),
+            ],
+        );
+        let paths = |predicate: Expression| -> Vec<String> {
+            let actions = actions.as_ref();
+            let predicate = Some(predicate);
+            let mut scanner = LogReplayScanner::new(
+                ScanFilterBuilder::new(
+                    &engine_interface,
+                    &table_schema,
+                    &partition_columns,
+                    &predicate,
+                )
+                .build(),
+            );
+            scanner
+                .process_batch(actions, true)
+                .unwrap()
+                .into_iter()
+                .map(|add| add.path)
+                .sorted()
+                .collect()
+        };
+
+        let part_predicate = Expression::column("part").eq(Expression::literal("x"));
+        let data_predicate = Expression::column("value").gt(Expression::literal(5));
+        assert_eq!(
+            paths(part_predicate.clone().and(data_predicate.clone())),
+            vec!["part=x/large"]
+        );
+        assert_eq!(
+            paths(part_predicate.clone()),
+            vec!["part=x/large", "part=x/small"]
+        );
+        assert_eq!(
+            paths(data_predicate.clone()),
+            vec!["part=x/large", "part=y/large"]
+        );
+
+        // A disjunction of a partition and a data predicate keeps all files of the matching
+        // partitions, and the files of other partitions that may match the data predicate.
+        assert_eq!(
+            paths(part_predicate.clone().or(data_predicate.clone())),
+            vec!["part=x/large", "part=x/small", "part=y/large"]
+        );
+        let small_predicate = Expression::column("value").lt(Expression::literal(2));
+        assert_eq!(
+            paths(Expression::and_from([
+                part_predicate.or(data_predicate),
+                small_predicate
+            ])),
+            vec!["part=x/small"]
+        );
+    }
+}
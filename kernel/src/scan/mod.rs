@@ -5,21 +5,51 @@ use std::sync::Arc;
 use itertools::Itertools;
 use tracing::debug;
 
+pub use self::data_skipping::{
+    can_skip, should_apply_skipping, stats_schema_for, to_data_skipping_predicate,
+    DataSkippingFilter, DataSkippingResult, EligibilityReason, EvaluatorCache, MissingStatsPolicy,
+    SkipDecision, SkippingAnalysis, SkippingObserver, SkippingThresholds, StatsCache,
+    StatsColumnNames,
+};
 use self::file_stream::log_replay_iter;
+pub use self::parsed_stats::ParsedStats;
+pub use self::scan_filter::{ScanFilter, ScanFilterBuilder};
+pub use self::selection::{
+    combine_selection_vectors, combine_selection_vectors_in_place, SelectionBitmap,
+};
 use crate::actions::{get_log_schema, Add, ADD_NAME, REMOVE_NAME};
-use crate::expressions::{Expression, Scalar};
+use crate::expressions::{typecheck, Expression, Scalar};
 use crate::schema::{DataType, SchemaRef, StructField, StructType};
 use crate::snapshot::Snapshot;
-use crate::{DeltaResult, EngineData, EngineInterface, Error, FileMeta};
+use crate::{
+    DeltaResult, EngineData, EngineInterface, Error, ExpressionEvaluator, FileMeta, JsonHandler,
+};
 
 mod data_skipping;
 pub mod file_stream;
+mod parsed_stats;
+mod partition_pruning;
+mod scan_filter;
+pub(crate) mod selection;
+#[cfg(test)]
+mod test_utils;
 
 /// Builder to scan a snapshot of a table.
 pub struct ScanBuilder {
     snapshot: Arc<Snapshot>,
     schema: Option<SchemaRef>,
     predicate: Option<Expression>,
+    stats_cache: Option<Arc<StatsCache>>,
+    evaluator_cache: Option<Arc<EvaluatorCache>>,
+    stats_json_handler: Option<Arc<dyn JsonHandler>>,
+    projection: Option<Vec<(String, Expression)>>,
+    data_skipping: bool,
+    skip_empty_files_without_predicate: bool,
+    skipping_observer: Option<Arc<dyn SkippingObserver>>,
+    float_tolerance: f64,
+    null_skipping_only: bool,
+    missing_stats_policy: MissingStatsPolicy,
+    stats_column_names: StatsColumnNames,
 }
 
 impl std::fmt::Debug for ScanBuilder {
@@ -27,6 +57,20 @@ impl std::fmt::Debug for ScanBuilder {
         f.debug_struct("ScanBuilder")
             .field("schema", &self.schema)
             .field("predicate", &self.predicate)
+            .field("projection", &self.projection)
+            .field("stats_cache", &self.stats_cache)
+            .field("evaluator_cache", &self.evaluator_cache)
+            .field("stats_json_handler", &self.stats_json_handler.is_some())
+            .field("data_skipping", &self.data_skipping)
+            .field(
+                "skip_empty_files_without_predicate",
+                &self.skip_empty_files_without_predicate,
+            )
+            .field("skipping_observer", &self.skipping_observer.is_some())
+            .field("float_tolerance", &self.float_tolerance)
+            .field("null_skipping_only", &self.null_skipping_only)
+            .field("missing_stats_policy", &self.missing_stats_policy)
+            .field("stats_column_names", &self.stats_column_names)
             .finish()
     }
 }
@@ -38,6 +82,17 @@ impl ScanBuilder {
             snapshot,
             schema: None,
             predicate: None,
+            stats_cache: None,
+            evaluator_cache: None,
+            stats_json_handler: None,
+            projection: None,
+            data_skipping: true,
+            skip_empty_files_without_predicate: false,
+            skipping_observer: None,
+            float_tolerance: 0.0,
+            null_skipping_only: false,
+            missing_stats_policy: MissingStatsPolicy::default(),
+            stats_column_names: StatsColumnNames::default(),
         }
     }
 
@@ -71,6 +126,121 @@ impl ScanBuilder {
         self
     }
 
+    /// Provide several predicates, which rows must all satisfy. This is the same as calling
+    /// [`ScanBuilder::with_predicate`] with the AND of the predicates (and of any predicate already
+    /// provided), so data skipping still uses the predicates that are eligible for it when some
+    /// of them are not.
+    pub fn with_predicates(mut self, predicates: impl IntoIterator<Item = Expression>) -> Self {
+        let mut predicates: Vec<_> = self
+            .predicate
+            .take()
+            .into_iter()
+            .chain(predicates)
+            .collect();
+        self.predicate = match predicates.len() {
+            0 => None,
+            1 => predicates.pop(),
+            _ => Some(Expression::and_from(predicates)),
+        };
+        self
+    }
+
+    /// Provide a [`StatsCache`] to reuse the stats that data skipping parsed in other scans of the
+    /// same [`Snapshot`], and to cache the stats this scan parses for later scans. The cache must
+    /// only be shared between scans of the same snapshot.
+    ///
+    /// [`Snapshot`]: crate::snapshot::Snapshot
+    pub fn with_stats_cache(mut self, stats_cache: Arc<StatsCache>) -> Self {
+        self.stats_cache = Some(stats_cache);
+        self
+    }
+
+    /// Provide an [`EvaluatorCache`] that data skipping gets its expression evaluators from, so
+    /// that scans with predicates of the same shape (e.g. repeated scans with the same predicate)
+    /// share their evaluators instead of each creating new ones. Unlike a [`StatsCache`], the
+    /// cache can be shared between scans of any table.
+    pub fn with_evaluator_cache(mut self, evaluator_cache: Arc<EvaluatorCache>) -> Self {
+        self.evaluator_cache = Some(evaluator_cache);
+        self
+    }
+
+    /// Provide a [`JsonHandler`] that data skipping parses the stats of add actions with, instead
+    /// of the engine's own, e.g. one that tolerates malformed stats of some writer. The log itself
+    /// is still read with the engine's json handler.
+    pub fn with_stats_json_handler(mut self, json_handler: Arc<dyn JsonHandler>) -> Self {
+        self.stats_json_handler = Some(json_handler);
+        self
+    }
+
+    /// Provide the output columns of the scan as named expressions over the columns of the scan's
+    /// schema (see [`ScanBuilder::with_schema`]), in the order they should be returned.
+    ///
+    /// This can reorder, rename and compute columns, e.g. the projection `[("y", b), ("x", a + 1)]`
+    /// of a scan with the schema `[a, b]` returns the columns `y` and `x`. The output columns are
+    /// all nullable, and their types are those of the expressions (see [`Scan::output_schema`]).
+    pub fn with_projection(mut self, fields: Vec<(String, Expression)>) -> Self {
+        self.projection = Some(fields);
+        self
+    }
+
+    /// Enable or disable data skipping, which is enabled by default. A scan without data skipping
+    /// returns all files of the snapshot, without pruning them by their stats or partition values,
+    /// which helps to find out whether data skipping wrongly drops files. The predicate is still
+    /// available from [`Scan::predicate`] to filter the rows of the files.
+    pub fn with_data_skipping(mut self, enabled: bool) -> Self {
+        self.data_skipping = enabled;
+        self
+    }
+
+    /// Skip the files without records (`numRecords = 0`) of the snapshot even if the scan has no
+    /// predicate, or none that data skipping can use, and even if data skipping is disabled (see
+    /// [`ScanBuilder::with_data_skipping`]). Data skipping already skips such files when the
+    /// predicate is eligible for it. Otherwise, this parses the stats of every file just to read
+    /// their `numRecords`, so it is disabled by default.
+    pub fn with_skip_empty_files_without_predicate(mut self, enabled: bool) -> Self {
+        self.skip_empty_files_without_predicate = enabled;
+        self
+    }
+
+    /// Provide a [`SkippingObserver`] that data skipping reports its metrics to, e.g. how many
+    /// files it skipped in each batch of actions and how long that took.
+    pub fn with_skipping_observer(mut self, observer: Arc<dyn SkippingObserver>) -> Self {
+        self.skipping_observer = Some(observer);
+        self
+    }
+
+    /// Tolerate min/max stats of `FLOAT` and `DOUBLE` columns that differ from the actual min/max
+    /// values by up to `epsilon`, e.g. because the writer computed them with rounding. Data
+    /// skipping then only skips files whose stats rule out the predicate by more than `epsilon`.
+    /// Defaults to zero, which trusts the stats exactly.
+    pub fn with_float_tolerance(mut self, epsilon: f64) -> Self {
+        self.float_tolerance = epsilon;
+        self
+    }
+
+    /// Only skip files by their `nullCount` and `numRecords` stats, e.g. for queries that only care
+    /// whether a column is entirely (non-)null in a file. Data skipping then parses fewer stats,
+    /// but parts of the predicate that need the min/max values don't skip any files.
+    pub fn with_null_skipping_only(mut self, null_skipping_only: bool) -> Self {
+        self.null_skipping_only = null_skipping_only;
+        self
+    }
+
+    /// Decide what data skipping does with files whose stats can't decide the predicate, e.g.
+    /// because they are missing. Defaults to [`MissingStatsPolicy::Keep`]; see
+    /// [`MissingStatsPolicy::Skip`] for when skipping such files is correct.
+    pub fn with_missing_stats_policy(mut self, policy: MissingStatsPolicy) -> Self {
+        self.missing_stats_policy = policy;
+        self
+    }
+
+    /// Provide the names under which the add actions of the table store their stats, for logs of
+    /// writers that don't use the Delta names (see [`StatsColumnNames`]).
+    pub fn with_stats_column_names(mut self, names: StatsColumnNames) -> Self {
+        self.stats_column_names = names;
+        self
+    }
+
     /// Build the [`Scan`].
     ///
     /// This is lazy and performs no 'work' at this point. The [`Scan`] type itself can be used
@@ -84,6 +254,17 @@ impl ScanBuilder {
             snapshot: self.snapshot,
             read_schema,
             predicate: self.predicate,
+            stats_cache: self.stats_cache,
+            evaluator_cache: self.evaluator_cache,
+            stats_json_handler: self.stats_json_handler,
+            projection: self.projection,
+            data_skipping: self.data_skipping,
+            skip_empty_files_without_predicate: self.skip_empty_files_without_predicate,
+            skipping_observer: self.skipping_observer,
+            float_tolerance: self.float_tolerance,
+            null_skipping_only: self.null_skipping_only,
+            missing_stats_policy: self.missing_stats_policy,
+            stats_column_names: self.stats_column_names,
         }
     }
 }
@@ -116,6 +297,17 @@ pub struct Scan {
     snapshot: Arc<Snapshot>,
     read_schema: SchemaRef,
     predicate: Option<Expression>,
+    stats_cache: Option<Arc<StatsCache>>,
+    evaluator_cache: Option<Arc<EvaluatorCache>>,
+    stats_json_handler: Option<Arc<dyn JsonHandler>>,
+    projection: Option<Vec<(String, Expression)>>,
+    data_skipping: bool,
+    skip_empty_files_without_predicate: bool,
+    skipping_observer: Option<Arc<dyn SkippingObserver>>,
+    float_tolerance: f64,
+    null_skipping_only: bool,
+    missing_stats_policy: MissingStatsPolicy,
+    stats_column_names: StatsColumnNames,
 }
 
 impl std::fmt::Debug for Scan {
@@ -123,6 +315,19 @@ impl std::fmt::Debug for Scan {
         f.debug_struct("Scan")
             .field("schema", &self.read_schema)
             .field("predicate", &self.predicate)
+            .field("projection", &self.projection)
+            .field("evaluator_cache", &self.evaluator_cache)
+            .field("stats_json_handler", &self.stats_json_handler.is_some())
+            .field("data_skipping", &self.data_skipping)
+            .field(
+                "skip_empty_files_without_predicate",
+                &self.skip_empty_files_without_predicate,
+            )
+            .field("skipping_observer", &self.skipping_observer.is_some())
+            .field("float_tolerance", &self.float_tolerance)
+            .field("null_skipping_only", &self.null_skipping_only)
+            .field("missing_stats_policy", &self.missing_stats_policy)
+            .field("stats_column_names", &self.stats_column_names)
             .finish()
     }
 }
@@ -135,33 +340,100 @@ impl Scan {
         &self.read_schema
     }
 
+    /// Get the [`Schema`] of the data returned by [`Scan::execute`]. This is the schema of the
+    /// scan, unless a projection was provided with [`ScanBuilder::with_projection`], which names
+    /// the output columns. Fails if an expression of the projection is not valid for the schema
+    /// of the scan.
+    ///
+    /// [`Schema`]: crate::schema::Schema
+    pub fn output_schema(&self) -> DeltaResult<SchemaRef> {
+        let Some(projection) = &self.projection else {
+            return Ok(self.read_schema.clone());
+        };
+        let fields: Vec<_> = projection
+            .iter()
+            .map(|(name, expr)| {
+                let data_type = typecheck(expr, &self.read_schema)?;
+                Ok::<_, Error>(StructField::new(name, data_type, true))
+            })
+            .try_collect()?;
+        Ok(Arc::new(StructType::new(fields)))
+    }
+
     /// Get the predicate [`Expression`] of the scan.
     pub fn predicate(&self) -> &Option<Expression> {
         &self.predicate
     }
 
+    /// Get an [`ExpressionEvaluator`] of the predicate of the scan, which engines can use to
+    /// filter the rows of the data read from the files of [`Scan::files`]. Data skipping only
+    /// drops the files that can't have matching rows, so the rows of the remaining files still
+    /// need to be filtered. The evaluator takes data of the [schema] of the scan, including the
+    /// partition columns like the data of [`Scan::execute`] before any projection, and returns a
+    /// boolean column that is true for the rows that satisfy the predicate. Returns `None` if the
+    /// scan has no predicate, and fails if the predicate is not a valid boolean expression for the
+    /// schema of the scan.
+    ///
+    /// [schema]: Scan::schema
+    pub fn residual_predicate_evaluator(
+        &self,
+        engine_interface: &dyn EngineInterface,
+    ) -> DeltaResult<Option<Arc<dyn ExpressionEvaluator>>> {
+        let Some(predicate) = &self.predicate else {
+            return Ok(None);
+        };
+        let data_type = typecheck(predicate, &self.read_schema)?;
+        if data_type != DataType::BOOLEAN {
+            return Err(Error::generic(format!(
+                "Scan predicate must be a boolean expression, but is {data_type}"
+            )));
+        }
+        let evaluator = engine_interface.get_expression_handler().get_evaluator(
+            self.read_schema.clone(),
+            predicate.clone(),
+            DataType::BOOLEAN,
+        );
+        Ok(Some(evaluator))
+    }
+
     /// Get an iterator of Add actions that should be included in scan for a query. This handles
-    /// log-replay, reconciling Add and Remove actions, and applying data skipping (if possible)
+    /// log-replay, reconciling Add and Remove actions, and applying data skipping (if possible and
+    /// enabled, see [`ScanBuilder::with_data_skipping`])
     pub fn files(
         &self,
         engine_interface: &dyn EngineInterface,
     ) -> DeltaResult<impl Iterator<Item = DeltaResult<Add>>> {
         let commit_read_schema = get_log_schema().project(&[ADD_NAME, REMOVE_NAME])?;
         let checkpoint_read_schema = get_log_schema().project(&[ADD_NAME])?;
+        let skipping_predicate = match self.data_skipping {
+            true => self.predicate.clone(),
+            false => None,
+        };
 
         let log_iter = self.snapshot.log_segment.replay(
             engine_interface,
             commit_read_schema,
             checkpoint_read_schema,
-            self.predicate.clone(),
+            skipping_predicate.clone(),
         )?;
 
-        Ok(log_replay_iter(
+        let filter = ScanFilterBuilder::new(
             engine_interface,
-            log_iter,
             &self.read_schema,
-            &self.predicate,
-        ))
+            &self.snapshot.metadata().partition_columns,
+            &skipping_predicate,
+        )
+        .with_stats_cache(self.stats_cache.clone())
+        .with_evaluator_cache(self.evaluator_cache.clone())
+        .with_json_handler(self.stats_json_handler.clone())
+        .with_observer(self.skipping_observer.clone())
+        .with_skip_empty_files_without_predicate(self.skip_empty_files_without_predicate)
+        .with_float_tolerance(self.float_tolerance)
+        .with_null_skipping_only(self.null_skipping_only)
+        .with_missing_stats_policy(self.missing_stats_policy)
+        .with_stats_column_names(self.stats_column_names.clone())
+        .build();
+        Ok(log_replay_iter(log_iter, filter))
     }
 
     /// This is the main method to 'materialize' the scan. It returns a [`Result`] of
@@ -198,6 +470,22 @@ impl Scan {
         debug!("Executing scan with read schema {read_schema:#?}");
         let output_schema = DataType::Struct(Box::new(self.schema().as_ref().clone()));
         let parquet_handler = engine_interface.get_parquet_handler();
+        // The projection is evaluated on the output of the read expression, i.e. on the data of
+        // the scan's schema including the partition columns
+        let projection_evaluator = match &self.projection {
+            Some(projection) => {
+                let output_type =
+                    DataType::Struct(Box::new(self.output_schema()?.as_ref().clone()));
+                let expression =
+                    Expression::struct_expr(projection.iter().map(|(_, expr)| expr.clone()));
+                Some(engine_interface.get_expression_handler().get_evaluator(
+                    self.read_schema.clone(),
+                    expression,
+                    output_type,
+                ))
+            }
+            None => None,
+        };
 
         let mut results: Vec<ScanResult> = vec![];
         let files = self.files(engine_interface)?;
@@ -265,6 +553,12 @@ impl Scan {
                         read_result
                     }
                 };
+                let read_result = match projection_evaluator {
+                    Some(ref evaluator) => {
+                        read_result.and_then(|data| evaluator.evaluate(data.as_ref()))
+                    }
+                    None => read_result,
+                };
 
                 // need to split the dv_mask. what's left in dv_mask covers this result, and rest
                 // will cover the following results
@@ -297,6 +591,7 @@ fn parse_partition_value(raw: Option<&String>, data_type: &DataType) -> DeltaRes
 #[cfg(all(test, feature = "default-client"))]
 mod tests {
     use std::path::PathBuf;
+    use std::sync::atomic::{AtomicUsize, Ordering};
 
     use super::*;
     use crate::client::sync::SyncEngineInterface;
@@ -327,6 +622,418 @@ mod tests {
         assert!(&files[0].deletion_vector.is_none());
     }
 
+    #[test]
+    fn test_scan_with_predicates() {
+        let path =
+            std::fs::canonicalize(PathBuf::from("./tests/data/table-without-dv-small/")).unwrap();
+        let url = url::Url::from_directory_path(path).unwrap();
+        let engine_interface = SyncEngineInterface::new();
+
+        let table = Table::new(url);
+        let snapshot = table.snapshot(&engine_interface, None).unwrap();
+        let value = || Expression::column("value");
+
+        // the single file has values between 0 and 9, and no stats for the missing column, so the
+        // third predicate is ignored while the other two are used for skipping
+        let cases = [
+            (value().gt(Expression::literal(2_i64)), 1),
+            (value().gt(Expression::literal(20_i64)), 0),
+        ];
+        for (predicate, expected_files) in cases {
+            let scan = ScanBuilder::new(snapshot.clone())
+                .with_predicates([
+                    value().gt_eq(Expression::literal(0_i64)),
+                    predicate.clone(),
+                    Expression::column("missing").eq(Expression::literal(5_i64)),
+                ])
+                .build();
+            assert_eq!(
+                scan.predicate(),
+                &Some(Expression::and_from([
+                    value().gt_eq(Expression::literal(0_i64)),
+                    predicate,
+                    Expression::column("missing").eq(Expression::literal(5_i64)),
+                ]))
+            );
+            let files: Vec<Add> = scan
+                .files(&engine_interface)
+                .unwrap()
+                .try_collect()
+                .unwrap();
+            assert_eq!(files.len(), expected_files);
+        }
+
+        // predicates are combined with an existing predicate, and a single one is kept as is
+        let predicate = value().lt(Expression::literal(5_i64));
+        let scan = ScanBuilder::new(snapshot.clone())
+            .with_predicates([predicate.clone()])
+            .build();
+        assert_eq!(scan.predicate(), &Some(predicate.clone()));
+        let scan = ScanBuilder::new(snapshot.clone())
+            .with_predicate(predicate.clone())
+            .with_predicates([value().gt(Expression::literal(1_i64))])
+            .build();
+        assert_eq!(
+            scan.predicate(),
+            &Some(predicate.and(value().gt(Expression::literal(1_i64))))
+        );
+        let scan = ScanBuilder::new(snapshot).with_predicates([]).build();
+        assert_eq!(scan.predicate(), &None);
+    }
+
+    /// Counts how often stats are parsed.
+    struct CountingJsonHandler {
+        inner: Arc<dyn JsonHandler>,
+        parse_count: AtomicUsize,
+    }
+
+    impl JsonHandler for CountingJsonHandler {
+        fn parse_json(
+            &self,
+            json_strings: Box<dyn EngineData>,
+            output_schema: SchemaRef,
+        ) -> DeltaResult<Box<dyn EngineData>> {
+            self.parse_count.fetch_add(1, Ordering::SeqCst);
+            self.inner.parse_json(json_strings, output_schema)
+        }
+
+        fn read_json_files(
+            &self,
+            files: &[FileMeta],
+            physical_schema: SchemaRef,
+            predicate: Option<Expression>,
+        ) -> DeltaResult<crate::FileDataReadResultIterator> {
+            self.inner
+                .read_json_files(files, physical_schema, predicate)
+        }
+    }
+
+    #[test]
+    fn test_scan_with_stats_cache() {
+        use crate::{ExpressionHandler, FileSystemClient, ParquetHandler};
+
+        struct CountingEngineInterface {
+            inner: SyncEngineInterface,
+            json_handler: Arc<CountingJsonHandler>,
+        }
+
+        impl EngineInterface for CountingEngineInterface {
+            fn get_expression_handler(&self) -> Arc<dyn ExpressionHandler> {
+                self.inner.get_expression_handler()
+            }
+            fn get_file_system_client(&self) -> Arc<dyn FileSystemClient> {
+                self.inner.get_file_system_client()
+            }
+            fn get_json_handler(&self) -> Arc<dyn JsonHandler> {
+                self.json_handler.clone()
+            }
+            fn get_parquet_handler(&self) -> Arc<dyn ParquetHandler> {
+                self.inner.get_parquet_handler()
+            }
+        }
+
+        let path =
+            std::fs::canonicalize(PathBuf::from("./tests/data/table-without-dv-small/")).unwrap();
+        let url = url::Url::from_directory_path(path).unwrap();
+        let inner = SyncEngineInterface::new();
+        let engine_interface = CountingEngineInterface {
+            json_handler: Arc::new(CountingJsonHandler {
+                inner: inner.get_json_handler(),
+                parse_count: AtomicUsize::new(0),
+            }),
+            inner,
+        };
+        let parse_count = || {
+            engine_interface
+                .json_handler
+                .parse_count
+                .load(Ordering::SeqCst)
+        };
+
+        let table = Table::new(url);
+        let snapshot = table.snapshot(&engine_interface, None).unwrap();
+        let stats_cache = Arc::new(StatsCache::new());
+        let scan_files = |predicate: Expression| -> usize {
+            let scan = ScanBuilder::new(snapshot.clone())
+                .with_predicate(predicate)
+                .with_stats_cache(stats_cache.clone())
+                .build();
+            let files: Vec<Add> = scan
+                .files(&engine_interface)
+                .unwrap()
+                .try_collect()
+                .unwrap();
+            files.len()
+        };
+        let value = || Expression::column("value");
+
+        // the first scan parses the stats ...
+        assert_eq!(scan_files(value().gt(Expression::literal(2_i64))), 1);
+        assert_eq!(parse_count(), 1);
+        assert_eq!(stats_cache.len(), 1);
+
+        // ... which a scan with another predicate on the same stats reuses
+        assert_eq!(scan_files(value().gt(Expression::literal(20_i64))), 0);
+        assert_eq!(parse_count(), 1);
+
+        // stats of other columns are parsed and cached separately
+        assert_eq!(scan_files(value().is_null()), 0);
+        assert_eq!(parse_count(), 2);
+        assert_eq!(stats_cache.len(), 2);
+
+        stats_cache.clear();
+        assert!(stats_cache.is_empty());
+        assert_eq!(scan_files(value().gt(Expression::literal(2_i64))), 1);
+        assert_eq!(parse_count(), 3);
+    }
+
+    #[test]
+    fn test_scan_with_evaluator_cache() {
+        let path =
+            std::fs::canonicalize(PathBuf::from("./tests/data/table-without-dv-small/")).unwrap();
+        let url = url::Url::from_directory_path(path).unwrap();
+        let engine_interface = SyncEngineInterface::new();
+
+        let table = Table::new(url);
+        let snapshot = table.snapshot(&engine_interface, None).unwrap();
+        let evaluator_cache = Arc::new(EvaluatorCache::new(16));
+        let scan_files = |predicate: Expression| -> usize {
+            let scan = ScanBuilder::new(snapshot.clone())
+                .with_predicate(predicate)
+                .with_evaluator_cache(evaluator_cache.clone())
+                .build();
+            let files: Vec<Add> = scan
+                .files(&engine_interface)
+                .unwrap()
+                .try_collect()
+                .unwrap();
+            files.len()
+        };
+        let value = || Expression::column("value");
+
+        // the first scan creates the evaluators, which a second scan of the same shape reuses
+        assert_eq!(scan_files(value().gt(Expression::literal(2_i64))), 1);
+        let len = evaluator_cache.len();
+        assert!(len > 0);
+        assert_eq!(scan_files(value().gt(Expression::literal(2_i64))), 1);
+        assert_eq!(evaluator_cache.len(), len);
+
+        // a predicate of another shape needs other evaluators
+        assert_eq!(scan_files(value().gt(Expression::literal(20_i64))), 0);
+        assert!(evaluator_cache.len() > len);
+    }
+
+    #[test]
+    fn test_scan_with_stats_json_handler() {
+        let path =
+            std::fs::canonicalize(PathBuf::from("./tests/data/table-without-dv-small/")).unwrap();
+        let url = url::Url::from_directory_path(path).unwrap();
+        let engine_interface = SyncEngineInterface::new();
+
+        let table = Table::new(url);
+        let snapshot = table.snapshot(&engine_interface, None).unwrap();
+        let json_handler = Arc::new(CountingJsonHandler {
+            inner: engine_interface.get_json_handler(),
+            parse_count: AtomicUsize::new(0),
+        });
+        let scan = ScanBuilder::new(snapshot)
+            .with_predicate(Expression::column("value").gt(Expression::literal(20_i64)))
+            .with_stats_json_handler(json_handler.clone())
+            .build();
+        let files: Vec<Add> = scan
+            .files(&engine_interface)
+            .unwrap()
+            .try_collect()
+            .unwrap();
+        assert_eq!(files.len(), 0);
+        // the stats of the single batch of actions are parsed with the handler of the scan
+        assert_eq!(json_handler.parse_count.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_scan_skip_empty_files_without_predicate() {
+        let path =
+            std::fs::canonicalize(PathBuf::from("./tests/data/table-without-dv-small/")).unwrap();
+        let url = url::Url::from_directory_path(path).unwrap();
+        let engine_interface = SyncEngineInterface::new();
+        let snapshot = Table::new(url).snapshot(&engine_interface, None).unwrap();
+
+        let cases = [(false, 0), (true, 1)];
+        for (skip_empty_files, expected_parse_count) in cases {
+            let json_handler = Arc::new(CountingJsonHandler {
+                inner: engine_interface.get_json_handler(),
+                parse_count: AtomicUsize::new(0),
+            });
+            let scan = ScanBuilder::new(snapshot.clone())
+                .with_stats_json_handler(json_handler.clone())
+                .with_skip_empty_files_without_predicate(skip_empty_files)
+                .build();
+            let files: Vec<Add> = scan
+                .files(&engine_interface)
+                .unwrap()
+                .try_collect()
+                .unwrap();
+            // the file of the table isn't empty
+            assert_eq!(files.len(), 1);
+            // without a predicate, stats are only parsed to find the empty files if asked to
+            assert_eq!(
+                json_handler.parse_count.load(Ordering::SeqCst),
+                expected_parse_count,
+                "{skip_empty_files}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_residual_predicate_evaluator() {
+        use arrow_array::cast::AsArray;
+        use arrow_array::{Int64Array, RecordBatch};
+        use arrow_schema::{DataType as ArrowDataType, Field, Schema as ArrowSchema};
+
+        use crate::client::arrow_data::ArrowEngineData;
+
+        let path =
+            std::fs::canonicalize(PathBuf::from("./tests/data/table-without-dv-small/")).unwrap();
+        let url = url::Url::from_directory_path(path).unwrap();
+        let engine_interface = SyncEngineInterface::new();
+        let snapshot = Table::new(url).snapshot(&engine_interface, None).unwrap();
+
+        let scan = ScanBuilder::new(snapshot.clone()).build();
+        assert!(scan
+            .residual_predicate_evaluator(&engine_interface)
+            .unwrap()
+            .is_none());
+
+        let value = || Expression::column("value");
+        let scan = ScanBuilder::new(snapshot.clone())
+            .with_predicates([
+                value().gt(Expression::literal(1_i64)),
+                value().ne(Expression::literal(3_i64)),
+            ])
+            .build();
+        let evaluator = scan
+            .residual_predicate_evaluator(&engine_interface)
+            .unwrap()
+            .unwrap();
+        let schema = ArrowSchema::new(vec![Field::new("value", ArrowDataType::Int64, true)]);
+        let values = Int64Array::from(vec![Some(0), Some(2), Some(3), None, Some(4)]);
+        let batch = RecordBatch::try_new(Arc::new(schema), vec![Arc::new(values)]).unwrap();
+        let result = evaluator.evaluate(&ArrowEngineData::new(batch)).unwrap();
+        let result = ArrowEngineData::try_from_engine_data(result).unwrap();
+        let selected: Vec<_> = result
+            .record_batch()
+            .column(0)
+            .as_boolean()
+            .iter()
+            .collect();
+        assert_eq!(
+            selected,
+            [Some(false), Some(true), Some(false), None, Some(true)]
+        );
+
+        // the predicate must be a boolean expression of the columns of the scan
+        let scan = ScanBuilder::new(snapshot.clone())
+            .with_predicate(value() + Expression::literal(1_i64))
+            .build();
+        assert!(scan
+            .residual_predicate_evaluator(&engine_interface)
+            .is_err());
+        let scan = ScanBuilder::new(snapshot)
+            .with_predicate(Expression::column("missing").is_null())
+            .build();
+        assert!(scan
+            .residual_predicate_evaluator(&engine_interface)
+            .is_err());
+    }
+
+    #[test]
+    fn test_scan_without_data_skipping() {
+        let engine_interface = SyncEngineInterface::new();
+        let snapshot = |path: &str| {
+            let path = std::fs::canonicalize(PathBuf::from(path)).unwrap();
+            let url = url::Url::from_directory_path(path).unwrap();
+            Table::new(url).snapshot(&engine_interface, None).unwrap()
+        };
+        let scan_files = |snapshot, predicate: Expression, data_skipping| {
+            let scan = ScanBuilder::new(snapshot)
+                .with_predicate(predicate.clone())
+                .with_data_skipping(data_skipping)
+                .build();
+            // the predicate is kept to filter rows either way
+            assert_eq!(scan.predicate(), &Some(predicate));
+            let files: Vec<Add> = scan
+                .files(&engine_interface)
+                .unwrap()
+                .try_collect()
+                .unwrap();
+            files.len()
+        };
+
+        // the stats of the single file say that no value is greater than 20
+        let table = snapshot("./tests/data/table-without-dv-small/");
+        let predicate = Expression::column("value").gt(Expression::literal(20_i64));
+        assert_eq!(scan_files(table.clone(), predicate.clone(), true), 0);
+        assert_eq!(scan_files(table, predicate, false), 1);
+
+        // partition pruning is disabled as well
+        let table = snapshot("./tests/data/basic_partitioned/");
+        let predicate = Expression::column("letter").eq(Expression::literal("a"));
+        assert_eq!(scan_files(table.clone(), predicate.clone(), true), 2);
+        assert_eq!(scan_files(table, predicate, false), 6);
+    }
+
+    #[test]
+    fn test_scan_with_skipping_observer() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        /// Counts the built filters and the actions they scanned and skipped.
+        #[derive(Default)]
+        struct CountingObserver {
+            filters: AtomicUsize,
+            scanned: AtomicUsize,
+            skipped: AtomicUsize,
+        }
+
+        impl SkippingObserver for CountingObserver {
+            fn on_filter_built(&self, _stats_schema: &SchemaRef) {
+                self.filters.fetch_add(1, Ordering::SeqCst);
+            }
+
+            fn on_batch_applied(
+                &self,
+                scanned: usize,
+                skipped: usize,
+                _duration: std::time::Duration,
+            ) {
+                self.scanned.fetch_add(scanned, Ordering::SeqCst);
+                self.skipped.fetch_add(skipped, Ordering::SeqCst);
+            }
+        }
+
+        let path =
+            std::fs::canonicalize(PathBuf::from("./tests/data/table-without-dv-small/")).unwrap();
+        let url = url::Url::from_directory_path(path).unwrap();
+        let engine_interface = SyncEngineInterface::new();
+        let snapshot = Table::new(url).snapshot(&engine_interface, None).unwrap();
+
+        let observer = Arc::new(CountingObserver::default());
+        let scan = ScanBuilder::new(snapshot)
+            .with_predicate(Expression::column("value").gt(Expression::literal(20_i64)))
+            .with_skipping_observer(observer.clone())
+            .build();
+        let files: Vec<Add> = scan
+            .files(&engine_interface)
+            .unwrap()
+            .try_collect()
+            .unwrap();
+        assert!(files.is_empty());
+        // the single commit has four actions, and the add action of the table's single file is
+        // skipped
+        assert_eq!(observer.filters.load(Ordering::SeqCst), 1);
+        assert_eq!(observer.scanned.load(Ordering::SeqCst), 4);
+        assert_eq!(observer.skipped.load(Ordering::SeqCst), 1);
+    }
+
     #[test]
     fn test_scan_data() {
         let path =
@@ -344,6 +1051,45 @@ mod tests {
         assert_eq!(num_rows, 10)
     }
 
+    #[test]
+    fn test_output_schema() {
+        let path =
+            std::fs::canonicalize(PathBuf::from("./tests/data/table-without-dv-small/")).unwrap();
+        let url = url::Url::from_directory_path(path).unwrap();
+        let engine_interface = SyncEngineInterface::new();
+        let table = Table::new(url);
+        let snapshot = table.snapshot(&engine_interface, None).unwrap();
+
+        // without a projection, the output schema is the scan's schema
+        let scan = ScanBuilder::new(snapshot.clone()).build();
+        assert_eq!(&scan.output_schema().unwrap(), scan.schema());
+
+        let scan = ScanBuilder::new(snapshot.clone())
+            .with_projection(vec![
+                (
+                    "positive".to_string(),
+                    Expression::column("value").gt(Expression::literal(0_i64)),
+                ),
+                ("v".to_string(), Expression::column("value")),
+            ])
+            .build();
+        let expected = StructType::new(vec![
+            StructField::new("positive", DataType::BOOLEAN, true),
+            StructField::new("v", DataType::LONG, true),
+        ]);
+        assert_eq!(scan.output_schema().unwrap().as_ref(), &expected);
+        let files = scan.execute(&engine_interface).unwrap();
+        let num_rows = files[0].raw_data.as_ref().unwrap().length();
+        assert_eq!(num_rows, 10);
+
+        // the projection can only use columns of the scan's schema
+        let scan = ScanBuilder::new(snapshot)
+            .with_projection(vec![("x".to_string(), Expression::column("missing"))])
+            .build();
+        assert!(scan.output_schema().is_err());
+        assert!(scan.execute(&engine_interface).is_err());
+    }
+
     #[test]
     fn test_get_partition_value() {
         let cases = [
@@ -376,6 +1122,11 @@ mod tests {
                 PrimitiveType::Timestamp,
                 Scalar::Timestamp(123456),
             ),
+            (
+                "1970-01-01 00:00:00.123456",
+                PrimitiveType::TimestampNtz,
+                Scalar::TimestampNtz(123456),
+            ),
         ];
 
         for (raw, data_type, expected) in &cases {
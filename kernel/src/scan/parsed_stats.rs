@@ -0,0 +1,404 @@
+//! Typed access to the stats that data skipping parsed from add actions
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use super::data_skipping::{StatsColumnNames, STAT_KINDS};
+use crate::engine_data::GetData;
+use crate::expressions::{Expression as Expr, Scalar};
+use crate::schema::{DataType, PrimitiveType, SchemaRef, StructField, StructType};
+use crate::{DataVisitor, DeltaResult, EngineData, EngineInterface};
+
+/// The stats of a batch of add actions, as parsed by a [`DataSkippingFilter`] (see
+/// [`DataSkippingFilter::apply_with_stats`]), with one row per action. The values of all stats
+/// are read from the engine data once, so that callers (e.g. for cost estimation) can look up the
+/// stats of each file by column without walking the engine data themselves.
+///
+/// Columns are named by their (possibly nested, e.g. `a.b`) path in the table schema. The
+/// accessors return `None` if the stats of a row don't have the value (e.g. because the file has
+/// no stats, or only NULL values in the column), or the filter didn't parse the stats of the
+/// column (see [`DataSkippingFilter::stats_schema`]).
+///
+/// [`DataSkippingFilter`]: super::DataSkippingFilter
+/// [`DataSkippingFilter::apply_with_stats`]: super::DataSkippingFilter::apply_with_stats
+/// [`DataSkippingFilter::stats_schema`]: super::DataSkippingFilter::stats_schema
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParsedStats {
+    num_rows: usize,
+    /// The values of each stats column by its Delta name (e.g. `minValues.a`), one per row.
+    values: HashMap<String, Vec<Option<Scalar>>>,
+}
+
+/// A primitive stats column, and how to read its values from the engine data.
+struct StatsLeaf {
+    /// The Delta name of the column, e.g. `minValues.a`.
+    name: String,
+    /// The path of the column in the stats schema, which uses the overridden names of the stats.
+    path: String,
+    data_type: PrimitiveType,
+}
+
+impl StatsLeaf {
+    /// The type to read values of the column as, since engine data can only be read as booleans,
+    /// integers, longs and strings (see [`GetData`]).
+    fn read_type(&self) -> DataType {
+        use PrimitiveType::*;
+        match self.data_type {
+            Boolean => DataType::BOOLEAN,
+            Byte | Short | Integer | Date => DataType::INTEGER,
+            Long | Timestamp | TimestampNtz => DataType::LONG,
+            String | Binary | Float | Double | Decimal(..) => DataType::STRING,
+        }
+    }
+
+    /// Reads the value of the column at `row` (read as [`Self::read_type`]).
+    fn read<'a>(&self, getter: &'a dyn GetData<'a>, row: usize) -> DeltaResult<Option<Scalar>> {
+        use PrimitiveType::*;
+        let value = match self.data_type {
+            Boolean => getter.get_bool(row, &self.name)?.map(Scalar::Boolean),
+            Byte | Short | Integer | Date => {
+                getter
+                    .get_int(row, &self.name)?
+                    .map(|value| match self.data_type {
+                        Byte => Scalar::Byte(value as i8),
+                        Short => Scalar::Short(value as i16),
+                        Date => Scalar::Date(value),
+                        _ => Scalar::Integer(value),
+                    })
+            }
+            Long | Timestamp | TimestampNtz => {
+                getter
+                    .get_long(row, &self.name)?
+                    .map(|value| match self.data_type {
+                        Timestamp => Scalar::Timestamp(value),
+                        TimestampNtz => Scalar::TimestampNtz(value),
+                        _ => Scalar::Long(value),
+                    })
+            }
+            String => getter.get_str(row, &self.name)?.map(Scalar::from),
+            Binary | Float | Double | Decimal(..) => {
+                let value = getter.get_str(row, &self.name)?;
+                value
+                    .map(|value| self.data_type.parse_scalar(value))
+                    .transpose()?
+            }
+        };
+        Ok(value)
+    }
+}
+
+impl ParsedStats {
+    /// Reads the stats parsed by a data skipping filter with the given `stats_schema` and
+    /// `stats_column_names`, using the engine's evaluator to select the values of all stats.
+    pub(crate) fn try_new(
+        engine_interface: &dyn EngineInterface,
+        stats_schema: &SchemaRef,
+        stats_column_names: &StatsColumnNames,
+        stats: &dyn EngineData,
+    ) -> DeltaResult<Self> {
+        let mut leaves = vec![];
+        for field in stats_schema.fields() {
+            let Some(kind) = STAT_KINDS
+                .into_iter()
+                .find(|kind| stats_column_names.name_of(kind) == field.name())
+            else {
+                continue;
+            };
+            collect_leaves(kind, field.name(), field.data_type(), &mut leaves);
+        }
+        let num_rows = stats.length();
+        let mut values: HashMap<_, _> = leaves
+            .iter()
+            .map(|leaf| (leaf.name.clone(), Vec::with_capacity(num_rows)))
+            .collect();
+        if leaves.is_empty() {
+            return Ok(Self { num_rows, values });
+        }
+
+        // select the stats as a flat struct of columns that can be read from the engine data
+        let read_fields: Vec<_> = leaves
+            .iter()
+            .map(|leaf| StructField::new(leaf.name.clone(), leaf.read_type(), true))
+            .collect();
+        let read_schema = Arc::new(StructType::new(read_fields));
+        let select = Expr::struct_expr(leaves.iter().map(|leaf| {
+            let column = Expr::column(&leaf.path);
+            match DataType::Primitive(leaf.data_type.clone()) == leaf.read_type() {
+                true => column,
+                false => column.cast(leaf.read_type()),
+            }
+        }));
+        let selected = engine_interface
+            .get_expression_handler()
+            .get_evaluator(
+                stats_schema.clone(),
+                select,
+                read_schema.as_ref().clone().into(),
+            )
+            .evaluate(stats)?;
+
+        let mut visitor = ParsedStatsVisitor {
+            leaves: &leaves,
+            values: &mut values,
+        };
+        selected.extract(read_schema, &mut visitor)?;
+        Ok(Self { num_rows, values })
+    }
+
+    /// The number of rows (i.e. actions) of the stats.
+    pub fn num_rows(&self) -> usize {
+        self.num_rows
+    }
+
+    /// The number of records in the file of the action at `row`.
+    pub fn num_records(&self, row: usize) -> Option<i64> {
+        match self.value("numRecords", row)? {
+            Scalar::Long(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// The number of NULL values of column `col` in the file of the action at `row`.
+    pub fn null_count(&self, row: usize, col: &str) -> Option<i64> {
+        match self.value(&format!("nullCount.{col}"), row)? {
+            Scalar::Long(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// The smallest value of column `col` in the file of the action at `row`.
+    pub fn min_value(&self, row: usize, col: &str) -> Option<Scalar> {
+        self.value(&format!("minValues.{col}"), row)
+    }
+
+    /// The largest value of column `col` in the file of the action at `row`.
+    pub fn max_value(&self, row: usize, col: &str) -> Option<Scalar> {
+        self.value(&format!("maxValues.{col}"), row)
+    }
+
+    fn value(&self, name: &str, row: usize) -> Option<Scalar> {
+        self.values.get(name)?.get(row)?.clone()
+    }
+}
+
+/// Collects the primitive columns of the stats of `kind`, which are at `path` in the stats
+/// schema and have type `data_type`. Columns of other types (e.g. arrays) have no min/max values.
+fn collect_leaves(kind: &str, path: &str, data_type: &DataType, leaves: &mut Vec<StatsLeaf>) {
+    match data_type {
+        DataType::Primitive(primitive) => {
+            let name = match path.split_once('.') {
+                Some((_, col)) => format!("{kind}.{col}"),
+                None => kind.to_string(),
+            };
+            leaves.push(StatsLeaf {
+                name,
+                path: path.to_string(),
+                data_type: primitive.clone(),
+            });
+        }
+        DataType::Struct(struct_type) => {
+            for field in struct_type.fields() {
+                let path = format!("{path}.{}", field.name());
+                collect_leaves(kind, &path, field.data_type(), leaves);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Reads the values of the stats columns from the flat struct of [`ParsedStats::try_new`].
+struct ParsedStatsVisitor<'a> {
+    leaves: &'a [StatsLeaf],
+    values: &'a mut HashMap<String, Vec<Option<Scalar>>>,
+}
+
+impl DataVisitor for ParsedStatsVisitor<'_> {
+    fn visit<'a>(&mut self, row_count: usize, getters: &[&'a dyn GetData<'a>]) -> DeltaResult<()> {
+        for (leaf, getter) in self.leaves.iter().zip(getters) {
+            // Safety: the map has an entry for every leaf
+            let values = self.values.get_mut(&leaf.name).unwrap();
+            for row in 0..row_count {
+                values.push(leaf.read(*getter, row)?);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use arrow_array::{RecordBatch, StringArray};
+    use arrow_schema::{DataType as ArrowDataType, Field, Schema as ArrowSchema};
+
+    use super::*;
+    use crate::actions::{get_log_schema, ADD_NAME};
+    use crate::client::arrow_data::ArrowEngineData;
+    use crate::client::sync::SyncEngineInterface;
+    use crate::scan::data_skipping::DataSkippingFilterBuilder;
+    use crate::schema::ArrayType;
+
+    /// Builds a batch of add actions, one per entry in `stats`.
+    fn add_actions_batch(
+        engine_interface: &dyn EngineInterface,
+        stats: &[Option<&str>],
+    ) -> Box<dyn EngineData> {
+        let json_strings: StringArray = stats
+            .iter()
+            .enumerate()
+            .map(|(i, stats)| {
+                serde_json::json!({
+                    "add": {
+                        "path": format!("part-{i:05}.parquet"),
+                        "partitionValues": {},
+                        "size": 1024,
+                        "modificationTime": 1677811178336_i64,
+                        "dataChange": true,
+                        "stats": stats,
+                    }
+                })
+                .to_string()
+            })
+            .collect::<Vec<_>>()
+            .into();
+        let string_field = Arc::new(Field::new("a", ArrowDataType::Utf8, true));
+        let schema = Arc::new(ArrowSchema::new(vec![string_field]));
+        let batch = RecordBatch::try_new(schema, vec![Arc::new(json_strings)]).unwrap();
+        engine_interface
+            .get_json_handler()
+            .parse_json(
+                Box::new(ArrowEngineData::new(batch)),
+                get_log_schema().project(&[ADD_NAME]).unwrap(),
+            )
+            .unwrap()
+    }
+
+    #[test]
+    fn test_parsed_stats() {
+        let engine_interface = SyncEngineInterface::new();
+        let table_schema = Arc::new(StructType::new(vec![
+            StructField::new("a", DataType::INTEGER, true),
+            StructField::new(
+                "s",
+                StructType::new(vec![StructField::new("b", DataType::STRING, true)]),
+                true,
+            ),
+            StructField::new("d", DataType::DATE, true),
+            StructField::new("t", DataType::TIMESTAMP, true),
+            StructField::new("x", DataType::DOUBLE, true),
+            StructField::new("price", DataType::decimal(5, 2), true),
+            StructField::new("tags", ArrayType::new(DataType::STRING, true), true),
+        ]));
+        let predicate = Some(Expr::and_from([
+            Expr::column("a").gt(Expr::literal(0)),
+            Expr::column("s.b").gt(Expr::literal("a")),
+            Expr::column("d").gt(Expr::literal(Scalar::Date(0))),
+            Expr::column("t").gt(Expr::literal(Scalar::Timestamp(0))),
+            Expr::column("x").gt(Expr::literal(Scalar::Double(0.0))),
+            Expr::column("price").gt(Expr::literal(Scalar::Decimal(0, 5, 2))),
+            Expr::column("tags").is_null(),
+        ]));
+        let filter = DataSkippingFilterBuilder::new(&table_schema, &predicate)
+            .build(&engine_interface)
+            .unwrap();
+        let actions = add_actions_batch(
+            &engine_interface,
+            &[
+                Some(
+                    r#"{"numRecords":3,
+                    "nullCount":{"a":0,"s":{"b":1},"d":0,"t":0,"x":0,"price":2,"tags":1},
+                    "minValues":{"a":1,"s":{"b":"apple"},"d":"2024-01-01",
+                        "t":"2024-01-01T00:00:00.000Z","x":-1.5,"price":1.23},
+                    "maxValues":{"a":9,"s":{"b":"cherry"},"d":"2024-01-03",
+                        "t":"2024-01-01T00:00:01.000Z","x":2.25,"price":1.23}}"#,
+                ),
+                // only some stats
+                Some(r#"{"numRecords":5,"minValues":{"a":4},"maxValues":{"a":7}}"#),
+                None,
+            ],
+        );
+        let stats = filter
+            .apply_with_stats(actions.as_ref())
+            .unwrap()
+            .parsed_stats;
+        let stats = filter
+            .parsed_stats(&engine_interface, stats.as_ref())
+            .unwrap();
+
+        assert_eq!(stats.num_rows(), 3);
+        assert_eq!(stats.num_records(0), Some(3));
+        assert_eq!(stats.null_count(0, "a"), Some(0));
+        assert_eq!(stats.null_count(0, "s.b"), Some(1));
+        assert_eq!(stats.null_count(0, "tags"), Some(1));
+        assert_eq!(stats.min_value(0, "a"), Some(Scalar::Integer(1)));
+        assert_eq!(stats.max_value(0, "a"), Some(Scalar::Integer(9)));
+        assert_eq!(stats.min_value(0, "s.b"), Some(Scalar::from("apple")));
+        assert_eq!(stats.max_value(0, "s.b"), Some(Scalar::from("cherry")));
+        assert_eq!(stats.min_value(0, "d"), Some(Scalar::Date(19723)));
+        assert_eq!(stats.max_value(0, "d"), Some(Scalar::Date(19725)));
+        assert_eq!(
+            stats.min_value(0, "t"),
+            Some(Scalar::Timestamp(1_704_067_200_000_000))
+        );
+        assert_eq!(
+            stats.max_value(0, "t"),
+            Some(Scalar::Timestamp(1_704_067_201_000_000))
+        );
+        assert_eq!(stats.min_value(0, "x"), Some(Scalar::Double(-1.5)));
+        assert_eq!(stats.max_value(0, "x"), Some(Scalar::Double(2.25)));
+        assert_eq!(
+            stats.min_value(0, "price"),
+            Some(Scalar::Decimal(123, 5, 2))
+        );
+        // arrays have no min/max values
+        assert_eq!(stats.min_value(0, "tags"), None);
+
+        assert_eq!(stats.num_records(1), Some(5));
+        assert_eq!(stats.null_count(1, "a"), None);
+        assert_eq!(stats.min_value(1, "a"), Some(Scalar::Integer(4)));
+        assert_eq!(stats.max_value(1, "s.b"), None);
+
+        // no stats, and rows or columns that don't exist
+        assert_eq!(stats.num_records(2), None);
+        assert_eq!(stats.min_value(2, "a"), None);
+        assert_eq!(stats.num_records(3), None);
+        assert_eq!(stats.min_value(0, "missing"), None);
+    }
+
+    #[test]
+    fn test_parsed_stats_renamed_columns() {
+        let engine_interface = SyncEngineInterface::new();
+        let stats_schema = Arc::new(StructType::new(vec![
+            StructField::new("rows", DataType::LONG, true),
+            StructField::new(
+                "low",
+                StructType::new(vec![StructField::new("a", DataType::INTEGER, true)]),
+                true,
+            ),
+        ]));
+        let stats_column_names = StatsColumnNames {
+            num_records: "rows".to_string(),
+            min_values: "low".to_string(),
+            ..Default::default()
+        };
+        let json_strings: StringArray = vec![r#"{"rows":2,"low":{"a":5}}"#, r#"{"rows":1}"#].into();
+        let string_field = Arc::new(Field::new("a", ArrowDataType::Utf8, true));
+        let schema = Arc::new(ArrowSchema::new(vec![string_field]));
+        let batch = RecordBatch::try_new(schema, vec![Arc::new(json_strings)]).unwrap();
+        let stats = engine_interface
+            .get_json_handler()
+            .parse_json(Box::new(ArrowEngineData::new(batch)), stats_schema.clone())
+            .unwrap();
+
+        let stats = ParsedStats::try_new(
+            &engine_interface,
+            &stats_schema,
+            &stats_column_names,
+            stats.as_ref(),
+        )
+        .unwrap();
+        assert_eq!(stats.num_records(0), Some(2));
+        assert_eq!(stats.min_value(0, "a"), Some(Scalar::Integer(5)));
+        assert_eq!(stats.num_records(1), Some(1));
+        assert_eq!(stats.min_value(1, "a"), None);
+    }
+}
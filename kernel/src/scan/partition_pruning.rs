@@ -0,0 +1,717 @@
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+
+use regex::Regex;
+use tracing::debug;
+
+use super::data_skipping::{DataSkippingFilter, DataSkippingFilterBuilder};
+use super::parse_partition_value;
+use crate::actions::{get_log_schema, ADD_NAME};
+use crate::engine_data::{GetData, TypedGetData};
+use crate::error::DeltaResult;
+use crate::expressions::{
+    cast_scalar, expand_quantified, BinaryOperator, Expression as Expr, Scalar, UnaryOperator,
+    VariadicOperator,
+};
+use crate::schema::{SchemaRef, StructField};
+use crate::{DataVisitor, EngineData, EngineInterface};
+
+/// Returns the operands of a (possibly nested) conjunction or disjunction, or the predicate itself
+/// if it is not one.
+fn split_variadic<'a>(predicate: &'a Expr, variadic_op: &VariadicOperator) -> Vec<&'a Expr> {
+    match predicate {
+        Expr::VariadicOperation { op, exprs } if op == variadic_op => exprs
+            .iter()
+            .flat_map(|expr| split_variadic(expr, variadic_op))
+            .collect(),
+        _ => vec![predicate],
+    }
+}
+
+/// Returns the conjunction of `exprs`, or `None` if there are none.
+fn conjunction(exprs: Vec<Expr>) -> Option<Expr> {
+    match exprs.len() {
+        0 => None,
+        1 => exprs.into_iter().next(),
+        _ => Some(Expr::and_from(exprs)),
+    }
+}
+
+/// Returns whether `expr` references columns, and all of them are partition columns.
+fn is_partition_predicate(expr: &Expr, partition_columns: &[String]) -> bool {
+    let references = expr.references();
+    !references.is_empty()
+        && references
+            .iter()
+            .all(|name| partition_columns.iter().any(|col| col == name))
+}
+
+/// Splits `predicate` into the conjunction of its operands that only reference partition columns,
+/// and the conjunction of its remaining operands. Either part is `None` if it has no operands.
+/// Operands that don't reference any column at all are kept with the remaining operands.
+pub(crate) fn split_partition_predicate(
+    predicate: &Expr,
+    partition_columns: &[String],
+) -> (Option<Expr>, Option<Expr>) {
+    let (partition_exprs, data_exprs): (Vec<_>, Vec<_>) =
+        split_variadic(predicate, &VariadicOperator::And)
+            .into_iter()
+            .cloned()
+            .partition(|expr| is_partition_predicate(expr, partition_columns));
+    (conjunction(partition_exprs), conjunction(data_exprs))
+}
+
+/// Splits a disjunction into the disjunction of its operands that only reference partition columns,
+/// and the disjunction of its operands that don't reference any partition column. Returns `None`
+/// if `predicate` doesn't have both kinds of operands, or has an operand that references both
+/// partition and data columns.
+fn split_partition_disjunction(
+    predicate: &Expr,
+    partition_columns: &[String],
+) -> Option<(Expr, Expr)> {
+    let mut partition_exprs = vec![];
+    let mut data_exprs = vec![];
+    for expr in split_variadic(predicate, &VariadicOperator::Or) {
+        if is_partition_predicate(expr, partition_columns) {
+            partition_exprs.push(expr.clone());
+        } else if expr
+            .references()
+            .iter()
+            .all(|name| partition_columns.iter().all(|col| col != name))
+        {
+            data_exprs.push(expr.clone());
+        } else {
+            return None;
+        }
+    }
+    if partition_exprs.is_empty() || data_exprs.is_empty() {
+        return None;
+    }
+    let disjunction = |mut exprs: Vec<Expr>| match exprs.len() {
+        1 => exprs.remove(0),
+        _ => Expr::or_from(exprs),
+    };
+    Some((disjunction(partition_exprs), disjunction(data_exprs)))
+}
+
+/// Splits `predicate` into its operands that are disjunctions of partition and data predicates
+/// (see [`PartitionDisjunctionFilter`]), and the conjunction of its remaining operands.
+pub(crate) fn split_partition_disjunctions(
+    predicate: &Expr,
+    partition_columns: &[String],
+) -> (Vec<Expr>, Option<Expr>) {
+    let (disjunctions, other_exprs): (Vec<_>, Vec<_>) =
+        split_variadic(predicate, &VariadicOperator::And)
+            .into_iter()
+            .cloned()
+            .partition(|expr| split_partition_disjunction(expr, partition_columns).is_some());
+    (disjunctions, conjunction(other_exprs))
+}
+
+/// Evaluates a value expression against the partition values of a file. Returns `None` if the
+/// expression is not a literal or a (known) partition column, or a cast of one that can't be
+/// converted exactly (see [`cast_scalar`]).
+fn evaluate_value(expr: &Expr, partition_values: &HashMap<&str, Scalar>) -> Option<Scalar> {
+    match expr {
+        Expr::Literal(value) => Some(value.clone()),
+        Expr::Column(name) => partition_values.get(name.as_str()).cloned(),
+        // only exact conversions, so that the result doesn't depend on how the engine rounds
+        Expr::Cast { expr, target_type } => {
+            cast_scalar(&evaluate_value(expr, partition_values)?, target_type)
+        }
+        _ => None,
+    }
+}
+
+/// The compiled regular expressions of the RLIKE patterns of a predicate, by pattern.
+type Regexes = HashMap<String, Regex>;
+
+/// Compiles the literal patterns of the RLIKE comparisons of `predicate`, so that a filter compiles
+/// each of them once instead of for every file. Invalid patterns are left out.
+fn compile_patterns(predicate: &Expr) -> Regexes {
+    predicate
+        .walk()
+        .flat_map(|expr| match expr {
+            Expr::BinaryOperation {
+                op: BinaryOperator::RLike,
+                right,
+                ..
+            } => vec![right.as_ref()],
+            Expr::Quantified {
+                op: BinaryOperator::RLike,
+                list,
+                ..
+            } => list.iter().collect(),
+            _ => vec![],
+        })
+        .filter_map(|pattern| match pattern {
+            Expr::Literal(Scalar::String(pattern)) => {
+                Some((pattern.clone(), Regex::new(pattern).ok()?))
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+/// Evaluates a predicate against the partition values of a file, following SQL semantics. The
+/// result is `Some(None)` if the predicate is NULL, and `None` if the predicate can't be evaluated
+/// from the partition values alone. RLIKE comparisons can only be evaluated for the patterns
+/// compiled in `regexes` (see [`compile_patterns`]).
+fn evaluate_predicate(
+    expr: &Expr,
+    partition_values: &HashMap<&str, Scalar>,
+    regexes: &Regexes,
+) -> Option<Option<bool>> {
+    use BinaryOperator::*;
+    use Expr::*;
+
+    match expr {
+        Literal(_) | Column(_) | Cast { .. } => match evaluate_value(expr, partition_values)? {
+            Scalar::Boolean(value) => Some(Some(value)),
+            Scalar::Null(_) => Some(None),
+            _ => None,
+        },
+        BinaryOperation {
+            op: op @ (Distinct | NullSafeEqual),
+            left,
+            right,
+        } => {
+            let left = evaluate_value(left, partition_values)?;
+            let right = evaluate_value(right, partition_values)?;
+            let equal = match (left, right) {
+                (Scalar::Null(_), Scalar::Null(_)) => true,
+                (Scalar::Null(_), _) | (_, Scalar::Null(_)) => false,
+                (left, right) => left.partial_cmp(&right)? == Ordering::Equal,
+            };
+            Some(Some(equal == (*op == NullSafeEqual)))
+        }
+        BinaryOperation { op, left, right } => {
+            let left = evaluate_value(left, partition_values)?;
+            let right = evaluate_value(right, partition_values)?;
+            if matches!(left, Scalar::Null(_)) || matches!(right, Scalar::Null(_)) {
+                return Some(None);
+            }
+            match (op, &left, &right) {
+                (StartsWith, Scalar::String(value), Scalar::String(prefix)) => {
+                    return Some(Some(value.starts_with(prefix.as_str())));
+                }
+                (RLike, Scalar::String(value), Scalar::String(pattern)) => {
+                    return Some(Some(regexes.get(pattern)?.is_match(value)));
+                }
+                _ => {}
+            }
+            let ordering = left.partial_cmp(&right)?;
+            let result = match op {
+                LessThan => ordering == Ordering::Less,
+                LessThanOrEqual => ordering != Ordering::Greater,
+                GreaterThan => ordering == Ordering::Greater,
+                GreaterThanOrEqual => ordering != Ordering::Less,
+                Equal => ordering == Ordering::Equal,
+                NotEqual => ordering != Ordering::Equal,
+                _ => return None, // not a predicate
+            };
+            Some(Some(result))
+        }
+        UnaryOperation {
+            op: UnaryOperator::Not,
+            expr,
+        } => Some(evaluate_predicate(expr, partition_values, regexes)?.map(|value| !value)),
+        UnaryOperation {
+            op: op @ (UnaryOperator::IsNull | UnaryOperator::IsNotNull),
+            expr,
+        } => {
+            let is_null = matches!(evaluate_value(expr, partition_values)?, Scalar::Null(_));
+            Some(Some(is_null == (*op == UnaryOperator::IsNull)))
+        }
+        VariadicOperation { op, exprs } => {
+            // AND is false if any operand is false, OR is true if any operand is true. Otherwise
+            // the result is NULL if any operand is NULL, unless an operand can't be evaluated.
+            let dominant = matches!(op, VariadicOperator::Or);
+            let mut result = Some(Some(!dominant));
+            for expr in exprs {
+                match evaluate_predicate(expr, partition_values, regexes) {
+                    Some(Some(value)) if value == dominant => return Some(Some(dominant)),
+                    Some(Some(_)) => {}
+                    Some(None) => result = result.map(|_| None),
+                    None => result = None,
+                }
+            }
+            result
+        }
+        // `value IN (list)` is true if the value equals a value of the list, and otherwise NULL if
+        // the value or a value of the list is NULL, so a NULL partition value never matches and
+        // `NOT IN` a list with a NULL value never keeps a file.
+        InList { expr, list } => {
+            let exprs = list
+                .iter()
+                .map(|value| Expr::eq(*expr.clone(), value.clone()));
+            evaluate_predicate(&Expr::or_from(exprs), partition_values, regexes)
+        }
+        Quantified {
+            op,
+            quantifier,
+            expr,
+            list,
+        } => evaluate_predicate(
+            &expand_quantified(op, quantifier, expr, list),
+            partition_values,
+            regexes,
+        ),
+        Between { expr, low, high } => {
+            let exprs = [
+                Expr::ge(*expr.clone(), *low.clone()),
+                Expr::le(*expr.clone(), *high.clone()),
+            ];
+            evaluate_predicate(&Expr::and_from(exprs), partition_values, regexes)
+        }
+        Struct(_)
+        | CaseWhen { .. }
+        | Coalesce(_)
+        | Greatest(_)
+        | Least(_)
+        | MapAccess { .. }
+        | ArraySize(_)
+        | ArrayElement { .. } => None,
+    }
+}
+
+/// Prunes files based on the partition values of their Add actions, by evaluating the part of a
+/// predicate that only references partition columns. This complements the [`DataSkippingFilter`],
+/// which can't skip based on partition columns because they have no stats.
+pub(crate) struct PartitionFilter {
+    partition_fields: Vec<StructField>,
+    predicate: Expr,
+    regexes: Regexes,
+}
+
+impl PartitionFilter {
+    /// Creates a new partition filter from the operands of `predicate` that only reference
+    /// partition columns. Returns None if there is no predicate, or no such operands.
+    pub(crate) fn new(
+        table_schema: &SchemaRef,
+        partition_columns: &[String],
+        predicate: &Option<Expr>,
+    ) -> Option<Self> {
+        let (predicate, _) = split_partition_predicate(predicate.as_ref()?, partition_columns);
+        let predicate = predicate?;
+        debug!("Creating a partition filter for {:#}", &predicate);
+
+        let references: HashSet<_> = predicate.references();
+        let partition_fields: Vec<_> = table_schema
+            .fields()
+            .filter(|field| references.contains(field.name().as_str()))
+            .cloned()
+            .collect();
+        if partition_fields.len() != references.len() {
+            // Without the type of a partition column we can't parse its values.
+            debug!("Partition columns of {predicate} not found in table schema, not pruning");
+            return None;
+        }
+        Some(Self {
+            partition_fields,
+            regexes: compile_patterns(&predicate),
+            predicate,
+        })
+    }
+
+    /// Returns whether a file with the given (raw) partition values must be kept.
+    fn keep_file(&self, partition_values: &HashMap<String, String>) -> DeltaResult<bool> {
+        let partition_values = self
+            .partition_fields
+            .iter()
+            .map(|field| {
+                let value =
+                    parse_partition_value(partition_values.get(field.name()), field.data_type())?;
+                Ok((field.name().as_str(), value))
+            })
+            .collect::<DeltaResult<_>>()?;
+        // The partition values are exact, so no row of the file can match if the predicate is
+        // either false or NULL.
+        let result = evaluate_predicate(&self.predicate, &partition_values, &self.regexes);
+        Ok(!matches!(result, Some(Some(false) | None)))
+    }
+
+    /// Apply the PartitionFilter to an EngineData batch of actions. Returns a selection vector
+    /// which can be applied to the actions to find those that passed partition pruning. Rows that
+    /// are not Add actions are always selected.
+    pub(crate) fn apply(&self, actions: &dyn EngineData) -> DeltaResult<Vec<bool>> {
+        let mut visitor = PartitionFilterVisitor {
+            filter: self,
+            selection_vector: vec![],
+        };
+        actions.extract(get_log_schema().project(&[ADD_NAME])?, &mut visitor)?;
+        Ok(visitor.selection_vector)
+    }
+}
+
+/// Skips files based on a disjunction of partition and data predicates, such as
+/// `part = 'x' OR value > 5`, which neither the [`PartitionFilter`] nor the [`DataSkippingFilter`]
+/// can skip on by itself.
+///
+/// Substituting the partition values of a file for the partition operands leaves either
+/// `TRUE OR <data predicate>`, which keeps the file, or `FALSE OR <data predicate>` (or NULL
+/// instead of FALSE), which can be skipped on the stats of the data operands. So a file is kept if
+/// either the partition filter or the data skipping filter of the respective operands keeps it.
+pub(crate) struct PartitionDisjunctionFilter {
+    partition_filter: PartitionFilter,
+    data_filter: DataSkippingFilter,
+}
+
+impl PartitionDisjunctionFilter {
+    /// Creates a new filter for a disjunction of partition and data predicates. Returns None if
+    /// `predicate` is not such a disjunction, or its data operands are ineligible for data
+    /// skipping.
+    pub(crate) fn new(
+        table_client: &dyn EngineInterface,
+        table_schema: &SchemaRef,
+        partition_columns: &[String],
+        predicate: &Expr,
+    ) -> Option<Self> {
+        let (partition_predicate, data_predicate) =
+            split_partition_disjunction(predicate, partition_columns)?;
+        let partition_filter =
+            PartitionFilter::new(table_schema, partition_columns, &Some(partition_predicate))?;
+        let data_filter = DataSkippingFilterBuilder::new(table_schema, &Some(data_predicate))
+            .with_referenced_stats_only(true)
+            .build(table_client)?;
+        Some(Self {
+            partition_filter,
+            data_filter,
+        })
+    }
+
+    /// Apply the PartitionDisjunctionFilter to an EngineData batch of actions. Returns a selection
+    /// vector which can be applied to the actions to find those that passed the filter.
+    pub(crate) fn apply(&self, actions: &dyn EngineData) -> DeltaResult<Vec<bool>> {
+        let partition_selection_vector = self.partition_filter.apply(actions)?;
+        let data_selection_vector = self.data_filter.apply(actions)?;
+        Ok(partition_selection_vector
+            .into_iter()
+            .zip(data_selection_vector)
+            .map(|(partition, data)| partition || data)
+            .collect())
+    }
+}
+
+struct PartitionFilterVisitor<'a> {
+    filter: &'a PartitionFilter,
+    selection_vector: Vec<bool>,
+}
+
+impl DataVisitor for PartitionFilterVisitor<'_> {
+    fn visit<'a>(&mut self, row_count: usize, getters: &[&'a dyn GetData<'a>]) -> DeltaResult<()> {
+        for i in 0..row_count {
+            // Add will have a path at index 0 if it is valid
+            let path: Option<String> = getters[0].get_opt(i, "add.path")?;
+            let keep = match path {
+                Some(_) => {
+                    let partition_values = getters[1].get(i, "add.partitionValues")?;
+                    self.filter.keep_file(&partition_values)?
+                }
+                None => true,
+            };
+            self.selection_vector.push(keep);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::schema::{DataType, StructType};
+
+    #[test]
+    fn test_split_partition_predicate() {
+        let partition_columns = vec!["p".to_string(), "q".to_string()];
+        let part = Expr::column("p").eq(Expr::literal("x"));
+        let part2 = Expr::column("p").lt(Expr::column("q"));
+        let data = Expr::column("a").gt(Expr::literal(5));
+        let mixed = Expr::column("p").eq(Expr::column("a"));
+
+        let cases = [
+            (
+                Expr::and_from([part.clone(), data.clone()]),
+                Some(part.clone()),
+                Some(data.clone()),
+            ),
+            (
+                Expr::and_from([
+                    part.clone(),
+                    Expr::and_from([data.clone(), part2.clone()]),
+                    mixed.clone(),
+                ]),
+                Some(Expr::and_from([part.clone(), part2.clone()])),
+                Some(Expr::and_from([data.clone(), mixed.clone()])),
+            ),
+            (part.clone(), Some(part.clone()), None),
+            (data.clone(), None, Some(data.clone())),
+            // an OR can't be split
+            (
+                part.clone().or(data.clone()),
+                None,
+                Some(part.clone().or(data.clone())),
+            ),
+            (
+                Expr::and_from([Expr::literal(true), part.clone()]),
+                Some(part.clone()),
+                Some(Expr::literal(true)),
+            ),
+        ];
+        for (predicate, partition_predicate, data_predicate) in cases {
+            assert_eq!(
+                split_partition_predicate(&predicate, &partition_columns),
+                (partition_predicate, data_predicate),
+                "{predicate}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_split_partition_disjunctions() {
+        let partition_columns = vec!["p".to_string()];
+        let part = Expr::column("p").eq(Expr::literal("x"));
+        let data = Expr::column("a").gt(Expr::literal(5));
+        let data2 = Expr::column("b").lt(Expr::literal(3));
+        let mixed = Expr::column("p").eq(Expr::column("a"));
+
+        let cases = [
+            (
+                part.clone().or(data.clone()),
+                vec![part.clone().or(data.clone())],
+                None,
+            ),
+            (
+                Expr::or_from([data.clone(), part.clone().or(data2.clone())]),
+                vec![Expr::or_from([
+                    data.clone(),
+                    part.clone().or(data2.clone()),
+                ])],
+                None,
+            ),
+            (
+                Expr::and_from([part.clone().or(data.clone()), data2.clone()]),
+                vec![part.clone().or(data.clone())],
+                Some(data2.clone()),
+            ),
+            // disjunctions without both kinds of operands, or with mixed operands, aren't split
+            (
+                data.clone().or(data2.clone()),
+                vec![],
+                Some(data.clone().or(data2.clone())),
+            ),
+            (
+                part.clone().or(mixed.clone()),
+                vec![],
+                Some(part.clone().or(mixed.clone())),
+            ),
+        ];
+        for (predicate, disjunctions, other) in cases {
+            assert_eq!(
+                split_partition_disjunctions(&predicate, &partition_columns),
+                (disjunctions, other),
+                "{predicate}"
+            );
+        }
+        assert_eq!(
+            split_partition_disjunction(
+                &Expr::or_from([data.clone(), part.clone().or(data2.clone())]),
+                &partition_columns
+            ),
+            Some((part, data.or(data2)))
+        );
+    }
+
+    #[test]
+    fn test_evaluate_predicate() {
+        let partition_values = HashMap::from([
+            ("s", Scalar::String("x".to_string())),
+            ("i", Scalar::Integer(5)),
+            ("b", Scalar::Boolean(true)),
+            ("n", Scalar::Null(DataType::INTEGER)),
+        ]);
+        let col = Expr::column;
+        let lit = |value: i32| Expr::literal(value);
+
+        let cases = [
+            (col("s").eq(Expr::literal("x")), Some(Some(true))),
+            (col("s").ne(Expr::literal("x")), Some(Some(false))),
+            (col("s").starts_with(Expr::literal("xy")), Some(Some(false))),
+            (col("s").rlike(Expr::literal("^x$")), Some(Some(true))),
+            (col("s").rlike(Expr::literal("[")), None),
+            // only literal patterns are compiled
+            (col("s").rlike(col("s")), None),
+            (col("i").lt(lit(6)), Some(Some(true))),
+            (lit(6).le(col("i")), Some(Some(false))),
+            (col("i").gt_eq(lit(5)), Some(Some(true))),
+            (col("b"), Some(Some(true))),
+            (!col("b"), Some(Some(false))),
+            (col("n").is_null(), Some(Some(true))),
+            (col("i").is_null(), Some(Some(false))),
+            (col("n").is_not_null(), Some(Some(false))),
+            (col("i").is_not_null(), Some(Some(true))),
+            // comparisons with NULL are NULL
+            (col("n").eq(lit(5)), Some(None)),
+            (!col("n").eq(lit(5)), Some(None)),
+            (col("n").distinct(lit(5)), Some(Some(true))),
+            (col("i").distinct(lit(5)), Some(Some(false))),
+            (col("n").null_safe_eq(lit(5)), Some(Some(false))),
+            (
+                col("n").null_safe_eq(Expr::literal(Scalar::Null(DataType::INTEGER))),
+                Some(Some(true)),
+            ),
+            (col("i").null_safe_eq(lit(5)), Some(Some(true))),
+            // Kleene logic
+            (
+                col("n").eq(lit(5)).and(col("i").eq(lit(4))),
+                Some(Some(false)),
+            ),
+            (col("n").eq(lit(5)).and(col("i").eq(lit(5))), Some(None)),
+            (
+                col("n").eq(lit(5)).or(col("i").eq(lit(5))),
+                Some(Some(true)),
+            ),
+            (col("n").eq(lit(5)).or(col("i").eq(lit(4))), Some(None)),
+            (col("n").eq(lit(5)).or(col("a").eq(lit(4))), None),
+            (
+                col("i").eq(lit(5)).or(col("a").eq(lit(4))),
+                Some(Some(true)),
+            ),
+            (
+                col("i").eq(lit(4)).and(col("a").eq(lit(4))),
+                Some(Some(false)),
+            ),
+            (col("i").in_list([lit(1), lit(5)]), Some(Some(true))),
+            (col("i").in_list([lit(1), lit(2)]), Some(Some(false))),
+            (col("i").not_in_list([lit(1), lit(2)]), Some(Some(true))),
+            (Expr::between(col("i"), lit(1), lit(5)), Some(Some(true))),
+            (Expr::between(col("i"), lit(6), lit(9)), Some(Some(false))),
+            // comparisons between different types, or with unknown columns, can't be evaluated
+            (col("i").eq(Expr::literal(5_i64)), None),
+            (col("a").eq(lit(5)), None),
+            ((col("i") + lit(1)).eq(lit(6)), None),
+        ];
+        for (predicate, expected) in cases {
+            assert_eq!(
+                evaluate_predicate(&predicate, &partition_values, &compile_patterns(&predicate)),
+                expected,
+                "{predicate}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_compile_patterns() {
+        let s = || Expr::column("s");
+        let predicate = Expr::and_from([
+            s().rlike(Expr::literal("^x")),
+            !s().rlike(Expr::literal("^x")),
+            s().rlike(Expr::literal("y$"))
+                .or(s().rlike(Expr::literal("["))),
+        ]);
+        let regexes = compile_patterns(&predicate);
+        let mut patterns: Vec<_> = regexes.keys().map(String::as_str).collect();
+        patterns.sort();
+        // each pattern is compiled once, and invalid ones not at all
+        assert_eq!(patterns, ["^x", "y$"]);
+    }
+
+    #[test]
+    fn test_in_list_pruning() {
+        let table_schema = Arc::new(StructType::new(vec![
+            StructField::new("p", DataType::STRING, true),
+            StructField::new("a", DataType::INTEGER, true),
+        ]));
+        let partition_columns = vec!["p".to_string()];
+        let partition_values = |p: Option<&str>| {
+            p.map(|p| HashMap::from([("p".to_string(), p.to_string())]))
+                .unwrap_or_default()
+        };
+        let lit = |value: &str| Expr::literal(value);
+        let null = || Expr::literal(Scalar::Null(DataType::STRING));
+
+        let cases = [
+            (
+                Expr::column("p").in_list([lit("a"), lit("b"), lit("c")]),
+                [true, true, true, false, false],
+            ),
+            // NOT IN keeps the files outside the set
+            (
+                Expr::column("p").not_in_list([lit("a"), lit("b")]),
+                [false, false, true, true, false],
+            ),
+            // a NULL value of the list matches nothing, but makes NOT IN unknown for all values
+            (
+                Expr::column("p").in_list([lit("a"), null()]),
+                [true, false, false, false, false],
+            ),
+            (
+                Expr::column("p").not_in_list([lit("a"), null()]),
+                [false, false, false, false, false],
+            ),
+            (
+                Expr::and_from([
+                    Expr::column("p").in_list([lit("a"), lit("d")]),
+                    Expr::column("a").gt(Expr::literal(5)),
+                ]),
+                [true, false, false, true, false],
+            ),
+        ];
+        for (predicate, expected) in cases {
+            let filter =
+                PartitionFilter::new(&table_schema, &partition_columns, &Some(predicate.clone()))
+                    .unwrap();
+            let kept = [Some("a"), Some("b"), Some("c"), Some("d"), None]
+                .map(|p| filter.keep_file(&partition_values(p)).unwrap());
+            assert_eq!(kept, expected, "{predicate}");
+        }
+    }
+
+    #[test]
+    fn test_keep_file() {
+        let table_schema = Arc::new(StructType::new(vec![
+            StructField::new("p", DataType::STRING, true),
+            StructField::new("d", DataType::DATE, true),
+            StructField::new("a", DataType::INTEGER, true),
+        ]));
+        let partition_columns = vec!["p".to_string(), "d".to_string()];
+        let predicate = Expr::and_from([
+            Expr::column("p").eq(Expr::literal("x")),
+            Expr::column("d").gt_eq(Expr::Literal(Scalar::Date(19723))),
+            Expr::column("a").gt(Expr::literal(5)),
+        ]);
+        let filter =
+            PartitionFilter::new(&table_schema, &partition_columns, &Some(predicate)).unwrap();
+
+        let partition_values = |p: Option<&str>, d: &str| {
+            let mut values = HashMap::from([("d".to_string(), d.to_string())]);
+            if let Some(p) = p {
+                values.insert("p".to_string(), p.to_string());
+            }
+            values
+        };
+        let cases = [
+            (partition_values(Some("x"), "2024-01-01"), true),
+            (partition_values(Some("x"), "2023-12-31"), false),
+            (partition_values(Some("y"), "2024-01-01"), false),
+            // a null partition value never equals a literal
+            (partition_values(None, "2024-01-01"), false),
+        ];
+        for (partition_values, expected) in cases {
+            assert_eq!(filter.keep_file(&partition_values).unwrap(), expected);
+        }
+
+        // partition values that can't be parsed as the column type are an error
+        assert!(filter
+            .keep_file(&partition_values(Some("x"), "not a date"))
+            .is_err());
+
+        // no filter if the predicate doesn't reference partition columns
+        let predicate = Expr::column("a").gt(Expr::literal(5));
+        assert!(
+            PartitionFilter::new(&table_schema, &partition_columns, &Some(predicate)).is_none()
+        );
+    }
+}
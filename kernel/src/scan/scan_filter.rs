@@ -0,0 +1,745 @@
+//! A filter that prunes the files of a scan by both their stats and their partition values
+
+use std::sync::Arc;
+
+use tracing::debug;
+
+use super::data_skipping::{
+    DataSkippingFilter, DataSkippingFilterBuilder, EvaluatorCache, MissingStatsPolicy,
+    SkippingObserver, StatsCache, StatsColumnNames,
+};
+use super::partition_pruning::{
+    split_partition_disjunctions, split_partition_predicate, PartitionDisjunctionFilter,
+    PartitionFilter,
+};
+use super::selection::combine_selection_vectors_in_place;
+use crate::expressions::Expression;
+use crate::schema::{SchemaRef, StructType};
+use crate::{DeltaResult, EngineData, EngineInterface, JsonHandler};
+
+/// Combines two optional selection vectors, keeping the actions that both of them select. `None`
+/// selects all actions.
+fn and_selection_vectors(
+    left: Option<Vec<bool>>,
+    right: Option<Vec<bool>>,
+) -> DeltaResult<Option<Vec<bool>>> {
+    match (left, right) {
+        (Some(mut left), Some(right)) => {
+            combine_selection_vectors_in_place(&mut left, &right)?;
+            Ok(Some(left))
+        }
+        (left, right) => Ok(left.or(right)),
+    }
+}
+
+/// Builder for a [`ScanFilter`], which splits a predicate into the parts that data skipping and
+/// partition pruning can each handle, so that engines don't have to.
+pub struct ScanFilterBuilder<'a> {
+    table_client: &'a dyn EngineInterface,
+    table_schema: &'a SchemaRef,
+    partition_columns: &'a [String],
+    predicate: &'a Option<Expression>,
+    stats_cache: Option<Arc<StatsCache>>,
+    evaluator_cache: Option<Arc<EvaluatorCache>>,
+    json_handler: Option<Arc<dyn JsonHandler>>,
+    actions_schema: Option<&'a StructType>,
+    observer: Option<Arc<dyn SkippingObserver>>,
+    skip_empty_files: bool,
+    skip_empty_files_without_predicate: bool,
+    float_tolerance: f64,
+    null_skipping_only: bool,
+    missing_stats_policy: MissingStatsPolicy,
+    stats_column_names: StatsColumnNames,
+    eligibility_report: bool,
+}
+
+impl<'a> ScanFilterBuilder<'a> {
+    /// Creates a builder for a filter of the files of a table with the given schema and partition
+    /// columns by `predicate`.
+    pub fn new(
+        table_client: &'a dyn EngineInterface,
+        table_schema: &'a SchemaRef,
+        partition_columns: &'a [String],
+        predicate: &'a Option<Expression>,
+    ) -> Self {
+        Self {
+            table_client,
+            table_schema,
+            partition_columns,
+            predicate,
+            stats_cache: None,
+            evaluator_cache: None,
+            json_handler: None,
+            actions_schema: None,
+            observer: None,
+            skip_empty_files: true,
+            skip_empty_files_without_predicate: false,
+            float_tolerance: 0.0,
+            null_skipping_only: false,
+            missing_stats_policy: MissingStatsPolicy::default(),
+            stats_column_names: StatsColumnNames::default(),
+            eligibility_report: false,
+        }
+    }
+
+    /// The cache data skipping reuses (and adds to) parsed stats from, if any.
+    pub fn with_stats_cache(mut self, stats_cache: Option<Arc<StatsCache>>) -> Self {
+        self.stats_cache = stats_cache;
+        self
+    }
+
+    /// The cache data skipping gets its evaluators from, if any, so that filters for predicates
+    /// of the same shape share them (see [`EvaluatorCache`]).
+    pub fn with_evaluator_cache(mut self, evaluator_cache: Option<Arc<EvaluatorCache>>) -> Self {
+        self.evaluator_cache = evaluator_cache;
+        self
+    }
+
+    /// The json handler data skipping parses stats with, if not the engine's own (e.g. one that is
+    /// more lenient with malformed stats).
+    pub fn with_json_handler(mut self, json_handler: Option<Arc<dyn JsonHandler>>) -> Self {
+        self.json_handler = json_handler;
+        self
+    }
+
+    /// The schema of the batches of actions the filter is applied to. If their add actions have a
+    /// `stats_parsed` struct column (e.g. in checkpoints of some writers), data skipping reads the
+    /// stats from it instead of parsing the `stats` json (see [`StatsColumnNames::stats_parsed`]).
+    pub fn with_actions_schema(mut self, actions_schema: Option<&'a StructType>) -> Self {
+        self.actions_schema = actions_schema;
+        self
+    }
+
+    /// The observer to report metrics of data skipping to, if any (see [`SkippingObserver`]).
+    pub fn with_observer(mut self, observer: Option<Arc<dyn SkippingObserver>>) -> Self {
+        self.observer = observer;
+        self
+    }
+
+    /// Whether data skipping also skips files without records. Defaults to true: such files have
+    /// no rows for the scan to read, and the data skipping filter of an eligible predicate only
+    /// needs to parse their `numRecords` stat in addition. Without an eligible predicate, there is
+    /// no data skipping filter that could skip them (see
+    /// [`Self::with_skip_empty_files_without_predicate`]).
+    pub fn with_skip_empty_files(mut self, skip_empty_files: bool) -> Self {
+        self.skip_empty_files = skip_empty_files;
+        self
+    }
+
+    /// Whether to skip files without records even if no part of the predicate is eligible for
+    /// data skipping (or there is none). Defaults to false, since this builds a data skipping
+    /// filter that parses the stats of every add action just to read their `numRecords`.
+    pub fn with_skip_empty_files_without_predicate(
+        mut self,
+        skip_empty_files_without_predicate: bool,
+    ) -> Self {
+        self.skip_empty_files_without_predicate = skip_empty_files_without_predicate;
+        self
+    }
+
+    /// How much the min/max stats of `FLOAT` and `DOUBLE` columns may differ from the actual
+    /// min/max values, e.g. because writers computed them with rounding. Files are only skipped if
+    /// their stats rule out the predicate by more than this. Defaults to zero.
+    pub fn with_float_tolerance(mut self, epsilon: f64) -> Self {
+        self.float_tolerance = epsilon;
+        self
+    }
+
+    /// Whether data skipping only uses the `nullCount` and `numRecords` stats, e.g. for queries
+    /// that only care whether a column is entirely (non-)null in a file. Parts of the predicate
+    /// that need the min/max values then don't skip any files, but their stats aren't parsed.
+    pub fn with_null_skipping_only(mut self, null_skipping_only: bool) -> Self {
+        self.null_skipping_only = null_skipping_only;
+        self
+    }
+
+    /// What data skipping does with files whose stats can't decide the predicate (see
+    /// [`MissingStatsPolicy`]).
+    pub fn with_missing_stats_policy(mut self, policy: MissingStatsPolicy) -> Self {
+        self.missing_stats_policy = policy;
+        self
+    }
+
+    /// The names under which the actions store their stats, instead of the Delta names (see
+    /// [`StatsColumnNames`]).
+    pub fn with_stats_column_names(mut self, names: StatsColumnNames) -> Self {
+        self.stats_column_names = names;
+        self
+    }
+
+    /// Whether to report for each column the data skipping part of the predicate references
+    /// whether data skipping uses its stats, and why not otherwise (see
+    /// [`DataSkippingFilter::column_eligibility`]). Partition columns are pruned by their values
+    /// instead, so they are not reported.
+    pub fn with_eligibility_report(mut self, eligibility_report: bool) -> Self {
+        self.eligibility_report = eligibility_report;
+        self
+    }
+
+    /// Builds the filter. Parts of the predicate that neither data skipping nor partition pruning
+    /// can use don't prune any files, so the filter is trivial if no part of it can.
+    pub fn build(self) -> ScanFilter {
+        let Self {
+            table_client,
+            table_schema,
+            partition_columns,
+            predicate,
+            stats_cache,
+            evaluator_cache,
+            json_handler,
+            actions_schema,
+            observer,
+            skip_empty_files,
+            skip_empty_files_without_predicate,
+            float_tolerance,
+            null_skipping_only,
+            missing_stats_policy,
+            stats_column_names,
+            eligibility_report,
+        } = self;
+        // Partition columns have no stats, so only the rest of the predicate is used for data
+        // skipping, while the partition filter takes care of the partition columns. Disjunctions
+        // of partition and data predicates need both, so they get filters of their own.
+        let data_predicate = predicate
+            .as_ref()
+            .and_then(|predicate| split_partition_predicate(predicate, partition_columns).1);
+        let (disjunctions, data_predicate) = match &data_predicate {
+            Some(data_predicate) => split_partition_disjunctions(data_predicate, partition_columns),
+            None => (vec![], None),
+        };
+        let disjunction_filters = disjunctions
+            .iter()
+            .filter_map(|disjunction| {
+                PartitionDisjunctionFilter::new(
+                    table_client,
+                    table_schema,
+                    partition_columns,
+                    disjunction,
+                )
+            })
+            .collect();
+        let filter = ScanFilter {
+            data_filter: DataSkippingFilterBuilder::new(table_schema, &data_predicate)
+                .with_referenced_stats_only(true)
+                .with_skip_empty_files(skip_empty_files)
+                .with_skip_empty_files_without_predicate(skip_empty_files_without_predicate)
+                .with_float_tolerance(float_tolerance)
+                .with_null_skipping_only(null_skipping_only)
+                .with_missing_stats_policy(missing_stats_policy)
+                .with_stats_column_names(stats_column_names)
+                .with_eligibility_report(eligibility_report)
+                .with_stats_cache(stats_cache)
+                .with_evaluator_cache(evaluator_cache)
+                .with_json_handler(json_handler)
+                .with_actions_schema(actions_schema)
+                .with_observer(observer)
+                .build(table_client),
+            partition_filter: PartitionFilter::new(table_schema, partition_columns, predicate),
+            disjunction_filters,
+        };
+        if filter.is_trivial() {
+            debug!("No part of the predicate can prune files, scanning all of them");
+        }
+        filter
+    }
+}
+
+/// Prunes the files of a scan by both data skipping and partition pruning (see
+/// [`ScanFilterBuilder`]). A file is kept if all of its filters keep it.
+pub struct ScanFilter {
+    pub(crate) data_filter: Option<DataSkippingFilter>,
+    pub(crate) partition_filter: Option<PartitionFilter>,
+    pub(crate) disjunction_filters: Vec<PartitionDisjunctionFilter>,
+}
+
+impl ScanFilter {
+    /// Whether the filter keeps all files, because no part of its predicate can prune any.
+    pub fn is_trivial(&self) -> bool {
+        self.data_filter.is_none()
+            && self.partition_filter.is_none()
+            && self.disjunction_filters.is_empty()
+    }
+
+    /// The filter that skips files by their stats, if any part of the predicate is eligible for
+    /// data skipping (or files without records are skipped regardless, see
+    /// [`ScanFilterBuilder::with_skip_empty_files_without_predicate`]), e.g. to check which stats
+    /// it parses.
+    pub fn data_filter(&self) -> Option<&DataSkippingFilter> {
+        self.data_filter.as_ref()
+    }
+
+    /// Apply the ScanFilter to an EngineData batch of actions. Returns a selection vector which
+    /// can be applied to the actions to find those that passed both data skipping and partition
+    /// pruning. Rows that are not Add actions are always selected.
+    pub fn apply(&self, actions: &dyn EngineData) -> DeltaResult<Vec<bool>> {
+        let data_selection_vector = self
+            .data_filter
+            .as_ref()
+            .map(|filter| filter.apply(actions))
+            .transpose()?;
+        let selection_vector = self.apply_partition_filters(actions, data_selection_vector)?;
+        Ok(selection_vector.unwrap_or_else(|| vec![true; actions.length()]))
+    }
+
+    /// Combines the selection vector of the data skipping filter, if it has been applied to
+    /// `actions` already (e.g. with cached stats), with those of the partition filters. `None`
+    /// selects all actions.
+    pub(crate) fn apply_partition_filters(
+        &self,
+        actions: &dyn EngineData,
+        data_selection_vector: Option<Vec<bool>>,
+    ) -> DeltaResult<Option<Vec<bool>>> {
+        let partition_selection_vector = self
+            .partition_filter
+            .as_ref()
+            .map(|filter| filter.apply(actions))
+            .transpose()?;
+        let mut selection_vector =
+            and_selection_vectors(data_selection_vector, partition_selection_vector)?;
+        for filter in &self.disjunction_filters {
+            selection_vector =
+                and_selection_vectors(selection_vector, Some(filter.apply(actions)?))?;
+        }
+        Ok(selection_vector)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use arrow_array::{RecordBatch, StringArray};
+    use arrow_schema::{DataType as ArrowDataType, Field, Schema as ArrowSchema};
+
+    use super::*;
+    use crate::actions::{get_log_schema, ADD_NAME};
+    use crate::client::arrow_data::ArrowEngineData;
+    use crate::client::sync::SyncEngineInterface;
+    use crate::expressions::Scalar;
+    use crate::scan::{DataSkippingResult, EligibilityReason};
+    use crate::schema::{DataType, StructField};
+
+    fn add_actions(engine_interface: &dyn EngineInterface) -> Box<dyn EngineData> {
+        let files = [("x", 1, 3), ("x", 6, 9), ("y", 1, 3), ("y", 6, 9)];
+        let json: Vec<_> = files
+            .into_iter()
+            .map(|(part, min, max)| {
+                let stats = format!(
+                    r#"{{"numRecords":1,"minValues":{{"value":{min}}},"maxValues":{{"value":{max}}}}}"#
+                );
+                serde_json::json!({
+                    "add": {
+                        "path": format!("part={part}/{min}-{max}"),
+                        "partitionValues": { "part": part },
+                        "size": 1024,
+                        "modificationTime": 1677811178336_i64,
+                        "dataChange": true,
+                        "stats": stats,
+                    }
+                })
+                .to_string()
+            })
+            .collect();
+        let string_field = Arc::new(Field::new("a", ArrowDataType::Utf8, true));
+        let schema = Arc::new(ArrowSchema::new(vec![string_field]));
+        let batch = RecordBatch::try_new(schema, vec![Arc::new(StringArray::from(json))]).unwrap();
+        engine_interface
+            .get_json_handler()
+            .parse_json(
+                Box::new(ArrowEngineData::new(batch)),
+                get_log_schema().project(&[ADD_NAME]).unwrap(),
+            )
+            .unwrap()
+    }
+
+    #[test]
+    fn test_scan_filter() {
+        let engine_interface = SyncEngineInterface::new();
+        let table_schema = Arc::new(StructType::new(vec![
+            StructField::new("part", DataType::STRING, true),
+            StructField::new("value", DataType::INTEGER, true),
+        ]));
+        let partition_columns = vec!["part".to_string()];
+        let actions = add_actions(&engine_interface);
+
+        let part = Expression::column("part").eq(Expression::literal("x"));
+        let data = Expression::column("value").gt(Expression::literal(5));
+        // (predicate, whether it uses data skipping, and partition pruning, expected selection).
+        // Empty files aren't skipped, so that only the predicate decides on the data skipping.
+        let cases = [
+            (data.clone(), true, false, vec![false, true, false, true]),
+            (part.clone(), false, true, vec![true, true, false, false]),
+            (
+                part.clone().and(data.clone()),
+                true,
+                true,
+                vec![false, true, false, false],
+            ),
+            (
+                part.clone().or(data.clone()),
+                false,
+                false,
+                vec![true, true, false, true],
+            ),
+        ];
+        for (predicate, has_data_filter, has_partition_filter, expected) in cases {
+            let message = predicate.to_string();
+            let predicate = Some(predicate);
+            let filter = ScanFilterBuilder::new(
+                &engine_interface,
+                &table_schema,
+                &partition_columns,
+                &predicate,
+            )
+            .with_skip_empty_files(false)
+            .build();
+            assert_eq!(filter.data_filter().is_some(), has_data_filter, "{message}");
+            if let Some(data_filter) = filter.data_filter() {
+                // only the stats of the data column are parsed
+                let stats_schema = StructType::new(vec![StructField::new(
+                    "maxValues",
+                    StructType::new(vec![StructField::new("value", DataType::INTEGER, true)]),
+                    true,
+                )]);
+                assert_eq!(
+                    data_filter.stats_schema().as_ref(),
+                    &stats_schema,
+                    "{message}"
+                );
+            }
+            assert_eq!(
+                filter.partition_filter.is_some(),
+                has_partition_filter,
+                "{message}"
+            );
+            assert!(!filter.is_trivial(), "{message}");
+            assert_eq!(
+                filter.apply(actions.as_ref()).unwrap(),
+                expected,
+                "{message}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_estimated_surviving_rows() {
+        let engine_interface = SyncEngineInterface::new();
+        let table_schema = Arc::new(StructType::new(vec![
+            StructField::new("part", DataType::STRING, true),
+            StructField::new("value", DataType::INTEGER, true),
+        ]));
+        let partition_columns = vec!["part".to_string()];
+        let actions = add_actions(&engine_interface);
+
+        let predicate = Some(Expression::column("value").gt(Expression::literal(5)));
+        let builder = || {
+            ScanFilterBuilder::new(
+                &engine_interface,
+                &table_schema,
+                &partition_columns,
+                &predicate,
+            )
+        };
+        // two files of one record each survive
+        let filter = builder().build();
+        let data_filter = filter.data_filter().unwrap();
+        assert_eq!(
+            data_filter
+                .estimated_surviving_rows(actions.as_ref())
+                .unwrap(),
+            2
+        );
+
+        // without skipping empty files, the filter only parses the max values
+        let filter = builder().with_skip_empty_files(false).build();
+        let data_filter = filter.data_filter().unwrap();
+        assert!(data_filter
+            .estimated_surviving_rows(actions.as_ref())
+            .is_err());
+    }
+
+    #[test]
+    fn test_float_tolerance() {
+        let engine_interface = SyncEngineInterface::new();
+        let table_schema = Arc::new(StructType::new(vec![
+            StructField::new("part", DataType::STRING, true),
+            StructField::new("value", DataType::DOUBLE, true),
+        ]));
+        let partition_columns = vec!["part".to_string()];
+        let actions = add_actions(&engine_interface);
+
+        // the files with a max of 9 may have a value above 9.05 if their stats were rounded
+        let predicate =
+            Some(Expression::column("value").gt(Expression::literal(Scalar::Double(9.05))));
+        let cases = [(0.0, vec![false; 4]), (0.1, vec![false, true, false, true])];
+        for (tolerance, expected) in cases {
+            let filter = ScanFilterBuilder::new(
+                &engine_interface,
+                &table_schema,
+                &partition_columns,
+                &predicate,
+            )
+            .with_float_tolerance(tolerance)
+            .build();
+            assert_eq!(
+                filter.apply(actions.as_ref()).unwrap(),
+                expected,
+                "tolerance {tolerance}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_skipping_options() {
+        let engine_interface = SyncEngineInterface::new();
+        let table_schema = Arc::new(StructType::new(vec![
+            StructField::new("part", DataType::STRING, true),
+            StructField::new("value", DataType::INTEGER, true),
+        ]));
+        let partition_columns = vec!["part".to_string()];
+        let actions = add_actions(&engine_interface);
+
+        // the files have min/max values, but no null counts
+        let is_null = Expression::column("value").is_null();
+        let gt = Expression::column("value").gt(Expression::literal(5));
+        let cases = [
+            (
+                is_null.clone(),
+                false,
+                MissingStatsPolicy::Keep,
+                vec![true; 4],
+            ),
+            (is_null, false, MissingStatsPolicy::Skip, vec![false; 4]),
+            (
+                gt.clone(),
+                false,
+                MissingStatsPolicy::Keep,
+                vec![false, true, false, true],
+            ),
+            (gt, true, MissingStatsPolicy::Keep, vec![true; 4]),
+        ];
+        for (predicate, null_skipping_only, policy, expected) in cases {
+            let message = format!("{predicate} ({null_skipping_only}, {policy:?})");
+            let predicate = Some(predicate);
+            let filter = ScanFilterBuilder::new(
+                &engine_interface,
+                &table_schema,
+                &partition_columns,
+                &predicate,
+            )
+            .with_null_skipping_only(null_skipping_only)
+            .with_missing_stats_policy(policy)
+            .build();
+            assert_eq!(
+                filter.apply(actions.as_ref()).unwrap(),
+                expected,
+                "{message}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_stats_column_names() {
+        let engine_interface = SyncEngineInterface::new();
+        let table_schema = Arc::new(StructType::new(vec![
+            StructField::new("part", DataType::STRING, true),
+            StructField::new("value", DataType::INTEGER, true),
+        ]));
+        let partition_columns = vec!["part".to_string()];
+        let actions = add_actions(&engine_interface);
+
+        // the files store their max values as `maxValues`, so with another name they have none
+        let predicate = Some(Expression::column("value").gt(Expression::literal(5)));
+        let renamed = StatsColumnNames {
+            max_values: "maxima".to_string(),
+            ..Default::default()
+        };
+        let cases = [
+            (StatsColumnNames::default(), vec![false, true, false, true]),
+            (renamed, vec![true; 4]),
+        ];
+        for (names, expected) in cases {
+            let message = format!("{names:?}");
+            let filter = ScanFilterBuilder::new(
+                &engine_interface,
+                &table_schema,
+                &partition_columns,
+                &predicate,
+            )
+            .with_stats_column_names(names)
+            .build();
+            assert_eq!(
+                filter.apply(actions.as_ref()).unwrap(),
+                expected,
+                "{message}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_parsed_stats() {
+        let engine_interface = SyncEngineInterface::new();
+        let table_schema = Arc::new(StructType::new(vec![
+            StructField::new("part", DataType::STRING, true),
+            StructField::new("value", DataType::INTEGER, true),
+        ]));
+        let partition_columns = vec!["part".to_string()];
+        let actions = add_actions(&engine_interface);
+
+        let predicate = Some(Expression::column("value").gt(Expression::literal(5)));
+        let filter = ScanFilterBuilder::new(
+            &engine_interface,
+            &table_schema,
+            &partition_columns,
+            &predicate,
+        )
+        .build();
+        let data_filter = filter.data_filter().unwrap();
+        let DataSkippingResult {
+            selection_vector,
+            parsed_stats: stats,
+            ..
+        } = data_filter.apply_with_stats(actions.as_ref()).unwrap();
+        assert_eq!(selection_vector, vec![false, true, false, true]);
+        let stats = data_filter
+            .parsed_stats(&engine_interface, stats.as_ref())
+            .unwrap();
+        assert_eq!(stats.num_rows(), 4);
+        assert_eq!(stats.num_records(1), Some(1));
+        assert_eq!(stats.max_value(1, "value"), Some(Scalar::Integer(9)));
+        // only the stats the predicate needs are parsed
+        assert_eq!(stats.min_value(1, "value"), None);
+    }
+
+    #[test]
+    fn test_eligibility_report() {
+        let engine_interface = SyncEngineInterface::new();
+        let table_schema = Arc::new(StructType::new(vec![
+            StructField::new("part", DataType::STRING, true),
+            StructField::new("value", DataType::INTEGER, true),
+        ]));
+        let partition_columns = vec!["part".to_string()];
+
+        let predicate = Some(Expression::and_from([
+            Expression::column("part").eq(Expression::literal("x")),
+            Expression::column("value").gt(Expression::literal(5)),
+            Expression::column("missing").eq(Expression::literal(1)),
+        ]));
+        let builder = || {
+            ScanFilterBuilder::new(
+                &engine_interface,
+                &table_schema,
+                &partition_columns,
+                &predicate,
+            )
+        };
+        let filter = builder().build();
+        assert_eq!(filter.data_filter().unwrap().column_eligibility(), None);
+
+        let filter = builder().with_eligibility_report(true).build();
+        let expected = [
+            ("missing".to_string(), EligibilityReason::NotInSchema),
+            ("value".to_string(), EligibilityReason::Eligible),
+        ];
+        assert_eq!(
+            filter.data_filter().unwrap().column_eligibility(),
+            Some(expected.as_slice())
+        );
+    }
+
+    #[test]
+    fn test_stats_parsed() {
+        let engine_interface = SyncEngineInterface::new();
+        let table_schema = Arc::new(StructType::new(vec![StructField::new(
+            "value",
+            DataType::INTEGER,
+            true,
+        )]));
+        let value_stats = StructType::new(vec![StructField::new("value", DataType::INTEGER, true)]);
+        let stats_parsed = StructType::new(vec![
+            StructField::new("numRecords", DataType::LONG, true),
+            StructField::new("minValues", value_stats.clone(), true),
+            StructField::new("maxValues", value_stats, true),
+        ]);
+        let add_schema = StructType::new(vec![
+            StructField::new("path", DataType::STRING, true),
+            StructField::new("stats", DataType::STRING, true),
+            StructField::new("stats_parsed", stats_parsed, true),
+        ]);
+        let actions_schema = StructType::new(vec![StructField::new("add", add_schema, true)]);
+        let json: Vec<_> = [(1, 3), (6, 9)]
+            .into_iter()
+            .map(|(min, max)| {
+                serde_json::json!({
+                    "add": {
+                        "path": format!("{min}-{max}"),
+                        "stats_parsed": {
+                            "numRecords": 1,
+                            "minValues": { "value": min },
+                            "maxValues": { "value": max },
+                        },
+                    }
+                })
+                .to_string()
+            })
+            .collect();
+        let string_field = Arc::new(Field::new("a", ArrowDataType::Utf8, true));
+        let schema = Arc::new(ArrowSchema::new(vec![string_field]));
+        let batch = RecordBatch::try_new(schema, vec![Arc::new(StringArray::from(json))]).unwrap();
+        let actions = engine_interface
+            .get_json_handler()
+            .parse_json(
+                Box::new(ArrowEngineData::new(batch)),
+                Arc::new(actions_schema.clone()),
+            )
+            .unwrap();
+
+        // without the schema of the actions, the filter looks for the (missing) stats json
+        let predicate = Some(Expression::column("value").gt(Expression::literal(5)));
+        let cases = [
+            (None, vec![true, true]),
+            (Some(&actions_schema), vec![false, true]),
+        ];
+        for (actions_schema, expected) in cases {
+            let filter = ScanFilterBuilder::new(&engine_interface, &table_schema, &[], &predicate)
+                .with_actions_schema(actions_schema)
+                .build();
+            assert_eq!(filter.apply(actions.as_ref()).unwrap(), expected);
+        }
+    }
+
+    #[test]
+    fn test_trivial_scan_filter() {
+        let engine_interface = SyncEngineInterface::new();
+        let table_schema = Arc::new(StructType::new(vec![
+            StructField::new("part", DataType::STRING, true),
+            StructField::new("value", DataType::INTEGER, true),
+        ]));
+        let partition_columns = vec!["part".to_string()];
+        let actions = add_actions(&engine_interface);
+
+        // comparing two columns for equality can't prune any files
+        let predicates = [
+            None,
+            Some(Expression::column("value").eq(Expression::column("part"))),
+        ];
+        for predicate in predicates {
+            let builder = || {
+                ScanFilterBuilder::new(
+                    &engine_interface,
+                    &table_schema,
+                    &partition_columns,
+                    &predicate,
+                )
+            };
+            let filter = builder().build();
+            assert!(filter.is_trivial());
+            assert_eq!(filter.apply(actions.as_ref()).unwrap(), vec![true; 4]);
+
+            // none of the files is empty
+            let filter = builder()
+                .with_skip_empty_files_without_predicate(true)
+                .build();
+            assert!(!filter.is_trivial());
+            assert_eq!(filter.apply(actions.as_ref()).unwrap(), vec![true; 4]);
+        }
+    }
+}
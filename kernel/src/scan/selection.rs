@@ -0,0 +1,179 @@
+//! Selection vectors, which store whether each row (e.g. each action of a batch) is selected,
+//! and a compact selection vector that stores this in a single bit per row
+
+use crate::{DeltaResult, Error};
+
+/// Combines two selection vectors of the same batch, e.g. of a data skipping filter and a
+/// partition filter, into one that selects the rows that both of them select. Returns an error if
+/// the selection vectors have different lengths.
+pub fn combine_selection_vectors(left: &[bool], right: &[bool]) -> DeltaResult<Vec<bool>> {
+    let mut combined = left.to_vec();
+    combine_selection_vectors_in_place(&mut combined, right)?;
+    Ok(combined)
+}
+
+/// Like [`combine_selection_vectors`], but updates `left` in place with the combined selection
+/// vector instead of allocating a new one. `left` is left unchanged if the lengths differ.
+pub fn combine_selection_vectors_in_place(left: &mut [bool], right: &[bool]) -> DeltaResult<()> {
+    if left.len() != right.len() {
+        return Err(Error::generic(format!(
+            "Cannot combine selection vectors of {} and {} rows",
+            left.len(),
+            right.len()
+        )));
+    }
+    for (left, right) in left.iter_mut().zip(right) {
+        *left &= *right;
+    }
+    Ok(())
+}
+
+/// A selection vector that packs the selection of 64 rows into each word, which takes an eighth of
+/// the memory of a `Vec<bool>`. It converts to and from a `Vec<bool>` for compatibility with the
+/// rest of the scan.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct SelectionBitmap {
+    words: Vec<u64>,
+    len: usize,
+}
+
+impl SelectionBitmap {
+    /// Creates a bitmap of `len` rows that are all selected (or all not selected).
+    pub fn repeat(selected: bool, len: usize) -> Self {
+        let word = if selected { u64::MAX } else { 0 };
+        let mut words = vec![word; len.div_ceil(64)];
+        // keep the bits past the end unset, so that whole words can be counted
+        let remainder = len % 64;
+        if let Some(last) = words.last_mut().filter(|_| remainder != 0) {
+            *last &= (1 << remainder) - 1;
+        }
+        Self { words, len }
+    }
+
+    /// Appends the selection of the next row.
+    pub fn push(&mut self, selected: bool) {
+        let (word, bit) = (self.len / 64, self.len % 64);
+        if bit == 0 {
+            self.words.push(0);
+        }
+        if selected {
+            self.words[word] |= 1 << bit;
+        }
+        self.len += 1;
+    }
+
+    /// Whether the row at `index` is selected, or `None` if there is no such row.
+    pub fn get(&self, index: usize) -> Option<bool> {
+        (index < self.len).then(|| self.words[index / 64] & (1 << (index % 64)) != 0)
+    }
+
+    /// The number of rows.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Whether there are no rows.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// The number of selected rows.
+    pub fn count_selected(&self) -> usize {
+        self.words
+            .iter()
+            .map(|word| word.count_ones() as usize)
+            .sum()
+    }
+
+    /// Iterates over the selection of each row.
+    pub fn iter(&self) -> impl Iterator<Item = bool> + '_ {
+        (0..self.len).map(|index| self.words[index / 64] & (1 << (index % 64)) != 0)
+    }
+}
+
+impl FromIterator<bool> for SelectionBitmap {
+    fn from_iter<I: IntoIterator<Item = bool>>(iter: I) -> Self {
+        let mut bitmap = Self::default();
+        for selected in iter {
+            bitmap.push(selected);
+        }
+        bitmap
+    }
+}
+
+impl From<&[bool]> for SelectionBitmap {
+    fn from(selection_vector: &[bool]) -> Self {
+        selection_vector.iter().copied().collect()
+    }
+}
+
+impl From<SelectionBitmap> for Vec<bool> {
+    fn from(bitmap: SelectionBitmap) -> Self {
+        bitmap.iter().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_combine_selection_vectors() {
+        let left = [true, true, false, false];
+        let right = [true, false, true, false];
+        assert_eq!(
+            combine_selection_vectors(&left, &right).unwrap(),
+            [true, false, false, false]
+        );
+
+        let mut combined = left;
+        combine_selection_vectors_in_place(&mut combined, &right).unwrap();
+        assert_eq!(combined, [true, false, false, false]);
+
+        // empty selection vectors of empty batches combine to an empty selection vector
+        assert!(combine_selection_vectors(&[], &[]).unwrap().is_empty());
+        combine_selection_vectors_in_place(&mut [], &[]).unwrap();
+
+        // selection vectors of different batches can't be combined
+        assert!(matches!(
+            combine_selection_vectors(&left, &right[..3]),
+            Err(Error::Generic(_))
+        ));
+        assert!(combine_selection_vectors(&[], &[true]).is_err());
+        let mut unchanged = left;
+        assert!(combine_selection_vectors_in_place(&mut unchanged, &[]).is_err());
+        assert_eq!(unchanged, left);
+    }
+
+    #[test]
+    fn test_selection_bitmap() {
+        for len in [0, 1, 63, 64, 65, 200] {
+            let selection_vector: Vec<bool> = (0..len).map(|i| i % 3 == 0).collect();
+            let bitmap = SelectionBitmap::from(selection_vector.as_slice());
+            assert_eq!(bitmap.len(), len);
+            assert_eq!(bitmap.is_empty(), len == 0);
+            assert_eq!(
+                bitmap.count_selected(),
+                selection_vector
+                    .iter()
+                    .filter(|selected| **selected)
+                    .count()
+            );
+            for (i, selected) in selection_vector.iter().enumerate() {
+                assert_eq!(bitmap.get(i), Some(*selected));
+            }
+            assert_eq!(bitmap.get(len), None);
+            assert_eq!(Vec::from(bitmap.clone()), selection_vector);
+
+            // 64 rows per word, instead of one per byte
+            assert_eq!(bitmap.words.len(), len.div_ceil(64));
+            assert!(bitmap.words.len() * std::mem::size_of::<u64>() <= len.div_ceil(8) + 7);
+
+            for selected in [true, false] {
+                let bitmap = SelectionBitmap::repeat(selected, len);
+                assert_eq!(Vec::from(bitmap.clone()), vec![selected; len]);
+                assert_eq!(bitmap.count_selected(), if selected { len } else { 0 });
+            }
+        }
+    }
+}
@@ -0,0 +1,259 @@
+//! Utilities to test data skipping, e.g. to check that a predicate never skips a file that has
+//! matching rows
+
+use std::sync::Arc;
+
+use arrow_array::cast::AsArray;
+use arrow_array::{RecordBatch, StringArray};
+use arrow_schema::{DataType as ArrowDataType, Field, Schema as ArrowSchema};
+use serde_json::{Map, Value};
+
+use super::data_skipping::{DataSkippingFilter, DataSkippingFilterBuilder};
+use crate::actions::{get_log_schema, ADD_NAME};
+use crate::client::arrow_data::ArrowEngineData;
+use crate::client::sync::SyncEngineInterface;
+use crate::expressions::Expression;
+use crate::schema::{DataType, SchemaRef, StructField, StructType};
+use crate::{EngineData, EngineInterface};
+
+/// Wraps `string_array` into engine data with a single string column, e.g. to parse json from.
+pub(crate) fn string_array_to_engine_data(string_array: StringArray) -> Box<dyn EngineData> {
+    let string_field = Arc::new(Field::new("a", ArrowDataType::Utf8, true));
+    let schema = Arc::new(ArrowSchema::new(vec![string_field]));
+    let batch = RecordBatch::try_new(schema, vec![Arc::new(string_array)])
+        .expect("Can't convert to record batch");
+    Box::new(ArrowEngineData::new(batch))
+}
+
+/// Builds a batch of add actions, one per entry in `stats`.
+pub(crate) fn add_actions_batch(
+    engine_interface: &dyn EngineInterface,
+    stats: &[&str],
+) -> Box<dyn EngineData> {
+    let json_strings: StringArray = stats
+        .iter()
+        .enumerate()
+        .map(|(i, stats)| {
+            serde_json::json!({
+                "add": {
+                    "path": format!("part-{i:05}.parquet"),
+                    "partitionValues": {},
+                    "size": 1024,
+                    "modificationTime": 1677811178336_i64,
+                    "dataChange": true,
+                    "stats": stats,
+                }
+            })
+            .to_string()
+        })
+        .collect::<Vec<_>>()
+        .into();
+    engine_interface
+        .get_json_handler()
+        .parse_json(
+            string_array_to_engine_data(json_strings),
+            get_log_schema().project(&[ADD_NAME]).unwrap(),
+        )
+        .unwrap()
+}
+
+/// A table schema with the single nullable column `name` of the given type.
+pub(crate) fn single_column_schema(name: &str, data_type: DataType) -> SchemaRef {
+    Arc::new(StructType::new(vec![StructField::new(
+        name, data_type, true,
+    )]))
+}
+
+/// A table schema with nested columns: `a` (a struct of the integer `b` and the struct `c` of the
+/// long `d` and the string `e`) and the integer `f`.
+pub(crate) fn nested_table_schema() -> SchemaRef {
+    Arc::new(StructType::new(vec![
+        StructField::new(
+            "a",
+            StructType::new(vec![
+                StructField::new("b", DataType::INTEGER, true),
+                StructField::new(
+                    "c",
+                    StructType::new(vec![
+                        StructField::new("d", DataType::LONG, true),
+                        StructField::new("e", DataType::STRING, true),
+                    ]),
+                    true,
+                ),
+            ]),
+            true,
+        ),
+        StructField::new("f", DataType::INTEGER, true),
+    ]))
+}
+
+/// A batch of add actions with the given stats for a table with the given schema, to check which
+/// files the data skipping filters of several predicates keep.
+pub(crate) struct SkippingFixture {
+    pub(crate) engine_interface: SyncEngineInterface,
+    pub(crate) table_schema: SchemaRef,
+    pub(crate) actions: Box<dyn EngineData>,
+}
+
+impl SkippingFixture {
+    /// Create a fixture with one add action per entry in `stats`.
+    pub(crate) fn new(table_schema: SchemaRef, stats: &[&str]) -> Self {
+        let engine_interface = SyncEngineInterface::new();
+        let actions = add_actions_batch(&engine_interface, stats);
+        Self {
+            engine_interface,
+            table_schema,
+            actions,
+        }
+    }
+
+    /// Create a fixture with one add action per file, with the stats a writer would record for
+    /// its rows (see [`file_stats`]).
+    pub(crate) fn with_files(table_schema: SchemaRef, files: &[&[&str]]) -> Self {
+        let stats: Vec<_> = files
+            .iter()
+            .map(|rows| file_stats(&table_schema, rows))
+            .collect();
+        Self::new(
+            table_schema,
+            &stats.iter().map(String::as_str).collect::<Vec<_>>(),
+        )
+    }
+
+    /// Builds the data skipping filter of `predicate` with the default options, or `None` if the
+    /// predicate can't skip any files.
+    pub(crate) fn filter(&self, predicate: &Expression) -> Option<DataSkippingFilter> {
+        DataSkippingFilterBuilder::new(&self.table_schema, &Some(predicate.clone()))
+            .build(&self.engine_interface)
+    }
+
+    /// Returns which of the files the data skipping filter of `predicate` keeps. Panics if the
+    /// predicate can't skip any files.
+    pub(crate) fn apply(&self, predicate: &Expression) -> Vec<bool> {
+        self.filter(predicate)
+            .unwrap_or_else(|| panic!("{predicate} can't skip files"))
+            .apply(self.actions.as_ref())
+            .unwrap()
+    }
+
+    /// Checks that the data skipping filter of each predicate keeps the expected files.
+    pub(crate) fn assert_cases<E: AsRef<[bool]>>(
+        &self,
+        cases: impl IntoIterator<Item = (Expression, E)>,
+    ) {
+        for (predicate, expected) in cases {
+            assert_eq!(self.apply(&predicate), expected.as_ref(), "{predicate}");
+        }
+    }
+}
+
+/// The `nullCount`, `minValues` and `maxValues` stats of a struct column.
+type StructStats = [Map<String, Value>; 3];
+
+/// Computes the stats of the struct `values` of a column with the given `schema`, as a writer
+/// would record them for the number and string values of each (nested) field.
+fn struct_stats(schema: &StructType, values: &[&Value]) -> StructStats {
+    fn compare(left: &&&Value, right: &&&Value) -> std::cmp::Ordering {
+        match (left, right) {
+            (Value::Number(left), Value::Number(right)) => {
+                let (left, right) = (left.as_f64().unwrap(), right.as_f64().unwrap());
+                left.partial_cmp(&right).unwrap()
+            }
+            (Value::String(left), Value::String(right)) => left.cmp(right),
+            (Value::Bool(left), Value::Bool(right)) => left.cmp(right),
+            _ => panic!("Can't compare {left} and {right}"),
+        }
+    }
+
+    let [mut null_count, mut min_values, mut max_values] = StructStats::default();
+    for field in schema.fields() {
+        let name = field.name().to_string();
+        let children: Vec<_> = values
+            .iter()
+            .map(|value| value.get(&name).unwrap_or(&Value::Null))
+            .collect();
+        if let DataType::Struct(child_schema) = field.data_type() {
+            let [child_null_count, child_min, child_max] = struct_stats(child_schema, &children);
+            null_count.insert(name.clone(), child_null_count.into());
+            min_values.insert(name.clone(), child_min.into());
+            max_values.insert(name, child_max.into());
+            continue;
+        }
+        let non_null: Vec<_> = children
+            .iter()
+            .copied()
+            .filter(|child| !child.is_null())
+            .collect();
+        null_count.insert(name.clone(), (children.len() - non_null.len()).into());
+        if let (Some(min), Some(max)) = (
+            non_null.iter().min_by(compare),
+            non_null.iter().max_by(compare),
+        ) {
+            min_values.insert(name.clone(), (*min).clone());
+            max_values.insert(name, (*max).clone());
+        }
+    }
+    [null_count, min_values, max_values]
+}
+
+/// Returns the stats json a writer would record for a file with the given `rows` (json objects
+/// with the fields of `table_schema`).
+pub(crate) fn file_stats(table_schema: &StructType, rows: &[&str]) -> String {
+    let values: Vec<Value> = rows
+        .iter()
+        .map(|row| serde_json::from_str(row).unwrap())
+        .collect();
+    let [null_count, min_values, max_values] =
+        struct_stats(table_schema, &values.iter().collect::<Vec<_>>());
+    serde_json::json!({
+        "numRecords": rows.len(),
+        "nullCount": null_count,
+        "minValues": min_values,
+        "maxValues": max_values,
+    })
+    .to_string()
+}
+
+/// Checks the soundness of data skipping: a file with the given `rows` (json objects with the
+/// fields of `table_schema`) and the stats a writer would record for them (see [`file_stats`])
+/// may only be skipped if `predicate` is not true for any of its rows. Panics otherwise.
+pub(crate) fn assert_skipping_sound(
+    table_schema: &SchemaRef,
+    predicate: &Expression,
+    rows: &[&str],
+) {
+    let engine_interface = SyncEngineInterface::new();
+    let stats = file_stats(table_schema, rows);
+    let Some(filter) = DataSkippingFilterBuilder::new(table_schema, &Some(predicate.clone()))
+        .build(&engine_interface)
+    else {
+        return; // every file is kept
+    };
+    let batch = add_actions_batch(&engine_interface, &[&stats]);
+    if filter.apply(batch.as_ref()).unwrap() == [true] {
+        return;
+    }
+
+    let data = engine_interface
+        .get_json_handler()
+        .parse_json(
+            string_array_to_engine_data(rows.iter().copied().map(Some).collect()),
+            table_schema.clone(),
+        )
+        .unwrap();
+    let result = engine_interface
+        .get_expression_handler()
+        .get_evaluator(table_schema.clone(), predicate.clone(), DataType::BOOLEAN)
+        .evaluate(data.as_ref())
+        .unwrap();
+    let result: RecordBatch = ArrowEngineData::try_from_engine_data(result)
+        .unwrap()
+        .into();
+    for (row, matches) in rows.iter().zip(result.column(0).as_boolean()) {
+        assert_ne!(
+            matches,
+            Some(true),
+            "{predicate} skipped a file with stats {stats}, but matches row {row}"
+        );
+    }
+}
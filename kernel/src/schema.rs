@@ -1,5 +1,6 @@
 //! Definitions and functions to create and manipulate kernel schema
 
+use std::borrow::Cow;
 use std::fmt::Formatter;
 use std::sync::Arc;
 use std::{collections::HashMap, fmt::Display};
@@ -104,6 +105,43 @@ impl StructField {
         self.metadata.get(key.as_ref())
     }
 
+    /// Returns the string metadata value of `key`, if present. Returns an error if the value is
+    /// not a string.
+    pub fn metadata_string(&self, key: impl AsRef<str>) -> DeltaResult<Option<&str>> {
+        match self.metadata.get(key.as_ref()) {
+            None => Ok(None),
+            Some(MetadataValue::String(value)) => Ok(Some(value)),
+            Some(MetadataValue::Number(value)) => Err(Error::generic(format!(
+                "Expected string metadata {} for field {}, got number {value}",
+                key.as_ref(),
+                self.name
+            ))),
+        }
+    }
+
+    /// Returns the integer metadata value of `key`, if present. Values stored as strings are
+    /// parsed, and returns an error if the value is not an integer.
+    pub fn metadata_i64(&self, key: impl AsRef<str>) -> DeltaResult<Option<i64>> {
+        match self.metadata.get(key.as_ref()) {
+            None => Ok(None),
+            Some(MetadataValue::Number(value)) => Ok(Some(*value as i64)),
+            Some(MetadataValue::String(value)) => value.parse().map(Some).map_err(|_| {
+                Error::generic(format!(
+                    "Expected integer metadata {} for field {}, got '{value}'",
+                    key.as_ref(),
+                    self.name
+                ))
+            }),
+        }
+    }
+
+    /// Returns the name this field is stored under in data files, which is its column mapping
+    /// physical name if it has one, and its (logical) name otherwise.
+    pub fn get_physical_name(&self) -> DeltaResult<&str> {
+        let physical_name = self.metadata_string(ColumnMetadataKey::ColumnMappingPhysicalName)?;
+        Ok(physical_name.unwrap_or(&self.name))
+    }
+
     #[inline]
     pub fn name(&self) -> &String {
         &self.name
@@ -180,6 +218,18 @@ impl StructType {
     pub fn fields(&self) -> impl Iterator<Item = &StructField> {
         self.fields.values()
     }
+
+    /// Parses a schema from its Delta JSON representation, e.g. the `schemaString` of a table's
+    /// metadata action.
+    pub fn from_json(json: &str) -> DeltaResult<Self> {
+        Ok(serde_json::from_str(json)?)
+    }
+
+    /// Serializes this schema to its Delta JSON representation. This is the inverse of
+    /// [`StructType::from_json`].
+    pub fn to_json(&self) -> DeltaResult<String> {
+        Ok(serde_json::to_string(self)?)
+    }
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -434,6 +484,7 @@ impl DataType {
     pub const BINARY: Self = DataType::Primitive(PrimitiveType::Binary);
     pub const DATE: Self = DataType::Primitive(PrimitiveType::Date);
     pub const TIMESTAMP: Self = DataType::Primitive(PrimitiveType::Timestamp);
+    pub const TIMESTAMP_NTZ: Self = DataType::Primitive(PrimitiveType::TimestampNtz);
 
     pub fn decimal(precision: u8, scale: i8) -> Self {
         DataType::Primitive(PrimitiveType::Decimal(precision, scale))
@@ -460,6 +511,472 @@ impl Display for DataType {
     }
 }
 
+/// Merges an `incoming` schema into a `base` schema, e.g. to reconcile the schema of a table
+/// across commits. The merged schema contains the fields of `base` (in order) followed by the
+/// fields only `incoming` has, which must be nullable. Fields present in both schemas are merged
+/// recursively: they're nullable if either one is, their metadata is combined (preferring `base`),
+/// and their types must either match or `incoming` must widen `base` (e.g. `int` to `long`, or a
+/// decimal to one with more integer and fractional digits). Any other change is an error.
+pub fn merge_schemas(base: &StructType, incoming: &StructType) -> DeltaResult<StructType> {
+    merge_structs(None, base, incoming)
+}
+
+fn merge_structs(
+    path: Option<&str>,
+    base: &StructType,
+    incoming: &StructType,
+) -> DeltaResult<StructType> {
+    let field_path = |name: &str| match path {
+        Some(path) => format!("{path}.{name}"),
+        None => name.to_string(),
+    };
+    let merged = base
+        .fields()
+        .map(|field| match incoming.field(field.name()) {
+            Some(other) => merge_fields(&field_path(field.name()), field, other),
+            None => Ok(field.clone()),
+        });
+    let added = incoming
+        .fields()
+        .filter(|field| base.field(field.name()).is_none())
+        .map(|field| match field.is_nullable() {
+            true => Ok(field.clone()),
+            false => Err(Error::generic(format!(
+                "Cannot add non-nullable field {}",
+                field_path(field.name())
+            ))),
+        });
+    Ok(StructType::new(merged.chain(added).try_collect()?))
+}
+
+fn merge_fields(
+    path: &str,
+    base: &StructField,
+    incoming: &StructField,
+) -> DeltaResult<StructField> {
+    let mut metadata = incoming.metadata.clone();
+    metadata.extend(base.metadata.clone());
+    Ok(StructField {
+        name: base.name.clone(),
+        data_type: merge_types(path, &base.data_type, &incoming.data_type)?,
+        nullable: base.nullable || incoming.nullable,
+        metadata,
+    })
+}
+
+fn merge_types(path: &str, base: &DataType, incoming: &DataType) -> DeltaResult<DataType> {
+    use PrimitiveType::*;
+    let merged = match (base, incoming) {
+        (DataType::Struct(base), DataType::Struct(incoming)) => {
+            merge_structs(Some(path), base, incoming)?.into()
+        }
+        (DataType::Array(base), DataType::Array(incoming)) => ArrayType::new(
+            merge_types(
+                &format!("{path}.element"),
+                &base.element_type,
+                &incoming.element_type,
+            )?,
+            base.contains_null || incoming.contains_null,
+        )
+        .into(),
+        (DataType::Map(base), DataType::Map(incoming)) => MapType::new(
+            merge_types(&format!("{path}.key"), &base.key_type, &incoming.key_type)?,
+            merge_types(
+                &format!("{path}.value"),
+                &base.value_type,
+                &incoming.value_type,
+            )?,
+            base.value_contains_null || incoming.value_contains_null,
+        )
+        .into(),
+        (DataType::Primitive(base_type), DataType::Primitive(incoming_type)) => {
+            let widens = match (base_type, incoming_type) {
+                (Byte, Short | Integer | Long) | (Short, Integer | Long) | (Integer, Long) => true,
+                (Float, Double) => true,
+                (Decimal(p1, s1), Decimal(p2, s2)) => {
+                    s1 <= s2 && (*p1 as i16 - *s1 as i16) <= (*p2 as i16 - *s2 as i16)
+                }
+                _ => base_type == incoming_type,
+            };
+            if !widens {
+                return Err(Error::generic(format!(
+                    "Cannot change type of field {path} from {base} to {incoming}"
+                )));
+            }
+            incoming.clone()
+        }
+        _ => {
+            return Err(Error::generic(format!(
+                "Cannot change type of field {path} from {base} to {incoming}"
+            )))
+        }
+    };
+    Ok(merged)
+}
+
+/// The differences between two schemas, as reported by [`diff_schemas`]. Fields are identified by
+/// their full (dotted) path, e.g. `a.b.c` for field `c` nested in struct fields `a` and `b`.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct SchemaDiff {
+    /// Paths of the fields that only the new schema has
+    pub added: Vec<String>,
+    /// Paths of the fields that only the old schema has
+    pub removed: Vec<String>,
+    /// Fields whose type or nullability changed
+    pub changed: Vec<ChangedField>,
+}
+
+impl SchemaDiff {
+    /// Returns true if the schemas are the same (ignoring field metadata).
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+/// A field whose type or nullability differs between two schemas. Struct fields are only reported
+/// when their nullability changed, since changes to their children are reported separately.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChangedField {
+    /// The full (dotted) path of the field
+    pub path: String,
+    /// The field in the old schema
+    pub old: StructField,
+    /// The field in the new schema
+    pub new: StructField,
+}
+
+impl ChangedField {
+    /// Returns true if the field's type changed (including for struct fields that became
+    /// non-struct fields or vice versa).
+    pub fn type_changed(&self) -> bool {
+        match (&self.old.data_type, &self.new.data_type) {
+            (DataType::Struct(_), DataType::Struct(_)) => false,
+            (old, new) => old != new,
+        }
+    }
+
+    /// Returns true if the field's nullability changed.
+    pub fn nullability_changed(&self) -> bool {
+        self.old.nullable != self.new.nullable
+    }
+}
+
+/// Reports the fields that were added to, removed from or changed between the `old` and the `new`
+/// schema, recursing into nested structs. Fields are matched by name, and changes to field
+/// metadata or field order are ignored.
+pub fn diff_schemas(old: &StructType, new: &StructType) -> SchemaDiff {
+    let mut diff = SchemaDiff::default();
+    diff_structs(None, old, new, &mut diff);
+    diff
+}
+
+fn diff_structs(path: Option<&str>, old: &StructType, new: &StructType, diff: &mut SchemaDiff) {
+    let field_path = |name: &str| match path {
+        Some(path) => format!("{path}.{name}"),
+        None => name.to_string(),
+    };
+    for old_field in old.fields() {
+        let path = field_path(old_field.name());
+        let Some(new_field) = new.field(old_field.name()) else {
+            diff.removed.push(path);
+            continue;
+        };
+        let changed = ChangedField {
+            path,
+            old: old_field.clone(),
+            new: new_field.clone(),
+        };
+        if let (DataType::Struct(old_type), DataType::Struct(new_type)) =
+            (old_field.data_type(), new_field.data_type())
+        {
+            let path = changed.path.clone();
+            if changed.nullability_changed() {
+                diff.changed.push(changed);
+            }
+            diff_structs(Some(&path), old_type, new_type, diff);
+        } else if changed.type_changed() || changed.nullability_changed() {
+            diff.changed.push(changed);
+        }
+    }
+    diff.added.extend(
+        new.fields()
+            .filter(|field| old.field(field.name()).is_none())
+            .map(|field| field_path(field.name())),
+    );
+}
+
+/// Generic framework for describing recursive bottom-up schema transforms. Transformations return
+/// `Option<Cow>` with the following semantics:
+/// * `Some(Cow::Owned)` -- The schema element was transformed and should propagate to its parent.
+/// * `Some(Cow::Borrowed)` -- The schema element was not transformed.
+/// * `None` -- The schema element was filtered out and the parent should no longer reference it.
+///
+/// The transform can start from whatever schema element is available (e.g. [`Self::transform_struct`]
+/// to start with a [`StructType`]), or it can start from the generic [`Self::transform`].
+///
+/// The provided `transform_xxx` methods all default to no-op (they call the corresponding
+/// `recurse_into_xxx` method), so implementations only need to override the methods for the schema
+/// elements they actually want to change.
+pub trait SchemaTransform<'a> {
+    /// Called for each primitive type encountered during the schema traversal.
+    fn transform_primitive(&mut self, ptype: &'a PrimitiveType) -> Option<Cow<'a, PrimitiveType>> {
+        Some(Cow::Borrowed(ptype))
+    }
+
+    /// Called for each struct type encountered during the schema traversal. Implementations can
+    /// call [`Self::recurse_into_struct`] if they wish to recursively transform the struct's fields.
+    fn transform_struct(&mut self, stype: &'a StructType) -> Option<Cow<'a, StructType>> {
+        self.recurse_into_struct(stype)
+    }
+
+    /// Called for each struct field encountered during the schema traversal. Implementations can
+    /// call [`Self::recurse_into_struct_field`] if they wish to recursively transform the field's
+    /// data type.
+    fn transform_struct_field(&mut self, field: &'a StructField) -> Option<Cow<'a, StructField>> {
+        self.recurse_into_struct_field(field)
+    }
+
+    /// Called for each array type encountered during the schema traversal. Implementations can
+    /// call [`Self::recurse_into_array`] if they wish to recursively transform the array's element
+    /// type.
+    fn transform_array(&mut self, atype: &'a ArrayType) -> Option<Cow<'a, ArrayType>> {
+        self.recurse_into_array(atype)
+    }
+
+    /// Called for each array element encountered during the schema traversal.
+    fn transform_array_element(&mut self, etype: &'a DataType) -> Option<Cow<'a, DataType>> {
+        self.transform(etype)
+    }
+
+    /// Called for each map type encountered during the schema traversal. Implementations can call
+    /// [`Self::recurse_into_map`] if they wish to recursively transform the map's key and value
+    /// types.
+    fn transform_map(&mut self, mtype: &'a MapType) -> Option<Cow<'a, MapType>> {
+        self.recurse_into_map(mtype)
+    }
+
+    /// Called for each map key encountered during the schema traversal.
+    fn transform_map_key(&mut self, ktype: &'a DataType) -> Option<Cow<'a, DataType>> {
+        self.transform(ktype)
+    }
+
+    /// Called for each map value encountered during the schema traversal.
+    fn transform_map_value(&mut self, vtype: &'a DataType) -> Option<Cow<'a, DataType>> {
+        self.transform(vtype)
+    }
+
+    /// General entry point for a recursive traversal over any data type. Dispatches to the
+    /// specific `transform_xxx` method for the data type's variant.
+    fn transform(&mut self, data_type: &'a DataType) -> Option<Cow<'a, DataType>> {
+        use Cow::*;
+        let result = match data_type {
+            DataType::Primitive(ptype) => match self.transform_primitive(ptype)? {
+                Borrowed(_) => Borrowed(data_type),
+                Owned(ptype) => Owned(DataType::Primitive(ptype)),
+            },
+            DataType::Array(atype) => match self.transform_array(atype)? {
+                Borrowed(_) => Borrowed(data_type),
+                Owned(atype) => Owned(atype.into()),
+            },
+            DataType::Struct(stype) => match self.transform_struct(stype)? {
+                Borrowed(_) => Borrowed(data_type),
+                Owned(stype) => Owned(stype.into()),
+            },
+            DataType::Map(mtype) => match self.transform_map(mtype)? {
+                Borrowed(_) => Borrowed(data_type),
+                Owned(mtype) => Owned(mtype.into()),
+            },
+        };
+        Some(result)
+    }
+
+    /// Recursively transforms a struct field's data type. If the data type changes, update the
+    /// field to reference it. Otherwise, no-op.
+    fn recurse_into_struct_field(
+        &mut self,
+        field: &'a StructField,
+    ) -> Option<Cow<'a, StructField>> {
+        use Cow::*;
+        let field = match self.transform(&field.data_type)? {
+            Borrowed(_) => Borrowed(field),
+            Owned(new_data_type) => Owned(StructField {
+                name: field.name.clone(),
+                data_type: new_data_type,
+                nullable: field.nullable,
+                metadata: field.metadata.clone(),
+            }),
+        };
+        Some(field)
+    }
+
+    /// Recursively transforms a struct's fields. If one or more fields were changed or removed,
+    /// update the struct to reference all surviving fields. Otherwise, no-op.
+    fn recurse_into_struct(&mut self, stype: &'a StructType) -> Option<Cow<'a, StructType>> {
+        use Cow::*;
+        let mut num_borrowed = 0;
+        let fields: Vec<_> = stype
+            .fields()
+            .filter_map(|field| self.transform_struct_field(field))
+            .inspect(|field| {
+                if let Borrowed(_) = field {
+                    num_borrowed += 1;
+                }
+            })
+            .collect();
+
+        if num_borrowed == stype.fields.len() {
+            return Some(Borrowed(stype)); // no-op
+        }
+        let fields = fields.into_iter().map(|field| field.into_owned()).collect();
+        Some(Owned(StructType::new(fields)))
+    }
+
+    /// Recursively transforms an array's element type. If the element type changes, update the
+    /// array to reference it. Otherwise, no-op.
+    fn recurse_into_array(&mut self, atype: &'a ArrayType) -> Option<Cow<'a, ArrayType>> {
+        use Cow::*;
+        let atype = match self.transform_array_element(&atype.element_type)? {
+            Borrowed(_) => Borrowed(atype),
+            Owned(element_type) => Owned(ArrayType::new(element_type, atype.contains_null)),
+        };
+        Some(atype)
+    }
+
+    /// Recursively transforms a map's key and value types. If either one changes, update the map
+    /// to reference them. If either one is removed, remove the map as well.
+    fn recurse_into_map(&mut self, mtype: &'a MapType) -> Option<Cow<'a, MapType>> {
+        use Cow::*;
+        let key_type = self.transform_map_key(&mtype.key_type)?;
+        let value_type = self.transform_map_value(&mtype.value_type)?;
+        let mtype = match (&key_type, &value_type) {
+            (Borrowed(_), Borrowed(_)) => Borrowed(mtype),
+            _ => Owned(MapType::new(
+                key_type.into_owned(),
+                value_type.into_owned(),
+                mtype.value_contains_null,
+            )),
+        };
+        Some(mtype)
+    }
+}
+
+/// The column mapping mode of a table, i.e. how the columns of its logical schema map to the
+/// columns of the parquet files (see the `delta.columnMapping.mode` table property).
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub enum ColumnMappingMode {
+    /// Columns are stored under their logical names.
+    #[default]
+    None,
+    /// Columns are stored under their physical names, and resolved by their field ids.
+    Id,
+    /// Columns are stored and resolved by their physical names.
+    Name,
+}
+
+impl ColumnMappingMode {
+    /// The table property that configures the column mapping mode.
+    pub const TABLE_PROPERTY: &'static str = "delta.columnMapping.mode";
+
+    /// Reads the column mapping mode from a table's configuration, which defaults to
+    /// [`ColumnMappingMode::None`] if it's not set.
+    pub fn from_configuration(configuration: &HashMap<String, String>) -> DeltaResult<Self> {
+        configuration
+            .get(Self::TABLE_PROPERTY)
+            .map_or(Ok(Self::None), |mode| mode.parse())
+    }
+}
+
+impl std::str::FromStr for ColumnMappingMode {
+    type Err = Error;
+
+    fn from_str(s: &str) -> DeltaResult<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "none" => Ok(Self::None),
+            "id" => Ok(Self::Id),
+            "name" => Ok(Self::Name),
+            _ => Err(Error::generic(format!("Invalid column mapping mode: {s}"))),
+        }
+    }
+}
+
+/// Renames each field of a schema to its physical name (the `delta.columnMapping.physicalName`
+/// field metadata), leaving its nested structure intact. This translates a table's logical schema
+/// into the names that parquet files and file statistics use. In `id` mode, each field must also
+/// have a `delta.columnMapping.id`, and fields are otherwise left unchanged in `none` mode.
+///
+/// Fields that lack the required metadata keep their name, and the first such field is reported
+/// by [`ColumnMappingTransform::physical_schema`].
+pub struct ColumnMappingTransform {
+    mode: ColumnMappingMode,
+    error: Option<Error>,
+}
+
+impl ColumnMappingTransform {
+    pub fn new(mode: ColumnMappingMode) -> Self {
+        Self { mode, error: None }
+    }
+
+    /// Returns the physical schema of `schema`, or an error if a field lacks the metadata that
+    /// the column mapping mode requires.
+    pub fn physical_schema(
+        mode: ColumnMappingMode,
+        schema: &StructType,
+    ) -> DeltaResult<StructType> {
+        let mut transform = Self::new(mode);
+        let physical_schema = transform.transform_struct(schema);
+        match transform.error {
+            Some(error) => Err(error),
+            None => Ok(physical_schema.map_or_else(|| StructType::new(vec![]), Cow::into_owned)),
+        }
+    }
+
+    fn physical_name<'a>(&mut self, field: &'a StructField) -> Option<&'a str> {
+        match self.try_physical_name(field) {
+            Ok(name) => Some(name),
+            Err(error) => {
+                self.error.get_or_insert(error);
+                None
+            }
+        }
+    }
+
+    fn try_physical_name<'a>(&self, field: &'a StructField) -> DeltaResult<&'a str> {
+        let missing = |key: ColumnMetadataKey| {
+            Error::missing_column(format!(
+                "Field {} has no {} in {:?} column mapping mode",
+                field.name(),
+                key.as_ref(),
+                self.mode
+            ))
+        };
+        let id_key = ColumnMetadataKey::ColumnMappingId;
+        if self.mode == ColumnMappingMode::Id && field.metadata_i64(&id_key)?.is_none() {
+            return Err(missing(id_key));
+        }
+        let name_key = ColumnMetadataKey::ColumnMappingPhysicalName;
+        field
+            .metadata_string(&name_key)?
+            .ok_or_else(|| missing(name_key))
+    }
+}
+
+impl<'a> SchemaTransform<'a> for ColumnMappingTransform {
+    fn transform_struct_field(&mut self, field: &'a StructField) -> Option<Cow<'a, StructField>> {
+        if self.mode == ColumnMappingMode::None {
+            return Some(Cow::Borrowed(field));
+        }
+        let physical_name = self.physical_name(field);
+        let field = self.recurse_into_struct_field(field)?;
+        match physical_name {
+            Some(name) if name != field.name() => Some(Cow::Owned(StructField {
+                name: name.to_string(),
+                ..field.into_owned()
+            })),
+            _ => Some(field),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -609,6 +1126,434 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_field_metadata_accessors() {
+        let field = StructField::new("a", DataType::INTEGER, true).with_metadata([
+            ("comment", MetadataValue::from("a comment".to_string())),
+            ("count", MetadataValue::from("12345678901".to_string())),
+            (
+                ColumnMetadataKey::ColumnMappingId.as_ref(),
+                MetadataValue::from(7),
+            ),
+            (
+                ColumnMetadataKey::ColumnMappingPhysicalName.as_ref(),
+                MetadataValue::from("col-a".to_string()),
+            ),
+        ]);
+
+        // present keys
+        assert_eq!(field.metadata_string("comment").unwrap(), Some("a comment"));
+        assert_eq!(field.metadata_i64("count").unwrap(), Some(12345678901));
+        assert_eq!(
+            field
+                .metadata_i64(ColumnMetadataKey::ColumnMappingId)
+                .unwrap(),
+            Some(7)
+        );
+        assert_eq!(field.get_physical_name().unwrap(), "col-a");
+
+        // absent keys
+        assert_eq!(field.metadata_string("missing").unwrap(), None);
+        assert_eq!(field.metadata_i64("missing").unwrap(), None);
+        let unmapped = StructField::new("b", DataType::INTEGER, true);
+        assert_eq!(unmapped.get_physical_name().unwrap(), "b");
+
+        // type mismatches
+        let error = field
+            .metadata_string(ColumnMetadataKey::ColumnMappingId)
+            .unwrap_err();
+        assert!(error.to_string().contains("got number 7"), "{error}");
+        let error = field.metadata_i64("comment").unwrap_err();
+        assert!(error.to_string().contains("got 'a comment'"), "{error}");
+        let bad_name = StructField::new("c", DataType::INTEGER, true).with_metadata([(
+            ColumnMetadataKey::ColumnMappingPhysicalName.as_ref(),
+            MetadataValue::from(1),
+        )]);
+        assert!(bad_name.get_physical_name().is_err());
+    }
+
+    fn mapped_field(
+        name: &str,
+        data_type: impl Into<DataType>,
+        id: i32,
+        physical_name: &str,
+    ) -> StructField {
+        StructField::new(name, data_type, true).with_metadata([
+            (
+                ColumnMetadataKey::ColumnMappingId.as_ref(),
+                MetadataValue::Number(id),
+            ),
+            (
+                ColumnMetadataKey::ColumnMappingPhysicalName.as_ref(),
+                MetadataValue::String(physical_name.to_string()),
+            ),
+        ])
+    }
+
+    #[test]
+    fn test_column_mapping_mode() {
+        let configuration = |mode: &str| {
+            HashMap::from([(
+                ColumnMappingMode::TABLE_PROPERTY.to_string(),
+                mode.to_string(),
+            )])
+        };
+        let mode = |configuration| ColumnMappingMode::from_configuration(&configuration).ok();
+        assert_eq!(mode(HashMap::new()), Some(ColumnMappingMode::None));
+        assert_eq!(mode(configuration("none")), Some(ColumnMappingMode::None));
+        assert_eq!(mode(configuration("id")), Some(ColumnMappingMode::Id));
+        assert_eq!(mode(configuration("Name")), Some(ColumnMappingMode::Name));
+        assert_eq!(mode(configuration("other")), None);
+    }
+
+    #[test]
+    fn test_column_mapping_transform() {
+        // struct<a: int, s: struct<b: array<struct<c: long>>, m: map<string, struct<d: string>>>>
+        let element = StructType::new(vec![mapped_field("c", DataType::LONG, 4, "col-c")]);
+        let value = StructType::new(vec![mapped_field("d", DataType::STRING, 5, "col-d")]);
+        let nested = StructType::new(vec![
+            mapped_field("b", ArrayType::new(element.into(), true), 2, "col-b"),
+            mapped_field(
+                "m",
+                MapType::new(DataType::STRING, value.into(), true),
+                3,
+                "col-m",
+            ),
+        ]);
+        let schema = StructType::new(vec![
+            mapped_field("a", DataType::INTEGER, 0, "col-a"),
+            mapped_field("s", nested, 1, "col-s"),
+        ]);
+
+        let expected = DataType::from(StructType::new(vec![
+            StructField::new("col-a", DataType::INTEGER, true),
+            StructField::new(
+                "col-s",
+                StructType::new(vec![
+                    StructField::new(
+                        "col-b",
+                        ArrayType::new(
+                            StructType::new(vec![StructField::new("col-c", DataType::LONG, true)])
+                                .into(),
+                            true,
+                        ),
+                        true,
+                    ),
+                    StructField::new(
+                        "col-m",
+                        MapType::new(
+                            DataType::STRING,
+                            StructType::new(vec![StructField::new(
+                                "col-d",
+                                DataType::STRING,
+                                true,
+                            )])
+                            .into(),
+                            true,
+                        ),
+                        true,
+                    ),
+                ]),
+                true,
+            ),
+        ]));
+        // Display ignores field metadata, which the transform leaves intact
+        for mode in [ColumnMappingMode::Id, ColumnMappingMode::Name] {
+            let physical = ColumnMappingTransform::physical_schema(mode, &schema).unwrap();
+            assert_eq!(DataType::from(physical).to_string(), expected.to_string());
+        }
+        let physical = ColumnMappingTransform::physical_schema(ColumnMappingMode::None, &schema);
+        assert_eq!(physical.unwrap(), schema);
+    }
+
+    #[test]
+    fn test_column_mapping_transform_missing_metadata() {
+        // Name mode only needs physical names, but id mode also needs field ids
+        let field = StructField::new("a", DataType::INTEGER, true).with_metadata([(
+            ColumnMetadataKey::ColumnMappingPhysicalName.as_ref(),
+            MetadataValue::String("col-a".to_string()),
+        )]);
+        let schema = StructType::new(vec![field]);
+        let physical =
+            ColumnMappingTransform::physical_schema(ColumnMappingMode::Name, &schema).unwrap();
+        assert!(physical.field("col-a").is_some());
+        let error = ColumnMappingTransform::physical_schema(ColumnMappingMode::Id, &schema)
+            .unwrap_err()
+            .to_string();
+        assert!(
+            error.contains("Field a has no delta.columnMapping.id"),
+            "{error}"
+        );
+
+        let schema = StructType::new(vec![StructField::new("a", DataType::INTEGER, true)]);
+        let error = ColumnMappingTransform::physical_schema(ColumnMappingMode::Name, &schema)
+            .unwrap_err()
+            .to_string();
+        assert!(
+            error.contains("Field a has no delta.columnMapping.physicalName"),
+            "{error}"
+        );
+    }
+
+    #[test]
+    fn test_merge_schemas() {
+        let base = StructType::new(vec![
+            StructField::new("id", DataType::INTEGER, false)
+                .with_metadata([("comment", MetadataValue::from("base".to_string()))]),
+            StructField::new(
+                "nested",
+                StructType::new(vec![StructField::new("x", DataType::FLOAT, false)]),
+                false,
+            ),
+            StructField::new("price", DataType::decimal(10, 2), true),
+            StructField::new(
+                "tags",
+                MapType::new(DataType::STRING, DataType::SHORT, false),
+                true,
+            ),
+        ]);
+        let incoming = StructType::new(vec![
+            StructField::new("id", DataType::LONG, false).with_metadata([
+                ("comment", MetadataValue::from("incoming".to_string())),
+                ("delta.columnMapping.id", MetadataValue::from(1)),
+            ]),
+            StructField::new(
+                "nested",
+                StructType::new(vec![
+                    StructField::new("x", DataType::DOUBLE, true),
+                    StructField::new("y", DataType::STRING, true),
+                ]),
+                false,
+            ),
+            StructField::new("price", DataType::decimal(12, 3), true),
+            StructField::new(
+                "tags",
+                MapType::new(DataType::STRING, DataType::INTEGER, true),
+                true,
+            ),
+            StructField::new("added", DataType::STRING, true),
+        ]);
+
+        let expected = StructType::new(vec![
+            StructField::new("id", DataType::LONG, false).with_metadata([
+                ("comment", MetadataValue::from("base".to_string())),
+                ("delta.columnMapping.id", MetadataValue::from(1)),
+            ]),
+            StructField::new(
+                "nested",
+                StructType::new(vec![
+                    StructField::new("x", DataType::DOUBLE, true),
+                    StructField::new("y", DataType::STRING, true),
+                ]),
+                false,
+            ),
+            StructField::new("price", DataType::decimal(12, 3), true),
+            StructField::new(
+                "tags",
+                MapType::new(DataType::STRING, DataType::INTEGER, true),
+                true,
+            ),
+            StructField::new("added", DataType::STRING, true),
+        ]);
+        assert_eq!(merge_schemas(&base, &incoming).unwrap(), expected);
+        // Merging is idempotent, and fields only the base has are kept
+        assert_eq!(
+            merge_schemas(&expected, &StructType::new(vec![])).unwrap(),
+            expected
+        );
+        assert_eq!(merge_schemas(&expected, &expected).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_merge_schemas_errors() {
+        let schema = |field| StructType::new(vec![field]);
+        let nested = |data_type: DataType| {
+            StructType::new(vec![StructField::new(
+                "a",
+                StructType::new(vec![StructField::new("b", data_type, true)]),
+                true,
+            )])
+        };
+        let cases = [
+            // narrowing
+            (
+                nested(DataType::LONG),
+                nested(DataType::INTEGER),
+                "Cannot change type of field a.b from bigint to int",
+            ),
+            (
+                schema(StructField::new("d", DataType::decimal(10, 2), true)),
+                schema(StructField::new("d", DataType::decimal(10, 3), true)),
+                "Cannot change type of field d from decimal(10, 2) to decimal(10, 3)",
+            ),
+            // incompatible
+            (
+                schema(StructField::new("s", DataType::STRING, true)),
+                schema(StructField::new("s", DataType::INTEGER, true)),
+                "Cannot change type of field s from string to int",
+            ),
+            (
+                schema(StructField::new(
+                    "a",
+                    ArrayType::new(DataType::INTEGER, true),
+                    true,
+                )),
+                schema(StructField::new(
+                    "a",
+                    ArrayType::new(DataType::STRING, true),
+                    true,
+                )),
+                "Cannot change type of field a.element from int to string",
+            ),
+            (
+                nested(DataType::LONG),
+                schema(StructField::new("a", DataType::LONG, true)),
+                "Cannot change type of field a from struct<b: bigint> to bigint",
+            ),
+            // non-nullable additions
+            (
+                nested(DataType::LONG),
+                StructType::new(vec![StructField::new("c", DataType::LONG, false)]),
+                "Cannot add non-nullable field c",
+            ),
+        ];
+        for (base, incoming, expected) in cases {
+            let error = merge_schemas(&base, &incoming).unwrap_err().to_string();
+            assert!(error.contains(expected), "{error}");
+        }
+    }
+
+    #[test]
+    fn test_diff_schemas() {
+        let old = StructType::new(vec![
+            StructField::new("id", DataType::LONG, false),
+            StructField::new("dropped", DataType::STRING, true),
+            StructField::new("value", DataType::INTEGER, true),
+            StructField::new(
+                "nested",
+                StructType::new(vec![
+                    StructField::new("x", DataType::INTEGER, true),
+                    StructField::new("gone", DataType::STRING, true),
+                    StructField::new(
+                        "deep",
+                        StructType::new(vec![StructField::new("y", DataType::DATE, true)]),
+                        true,
+                    ),
+                ]),
+                true,
+            ),
+        ]);
+        let new = StructType::new(vec![
+            // nullability-only change
+            StructField::new("id", DataType::LONG, true),
+            StructField::new("value", DataType::LONG, true),
+            StructField::new(
+                "nested",
+                StructType::new(vec![
+                    StructField::new("x", DataType::INTEGER, true),
+                    StructField::new(
+                        "deep",
+                        StructType::new(vec![
+                            StructField::new("y", DataType::DATE, true),
+                            StructField::new("z", DataType::STRING, true),
+                        ]),
+                        false,
+                    ),
+                    StructField::new("new", DataType::BOOLEAN, true),
+                ]),
+                true,
+            ),
+            StructField::new("added", DataType::STRING, true),
+        ]);
+
+        let diff = diff_schemas(&old, &new);
+        assert_eq!(diff.added, ["nested.deep.z", "nested.new", "added"]);
+        assert_eq!(diff.removed, ["dropped", "nested.gone"]);
+        let changed: Vec<_> = diff
+            .changed
+            .iter()
+            .map(|c| (c.path.as_str(), c.type_changed(), c.nullability_changed()))
+            .collect();
+        assert_eq!(
+            changed,
+            [
+                ("id", false, true),
+                ("value", true, false),
+                ("nested.deep", false, true),
+            ]
+        );
+
+        assert!(diff_schemas(&old, &old).is_empty());
+        // a struct that is replaced by a primitive is a type change, not a removal
+        let flat = StructType::new(vec![StructField::new("nested", DataType::STRING, true)]);
+        let diff = diff_schemas(&StructType::new(vec![old.fields["nested"].clone()]), &flat);
+        assert!(diff.added.is_empty() && diff.removed.is_empty());
+        assert_eq!(diff.changed.len(), 1);
+        assert!(diff.changed[0].type_changed());
+    }
+
+    #[test]
+    fn test_schema_json_roundtrip() {
+        let fixtures = [
+            "./tests/serde/schema.json",
+            "./tests/serde/checkpoint_schema.json",
+            "./tests/serde/column_mapping_schema.json",
+        ];
+        for fixture in fixtures {
+            let json = std::fs::read_to_string(fixture).unwrap();
+            let schema = StructType::from_json(&json).unwrap();
+            let roundtrip = schema.to_json().unwrap();
+            assert_eq!(StructType::from_json(&roundtrip).unwrap(), schema);
+            let expected: serde_json::Value = serde_json::from_str(&json).unwrap();
+            let actual: serde_json::Value = serde_json::from_str(&roundtrip).unwrap();
+            assert_eq!(actual, expected, "{fixture}");
+        }
+    }
+
+    #[test]
+    fn test_schema_from_json() {
+        let json = std::fs::read_to_string("./tests/serde/column_mapping_schema.json").unwrap();
+        let schema = StructType::from_json(&json).unwrap();
+
+        assert_eq!(
+            schema.field("price").unwrap().data_type(),
+            &DataType::decimal(38, 18)
+        );
+        assert_eq!(
+            schema.field("created").unwrap().data_type(),
+            &DataType::TIMESTAMP
+        );
+        assert_eq!(
+            schema.field("local_created").unwrap().data_type(),
+            &DataType::TIMESTAMP_NTZ
+        );
+        let id = schema.field("id").unwrap();
+        assert!(!id.is_nullable());
+        assert_eq!(
+            id.get_config_value(&ColumnMetadataKey::ColumnMappingId),
+            Some(&MetadataValue::Number(1))
+        );
+        assert_eq!(
+            id.metadata().get("comment"),
+            Some(&MetadataValue::String("primary key".to_string()))
+        );
+
+        let DataType::Struct(details) = schema.field("details").unwrap().data_type() else {
+            panic!("details should be a struct");
+        };
+        let expected = MapType::new(
+            DataType::STRING,
+            ArrayType::new(DataType::decimal(5, 2), false).into(),
+            false,
+        );
+        assert_eq!(
+            details.field("ratios").unwrap().data_type(),
+            &expected.into()
+        );
+
+        assert!(StructType::from_json(r#"{"type":"struct","fields":[{"name":"a"}]}"#).is_err());
+    }
+
     #[test]
     fn test_read_schemas() {
         let file = std::fs::File::open("./tests/serde/schema.json").unwrap();
@@ -329,12 +329,13 @@ async fn stats() -> Result<(), Box<dyn std::error::Error>> {
         (GreaterThanOrEqual, 4, vec![&batch2]),
         (GreaterThanOrEqual, 7, vec![&batch2]),
         (GreaterThanOrEqual, 8, vec![]),
+        // Only files whose values all equal the literal can be skipped, which neither file is
         (NotEqual, 0, vec![&batch2, &batch1]),
-        (NotEqual, 1, vec![&batch2]),
-        (NotEqual, 3, vec![&batch2]),
+        (NotEqual, 1, vec![&batch2, &batch1]),
+        (NotEqual, 3, vec![&batch2, &batch1]),
         (NotEqual, 4, vec![&batch2, &batch1]),
-        (NotEqual, 5, vec![&batch1]),
-        (NotEqual, 7, vec![&batch1]),
+        (NotEqual, 5, vec![&batch2, &batch1]),
+        (NotEqual, 7, vec![&batch2, &batch1]),
         (NotEqual, 8, vec![&batch2, &batch1]),
     ];
     for (op, value, expected_batches) in test_cases {
@@ -505,3 +506,62 @@ fn column_ordering_and_projection() -> Result<(), Box<dyn std::error::Error>> {
 
     Ok(())
 }
+
+#[test]
+fn projection_reorders_and_renames_columns() -> Result<(), Box<dyn std::error::Error>> {
+    let path = std::fs::canonicalize(PathBuf::from("./tests/data/basic_partitioned"))?;
+    let url = url::Url::from_directory_path(path).unwrap();
+    let table_client = DefaultEngineInterface::try_new(
+        &url,
+        std::iter::empty::<(&str, &str)>(),
+        Arc::new(TokioBackgroundExecutor::new()),
+    )?;
+
+    let table = Table::new(url);
+    let snapshot = table.snapshot(&table_client, None)?;
+    let scan = ScanBuilder::new(snapshot)
+        .with_projection(vec![
+            ("n".to_string(), Expression::column("number")),
+            ("l".to_string(), Expression::column("letter")),
+            (
+                "doubled".to_string(),
+                Expression::column("number") * Expression::literal(2_i64),
+            ),
+        ])
+        .build();
+
+    let output_schema = scan.output_schema()?;
+    let names: Vec<_> = output_schema.fields().map(|field| field.name()).collect();
+    assert_eq!(names, ["n", "l", "doubled"]);
+
+    let batches: Vec<RecordBatch> = scan
+        .execute(&table_client)?
+        .into_iter()
+        .map(|sr| {
+            let data = sr.raw_data.unwrap();
+            data.into_any()
+                .downcast::<ArrowEngineData>()
+                .unwrap()
+                .into()
+        })
+        .collect();
+    let schema = batches[0].schema();
+    let names: Vec<_> = schema.fields().iter().map(|field| field.name()).collect();
+    assert_eq!(names, ["n", "l", "doubled"]);
+    let batch = concat_batches(&schema, &batches)?;
+
+    let expected = vec![
+        "+---+---+---------+",
+        "| n | l | doubled |",
+        "+---+---+---------+",
+        "| 1 | a | 2       |",
+        "| 2 | b | 4       |",
+        "| 3 | c | 6       |",
+        "| 4 | a | 8       |",
+        "| 5 | e | 10      |",
+        "| 6 |   | 12      |",
+        "+---+---+---------+",
+    ];
+    assert_batches_sorted_eq!(&expected, &[batch]);
+    Ok(())
+}